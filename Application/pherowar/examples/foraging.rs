@@ -0,0 +1,71 @@
+//! Steps a small scenario headlessly and checks its outcome: two colonies of the same
+//! wandering brain, only one of them with food inside its nest. Its ants bring the food
+//! home as they walk over it, so that colony ends up with a larger stock and more ants.
+//!
+//! Run with `cargo run --example foraging`.
+
+use macroquad::prelude::Vec2;
+use pherowar::config::SimulationConfig;
+use pherowar::headless::HeadlessMatch;
+use pherowar::simulation::GameMap;
+use shared::{
+    AntInput, AntOutput, CHANNEL_NAME_SIZE, MEMORY_SIZE, PHEROMONE_CHANNEL_COUNT, PlayerSetup,
+    ROLE_COUNT,
+};
+
+const TICKS: u32 = 300;
+const INITIAL_POPULATION: u32 = 10;
+
+/// Walks straight, turning away from close walls
+fn wanderer(input: &AntInput, _memory: &mut [u8; MEMORY_SIZE], output: &mut AntOutput) {
+    if input.wall_sense.1 >= 0.0 && input.wall_sense.1 < 2.0 {
+        output.turn_angle = std::f32::consts::FRAC_PI_2;
+    }
+}
+
+fn main() {
+    let config = SimulationConfig {
+        colony_initial_population: INITIAL_POPULATION,
+        rng_seed: Some(1),
+        no_timeout: true,
+        ..SimulationConfig::default()
+    };
+    let mut map = GameMap::new(40, 20);
+    map.add_border_walls();
+    // Food right around the first nest's center only
+    for (x, y) in [(7, 10), (9, 10), (8, 9), (8, 11)] {
+        map.place_food_at(x, y, 50);
+    }
+
+    let setup = PlayerSetup {
+        decay_rates: [0.9; PHEROMONE_CHANNEL_COUNT],
+        initial_memory: [0; MEMORY_SIZE],
+        role_ratios: [0.0; ROLE_COUNT],
+        channel_names: [[0; CHANNEL_NAME_SIZE]; PHEROMONE_CHANNEL_COUNT],
+        diffusion_rates: [0.0; PHEROMONE_CHANNEL_COUNT],
+    };
+    let mut headless = HeadlessMatch::on_map(&config, map);
+    let fed = headless
+        .add_in_process_colony("fed", Vec2::new(8.5, 10.5), setup, wanderer)
+        .expect("in-process brains start without Podman");
+    let hungry = headless
+        .add_in_process_colony("hungry", Vec2::new(31.5, 10.5), setup, wanderer)
+        .expect("in-process brains start without Podman");
+
+    headless.step(TICKS);
+
+    let colonies = &headless.simulation.colonies;
+    let (fed, hungry) = (&colonies[&fed], &colonies[&hungry]);
+    println!(
+        "After {} ticks: fed colony {} food, {} ants; hungry colony {} food, {} ants",
+        TICKS,
+        fed.total_food,
+        fed.ants.len(),
+        hungry.total_food,
+        hungry.ants.len()
+    );
+    // A little food may still reach the hungry colony, e.g. dropped by dying fed ants
+    assert!(fed.total_food > hungry.total_food);
+    assert!(fed.ants.len() > INITIAL_POPULATION as usize);
+    assert!(fed.ants.len() > hungry.ants.len());
+}
@@ -1,12 +1,19 @@
-use crate::config::AppConfig;
+use crate::config::{
+    AUTOSAVE_MAP_NAME, AppConfig, ConfigError, PlayerConfig, SimulationConfig, load_player_configs,
+};
+use crate::editor::symmetry_mode::SymmetryMode;
 use crate::editor::{EditorManager, ToolType};
 use crate::engine::{CameraAction, Renderer};
-use crate::simulation::{GameMap, Simulation, THINK_INTERVAL};
+use crate::player::PlayerConnection;
+use crate::simulation::{
+    GameMap, MAP_THUMBNAIL_SIZE, MatchStats, Placement, Replay, Simulation, THINK_INTERVAL,
+};
 use crate::ui::UIManager;
 use crate::ui::components::DialogPopup;
 use crate::ui::events::AppAction;
 use macroquad::prelude::*;
 use std::cell::RefCell;
+use std::path::PathBuf;
 use std::time::Instant;
 
 thread_local! {
@@ -18,16 +25,90 @@ thread_local! {
 pub struct WinnerInfo {
     pub name: String,
     pub score: usize,
+    pub placements: Vec<Placement>,
+    /// Think latency report, taken before the players are cleaned up
+    pub think_latency: String,
+    pub foraging: String,
+    /// Likely cause of each eliminated colony's loss
+    pub losses: String,
+    pub stats: MatchStats,
+}
+
+/// Namespace of the compare simulation's player sockets and logs
+const COMPARE_NAMESPACE: &str = "compare";
+
+/// Second simulation of compare mode, shown in the right half of the screen.
+pub struct CompareView {
+    simulation: Simulation,
+    renderer: Renderer,
+    winner_announced: bool,
+}
+
+impl CompareView {
+    /// Loads the map a second time and spawns the compare players at its placeholders.
+    async fn new(
+        config: &SimulationConfig,
+        player_configs: Vec<PlayerConfig>,
+        map_name: &str,
+        players: &[String],
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut simulation = Simulation::new(config, player_configs, None);
+        simulation.map = GameMap::load_map(map_name)?;
+        simulation.namespace = COMPARE_NAMESPACE.to_string();
+        PWApp::spawn_players_at_placeholders(&mut simulation, players)?;
+
+        let renderer =
+            Renderer::new(simulation.map.width, simulation.map.height, &config.camera).await;
+
+        Ok(Self {
+            simulation,
+            renderer,
+            winner_announced: false,
+        })
+    }
+
+    /// Pauses the compare simulation and prints its result once a single colony remains.
+    fn check_winner(&mut self) {
+        let alive: Vec<u32> = self
+            .simulation
+            .colonies
+            .iter()
+            .filter(|(_, colony)| !colony.is_dead())
+            .map(|(&colony_id, _)| colony_id)
+            .collect();
+
+        if alive.len() == 1 && !self.winner_announced {
+            self.simulation.pause();
+            let winner = &self.simulation.colonies[&alive[0]];
+            println!(
+                "Compare winner: {}\nRemaining ants: {}",
+                self.simulation.colony_display_name(alive[0]),
+                winner.ants.len()
+            );
+            println!(
+                "Compare placements:\n{}",
+                format_placements(&self.simulation.placements())
+            );
+            self.winner_announced = true;
+        } else if alive.len() >= 2 {
+            self.winner_announced = false;
+        }
+    }
 }
 
 /// Main application structure for PheroWar.
 pub struct PWApp {
-    ui: UIManager,          // Manages all UI elements and interactions.
-    editor: EditorManager,  // Handles map editing tools and state.
-    renderer: Renderer,     // Responsible for drawing the game world and UI.
-    simulation: Simulation, // Core game logic, including ants, colonies, and map state.
-    winner_announced: bool, // Flag to ensure the winner announcement is handled only once.
-    evaluate_mode: bool,    // Flag to indicate if the game should run in evaluate mode.
+    ui: UIManager,                // Manages all UI elements and interactions.
+    editor: EditorManager,        // Handles map editing tools and state.
+    renderer: Renderer,           // Responsible for drawing the game world and UI.
+    simulation: Simulation,       // Core game logic, including ants, colonies, and map state.
+    winner_announced: bool,       // Flag to ensure the winner announcement is handled only once.
+    evaluate_mode: bool,          // Flag to indicate if the game should run in evaluate mode.
+    compare: Option<CompareView>, // Second simulation shown side by side in compare mode.
+    config_path: Option<PathBuf>, // Config file re-read by a config reload.
+    last_autosave: f64,           // Wall-clock time of the last map autosave check.
+    replay: Option<Replay>,       // Recording played instead of the simulation (`--replay`).
+    stats_out: Option<PathBuf>,   // File the match stats are written to when decided.
 }
 
 impl PWApp {
@@ -35,22 +116,16 @@ impl PWApp {
     pub async fn new(app_config: AppConfig) -> Result<Self, Box<dyn std::error::Error>> {
         let player_configs = app_config.player_configs;
 
-        let simulation = if let Some(map_name) = &app_config.map_name {
+        let mut replay = None;
+        let mut simulation = if let Some(path) = &app_config.replay {
+            let (opened, simulation) = Replay::open(path, &app_config.simulation)
+                .map_err(|e| format!("Cannot open replay '{}': {}", path.display(), e))?;
+            replay = Some(opened);
+            simulation
+        } else if let Some(map_name) = &app_config.map_name {
+            // Player count was checked against the map's placeholders by `AppConfig`
             let loaded_map = crate::simulation::GameMap::load_map(map_name)?;
 
-            // Validate player count if CLI players are provided
-            if let Some(ref players) = app_config.cli_players {
-                let expected_colonies = loaded_map.placeholder_colony_locations.len();
-                let provided_players = players.len();
-
-                if expected_colonies != provided_players {
-                    return Err(format!(
-                        "Colony count mismatch: Map '{}' expects {} colonies but {} players were provided",
-                        map_name, expected_colonies, provided_players
-                    ).into());
-                }
-            }
-
             // Create simulation with the loaded map
             let mut sim = Simulation::new(&app_config.simulation, player_configs.clone(), None);
             sim.map = loaded_map;
@@ -58,36 +133,79 @@ impl PWApp {
         } else {
             Simulation::new(&app_config.simulation, player_configs.clone(), None)
         };
+        if let Some(path) = &app_config.population_log {
+            simulation
+                .start_population_log(path)
+                .map_err(|e| format!("Cannot create population log '{}': {}", path.display(), e))?;
+        }
+        if let Some(path) = &app_config.record {
+            simulation
+                .start_recording(path)
+                .map_err(|e| format!("Cannot create replay '{}': {}", path.display(), e))?;
+        }
 
-        let renderer = Renderer::new(simulation.map.width, simulation.map.height).await;
+        // Colonies given on the command line can't run without Podman; the editor still can
+        let preflight = PlayerConnection::preflight();
+        if let Err(e) = &preflight
+            && (app_config.cli_players.is_some() || app_config.compare_players.is_some())
+        {
+            return Err(format!("Cannot start player containers: {}", e).into());
+        }
+
+        let renderer = Renderer::new(
+            simulation.map.width,
+            simulation.map.height,
+            &app_config.simulation.camera,
+        )
+        .await;
+
+        let compare = match (&app_config.compare_players, &app_config.map_name) {
+            (Some(players), Some(map_name)) => Some(
+                CompareView::new(
+                    &app_config.simulation,
+                    player_configs.clone(),
+                    map_name,
+                    players,
+                )
+                .await?,
+            ),
+            _ => None,
+        };
 
         let mut app = Self {
             ui: UIManager::new(),
-            editor: EditorManager::new(&simulation.player_configs),
+            editor: EditorManager::new(&simulation.player_configs, simulation.config.max_colonies),
             renderer,
             simulation,
             winner_announced: false,
             evaluate_mode: app_config.evaluate,
+            compare,
+            config_path: app_config.config_path,
+            last_autosave: get_time(),
+            replay,
+            stats_out: app_config.stats_out,
         };
 
         // Auto-spawn colonies if CLI players were provided
         if let Some(players) = app_config.cli_players {
-            let placeholder_locations = app.simulation.map.placeholder_colony_locations.clone();
-
-            for (i, player_name) in players.iter().enumerate() {
-                let player_cfg = player_configs
-                    .iter()
-                    .find(|p| p.name == *player_name)
-                    .ok_or_else(|| format!("Player config for '{}' not found", player_name))?
-                    .clone();
+            Self::spawn_players_at_placeholders(&mut app.simulation, &players)?;
+        }
 
-                let pos = placeholder_locations[i];
+        // An autosave left over means the last editing session didn't save its map
+        if !app.evaluate_mode && app_config.map_name.is_none() && GameMap::autosave_exists() {
+            app.ui.show_dialog(DialogPopup::new_recover_autosave());
+        }
 
-                let color = crate::editor::color_palette::PREDEFINED_COLONY_COLORS
-                    [i % crate::editor::color_palette::PREDEFINED_COLONY_COLORS.len()];
+        if let Err(e) = preflight {
+            eprintln!("Warning: {}", e);
+            app.ui.show_dialog(DialogPopup::new_info_with_title(
+                "Podman unavailable",
+                &format!("{}\n\nColonies can't be spawned until this is fixed.", e),
+            ));
+        }
 
-                app.simulation.spawn_colony(pos, color, player_cfg, None);
-            }
+        if app_config.spectator_lock {
+            app.ui.visual_options_panel.lock_spectator_view();
         }
 
         // Auto-start simulation in unlimited speed if evaluate mode is enabled
@@ -99,34 +217,94 @@ impl PWApp {
         Ok(app)
     }
 
+    /// Spawns a colony for each named player at the map's nest placeholders, in order.
+    pub(crate) fn spawn_players_at_placeholders(
+        simulation: &mut Simulation,
+        players: &[String],
+    ) -> Result<(), String> {
+        let placeholder_locations = simulation.map.placeholder_colony_locations.clone();
+        if players.len() > simulation.config.max_colonies {
+            return Err(format!(
+                "{} players were provided but at most {} colonies are allowed",
+                players.len(),
+                simulation.config.max_colonies
+            ));
+        }
+        if players.len() > placeholder_locations.len() {
+            return Err(format!(
+                "Map has {} colony placeholders but {} players were provided",
+                placeholder_locations.len(),
+                players.len()
+            ));
+        }
+
+        simulation.check_memory_budget(simulation.colonies.len() + players.len())?;
+
+        let spawn_positions = simulation.spawn_positions(&placeholder_locations[..players.len()]);
+        for (i, player_name) in players.iter().enumerate() {
+            let player_cfg = simulation
+                .player_configs
+                .iter()
+                .find(|p| p.name == *player_name)
+                .ok_or_else(|| format!("Player config for '{}' not found", player_name))?
+                .clone();
+
+            let pos = spawn_positions[i];
+            // The spawn only clears the placeholder under it, which jitter may have moved off
+            simulation
+                .map
+                .remove_placeholder_colony(placeholder_locations[i]);
+
+            let color = crate::editor::color_palette::colony_color(i);
+
+            simulation.spawn_colony(pos, color, player_cfg, None);
+        }
+        Ok(())
+    }
+
     /// Runs the main application loop.
     pub async fn run(&mut self) {
         let mut last_time = get_time(); // wall-clock seconds
+        // Handle the window close ourselves to save the UI preferences first
+        prevent_quit();
 
         loop {
+            if is_quit_requested() {
+                // Evaluate mode forces its own speed, keep the user's setup
+                if !self.evaluate_mode {
+                    self.ui.preferences().save();
+                }
+                return;
+            }
             let frame_start = get_time();
             // Measure real elapsed time since last frame
             let now = get_time();
             let dt = now - last_time;
             last_time = now;
 
-            if self.ui.unlimited() {
+            if let Some(replay) = &mut self.replay {
+                // Replayed colonies have no brain to update, frames are shown at the chosen speed
+                let time_multiplier = self.ui.time_multiplier().unwrap_or(1.0);
+                replay.advance(&mut self.simulation, dt as f32 * time_multiplier);
+            } else if self.ui.unlimited() {
                 // Dynamically adjust max_dt based on ant count
                 let ant_count = self.simulation.total_ant_count();
                 let max_dt = (THINK_INTERVAL / (ant_count as f32 / 1000.0)).min(THINK_INTERVAL);
                 // Run as many simulation steps as possible until it's time to render
-                let target_frame_time = 1.0 / 60.0; // 60 FPS
-                while get_time() - frame_start < target_frame_time {
+                let render_interval =
+                    1.0 / self.simulation.config.window.unlimited_render_fps as f64;
+                while get_time() - frame_start < render_interval {
                     self.simulation.update(max_dt);
                 }
             } else {
                 let time_multiplier = self.ui.time_multiplier().unwrap_or(1.0);
-                let mut sim_dt = (dt as f32) * time_multiplier;
-                while sim_dt > 0.0 {
-                    let step = sim_dt.min(THINK_INTERVAL);
-                    self.simulation.update(step);
-                    sim_dt -= step;
-                }
+                advance_simulation(&mut self.simulation, dt as f32 * time_multiplier);
+            }
+
+            if let Some(compare) = &mut self.compare {
+                let time_multiplier = self.ui.compare_time_multiplier();
+                advance_simulation(&mut compare.simulation, dt as f32 * time_multiplier);
+                compare.check_winner();
             }
 
             if self.simulation.colonies.len() > 1 {
@@ -139,6 +317,18 @@ impl PWApp {
                         "Winner: {}\nRemaining ants: {}",
                         winner_info.name, winner_info.score
                     );
+                    println!(
+                        "Placements:\n{}",
+                        format_placements(&winner_info.placements)
+                    );
+                    println!("Think latency:\n{}", winner_info.think_latency);
+                    println!("Foraging:\n{}", winner_info.foraging);
+                    println!("Losses:\n{}", winner_info.losses);
+                    if let Some(path) = &self.stats_out
+                        && let Err(e) = crate::headless::write_stats(path, &winner_info.stats)
+                    {
+                        eprintln!("Warning: Cannot write stats '{}': {}", path.display(), e);
+                    }
                     if self.evaluate_mode {
                         return;
                     }
@@ -146,6 +336,16 @@ impl PWApp {
                 }
             }
 
+            self.autosave_if_due();
+            if let Some(error) = self.simulation.spawn_error.take()
+                && !self.evaluate_mode
+            {
+                self.ui.show_dialog(DialogPopup::new_info_with_title(
+                    "Failed to start player",
+                    &error,
+                ));
+            }
+
             // Draw one frame
             self.update_ui();
             self.render();
@@ -170,15 +370,21 @@ impl PWApp {
         if alive_keys.len() == 1 && !self.winner_announced {
             self.simulation.pause();
             let winner_colony = &self.simulation.colonies[&alive_keys[0]];
-            let winner_name = winner_colony.player_config.name.clone();
+            let winner_name = self.simulation.colony_display_name(alive_keys[0]);
             let winner_score = winner_colony.ants.len();
+            let placements = self.simulation.placements();
 
             // In normal mode, show dialog if not already open
             if !self.evaluate_mode && self.ui.dialog_popup.is_none() {
+                let mut message = format!("Remaining: {} ants\nGreat antgineering.", winner_score);
+                // Full ranking only matters with more than two colonies
+                if placements.len() > 2 {
+                    message.push_str(&format!("\n\n{}", format_placements(&placements)));
+                }
                 self.ui
                     .show_dialog(crate::ui::components::DialogPopup::new_info_with_title(
                         &format!("🏆 {} wins! 🏆", winner_name),
-                        &format!("Remaining: {} ants\nGreat antgineering.", winner_score),
+                        &message,
                     ));
             }
 
@@ -186,6 +392,11 @@ impl PWApp {
             return Some(WinnerInfo {
                 name: winner_name,
                 score: winner_score,
+                placements,
+                think_latency: self.simulation.think_latency_report(),
+                foraging: self.simulation.foraging_report(),
+                losses: self.simulation.loss_report(),
+                stats: self.simulation.export_stats(),
             });
         } else if alive_keys.len() >= 2 {
             // Reset winner announcement flag if there are multiple colonies alive
@@ -197,6 +408,8 @@ impl PWApp {
 
     /// Updates the UI state and handles input.
     fn update_ui(&mut self) {
+        self.layout_viewports();
+
         // Handle global shortcuts first, as they might trigger actions
         let shortcut_handled = self.handle_global_shortcuts();
 
@@ -204,6 +417,7 @@ impl PWApp {
         let (app_action, ui_consumed_input) = self.ui.update(
             &mut self.editor,
             &self.simulation, // Pass simulation for UIManager to get ant data
+            self.compare.as_ref().map(|compare| &compare.simulation),
             &mut self.renderer.game_camera,
         );
 
@@ -229,8 +443,39 @@ impl PWApp {
         }
     }
 
+    /// Splits the screen between the main and compare simulations in compare mode.
+    fn layout_viewports(&mut self) {
+        let Some(compare) = &mut self.compare else {
+            return;
+        };
+        let half_width = (screen_width() / 2.0).floor();
+        self.renderer.game_camera.set_viewport(Some(Rect::new(
+            0.0,
+            0.0,
+            half_width,
+            screen_height(),
+        )));
+        compare.renderer.game_camera.set_viewport(Some(Rect::new(
+            half_width,
+            0.0,
+            screen_width() - half_width,
+            screen_height(),
+        )));
+    }
+
     /// Handles mouse and keyboard input related to the game world.
     fn handle_world_input(&mut self) {
+        // Only the camera of the compare view can be controlled
+        if let Some(compare) = &mut self.compare
+            && compare.renderer.game_camera.contains_mouse()
+        {
+            if mouse_wheel().1 != 0.0 {
+                compare.renderer.process_mouse_wheel_zoom();
+            }
+            compare.renderer.process_mouse_drag_pan();
+            return;
+        }
+
         // Ant selection (ALT + Click or Double Left Click)
         const DOUBLE_CLICK_MAX_MS: u128 = 350;
         const DOUBLE_CLICK_MAX_DIST: f32 = 8.0;
@@ -297,7 +542,8 @@ impl PWApp {
             .camera
             .screen_to_world(mouse_position().into());
 
-        if self.editor.current_tool().is_some() {
+        // Editing tools are off while watching a replay, only the camera moves
+        if self.editor.current_tool().is_some() && self.replay.is_none() {
             if ctrl_pressed {
                 if mouse_wheel_delta != 0.0 {
                     // CTRL + Wheel: Tool resize. Editor handles this. Camera does not zoom here.
@@ -365,6 +611,30 @@ impl PWApp {
         } else if is_key_pressed(KeyCode::Key3) {
             self.editor.set_tool(Some(ToolType::Colony));
             return true;
+        } else if is_key_pressed(KeyCode::Key4) {
+            self.editor.set_tool(Some(ToolType::Teleporter));
+            return true;
+        } else if is_key_pressed(KeyCode::Key5) {
+            self.editor.set_tool(Some(ToolType::Zone));
+            return true;
+        } else if is_key_pressed(KeyCode::Key6) {
+            self.editor.set_tool(Some(ToolType::Waypoint));
+            return true;
+        }
+        // Tool size shortcuts
+        else if self
+            .editor
+            .current_tool()
+            .is_some_and(|tool| tool.is_sizeable())
+            && (is_key_pressed(KeyCode::LeftBracket) || is_key_pressed(KeyCode::RightBracket))
+        {
+            let steps = if is_key_pressed(KeyCode::RightBracket) {
+                1.0
+            } else {
+                -1.0
+            };
+            self.editor.adjust_tool_size(steps);
+            return true;
         }
         // Simulation control shortcuts
         else if is_key_pressed(KeyCode::P) || is_key_pressed(KeyCode::Space) {
@@ -375,6 +645,9 @@ impl PWApp {
                 "Are you sure you want to reset the simulation?",
             ));
             return true;
+        } else if is_key_pressed(KeyCode::C) {
+            self.handle_app_actions(Some(AppAction::ReloadConfig));
+            return true;
         } else if is_key_pressed(KeyCode::S) {
             self.handle_app_actions(Some(AppAction::RequestSaveMap(String::new())));
             return true;
@@ -397,6 +670,11 @@ impl PWApp {
             self.ui.toggle_visual_options_panel();
             return true;
         }
+        // Toggle brain log panel shortcut
+        if is_key_pressed(KeyCode::B) {
+            self.ui.toggle_log_panel();
+            return true;
+        }
 
         false
     }
@@ -404,6 +682,13 @@ impl PWApp {
     /// Processes application-level actions triggered by UI or shortcuts.
     fn handle_app_actions(&mut self, action: Option<AppAction>) {
         if let Some(action) = action {
+            // A replay's map and colonies come from the recording only
+            if self.replay.is_some() && action.changes_match() {
+                self.ui.show_dialog(DialogPopup::new_info(
+                    "Not available while watching a replay.",
+                ));
+                return;
+            }
             match action {
                 AppAction::TogglePause => match self.simulation.try_toggle_pause() {
                     Ok(()) => {}
@@ -411,27 +696,129 @@ impl PWApp {
                         self.ui.show_dialog(DialogPopup::new_info(&msg));
                     }
                 },
+                AppAction::ToggleComparePause => {
+                    if let Some(compare) = &mut self.compare
+                        && let Err(msg) = compare.simulation.try_toggle_pause()
+                    {
+                        self.ui.show_dialog(DialogPopup::new_info(&msg));
+                    }
+                }
                 AppAction::RequestReset => {
                     self.reset();
                 }
+                AppAction::ReloadConfig => {
+                    self.reload_config();
+                }
                 AppAction::RequestSaveMap(name) => {
                     self.handle_save_map_request(name);
                 }
                 AppAction::RequestLoadMap(name) => {
                     self.handle_load_map_request(name);
                 }
-                AppAction::RequestNewMap { width, height } => {
+                AppAction::RequestNewMap {
+                    width,
+                    height,
+                    bordered,
+                    food,
+                } => {
+                    // The new editor starts without edits, keep the current ones
+                    self.autosave_edits();
                     self.simulation.create_new_map(width, height);
+                    if bordered {
+                        self.simulation.map.add_border_walls();
+                    }
+                    if let Some(food) = food {
+                        self.simulation.map.scatter_food(
+                            food.density,
+                            food.clustering,
+                            food.seed,
+                            self.editor.symmetry_mode,
+                        );
+                    }
                     self.renderer.reset(width, height);
-                    self.editor = EditorManager::new(&self.simulation.player_configs);
+                    self.editor = EditorManager::new(
+                        &self.simulation.player_configs,
+                        self.simulation.config.max_colonies,
+                    );
+                }
+                AppAction::RequestQuickMatch { map_name, players } => {
+                    self.handle_quick_match_request(&map_name, &players);
                 }
                 AppAction::ToggleCameraLockOnSelectedAnt => {
                     self.ui.toggle_camera_lock();
                 }
+                AppAction::ClearPheromones => self.simulation.clear_all_pheromones(),
+                AppAction::ToggleTraceOnSelectedAnt => {
+                    let Some(ant_ref) = self.ui.get_selected_ant_ref().cloned() else {
+                        return;
+                    };
+                    if self
+                        .simulation
+                        .get_ant(&ant_ref)
+                        .is_some_and(|ant| ant.trace)
+                    {
+                        self.simulation.stop_trace();
+                    } else {
+                        match self.simulation.start_trace(&ant_ref) {
+                            Ok(file_name) => println!("Tracing {:?} to {}", ant_ref, file_name),
+                            Err(e) => self.ui.show_dialog(DialogPopup::new_info(&format!(
+                                "Failed to start the trace: {}",
+                                e
+                            ))),
+                        }
+                    }
+                }
+                AppAction::RequestSymmetryCheck => {
+                    self.handle_symmetry_check_request();
+                }
+                AppAction::RequestEnforceSymmetry(mode) => {
+                    let mirrored_map = self.simulation.map.mirrored(mode);
+                    self.simulation.map = mirrored_map;
+                    self.simulation.clear_colonies();
+                    self.renderer.mark_dirty();
+                    self.editor.color_palette.update_selection(&self.simulation);
+                    self.editor.map_edited = true;
+                }
+                AppAction::RecoverAutosave => match self.load_map(AUTOSAVE_MAP_NAME) {
+                    Ok(()) => {
+                        // Saving asks for a real name, the autosave stays until then
+                        self.simulation.map.loaded_map_name = None;
+                        self.editor.map_edited = true;
+                    }
+                    Err(e) => {
+                        self.ui.show_dialog(DialogPopup::new_info(&format!(
+                            "Failed to recover the map: {}",
+                            e
+                        )));
+                    }
+                },
+                AppAction::DiscardAutosave => GameMap::remove_autosave(),
             }
         }
     }
 
+    /// Saves the edited map to the autosave file every `map_autosave_interval` seconds.
+    fn autosave_if_due(&mut self) {
+        let interval = self.simulation.config.map_autosave_interval as f64;
+        if get_time() - self.last_autosave < interval {
+            return;
+        }
+        self.last_autosave = get_time();
+        self.autosave_edits();
+    }
+
+    /// Saves the map to the autosave file if it has unsaved edits and autosave is on.
+    fn autosave_edits(&mut self) {
+        let interval = self.simulation.config.map_autosave_interval;
+        if self.evaluate_mode || interval <= 0.0 || !self.editor.map_edited {
+            return;
+        }
+        match self.simulation.map.autosave() {
+            Ok(()) => self.editor.map_edited = false,
+            Err(e) => eprintln!("Warning: Failed to autosave the map: {}", e),
+        }
+    }
+
     /// Handles the request to save the current map.
     fn handle_save_map_request(&mut self, name: String) {
         if name.is_empty() {
@@ -449,18 +836,54 @@ impl PWApp {
                 self.ui
                     .show_dialog(DialogPopup::new_info(&format!("Failed to save map: {}", e)));
             } else {
+                self.editor.map_edited = false;
+                GameMap::remove_autosave();
                 self.ui
                     .show_dialog(DialogPopup::new_info("Map saved successfully."));
             }
         }
     }
 
+    /// Checks the map symmetry, using the editor's symmetry mode if set or the detected one otherwise.
+    fn handle_symmetry_check_request(&mut self) {
+        let (mode, score) = if self.editor.symmetry_mode != SymmetryMode::None {
+            let mode = self.editor.symmetry_mode;
+            (mode, self.simulation.map.symmetry_score(mode))
+        } else {
+            self.simulation.map.detect_symmetry()
+        };
+
+        if mode == SymmetryMode::None {
+            self.ui
+                .show_dialog(DialogPopup::new_info("No symmetry applies to this map."));
+        } else if score >= 1.0 {
+            self.ui.show_dialog(DialogPopup::new_info(&format!(
+                "The map is perfectly symmetric (\"{}\").",
+                mode.label()
+            )));
+        } else {
+            self.ui
+                .show_dialog(DialogPopup::new_enforce_symmetry(mode, score));
+        }
+    }
+
     /// Handles the request to load a map from file.
     fn handle_load_map_request(&mut self, name: String) {
         if name.is_empty() {
             match GameMap::list_maps() {
                 Ok(map_list) if !map_list.is_empty() => {
-                    self.ui.show_dialog(DialogPopup::new_map_picker(map_list));
+                    let thumbnails = map_list
+                        .iter()
+                        .map(|map_name| {
+                            GameMap::load_thumbnail(map_name, MAP_THUMBNAIL_SIZE)
+                                .map_err(|e| {
+                                    eprintln!("Failed to load thumbnail of '{}': {}", map_name, e)
+                                })
+                                .ok()
+                        })
+                        .collect();
+                    self.ui
+                        .show_dialog(DialogPopup::new_map_picker(map_list, thumbnails));
                 }
                 Ok(_) => {
                     self.ui
@@ -474,14 +897,8 @@ impl PWApp {
                 }
             }
         } else {
-            match GameMap::load_map(&name) {
-                Ok(new_game_map) => {
-                    let width = new_game_map.width;
-                    let height = new_game_map.height;
-                    self.simulation.map = new_game_map;
-                    self.simulation.colonies.clear();
-                    self.renderer.reset(width, height);
-                    self.editor.color_palette.update_selection(&self.simulation);
+            match self.load_map(&name) {
+                Ok(()) => {
                     self.ui.show_dialog(DialogPopup::new_info("Map loaded."));
                 }
                 Err(e) => {
@@ -492,14 +909,67 @@ impl PWApp {
         }
     }
 
+    /// Replaces the current map with the named map file, removing all colonies.
+    /// Unsaved edits of the current map are autosaved first, as the loaded map has none.
+    fn load_map(&mut self, name: &str) -> std::io::Result<()> {
+        let new_game_map = GameMap::load_map(name)?;
+        if name != AUTOSAVE_MAP_NAME {
+            self.autosave_edits();
+        }
+        self.editor.map_edited = false;
+        let width = new_game_map.width;
+        let height = new_game_map.height;
+        self.simulation.map = new_game_map;
+        self.simulation.clear_colonies();
+        self.renderer.reset(width, height);
+        self.editor.color_palette.update_selection(&self.simulation);
+        Ok(())
+    }
+
+    /// Handles the request to start a quick match on a map with the given players.
+    fn handle_quick_match_request(&mut self, map_name: &str, players: &[String]) {
+        if let Err(e) = self.load_map(map_name) {
+            self.ui
+                .show_dialog(DialogPopup::new_info(&format!("Failed to load map: {}", e)));
+            return;
+        }
+
+        // Pick up brains added since startup
+        self.simulation.player_configs = load_player_configs();
+        self.editor = EditorManager::new(
+            &self.simulation.player_configs,
+            self.simulation.config.max_colonies,
+        );
+
+        if let Err(e) = Self::spawn_players_at_placeholders(&mut self.simulation, players) {
+            self.simulation.clear_colonies();
+            self.ui.show_dialog(DialogPopup::new_info(&format!(
+                "Failed to start quick match: {}",
+                e
+            )));
+            return;
+        }
+
+        self.editor.color_palette.update_selection(&self.simulation);
+        self.simulation.unpause();
+    }
+
     /// Renders the current game state and UI.
     fn render(&mut self) {
         // Set the background color and camera for rendering game
         clear_background(Color::from_hex(0x181820));
+        self.renderer.game_camera.update(get_frame_time());
         set_camera(&self.renderer.game_camera.camera);
+        if std::mem::take(&mut self.simulation.walls_changed) {
+            self.renderer.mark_dirty();
+        }
 
         let pheromone_mode = self.ui.pheromone_display_mode();
         let show_ants = self.ui.show_ants(); // Get ant visibility state
+        let show_sense_cone = self.ui.show_sense_cone();
+        let show_lay_pulses = self.ui.show_lay_pulses();
+        let show_combat_effects = self.ui.show_combat_effects();
+        let view_mode = self.ui.view_mode();
 
         // Get selected ant *reference* via UIManager for rendering highlight
         let selected_ant_ref_for_render = self.ui.get_selected_ant_ref();
@@ -509,28 +979,137 @@ impl PWApp {
             pheromone_mode,
             selected_ant_ref_for_render,
             show_ants,
+            show_sense_cone,
+            show_lay_pulses,
+            show_combat_effects,
+            view_mode,
         );
 
         // Render tool preview with the same camera if a tool is selected
-        if self.editor.current_tool().is_some() {
+        if self.editor.current_tool().is_some() && self.replay.is_none() {
             // Get world position directly from the camera
             let world_pos = self.renderer.game_camera.get_mouse_world_pos();
-            self.editor.render_tool_preview(world_pos);
+            self.editor.render_tool_preview(world_pos, &self.simulation);
+        }
+
+        if let Some(compare) = &mut self.compare {
+            compare.renderer.game_camera.update(get_frame_time());
+            if std::mem::take(&mut compare.simulation.walls_changed) {
+                compare.renderer.mark_dirty();
+            }
+            compare.renderer.render(
+                &compare.simulation,
+                pheromone_mode,
+                None,
+                show_ants,
+                false,
+                show_lay_pulses,
+                show_combat_effects,
+                view_mode,
+            );
         }
 
         // Switch to default camera for UI rendering
         set_default_camera();
 
+        if self.compare.is_some() {
+            let split_x = (screen_width() / 2.0).floor();
+            draw_line(
+                split_x,
+                0.0,
+                split_x,
+                screen_height(),
+                2.0,
+                Color::from_hex(0x181820),
+            );
+        }
+
         // Render UI
         self.ui.render();
     }
 
+    /// Re-reads the config file and applies its settings to the running match, reporting
+    /// the changed settings or why nothing was applied.
+    fn reload_config(&mut self) {
+        if self.evaluate_mode {
+            self.ui.show_dialog(DialogPopup::new_info(
+                "Config reload is disabled in evaluate mode.",
+            ));
+            return;
+        }
+        let Some(path) = &self.config_path else {
+            self.ui
+                .show_dialog(DialogPopup::new_info("No config file to reload."));
+            return;
+        };
+        let config = match SimulationConfig::from_file(path) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Warning: {}", e);
+                self.ui.show_dialog(DialogPopup::new_info(&e));
+                return;
+            }
+        };
+
+        let describe = |result: Result<Vec<String>, Vec<ConfigError>>| match result {
+            Ok(changes) if changes.is_empty() => "No setting changed.".to_string(),
+            Ok(changes) => format!("Changed settings:\n{}", changes.join("\n")),
+            Err(errors) => format!(
+                "Invalid config, nothing applied:\n{}",
+                errors
+                    .iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+        };
+        // Both matches of compare mode play by the same settings
+        let compare_message = self
+            .compare
+            .as_mut()
+            .map(|compare| describe(compare.simulation.reload_config(config.clone())));
+        let mut message = describe(self.simulation.reload_config(config));
+        if let Some(compare_message) = compare_message {
+            message = format!("{}\n\nCompare match: {}", message, compare_message);
+        }
+        println!("Config reloaded from '{}'. {}", path.display(), message);
+        self.ui
+            .show_dialog(DialogPopup::new_info_with_title("Config reload", &message));
+    }
+
     /// Resets the application to its initial state with the current map.
     fn reset(&mut self) {
         self.simulation.reset();
-        self.editor = EditorManager::new(&self.simulation.player_configs);
+        self.editor = EditorManager::new(
+            &self.simulation.player_configs,
+            self.simulation.config.max_colonies,
+        );
         self.renderer
             .reset(self.simulation.map.width, self.simulation.map.height);
         self.editor.color_palette.update_selection(&self.simulation);
     }
 }
+
+/// Advances a simulation by `sim_dt` seconds, in steps of at most one think interval.
+fn advance_simulation(simulation: &mut Simulation, mut sim_dt: f32) {
+    while sim_dt > 0.0 {
+        let step = sim_dt.min(THINK_INTERVAL);
+        simulation.update(step);
+        sim_dt -= step;
+    }
+}
+
+/// Formats placements one per line, e.g. `2. name (eliminated at tick 1200)`.
+pub(crate) fn format_placements(placements: &[Placement]) -> String {
+    placements
+        .iter()
+        .map(|placement| match placement.eliminated_at {
+            Some(tick) => format!(
+                "{}. {} (eliminated at tick {})",
+                placement.place, placement.name, tick
+            ),
+            None => format!("{}. {}", placement.place, placement.name),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
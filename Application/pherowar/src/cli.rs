@@ -0,0 +1,115 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+use crate::headless::DEFAULT_RUN_MAX_TICKS;
+use crate::simulation::DEFAULT_ASCII_MAX_WIDTH;
+
+/// Command-line arguments for PheroWar.
+#[derive(Parser)]
+#[command(name = "PheroWar", version, about = "PheroWar Simulation")]
+pub struct Cli {
+    /// Path to the TOML configuration file.
+    #[arg(short, long, default_value = "./Application/config.toml")]
+    pub config: Option<PathBuf>,
+
+    /// Name of the map to load. Example: "Relic", "Labyrinth".
+    /// "random" picks one from the config's `maps` list or the maps directory.
+    #[arg(short, long)]
+    pub map: Option<String>,
+
+    /// List of colony players to spawn (player names separated by commas).
+    /// A name may be repeated to pit a player against itself.
+    #[arg(short = 'p', long, value_delimiter = ',')]
+    pub players: Option<Vec<String>>,
+
+    /// Compare mode: run the map a second time with these players, shown side by side
+    /// with the `--players` match in split-screen.
+    #[arg(long, value_delimiter = ',')]
+    pub compare: Option<Vec<String>>,
+
+    /// Lock the view to spectator mode, disabling per-colony fog views (e.g. for streamed matches).
+    #[arg(long)]
+    pub spectator_lock: bool,
+
+    /// Initial window width in pixels, overriding the config's `[window]` section.
+    #[arg(long)]
+    pub width: Option<u32>,
+
+    /// Initial window height in pixels, overriding the config's `[window]` section.
+    #[arg(long)]
+    pub height: Option<u32>,
+
+    /// Start in fullscreen.
+    #[arg(long)]
+    pub fullscreen: bool,
+
+    /// Development only: never kill ants for slow brain calls, e.g. to step through a brain
+    /// in a debugger. Can't be combined with `--evaluate`.
+    #[arg(long)]
+    pub no_timeout: bool,
+
+    /// Development only: run each player's `<name>.build` command on the host before its
+    /// container starts. Can't be combined with `--evaluate`.
+    #[arg(long)]
+    pub build_brains: bool,
+
+    /// Seed of the match's random streams, overriding the config's `rng_seed`, to replay a match.
+    /// Capped like TOML integers, so the seed can be written back to a config.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(..=i64::MAX as u64))]
+    pub seed: Option<u64>,
+
+    /// Write each colony's ants and food once per simulation second to this TSV file.
+    #[arg(long)]
+    pub population_log: Option<PathBuf>,
+
+    /// Write each colony's match statistics as JSON to this file once the match is decided.
+    #[arg(long)]
+    pub stats_out: Option<PathBuf>,
+
+    /// Record the match tick by tick to this file, to watch it again with `--replay`.
+    #[arg(long)]
+    pub record: Option<PathBuf>,
+
+    /// Watch a match recorded with `--record`, without starting any player. Pause and speed
+    /// controls work as in a live match.
+    #[arg(long)]
+    pub replay: Option<PathBuf>,
+
+    /// Run the `--map`/`--players` match without a window, like `run`, and print the results
+    /// as JSON.
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Evaluate mode: auto-start and exit when there is a winner. Requires players to be set and >= 2.
+    #[arg(long)]
+    pub evaluate: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Tools run from the command line, without opening a window.
+#[derive(Subcommand)]
+pub enum Command {
+    /// Print a map as ASCII art: `#` walls, `*` food, `@` colonies, `.` empty.
+    DumpMap {
+        /// Name of the map file in the maps directory.
+        name: String,
+
+        /// Widest output in characters. Larger maps are downsampled.
+        #[arg(long, default_value_t = DEFAULT_ASCII_MAX_WIDTH)]
+        max_width: usize,
+    },
+
+    /// Run the `--map`/`--players` match without a window and print the placements.
+    Run {
+        /// Ticks after which the match stops if no single colony remains.
+        #[arg(long, default_value_t = DEFAULT_RUN_MAX_TICKS)]
+        max_ticks: u32,
+
+        /// Print the results as a single JSON object.
+        #[arg(long)]
+        json: bool,
+    },
+}
@@ -2,27 +2,847 @@ use bincode_derive::{Decode, Encode};
 use macroquad::prelude::Conf;
 use serde::Deserialize;
 use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::simulation::{
+    GameMap, MAX_ANT_LONGEVITY, MAX_BEACON_EMISSION_RATE, MAX_BEACONS, MAX_PROCESSING_TIME_LIMIT,
+    SeededRng,
+};
 
 // Window constants
 pub const DEFAULT_WINDOW_WIDTH: f32 = 1920.0;
 pub const DEFAULT_WINDOW_HEIGHT: f32 = 1080.0;
+pub const DEFAULT_UNLIMITED_RENDER_FPS: f32 = 60.0;
+pub const MIN_UNLIMITED_RENDER_FPS: f32 = 10.0; // Keeps the UI responsive enough to pause
+
+// Camera constants
+pub const DEFAULT_MIN_ZOOM: f32 = 1.0; // 1.0 = full map view
+pub const DEFAULT_MAX_ZOOM: f32 = 50.0;
+pub const DEFAULT_ZOOM_SMOOTHING: f32 = 0.0; // 0.0 = instant zoom steps
+
+// Player container constants
+pub const MAX_CONTAINER_START_TIMEOUT: f32 = 600.0; // Longest wait for a container's socket, in seconds
 
 // Directory path constants
 pub const MAPS_DIR: &str = "./Application/maps/";
+/// Map file the editor autosaves to, in `MAPS_DIR`, hidden from the map lists
+pub const AUTOSAVE_MAP_NAME: &str = ".autosave.map";
 pub const PLAYERS_DIR: &str = "./players/";
 pub const ASSETS_DIR: &str = "./Application/assets/";
+pub const UI_PREFERENCES_PATH: &str = "./Application/ui_preferences.toml";
+
+/// `--map` value picking a random map at match start
+pub const RANDOM_MAP: &str = "random";
+
+/// Declares the settings fixed for a whole match, as `MATCH_SETTINGS` and
+/// `SimulationConfig::reloaded`, from a single list.
+macro_rules! match_settings {
+    ($($field:ident),* $(,)?) => {
+        /// Settings fixed for a whole match: a config reload keeps their running values.
+        /// Sections are listed by name and cover all their keys.
+        const MATCH_SETTINGS: &[&str] = &[$(stringify!($field)),*];
+
+        impl SimulationConfig {
+            /// `new` with the match settings of this running config.
+            #[allow(clippy::clone_on_copy)]
+            pub fn reloaded(&self, mut new: SimulationConfig) -> SimulationConfig {
+                $(new.$field = self.$field.clone();)*
+                new
+            }
+        }
+    };
+}
+
+match_settings!(
+    rng_seed,
+    maps,
+    max_colonies,
+    pheromone_resolution,
+    no_timeout,
+    build_brains,
+    camera,
+    window,
+);
 
 #[derive(Deserialize, Debug, Clone, Serialize, Encode, Decode)]
 pub struct PlayerConfig {
     pub name: String,
     pub so_path: String,
+    /// Ant texture drawn for this player's colonies instead of `assets/ant.png`
+    #[serde(default)]
+    pub texture_path: Option<String>,
+    /// Shell command building `so_path`, run from its directory before each container start
+    /// when `build_brains` is set
+    #[serde(default)]
+    pub build_command: Option<String>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct SimulationConfig {
     pub colony_initial_population: u32,
+    /// How far an ant senses friendly pheromone signals, independent of its vision range
+    #[serde(default = "default_ant_comm_range")]
+    pub ant_comm_range: f32,
+    /// Seconds during which newly spawned ants ignore damage (0.0 = off)
+    #[serde(default)]
+    pub spawn_protection: f32,
+    /// Seconds newly spawned ants stay in the nest, inactive and immune, before their
+    /// spawn protection starts (0.0 = off)
+    #[serde(default)]
+    pub emergence_delay: f32,
+    /// Whether ants can tell if the friends and enemies they sense are carrying food
+    #[serde(default = "default_sense_carried_food")]
+    pub sense_carried_food: bool,
+    /// Standard deviation of the Gaussian noise added to the ants' senses, as a fraction of
+    /// each value's range (0.0 = exact senses)
+    #[serde(default)]
+    pub sense_noise: f32,
+    /// Whether ants are given the clearest direction of their sense cone as a steering hint
+    #[serde(default)]
+    pub suggest_heading: bool,
+    /// Whether ants sense the pheromones of other colonies, read-only, besides their own
+    #[serde(default)]
+    pub sense_enemy_pheromones: bool,
+    /// Whether a colony removed mid-match leaves its pheromones on screen until they decay
+    #[serde(default)]
+    pub ghost_pheromones: bool,
+    /// Whether ants not carrying food take one unit from the stock of enemy nests they stand on
+    #[serde(default)]
+    pub food_stealing: bool,
+    /// Food stock at which the ants' `colony_food_level` input reaches 1.0
+    #[serde(default = "default_colony_food_reference")]
+    pub colony_food_reference: f32,
+    /// Distance from an ant to the center of a food cell it can pick food from.
+    /// The cell the ant stands on is always in range
+    #[serde(default = "default_food_pickup_range")]
+    pub food_pickup_range: f32,
+    /// Food units every dying ant leaves on its cell, on top of the food it carried
+    #[serde(default)]
+    pub corpse_food: u32,
+    /// Food units turned into each new ant
+    #[serde(default = "default_spawn_food_cost")]
+    pub spawn_food_cost: u32,
+    /// Longevity taken by a hit, before the rear and front modifiers
+    #[serde(default = "default_attack_damage")]
+    pub attack_damage: f32,
+    /// Milliseconds a brain call may take before the ant dies
+    #[serde(default = "default_max_processing_time")]
+    pub max_processing_time: f32,
+    /// Per-player overrides of `max_processing_time`, keyed by player name
+    #[serde(default)]
+    pub player_max_processing_time: HashMap<String, f32>,
+    /// Development only: never kill ants for slow brain calls, e.g. to step through a brain
+    /// in a debugger. Refused in evaluate mode
+    #[serde(default)]
+    pub no_timeout: bool,
+    /// Development only: run each player's `<name>.build` command on the host before its
+    /// container starts. Refused in evaluate mode
+    #[serde(default)]
+    pub build_brains: bool,
+    /// Seconds a player container has to open its socket before its colony fails to spawn
+    #[serde(default = "default_container_start_timeout")]
+    pub container_start_timeout: f32,
+    /// Seed of the colonies' random streams (perception, spawns). Unset = random each run
+    #[serde(default)]
+    pub rng_seed: Option<u64>,
+    /// Map files a random map is picked from at match start (empty = the whole maps directory)
+    #[serde(default)]
+    pub maps: Vec<String>,
+    /// Longevity below which ants ignore their brain's heading and walk straight home (0.0 = off)
+    #[serde(default)]
+    pub flee_longevity: f32,
+    /// Whether ants can attack ants of their own colony
+    #[serde(default)]
+    pub friendly_fire: bool,
+    /// Extra damage fraction dealt by attacks from straight behind the target (0.0 = flat damage)
+    #[serde(default)]
+    pub rear_attack_bonus: f32,
+    /// Damage fraction avoided by targets facing their attacker (0.0 = flat damage)
+    #[serde(default)]
+    pub front_attack_reduction: f32,
+    /// Largest angle (radians) between an ant's facing and the direction to an ant it starts a
+    /// fight with (PI = any direction)
+    #[serde(default = "default_max_attack_angle")]
+    pub max_attack_angle: f32,
+    /// Largest angle (radians) between an ant's facing and its movement direction (0.0 = forward only)
+    #[serde(default = "default_max_move_offset")]
+    pub max_move_offset: f32,
+    /// Speed factor of ants moving straight backward, scaled down linearly from 1.0 moving forward
+    #[serde(default = "default_backward_speed")]
+    pub backward_speed: f32,
+    /// Maximum number of colonies in a match
+    #[serde(default = "default_max_colonies")]
+    pub max_colonies: usize,
+    /// Radius of colony nests, in tiles
+    #[serde(default = "default_nest_radius")]
+    pub nest_radius: f32,
+    /// Tiles colonies are randomly moved away from their nest placeholders at match start (0.0 = off)
+    #[serde(default)]
+    pub spawn_jitter: f32,
+    /// Maximum total pheromone mass per colony, across all channels (0.0 = unlimited)
+    #[serde(default)]
+    pub pheromone_budget: f32,
+    /// What happens to deposits that would exceed `pheromone_budget`
+    #[serde(default)]
+    pub pheromone_overflow: PheromoneOverflow,
+    /// Spread each deposit over the cells crossed since the last think tick, instead of the current cell
+    #[serde(default)]
+    pub pheromone_trail: bool,
+    /// Pheromone amounts below this are cleared to 0.0 on decay
+    #[serde(default = "default_pheromone_threshold")]
+    pub pheromone_threshold: f32,
+    /// Map tiles per pheromone cell side: 2 stores one value per 2x2 tiles, cutting memory by 4
+    #[serde(default = "default_pheromone_resolution")]
+    pub pheromone_resolution: u32,
+    /// Seconds between autosaves of an edited map (0.0 = off)
+    #[serde(default = "default_map_autosave_interval")]
+    pub map_autosave_interval: f32,
+    /// Estimated memory above which colonies aren't spawned, in MB (0 = no limit)
+    #[serde(default = "default_max_memory_mb")]
+    pub max_memory_mb: u32,
+    /// What happens to ants moving off the map
+    #[serde(default)]
+    pub edge_behavior: EdgeBehavior,
+    /// Whether ants move continuously or cell to cell
+    #[serde(default)]
+    pub movement: MovementMode,
+    /// Neighbor cells pheromones diffuse to, on channels given a `diffusion_rates` entry
+    #[serde(default)]
+    pub diffusion_neighborhood: DiffusionNeighborhood,
+    /// What an ant does on a think tick whose brain call failed
+    #[serde(default)]
+    pub think_error_fallback: ThinkErrorFallback,
+    /// What happens to ants on a cell when a wall is placed over it
+    #[serde(default)]
+    pub wall_crush: WallCrushPolicy,
+    /// Order in which colonies are updated each tick
+    #[serde(default)]
+    pub colony_order: ColonyOrder,
+    #[serde(default)]
+    pub think_events: ThinkEventsConfig,
+    #[serde(default)]
+    pub trophallaxis: TrophallaxisConfig,
+    #[serde(default)]
+    pub sudden_death: SuddenDeathConfig,
+    #[serde(default)]
+    pub beacons: BeaconsConfig,
+    #[serde(default)]
+    pub camera: CameraConfig,
+    #[serde(default)]
+    pub window: WindowConfig,
+}
+
+impl SimulationConfig {
+    /// Max brain call time of the player's colonies in nanoseconds, its override if any.
+    /// None when `no_timeout` is set.
+    pub fn max_processing_time_for(&self, player_name: &str) -> Option<u128> {
+        (!self.no_timeout).then(|| self.think_budget_for(player_name))
+    }
+
+    /// Brain call time budget of the player's colonies in nanoseconds, its override if any,
+    /// whether or not it is enforced.
+    pub fn think_budget_for(&self, player_name: &str) -> u128 {
+        let millis = self
+            .player_max_processing_time
+            .get(player_name)
+            .copied()
+            .unwrap_or(self.max_processing_time);
+        (millis as f64 * 1_000_000.0) as u128
+    }
+
+    /// Reads a config file, describing why it can't be read or parsed.
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config file '{}': {}", path.display(), e))?;
+        toml::from_str(&content).map_err(|e| format!("Failed to parse config file: {}", e))
+    }
+
+    /// `field: old -> new` for every setting differing in `new`, sections as `section.field`.
+    /// Match settings are marked as kept until the next match.
+    pub fn changes_to(&self, new: &SimulationConfig) -> Vec<String> {
+        let (old, new) = match (flat_settings(self), flat_settings(new)) {
+            (Ok(old), Ok(new)) => (old, new),
+            (Err(e), _) | (_, Err(e)) => {
+                return vec![format!("(the changed settings can't be listed: {})", e)];
+            }
+        };
+        let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+        keys.into_iter()
+            .filter(|key| old.get(*key) != new.get(*key))
+            .map(|key| {
+                let show = |value: Option<&toml::Value>| match value {
+                    // Widened from f32, shown back at f32 precision
+                    Some(toml::Value::Float(f)) => (*f as f32).to_string(),
+                    Some(value) => value.to_string(),
+                    None => "unset".to_string(),
+                };
+                let kept = if is_match_setting(key) {
+                    " (kept until the next match)"
+                } else {
+                    ""
+                };
+                format!(
+                    "{}: {} -> {}{}",
+                    key,
+                    show(old.get(key)),
+                    show(new.get(key)),
+                    kept
+                )
+            })
+            .collect()
+    }
+
+    /// Checks the numeric settings, returning one error per out-of-range value.
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+        let mut check = |field: &str, value: f32, valid: bool, expected: &'static str| {
+            // NaN fails every comparison, so it is reported too
+            if !valid {
+                errors.push(ConfigError::InvalidValue {
+                    field: field.to_string(),
+                    value,
+                    expected,
+                });
+            }
+        };
+        let pi = std::f32::consts::PI;
+        let v = self.ant_comm_range;
+        check("ant_comm_range", v, v >= 0.0, ">= 0");
+        let v = self.spawn_protection;
+        check("spawn_protection", v, v >= 0.0, ">= 0");
+        let v = self.emergence_delay;
+        check("emergence_delay", v, v >= 0.0, ">= 0");
+        let v = self.colony_food_reference;
+        check("colony_food_reference", v, v > 0.0, "> 0");
+        let v = self.food_pickup_range;
+        check("food_pickup_range", v, v >= 0.0, ">= 0");
+        let v = self.max_processing_time;
+        check(
+            "max_processing_time",
+            v,
+            v > 0.0 && v <= MAX_PROCESSING_TIME_LIMIT,
+            "> 0 and <= 100",
+        );
+        let mut overrides: Vec<_> = self.player_max_processing_time.iter().collect();
+        overrides.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        for (player, &v) in overrides {
+            check(
+                &format!("player_max_processing_time.{}", player),
+                v,
+                v > 0.0 && v <= MAX_PROCESSING_TIME_LIMIT,
+                "> 0 and <= 100",
+            );
+        }
+        let v = self.container_start_timeout;
+        check(
+            "container_start_timeout",
+            v,
+            v > 0.0 && v <= MAX_CONTAINER_START_TIMEOUT,
+            "> 0 and <= 600",
+        );
+        let v = self.spawn_food_cost as f32;
+        check("spawn_food_cost", v, v >= 1.0, ">= 1");
+        let v = self.attack_damage;
+        check("attack_damage", v, v >= 0.0, ">= 0");
+        let v = self.rear_attack_bonus;
+        check("rear_attack_bonus", v, v >= 0.0, ">= 0");
+        let v = self.front_attack_reduction;
+        check(
+            "front_attack_reduction",
+            v,
+            (0.0..=1.0).contains(&v),
+            "between 0 and 1",
+        );
+        let v = self.max_attack_angle;
+        check(
+            "max_attack_angle",
+            v,
+            (0.0..=pi).contains(&v),
+            "between 0 and PI",
+        );
+        let v = self.max_colonies as f32;
+        check("max_colonies", v, v >= 2.0, ">= 2");
+        let v = self.nest_radius;
+        check("nest_radius", v, v > 0.0, "> 0");
+        let v = self.sense_noise;
+        check("sense_noise", v, v >= 0.0, ">= 0 (0 = off)");
+        let v = self.flee_longevity;
+        check(
+            "flee_longevity",
+            v,
+            (0.0..=MAX_ANT_LONGEVITY).contains(&v),
+            "0 to MAX_ANT_LONGEVITY (0 = off)",
+        );
+        let v = self.spawn_jitter;
+        check("spawn_jitter", v, v >= 0.0, ">= 0");
+        let v = self.map_autosave_interval;
+        check("map_autosave_interval", v, v >= 0.0, ">= 0 (0 = off)");
+        let v = self.pheromone_budget;
+        check("pheromone_budget", v, v >= 0.0, ">= 0 (0 = unlimited)");
+        let v = self.pheromone_threshold;
+        check("pheromone_threshold", v, v >= 0.0, ">= 0");
+        let v = self.pheromone_resolution as f32;
+        check("pheromone_resolution", v, v >= 1.0, ">= 1");
+        let v = self.max_move_offset;
+        check(
+            "max_move_offset",
+            v,
+            (0.0..=pi).contains(&v),
+            "between 0 and PI",
+        );
+        let v = self.backward_speed;
+        check(
+            "backward_speed",
+            v,
+            (0.0..=1.0).contains(&v),
+            "between 0 and 1",
+        );
+        let v = self.think_events.interval;
+        check("think_events.interval", v, v > 0.0, "> 0");
+        let v = self.trophallaxis.memory_blend;
+        check(
+            "trophallaxis.memory_blend",
+            v,
+            (0.0..=1.0).contains(&v),
+            "between 0 and 1",
+        );
+        let v = self.sudden_death.shrink_interval as f32;
+        check("sudden_death.shrink_interval", v, v >= 1.0, ">= 1");
+        let v = self.beacons.max_count as f32;
+        check("beacons.max_count", v, v <= MAX_BEACONS as f32, "<= 16");
+        let v = self.beacons.emission_rate;
+        check(
+            "beacons.emission_rate",
+            v,
+            (0.0..=MAX_BEACON_EMISSION_RATE).contains(&v),
+            "between 0 and 50",
+        );
+        let v = self.camera.min_zoom;
+        check("camera.min_zoom", v, v > 0.0, "> 0");
+        let v = self.camera.max_zoom;
+        check(
+            "camera.max_zoom",
+            v,
+            v >= self.camera.min_zoom,
+            ">= camera.min_zoom",
+        );
+        let v = self.camera.zoom_smoothing;
+        check("camera.zoom_smoothing", v, v >= 0.0, ">= 0");
+        let v = self.window.unlimited_render_fps;
+        check(
+            "window.unlimited_render_fps",
+            v,
+            v >= MIN_UNLIMITED_RENDER_FPS,
+            ">= 10",
+        );
+        errors
+    }
+}
+
+fn is_match_setting(key: &str) -> bool {
+    MATCH_SETTINGS.iter().any(|setting| {
+        key.strip_prefix(setting)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+    })
+}
+
+/// Every setting of the config by dotted name, e.g. `beacons.max_count`.
+fn flat_settings(
+    config: &SimulationConfig,
+) -> Result<BTreeMap<String, toml::Value>, toml::ser::Error> {
+    fn flatten(prefix: String, value: toml::Value, out: &mut BTreeMap<String, toml::Value>) {
+        match value {
+            toml::Value::Table(table) => {
+                for (key, value) in table {
+                    let key = if prefix.is_empty() {
+                        key
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    flatten(key, value, out);
+                }
+            }
+            value => {
+                out.insert(prefix, value);
+            }
+        }
+    }
+    let mut out = BTreeMap::new();
+    // A seed above i64::MAX has no TOML integer, so it is compared as text
+    let seedless = SimulationConfig {
+        rng_seed: None,
+        ..config.clone()
+    };
+    flatten(String::new(), toml::Value::try_from(&seedless)?, &mut out);
+    if let Some(seed) = config.rng_seed {
+        let value = i64::try_from(seed)
+            .map(toml::Value::Integer)
+            .unwrap_or_else(|_| toml::Value::String(seed.to_string()));
+        out.insert("rng_seed".to_string(), value);
+    }
+    Ok(out)
+}
+
+/// Problem found while validating the configuration and the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    /// A numeric setting is out of its valid range
+    InvalidValue {
+        field: String,
+        value: f32,
+        expected: &'static str,
+    },
+    /// A player named on the command line has no brain in the players directory
+    MissingPlayer(String),
+    /// The map can't be loaded, or no random map fits
+    BadMap { map: String, reason: String },
+    /// The map doesn't have one colony placeholder per player
+    PlayerCountMismatch {
+        map: String,
+        placeholders: usize,
+        players: usize,
+    },
+    /// More players than `max_colonies` allows
+    TooManyPlayers { players: usize, max_colonies: usize },
+    /// Invalid combination of command line options
+    InvalidArguments(String),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::InvalidValue {
+                field,
+                value,
+                expected,
+            } => write!(f, "`{}` is {} but must be {}", field, value, expected),
+            ConfigError::MissingPlayer(name) => {
+                write!(f, "Player '{}' not found in {}", name, PLAYERS_DIR)
+            }
+            ConfigError::BadMap { map, reason } => write!(f, "Map '{}': {}", map, reason),
+            ConfigError::PlayerCountMismatch {
+                map,
+                placeholders,
+                players,
+            } => write!(
+                f,
+                "Colony count mismatch: Map '{}' expects {} colonies but {} players were provided",
+                map, placeholders, players
+            ),
+            ConfigError::TooManyPlayers {
+                players,
+                max_colonies,
+            } => write!(
+                f,
+                "{} players were provided but `max_colonies` is {}",
+                players, max_colonies
+            ),
+            ConfigError::InvalidArguments(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+fn default_spawn_food_cost() -> u32 {
+    crate::simulation::DEFAULT_ANT_SPAWN_FOOD_COST
+}
+
+fn default_attack_damage() -> f32 {
+    crate::simulation::DEFAULT_ANT_ATTACK_DAMAGE
+}
+
+fn default_max_colonies() -> usize {
+    crate::simulation::MAX_COLONIES
+}
+
+fn default_pheromone_threshold() -> f32 {
+    crate::simulation::DEFAULT_PHEROMONE_THRESHOLD
+}
+
+fn default_pheromone_resolution() -> u32 {
+    1
+}
+
+fn default_ant_comm_range() -> f32 {
+    crate::simulation::DEFAULT_ANT_COMM_RANGE
+}
+
+fn default_max_move_offset() -> f32 {
+    crate::simulation::DEFAULT_MAX_MOVE_OFFSET
+}
+
+fn default_max_attack_angle() -> f32 {
+    std::f32::consts::PI
+}
+
+fn default_backward_speed() -> f32 {
+    crate::simulation::DEFAULT_BACKWARD_SPEED
+}
+
+fn default_nest_radius() -> f32 {
+    crate::simulation::COLONY_NEST_SIZE / 2.0
+}
+
+fn default_map_autosave_interval() -> f32 {
+    60.0
+}
+
+fn default_max_memory_mb() -> u32 {
+    4096
+}
+
+fn default_sense_carried_food() -> bool {
+    true
+}
+
+fn default_food_pickup_range() -> f32 {
+    crate::simulation::DEFAULT_FOOD_PICKUP_RANGE
+}
+
+fn default_max_processing_time() -> f32 {
+    crate::simulation::MAX_ANT_PROCESSING_TIME as f32 / 1_000_000.0
+}
+
+fn default_container_start_timeout() -> f32 {
+    3.0
+}
+
+fn default_colony_food_reference() -> f32 {
+    crate::simulation::DEFAULT_COLONY_FOOD_REFERENCE
+}
+
+/// Policy applied when a colony's pheromone deposit would exceed its budget.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PheromoneOverflow {
+    /// Drop the whole deposit
+    Reject,
+    /// Scale the deposit down to what is left of the budget
+    #[default]
+    Scale,
+}
+
+/// What happens to an ant moving past the map's edge.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeBehavior {
+    /// The edge blocks the ant like a wall
+    #[default]
+    Wall,
+    /// The ant falls off the map and dies
+    Death,
+    /// The ant bounces off the edge, its heading mirrored
+    Bounce,
+}
+
+/// How ants move across the map.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MovementMode {
+    /// Ants move every tick by their speed, anywhere within cells
+    #[default]
+    Continuous,
+    /// Ants stand on cell centers and step to a neighboring cell on each think tick
+    Grid,
+}
+
+/// Neighbor cells a pheromone cell diffuses to.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffusionNeighborhood {
+    /// The 4 cells sharing a side
+    #[default]
+    Four,
+    /// The 8 cells sharing a side or a corner
+    Eight,
+}
+
+/// What an ant does when its brain call fails without killing it (timeouts always kill).
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ThinkErrorFallback {
+    /// Skip the think tick: no turn, deposit or move this tick
+    #[default]
+    Skip,
+    /// Reuse the ant's last successful output, so it keeps turning and moving as before
+    Coast,
+}
+
+/// What happens to ants crushed by a wall placed over their cell.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WallCrushPolicy {
+    /// The ant is replaced by a fresh one at its nest
+    #[default]
+    Respawn,
+    /// The ant dies, dropping its food on the nearest free cell
+    Kill,
+    /// The ant is moved to the nearest free cell, keeping its state
+    Relocate,
+}
+
+/// Order in which colonies are updated within a tick.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ColonyOrder {
+    /// A new random order every tick, drawn from its own stream seeded by `rng_seed`
+    #[default]
+    Shuffle,
+    /// Always by increasing colony id
+    Fixed,
+}
+
+/// Event-driven thinking, read from the `[think_events]` section of the config file.
+/// When enabled, ants think on the selected events plus every `interval` seconds.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct ThinkEventsConfig {
+    pub enabled: bool,
+    /// Background think interval in seconds, replacing `THINK_INTERVAL` (never shorter)
+    pub interval: f32,
+    /// Think when entering or leaving food
+    pub on_food: bool,
+    /// Think when entering or leaving the nest
+    pub on_colony: bool,
+    /// Think when an enemy shows up in the ant's cell
+    pub on_enemy: bool,
+    /// Think when bumping into a wall
+    pub on_wall: bool,
+}
+
+impl ThinkEventsConfig {
+    /// Whether a change of the food or nest status of an ant forces a think.
+    /// Outside of event mode, only entering does.
+    pub fn forces_think(&self, trigger: bool, entered: bool) -> bool {
+        if self.enabled { trigger } else { entered }
+    }
+}
+
+impl Default for ThinkEventsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: crate::simulation::DEFAULT_EVENT_THINK_INTERVAL,
+            on_food: true,
+            on_colony: true,
+            on_enemy: true,
+            on_wall: true,
+        }
+    }
+}
+
+/// Sharing between friendly ants meeting in a cell, read from the `[trophallaxis]` section.
+/// An ant entering a cell exchanges with at most one friend there, and each ant once per tick.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct TrophallaxisConfig {
+    pub enabled: bool,
+    /// Whether a carrying ant hands its food over to a non-carrying one
+    pub share_food: bool,
+    /// How far both memories move toward their byte-wise average (0.0 = untouched, 1.0 = averaged)
+    pub memory_blend: f32,
+}
+
+impl Default for TrophallaxisConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            share_food: true,
+            memory_blend: 0.0,
+        }
+    }
+}
+
+/// Colony beacons planted by ants, read from the `[beacons]` section of the config file.
+/// Each beacon lays `emission_rate` pheromone per second on its cell until removed.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct BeaconsConfig {
+    pub enabled: bool,
+    /// Beacons a colony may have at once, planting more replaces the oldest
+    pub max_count: usize,
+    pub emission_rate: f32,
+}
+
+impl Default for BeaconsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_count: 4,
+            emission_rate: 10.0,
+        }
+    }
+}
+
+/// Endgame forcing a decision, read from the `[sudden_death]` section of the config file.
+/// From `start_tick` on, colonies stop spawning ants and the map shrinks by one ring of
+/// walls every `shrink_interval` ticks.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct SuddenDeathConfig {
+    pub enabled: bool,
+    pub start_tick: u32,
+    pub shrink_interval: u32,
+}
+
+impl Default for SuddenDeathConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start_tick: 36_000,
+            shrink_interval: 60,
+        }
+    }
+}
+
+/// Camera settings, read from the `[camera]` section of the config file.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct CameraConfig {
+    /// Minimum zoom level (1.0 = full map view)
+    pub min_zoom: f32,
+    /// Maximum zoom level
+    pub max_zoom: f32,
+    /// Time constant (in seconds) of the zoom interpolation. 0.0 disables smoothing.
+    pub zoom_smoothing: f32,
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
+        Self {
+            min_zoom: DEFAULT_MIN_ZOOM,
+            max_zoom: DEFAULT_MAX_ZOOM,
+            zoom_smoothing: DEFAULT_ZOOM_SMOOTHING,
+        }
+    }
+}
+
+/// Initial window, read from the `[window]` section of the config file.
+/// The `--width`, `--height` and `--fullscreen` command line options take precedence.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct WindowConfig {
+    pub width: u32,
+    pub height: u32,
+    pub fullscreen: bool,
+    /// Frames rendered per second at unlimited speed; the simulation runs flat-out in between
+    pub unlimited_render_fps: f32,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            width: DEFAULT_WINDOW_WIDTH as u32,
+            height: DEFAULT_WINDOW_HEIGHT as u32,
+            fullscreen: false,
+            unlimited_render_fps: DEFAULT_UNLIMITED_RENDER_FPS,
+        }
+    }
 }
 
 /// Configuration for the entire application including CLI parameters
@@ -32,29 +852,156 @@ pub struct AppConfig {
     pub player_configs: Vec<PlayerConfig>,
     pub map_name: Option<String>,
     pub evaluate: bool,
+    /// Players of the second simulation in compare mode
+    pub compare_players: Option<Vec<String>>,
+    /// Keep the view on spectator mode
+    pub spectator_lock: bool,
+    /// Config file the simulation settings were read from, re-read by a config reload
+    pub config_path: Option<PathBuf>,
+    /// File the main simulation's population samples are written to
+    pub population_log: Option<PathBuf>,
+    /// File the per-colony stats are written to when the match is decided
+    pub stats_out: Option<PathBuf>,
+    /// File the main simulation's match is recorded to
+    pub record: Option<PathBuf>,
+    /// Recording played instead of a live match
+    pub replay: Option<PathBuf>,
 }
 
 impl Default for SimulationConfig {
     fn default() -> Self {
         Self {
             colony_initial_population: 10000,
+            ant_comm_range: default_ant_comm_range(),
+            spawn_protection: 0.0,
+            emergence_delay: 0.0,
+            sense_carried_food: default_sense_carried_food(),
+            sense_noise: 0.0,
+            suggest_heading: false,
+            sense_enemy_pheromones: false,
+            ghost_pheromones: false,
+            food_stealing: false,
+            colony_food_reference: default_colony_food_reference(),
+            food_pickup_range: default_food_pickup_range(),
+            corpse_food: 0,
+            spawn_food_cost: default_spawn_food_cost(),
+            attack_damage: default_attack_damage(),
+            max_processing_time: default_max_processing_time(),
+            player_max_processing_time: HashMap::new(),
+            no_timeout: false,
+            build_brains: false,
+            container_start_timeout: default_container_start_timeout(),
+            rng_seed: None,
+            maps: Vec::new(),
+            flee_longevity: 0.0,
+            friendly_fire: false,
+            rear_attack_bonus: 0.0,
+            front_attack_reduction: 0.0,
+            max_attack_angle: default_max_attack_angle(),
+            max_move_offset: default_max_move_offset(),
+            backward_speed: default_backward_speed(),
+            max_colonies: default_max_colonies(),
+            nest_radius: default_nest_radius(),
+            spawn_jitter: 0.0,
+            pheromone_budget: 0.0,
+            pheromone_overflow: PheromoneOverflow::default(),
+            pheromone_trail: false,
+            pheromone_threshold: default_pheromone_threshold(),
+            pheromone_resolution: default_pheromone_resolution(),
+            map_autosave_interval: default_map_autosave_interval(),
+            max_memory_mb: default_max_memory_mb(),
+            edge_behavior: EdgeBehavior::default(),
+            movement: MovementMode::default(),
+            diffusion_neighborhood: DiffusionNeighborhood::default(),
+            think_error_fallback: ThinkErrorFallback::default(),
+            wall_crush: WallCrushPolicy::default(),
+            colony_order: ColonyOrder::default(),
+            think_events: ThinkEventsConfig::default(),
+            trophallaxis: TrophallaxisConfig::default(),
+            sudden_death: SuddenDeathConfig::default(),
+            beacons: BeaconsConfig::default(),
+            camera: CameraConfig::default(),
+            window: WindowConfig::default(),
         }
     }
 }
 
 impl AppConfig {
+    /// Resolves the map and validates the configuration and command line.
+    /// Every problem found is reported, not only the first one.
     pub fn from_cli_and_config(
-        cli: crate::Cli,
-        simulation: SimulationConfig,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
+        cli: crate::cli::Cli,
+        mut simulation: SimulationConfig,
+    ) -> Result<Self, Vec<ConfigError>> {
+        simulation.no_timeout |= cli.no_timeout;
+        simulation.build_brains |= cli.build_brains;
+        if cli.seed.is_some() {
+            simulation.rng_seed = cli.seed;
+        }
+        let mut errors = simulation.validate();
         let cli_players = cli.players;
-        let map_name = cli.map.or_else(|| Self::find_first_available_map());
         let evaluate = cli.evaluate;
+        let compare_players = cli.compare;
+        let spectator_lock = cli.spectator_lock;
+        let config_path = cli.config;
+        let population_log = cli.population_log;
+        let stats_out = cli.stats_out;
+        let record = cli.record;
+        let replay = cli.replay;
 
         let player_configs = load_player_configs();
 
-        if cli_players.is_some() && map_name.is_none() {
-            return Err("CLI players provided but no map specified".into());
+        // Every named player needs a brain in the players directory
+        let mut missing_players: Vec<&String> = Vec::new();
+        for name in cli_players.iter().chain(compare_players.iter()).flatten() {
+            if !player_configs.iter().any(|p| p.name == *name) && !missing_players.contains(&name) {
+                missing_players.push(name);
+                errors.push(ConfigError::MissingPlayer(name.clone()));
+            }
+        }
+
+        if let Some(players) = &cli_players
+            && players.len() > simulation.max_colonies
+        {
+            errors.push(ConfigError::TooManyPlayers {
+                players: players.len(),
+                max_colonies: simulation.max_colonies,
+            });
+        }
+
+        let errors_before_map = errors.len();
+        let map_name = match cli.map {
+            Some(name) if name == RANDOM_MAP => {
+                Self::pick_random_map(&simulation, cli_players.as_deref())
+                    .map_err(|e| errors.push(e))
+                    .ok()
+            }
+            None if !simulation.maps.is_empty() => {
+                Self::pick_random_map(&simulation, cli_players.as_deref())
+                    .map_err(|e| errors.push(e))
+                    .ok()
+            }
+            Some(name) => {
+                errors.extend(Self::check_map(&name, cli_players.as_deref()));
+                Some(name)
+            }
+            None => {
+                let first_map = Self::find_first_available_map();
+                if let Some(name) = &first_map {
+                    errors.extend(Self::check_map(name, cli_players.as_deref()));
+                }
+                first_map
+            }
+        };
+
+        // A failed map lookup is already reported
+        let map_reported = errors.len() > errors_before_map;
+        let mut invalid_arguments = |message: String| {
+            errors.push(ConfigError::InvalidArguments(message));
+        };
+
+        if cli_players.is_some() && map_name.is_none() && !map_reported {
+            invalid_arguments("CLI players provided but no map specified".to_string());
         }
 
         // Validate evaluate mode requirements
@@ -63,25 +1010,167 @@ impl AppConfig {
                 Some(players) if players.len() >= 2 => {
                     // Valid: evaluate mode with 2+ players
                 }
-                Some(players) => {
-                    return Err(format!(
-                        "Evaluate mode requires at least 2 players, but only {} provided",
-                        players.len()
-                    )
-                    .into());
+                Some(players) => invalid_arguments(format!(
+                    "Evaluate mode requires at least 2 players, but only {} provided",
+                    players.len()
+                )),
+                None => {
+                    invalid_arguments("Evaluate mode requires players to be specified".to_string())
                 }
+            }
+        }
+
+        if simulation.no_timeout {
+            if evaluate {
+                invalid_arguments(
+                    "The processing timeout can't be disabled in evaluate mode (no_timeout is for development only)"
+                        .to_string(),
+                );
+            } else {
+                eprintln!(
+                    "Warning: Processing timeout disabled, slow brains won't be killed. For development only."
+                );
+            }
+        }
+
+        // Build commands run on the host, outside the container sandbox
+        if simulation.build_brains && evaluate {
+            invalid_arguments(
+                "Brain build commands can't run in evaluate mode (build_brains is for development only)"
+                    .to_string(),
+            );
+        }
+
+        // Validate compare mode requirements
+        if let Some(compare_players) = &compare_players {
+            if evaluate {
+                invalid_arguments("Compare mode cannot be combined with evaluate mode".to_string());
+            }
+            match &cli_players {
+                Some(players) if players.len() == compare_players.len() => {}
+                Some(players) => invalid_arguments(format!(
+                    "Compare mode requires as many players as the main match ({}), but {} provided",
+                    players.len(),
+                    compare_players.len()
+                )),
                 None => {
-                    return Err("Evaluate mode requires players to be specified".into());
+                    invalid_arguments("Compare mode requires players to be specified".to_string())
                 }
             }
         }
 
+        // Replays have no players and nothing new to record
+        if replay.is_some() {
+            if cli_players.is_some() || compare_players.is_some() || evaluate {
+                invalid_arguments(
+                    "A replay can't be combined with players, compare or evaluate mode".to_string(),
+                );
+            }
+            if record.is_some() {
+                invalid_arguments("A replay can't be recorded again".to_string());
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
         Ok(Self {
             simulation,
             cli_players,
             player_configs,
             map_name,
             evaluate,
+            compare_players,
+            spectator_lock,
+            config_path,
+            population_log,
+            stats_out,
+            record,
+            replay,
+        })
+    }
+
+    /// Checks that a map loads and, if players are given, has one colony placeholder per player.
+    fn check_map(map_name: &str, players: Option<&[String]>) -> Option<ConfigError> {
+        let map = match GameMap::load_map(map_name) {
+            Ok(map) => map,
+            Err(e) => {
+                return Some(ConfigError::BadMap {
+                    map: map_name.to_string(),
+                    reason: e.to_string(),
+                });
+            }
+        };
+        let placeholders = map.placeholder_colony_locations.len();
+        match players {
+            Some(players) if players.len() != placeholders => {
+                Some(ConfigError::PlayerCountMismatch {
+                    map: map_name.to_string(),
+                    placeholders,
+                    players: players.len(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Picks a random map from the `maps` pool (or the maps directory) using `rng_seed`,
+    /// skipping maps whose colony placeholders don't match the number of players.
+    fn pick_random_map(
+        simulation: &SimulationConfig,
+        players: Option<&[String]>,
+    ) -> Result<String, ConfigError> {
+        let mut candidates = if simulation.maps.is_empty() {
+            GameMap::list_maps().map_err(|e| ConfigError::BadMap {
+                map: RANDOM_MAP.to_string(),
+                reason: format!("can't list {}: {}", MAPS_DIR, e),
+            })?
+        } else {
+            simulation.maps.clone()
+        };
+        // Directory order is not stable across systems
+        candidates.sort();
+        candidates.dedup();
+
+        let seed = simulation.rng_seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+        });
+        let mut rng = SeededRng::new(seed);
+        for i in (1..candidates.len()).rev() {
+            candidates.swap(i, rng.below(i + 1));
+        }
+
+        for map_name in candidates {
+            let map = match GameMap::load_map(&map_name) {
+                Ok(map) => map,
+                Err(e) => {
+                    eprintln!("Warning: Skipping random map '{}': {}", map_name, e);
+                    continue;
+                }
+            };
+            let placeholders = map.placeholder_colony_locations.len();
+            if let Some(players) = players
+                && placeholders != players.len()
+            {
+                eprintln!(
+                    "Skipping random map '{}': {} colony placeholders for {} players",
+                    map_name,
+                    placeholders,
+                    players.len()
+                );
+                continue;
+            }
+            eprintln!("Selected random map '{}' (seed {})", map_name, seed);
+            return Ok(map_name);
+        }
+        Err(ConfigError::BadMap {
+            map: RANDOM_MAP.to_string(),
+            reason: match players {
+                Some(players) => format!("no candidate fits {} players", players.len()),
+                None => "no candidate could be loaded".to_string(),
+            },
         })
     }
 
@@ -92,7 +1181,7 @@ impl AppConfig {
             for entry in entries.flatten() {
                 let path = entry.path();
                 if let Some(ext) = path.extension() {
-                    if ext == "map" {
+                    if ext == "map" && path.file_name() != Some(AUTOSAVE_MAP_NAME.as_ref()) {
                         if let Some(file_name) = path.file_name() {
                             return Some(file_name.to_string_lossy().to_string());
                         }
@@ -104,36 +1193,174 @@ impl AppConfig {
     }
 }
 
-pub fn window_conf() -> Conf {
+/// Window settings from the command line and the config file's `[window]` section.
+/// Read before the window exists; config errors are reported once `main` loads the config.
+pub fn window_conf(cli: &crate::cli::Cli) -> Conf {
+    let mut window = cli
+        .config
+        .as_ref()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| toml::from_str::<SimulationConfig>(&content).ok())
+        .map(|config| config.window)
+        .unwrap_or_default();
+    window.width = cli.width.unwrap_or(window.width).max(1);
+    window.height = cli.height.unwrap_or(window.height).max(1);
+    window.fullscreen |= cli.fullscreen;
+
     Conf {
         window_title: "PheroWar".to_owned(),
-        window_width: DEFAULT_WINDOW_WIDTH as i32,
-        window_height: DEFAULT_WINDOW_HEIGHT as i32,
+        window_width: window.width as i32,
+        window_height: window.height as i32,
+        fullscreen: window.fullscreen,
         high_dpi: true,
         ..Default::default()
     }
 }
 
 pub fn load_player_configs() -> Vec<PlayerConfig> {
+    list_players().unwrap_or_else(|_| {
+        eprintln!("Warning: players directory not found");
+        Vec::new()
+    })
+}
+
+/// Scans the players directory for player brains (`.so` files), sorted by name.
+/// A `<name>.png` next to a brain becomes its ant texture, and the first line of a
+/// `<name>.build` its build command.
+pub fn list_players() -> io::Result<Vec<PlayerConfig>> {
     let mut players = Vec::new();
     let players_dir = Path::new(PLAYERS_DIR);
-    if let Ok(entries) = fs::read_dir(players_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path().canonicalize().unwrap_or_default();
-            if let Some(ext) = path.extension() {
-                if ext == "so" {
-                    if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
-                        players.push(PlayerConfig {
-                            name: name.to_string(),
-                            so_path: path.to_string_lossy().to_string(),
-                        });
-                    }
+    for entry in fs::read_dir(players_dir)?.flatten() {
+        let path = entry.path().canonicalize().unwrap_or_default();
+        if let Some(ext) = path.extension() {
+            if ext == "so" {
+                if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+                    let texture = path.with_extension("png");
+                    let build_command = fs::read_to_string(path.with_extension("build"))
+                        .ok()
+                        .and_then(|content| content.lines().next().map(|l| l.trim().to_string()))
+                        .filter(|command| !command.is_empty());
+                    players.push(PlayerConfig {
+                        name: name.to_string(),
+                        so_path: path.to_string_lossy().to_string(),
+                        texture_path: texture
+                            .is_file()
+                            .then(|| texture.to_string_lossy().to_string()),
+                        build_command,
+                    });
                 }
             }
         }
-    } else {
-        eprintln!("Warning: players directory not found");
     }
     players.sort_by(|a, b| a.name.cmp(&b.name));
-    players
+    Ok(players)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_reports_every_invalid_value() {
+        assert!(SimulationConfig::default().validate().is_empty());
+
+        let mut config = SimulationConfig {
+            nest_radius: 0.0,
+            backward_speed: f32::NAN,
+            container_start_timeout: f32::INFINITY,
+            ..Default::default()
+        };
+        config.camera.max_zoom = config.camera.min_zoom / 2.0;
+        let errors = config.validate();
+        let fields: Vec<&str> = errors
+            .iter()
+            .map(|e| match e {
+                ConfigError::InvalidValue { field, .. } => field.as_str(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "container_start_timeout",
+                "nest_radius",
+                "backward_speed",
+                "camera.max_zoom"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_max_processing_time_override() {
+        let mut config = SimulationConfig::default();
+        assert_eq!(
+            config.max_processing_time_for("any"),
+            Some(crate::simulation::MAX_ANT_PROCESSING_TIME)
+        );
+
+        config
+            .player_max_processing_time
+            .insert("heavy".to_string(), 4.0);
+        assert_eq!(config.max_processing_time_for("heavy"), Some(4_000_000));
+        assert_eq!(config.max_processing_time_for("light"), Some(1_500_000));
+        config.no_timeout = true;
+        assert_eq!(config.max_processing_time_for("heavy"), None);
+
+        config
+            .player_max_processing_time
+            .insert("stalling".to_string(), MAX_PROCESSING_TIME_LIMIT * 2.0);
+        let errors = config.validate();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "`player_max_processing_time.stalling` is 200 but must be > 0 and <= 100"
+        );
+    }
+
+    #[test]
+    fn test_reload_reports_changes_and_keeps_match_settings() {
+        let running = SimulationConfig {
+            rng_seed: Some(7),
+            ..Default::default()
+        };
+        let mut new = SimulationConfig {
+            attack_damage: 8.0,
+            max_colonies: 3,
+            ..Default::default()
+        };
+        new.beacons.max_count = 2;
+        new.player_max_processing_time
+            .insert("heavy".to_string(), 4.0);
+        assert_eq!(
+            running.changes_to(&new),
+            [
+                "attack_damage: 5 -> 8",
+                "beacons.max_count: 4 -> 2",
+                "max_colonies: 5 -> 3 (kept until the next match)",
+                "player_max_processing_time.heavy: unset -> 4",
+                "rng_seed: 7 -> unset (kept until the next match)",
+            ]
+        );
+
+        let reloaded = running.reloaded(new);
+        assert_eq!(reloaded.attack_damage, 8.0);
+        assert_eq!(reloaded.max_colonies, 5);
+        assert_eq!(reloaded.rng_seed, Some(7));
+        assert!(running.changes_to(&running.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_reload_lists_a_seed_beyond_toml_integers() {
+        let running = SimulationConfig {
+            rng_seed: Some(u64::MAX),
+            ..Default::default()
+        };
+        assert_eq!(
+            running.changes_to(&SimulationConfig::default()),
+            [format!(
+                "rng_seed: \"{}\" -> unset (kept until the next match)",
+                u64::MAX
+            )]
+        );
+    }
 }
@@ -1,4 +1,5 @@
 use crate::simulation::Simulation;
+use macroquad::color::hsl_to_rgb;
 use macroquad::prelude::Color;
 
 pub const PREDEFINED_COLONY_COLORS: [Color; 5] = [
@@ -9,20 +10,44 @@ pub const PREDEFINED_COLONY_COLORS: [Color; 5] = [
     Color::new(0.616, 0.306, 0.867, 1.0), // Purple
 ];
 
-/// Manages selection of colony colors from a predefined palette.
+/// Hue step between procedural colors (golden angle), keeping consecutive hues far apart
+const PROCEDURAL_HUE_STEP: f32 = 0.381_966;
+
+/// Color of the colony palette at `index`: the predefined colors first, then procedural hues.
+pub fn colony_color(index: usize) -> Color {
+    if let Some(&color) = PREDEFINED_COLONY_COLORS.get(index) {
+        return color;
+    }
+    let n = index - PREDEFINED_COLONY_COLORS.len();
+    // Start between the predefined red and yellow, and alternate lightness for extra contrast
+    let hue = (0.08 + n as f32 * PROCEDURAL_HUE_STEP).fract();
+    let lightness = if n.is_multiple_of(2) { 0.55 } else { 0.7 };
+    hsl_to_rgb(hue, 0.75, lightness)
+}
+
+/// Manages selection of colony colors from a palette of `size` colors.
 pub struct ColorPalette {
-    selected_index: usize, // Index of the currently selected color in PREDEFINED_COLONY_COLORS
+    selected_index: usize, // Index of the currently selected color, see `colony_color`
+    size: usize,
 }
 
 impl ColorPalette {
-    /// Creates a new `ColorPalette`, selecting the first color by default.
-    pub fn new() -> Self {
-        Self { selected_index: 0 }
+    /// Creates a new `ColorPalette` with one color per allowed colony, selecting the first color by default.
+    pub fn new(size: usize) -> Self {
+        Self {
+            selected_index: 0,
+            size: size.max(1),
+        }
+    }
+
+    /// Colors of the palette, in order
+    pub fn colors(&self) -> impl Iterator<Item = Color> {
+        (0..self.size).map(colony_color)
     }
 
     /// Gets the currently selected `Color`.
     pub fn get_selected_color(&self) -> Color {
-        PREDEFINED_COLONY_COLORS[self.selected_index]
+        colony_color(self.selected_index)
     }
 
     /// Gets the index of the currently selected color.
@@ -32,13 +57,13 @@ impl ColorPalette {
 
     /// Sets the selected color index, ensuring it's within bounds.
     pub fn set_selected_index(&mut self, index: usize) {
-        if index < PREDEFINED_COLONY_COLORS.len() {
+        if index < self.size {
             self.selected_index = index;
         } else {
             eprintln!(
                 "Attempted to set invalid color index: {} (max is {})",
                 index,
-                PREDEFINED_COLONY_COLORS.len() - 1
+                self.size - 1
             );
             // Keeps current index if out of bounds
         }
@@ -65,16 +90,12 @@ impl ColorPalette {
             .any(|&used_color| Self::colors_are_close(color, used_color))
     }
 
-    /// Checks if all predefined colors are currently in use by colonies.
+    /// Checks if all palette colors are currently in use by colonies.
     pub fn are_all_colors_used(simulation: &Simulation) -> bool {
         let used_colors = Self::get_used_colors(simulation);
-        // True if number of unique used colors is at least the number of predefined colors.
-        // This simple check assumes predefined colors are distinct and used colors are from this set.
-        used_colors.len() >= PREDEFINED_COLONY_COLORS.len()
-        // A more robust check (if external colors or duplicates were possible):
-        // PREDEFINED_COLONY_COLORS.iter().all(|&palette_color| {
-        //     used_colors.iter().any(|&used_color| Self::colors_are_close(palette_color, used_color))
-        // })
+        // True if number of unique used colors is at least one color per allowed colony.
+        // This simple check assumes palette colors are distinct and used colors are from this set.
+        used_colors.len() >= simulation.config.max_colonies
     }
 
     /// Updates selected color to the first available one if current is used.
@@ -93,10 +114,10 @@ impl ColorPalette {
         }
 
         // Current selection is used, find the first available alternative.
-        for (idx, candidate_color) in PREDEFINED_COLONY_COLORS.iter().enumerate() {
+        for (idx, candidate_color) in (0..self.size).map(colony_color).enumerate() {
             let is_candidate_used = used_colors
                 .iter()
-                .any(|&used| Self::colors_are_close(*candidate_color, used));
+                .any(|&used| Self::colors_are_close(candidate_color, used));
 
             if !is_candidate_used {
                 if self.selected_index != idx {
@@ -115,6 +136,24 @@ impl ColorPalette {
 
 impl Default for ColorPalette {
     fn default() -> Self {
-        Self::new()
+        Self::new(crate::simulation::MAX_COLONIES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_extends_predefined_colors_with_distinct_ones() {
+        let palette = ColorPalette::new(12);
+        let colors: Vec<Color> = palette.colors().collect();
+        assert_eq!(colors.len(), 12);
+        assert_eq!(colors[..5], PREDEFINED_COLONY_COLORS);
+        for (i, a) in colors.iter().enumerate() {
+            for b in &colors[i + 1..] {
+                assert!(!ColorPalette::colors_are_close(*a, *b));
+            }
+        }
     }
 }
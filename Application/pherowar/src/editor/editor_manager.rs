@@ -1,6 +1,6 @@
 use crate::config::PlayerConfig;
 use crate::engine::Renderer;
-use crate::simulation::Simulation;
+use crate::simulation::{Simulation, Zone};
 use macroquad::prelude::{
     KeyCode, MouseButton, Vec2, is_key_down, is_mouse_button_down, mouse_wheel,
 };
@@ -15,7 +15,14 @@ use crate::editor::tools::colony_tool::{
     apply_colony, is_colony_tool_draggable, render_colony_preview,
 };
 use crate::editor::tools::food_tool::{apply_food, is_food_tool_draggable, render_food_preview};
+use crate::editor::tools::teleporter_tool::{
+    apply_teleporter, is_teleporter_tool_draggable, render_teleporter_preview,
+};
 use crate::editor::tools::wall_tool::{apply_wall, is_wall_tool_draggable, render_wall_preview};
+use crate::editor::tools::waypoint_tool::{
+    apply_waypoint, is_waypoint_tool_draggable, render_waypoint_preview,
+};
+use crate::editor::tools::zone_tool::{apply_zone, is_zone_tool_draggable, render_zone_preview};
 
 /// Minimum allowed tool size
 pub const MIN_TOOL_SIZE: f32 = 1.0;
@@ -34,11 +41,15 @@ pub struct EditorManager {
     pub color_palette: ColorPalette,
     pub symmetry_mode: SymmetryMode,
     player_configs: Vec<PlayerConfig>, // Available player configurations
+    pending_teleporters: Option<Vec<(usize, usize)>>, // Entrances waiting for their exit click
+    pub zone_type: Zone,               // Zone painted by the zone tool
+    pub waypoint_name: String,         // Name given by the waypoint tool
+    pub map_edited: bool,              // Set by tool edits, cleared once the map is saved
 }
 
 impl EditorManager {
-    /// Creates a new `EditorManager`.
-    pub fn new(player_configs_ref: &Vec<PlayerConfig>) -> Self {
+    /// Creates a new `EditorManager`, with one palette color per allowed colony.
+    pub fn new(player_configs_ref: &Vec<PlayerConfig>, max_colonies: usize) -> Self {
         let initial_player_index = if !player_configs_ref.is_empty() {
             Some(1) // Default to first player
         } else {
@@ -50,9 +61,13 @@ impl EditorManager {
             is_removing: false,
             last_drag_pos: None,
             current_player_index: initial_player_index,
-            color_palette: ColorPalette::new(),
+            color_palette: ColorPalette::new(max_colonies),
             symmetry_mode: SymmetryMode::None,
             player_configs: player_configs_ref.clone(),
+            pending_teleporters: None,
+            zone_type: Zone::Mud,
+            waypoint_name: "waypoint".to_string(),
+            map_edited: false,
         }
     }
 
@@ -63,6 +78,9 @@ impl EditorManager {
 
     /// Sets the active tool.
     pub fn set_tool(&mut self, tool_opt: Option<ToolType>) {
+        if tool_opt != self.current_tool_type {
+            self.pending_teleporters = None;
+        }
         self.current_tool_type = tool_opt;
     }
 
@@ -86,6 +104,12 @@ impl EditorManager {
         self.tool_size = size.clamp(MIN_TOOL_SIZE, MAX_TOOL_SIZE);
     }
 
+    /// Grows (positive `steps`) or shrinks the tool size, with a step proportional to the current size.
+    pub fn adjust_tool_size(&mut self, steps: f32) {
+        let speed = (self.tool_size / 10.0).max(1.0);
+        self.set_tool_size(self.tool_size + steps * speed);
+    }
+
     /// Handles user input for the editor.
     pub fn handle_input(
         &mut self,
@@ -102,8 +126,7 @@ impl EditorManager {
         if ctrl_pressed {
             let wheel = mouse_wheel().1;
             if wheel != 0.0 {
-                let speed = (self.tool_size / 10.0).max(1.0);
-                self.set_tool_size(self.tool_size - wheel * speed);
+                self.adjust_tool_size(-wheel);
                 return true; // Input handled
             }
             // Ctrl pressed but no wheel: let other bindings proceed.
@@ -119,6 +142,9 @@ impl EditorManager {
                 Some(ToolType::Food) => is_food_tool_draggable(),
                 Some(ToolType::Wall) => is_wall_tool_draggable(),
                 Some(ToolType::Colony) => is_colony_tool_draggable(),
+                Some(ToolType::Teleporter) => is_teleporter_tool_draggable(),
+                Some(ToolType::Zone) => is_zone_tool_draggable(),
+                Some(ToolType::Waypoint) => is_waypoint_tool_draggable(),
                 None => false, // Should be caught by early exit
             };
 
@@ -139,6 +165,7 @@ impl EditorManager {
             if apply_this_frame {
                 if self.apply_active_tool_with_symmetry(world_pos, simulation) {
                     renderer.mark_dirty(); // Mark renderer dirty if changes were made
+                    self.map_edited = true;
                 }
             }
             // Store current position for next frame's drag check or to prevent re-application.
@@ -163,6 +190,29 @@ impl EditorManager {
     ) -> bool {
         let mut overall_change = false;
 
+        // Teleporter pairs span two clicks: the tool needs all symmetric cells of a click at once
+        if self.current_tool_type == Some(ToolType::Teleporter) {
+            let mut positions = vec![primary_world_pos];
+            if self.symmetry_mode != SymmetryMode::None {
+                let map_w = simulation.map.width as f32;
+                let map_h = simulation.map.height as f32;
+                for sym_pos in
+                    self.symmetry_mode
+                        .symmetric_positions(primary_world_pos, map_w, map_h)
+                {
+                    if (sym_pos - primary_world_pos).length_squared() >= 0.001 {
+                        positions.push(sym_pos);
+                    }
+                }
+            }
+            return apply_teleporter(
+                &positions,
+                self.is_removing,
+                &mut self.pending_teleporters,
+                simulation,
+            );
+        }
+
         // Primary application
         if self.dispatch_tool_action(primary_world_pos, simulation) {
             overall_change = true;
@@ -207,12 +257,60 @@ impl EditorManager {
                 &mut self.color_palette,
                 simulation,
             ),
+            // Applied on all symmetric cells at once by `apply_active_tool_with_symmetry`
+            Some(ToolType::Teleporter) => false,
+            Some(ToolType::Zone) => apply_zone(
+                world_pos,
+                self.tool_size,
+                self.is_removing,
+                self.zone_type,
+                simulation,
+            ),
+            Some(ToolType::Waypoint) => {
+                apply_waypoint(world_pos, self.is_removing, &self.waypoint_name, simulation)
+            }
             None => false,
         }
     }
 
-    /// Renders the preview for the currently active tool.
-    pub fn render_tool_preview(&self, world_pos: Vec2) {
+    /// Renders the preview for the currently active tool, and its ghosts at the symmetric
+    /// positions where the edit will be mirrored.
+    pub fn render_tool_preview(&self, world_pos: Vec2, simulation: &Simulation) {
+        // Same positions, in the same order, as `apply_active_tool_with_symmetry`
+        let mut positions = vec![world_pos];
+        if self.current_tool_type.is_some() && self.symmetry_mode != SymmetryMode::None {
+            let map_w = simulation.map.width as f32;
+            let map_h = simulation.map.height as f32;
+            for sym_pos in self
+                .symmetry_mode
+                .symmetric_positions(world_pos, map_w, map_h)
+            {
+                if (sym_pos - world_pos).length_squared() >= 0.001 {
+                    positions.push(sym_pos);
+                }
+            }
+        }
+
+        // Each pending teleporter entrance links to the exit placed from the matching position
+        let pending = self.pending_teleporters.as_deref();
+        let paired = pending.is_some_and(|p| p.len() == positions.len());
+        for (i, &pos) in positions.iter().enumerate() {
+            let pending = match pending {
+                Some(p) if paired => Some(&p[i..=i]),
+                Some(p) if i == 0 => Some(p),
+                _ => None,
+            };
+            self.render_preview_at(pos, simulation, pending);
+        }
+    }
+
+    /// Renders the preview of the active tool at `world_pos`.
+    fn render_preview_at(
+        &self,
+        world_pos: Vec2,
+        simulation: &Simulation,
+        pending_teleporters: Option<&[(usize, usize)]>,
+    ) {
         match self.current_tool_type {
             Some(ToolType::Food) => {
                 render_food_preview(world_pos, self.tool_size, self.is_removing)
@@ -220,9 +318,19 @@ impl EditorManager {
             Some(ToolType::Wall) => {
                 render_wall_preview(world_pos, self.tool_size, self.is_removing)
             }
-            Some(ToolType::Colony) => {
-                render_colony_preview(world_pos, self.is_removing, self.current_player_index)
+            Some(ToolType::Colony) => render_colony_preview(
+                world_pos,
+                simulation.new_nest_radius(),
+                self.is_removing,
+                self.current_player_index,
+            ),
+            Some(ToolType::Teleporter) => {
+                render_teleporter_preview(world_pos, self.is_removing, pending_teleporters)
+            }
+            Some(ToolType::Zone) => {
+                render_zone_preview(world_pos, self.tool_size, self.is_removing, self.zone_type)
             }
+            Some(ToolType::Waypoint) => render_waypoint_preview(world_pos, self.is_removing),
             None => {} // No tool, no preview
         }
     }
@@ -26,6 +26,48 @@ impl SymmetryMode {
         SymmetryMode::MirrorBoth,
         SymmetryMode::Center,
     ];
+
+    /// Returns the authoritative cell for `(x, y)`: the cell in the top/left part of the map
+    /// that `(x, y)` mirrors under this mode. Cells in the authoritative part map to themselves.
+    pub fn source_cell(&self, x: usize, y: usize, map_w: usize, map_h: usize) -> (usize, usize) {
+        let mirror_x = map_w - 1 - x;
+        let mirror_y = map_h - 1 - y;
+        match self {
+            SymmetryMode::None => (x, y),
+            SymmetryMode::MirrorVertical => (x, y.min(mirror_y)),
+            SymmetryMode::MirrorHorizontal => (x.min(mirror_x), y),
+            SymmetryMode::MirrorBoth => (x.min(mirror_x), y.min(mirror_y)),
+            SymmetryMode::Center => {
+                if (y, x) <= (mirror_y, mirror_x) {
+                    (x, y)
+                } else {
+                    (mirror_x, mirror_y)
+                }
+            }
+        }
+    }
+
+    /// Returns the cells mirroring the authoritative cell `(x, y)` under this mode, one per
+    /// reflection and always in the same order, so the ends of a pair mirror in step.
+    /// A cell on a mirror axis is its own image.
+    pub fn mirror_cells(
+        &self,
+        x: usize,
+        y: usize,
+        map_w: usize,
+        map_h: usize,
+    ) -> Vec<(usize, usize)> {
+        let mirror_x = map_w - 1 - x;
+        let mirror_y = map_h - 1 - y;
+        match self {
+            SymmetryMode::None => Vec::new(),
+            SymmetryMode::MirrorVertical => vec![(x, mirror_y)],
+            SymmetryMode::MirrorHorizontal => vec![(mirror_x, y)],
+            SymmetryMode::MirrorBoth => vec![(mirror_x, y), (x, mirror_y), (mirror_x, mirror_y)],
+            SymmetryMode::Center => vec![(mirror_x, mirror_y)],
+        }
+    }
+
     /// Calculates symmetric positions.
     /// `pos`: original world position.
     /// `map_w`, `map_h`: map dimensions.
@@ -5,11 +5,21 @@ pub enum ToolType {
     Food,
     Wall,
     Colony,
+    Teleporter,
+    Zone,
+    Waypoint,
 }
 
 impl ToolType {
     pub fn all() -> &'static [ToolType] {
-        &[ToolType::Food, ToolType::Wall, ToolType::Colony]
+        &[
+            ToolType::Food,
+            ToolType::Wall,
+            ToolType::Colony,
+            ToolType::Teleporter,
+            ToolType::Zone,
+            ToolType::Waypoint,
+        ]
     }
 
     pub fn label(&self) -> &'static str {
@@ -17,6 +27,9 @@ impl ToolType {
             ToolType::Food => "Food",
             ToolType::Wall => "Wall",
             ToolType::Colony => "Colony",
+            ToolType::Teleporter => "Teleporter",
+            ToolType::Zone => "Zone",
+            ToolType::Waypoint => "Waypoint",
         }
     }
 
@@ -25,6 +38,9 @@ impl ToolType {
             ToolType::Food => true,
             ToolType::Wall => true,
             ToolType::Colony => false,
+            ToolType::Teleporter => false,
+            ToolType::Zone => true,
+            ToolType::Waypoint => false,
         }
     }
 }
@@ -1,6 +1,6 @@
 use crate::config::PlayerConfig;
 use crate::editor::color_palette::ColorPalette;
-use crate::simulation::{COLONY_NEST_SIZE, Simulation};
+use crate::simulation::{Simulation, Terrain};
 use macroquad::prelude::{Color, IVec2, Vec2, WHITE};
 
 /// Converts world position (Vec2) to integer tile coordinates (IVec2).
@@ -13,12 +13,16 @@ fn tile_coord_to_world_center(tile_coord: IVec2) -> Vec2 {
     Vec2::new(tile_coord.x as f32 + 0.5, tile_coord.y as f32 + 0.5)
 }
 
+/// Half size, in tiles, of the square footprint of a nest with the given radius.
+fn footprint_half_size(nest_radius: f32) -> i32 {
+    nest_radius.floor() as i32
+}
+
 /// Determines effective target tile: snaps to an existing entity's center if click is within its footprint.
 fn determine_effective_target_tile(clicked_tile: IVec2, simulation: &Simulation) -> IVec2 {
-    let entity_half_size = (COLONY_NEST_SIZE / 2.0).floor() as i32;
-
     // Check colonies
     for colony in simulation.colonies.values() {
+        let entity_half_size = footprint_half_size(colony.nest_radius);
         let colony_center_tile = world_pos_to_tile_coord(colony.pos);
         let min_x = colony_center_tile.x - entity_half_size;
         let max_x = colony_center_tile.x + entity_half_size;
@@ -35,6 +39,7 @@ fn determine_effective_target_tile(clicked_tile: IVec2, simulation: &Simulation)
     }
 
     // Check placeholders
+    let entity_half_size = footprint_half_size(simulation.new_nest_radius());
     for p_center_world_pos in &simulation.map.placeholder_colony_locations {
         let placeholder_center_tile = world_pos_to_tile_coord(*p_center_world_pos);
         let min_x = placeholder_center_tile.x - entity_half_size;
@@ -100,9 +105,11 @@ fn clear_tile_for_new_entity(target_tile_coord: IVec2, simulation: &mut Simulati
     handle_remove_entity_at_tile(simulation, target_tile_coord)
 }
 
-/// Checks if placing a new entity (5x5 area) at `target_center_tile` would overlap with OTHERS.
+/// Checks if placing a new entity at `target_center_tile` would overlap with OTHERS.
+/// Footprints are squares covering each nest's radius.
 fn is_placement_area_valid(target_center_tile: IVec2, simulation: &Simulation) -> bool {
-    let entity_half_size = (COLONY_NEST_SIZE / 2.0).floor() as i32;
+    let new_radius = simulation.new_nest_radius();
+    let entity_half_size = footprint_half_size(new_radius);
 
     // Bounding box of the new entity
     let new_min_x = target_center_tile.x - entity_half_size;
@@ -117,10 +124,11 @@ fn is_placement_area_valid(target_center_tile: IVec2, simulation: &Simulation) -
             continue;
         } // Skip self (already cleared)
 
-        let existing_min_x = existing_center_tile.x - entity_half_size;
-        let existing_max_x = existing_center_tile.x + entity_half_size;
-        let existing_min_y = existing_center_tile.y - entity_half_size;
-        let existing_max_y = existing_center_tile.y + entity_half_size;
+        let existing_half_size = footprint_half_size(colony.nest_radius);
+        let existing_min_x = existing_center_tile.x - existing_half_size;
+        let existing_max_x = existing_center_tile.x + existing_half_size;
+        let existing_min_y = existing_center_tile.y - existing_half_size;
+        let existing_max_y = existing_center_tile.y + existing_half_size;
 
         // AABB collision check
         if new_min_x <= existing_max_x
@@ -129,8 +137,8 @@ fn is_placement_area_valid(target_center_tile: IVec2, simulation: &Simulation) -
             && new_max_y >= existing_min_y
         {
             eprintln!(
-                "[WARN] Proximity (Tile): Too close to colony at {:?}. Target: {:?}. Size: {}",
-                existing_center_tile, target_center_tile, COLONY_NEST_SIZE
+                "[WARN] Proximity (Tile): Too close to colony at {:?}. Target: {:?}. Radius: {}",
+                existing_center_tile, target_center_tile, new_radius
             );
             return false;
         }
@@ -154,8 +162,8 @@ fn is_placement_area_valid(target_center_tile: IVec2, simulation: &Simulation) -
             && new_max_y >= existing_min_y
         {
             eprintln!(
-                "[WARN] Proximity (Tile): Too close to placeholder at {:?}. Target: {:?}. Size: {}",
-                existing_center_tile, target_center_tile, COLONY_NEST_SIZE
+                "[WARN] Proximity (Tile): Too close to placeholder at {:?}. Target: {:?}. Radius: {}",
+                existing_center_tile, target_center_tile, new_radius
             );
             return false;
         }
@@ -225,7 +233,22 @@ pub fn apply_colony(
             change_occurred_before_placement = true;
         }
 
-        // 2. Validate Position: check 5x5 area overlap with *other* entities.
+        // 2. Validate Position: nests can't sit inside walls, nor overlap *other* entities.
+        if simulation
+            .map
+            .get_terrain_at(
+                effective_target_tile.x as usize,
+                effective_target_tile.y as usize,
+            )
+            .is_some_and(|terrain| *terrain == Terrain::Wall)
+        {
+            eprintln!(
+                "[WARN] Placement failed: tile {:?} is a wall.",
+                effective_target_tile
+            );
+            return change_occurred_before_placement;
+        }
+
         if !is_placement_area_valid(effective_target_tile, simulation) {
             eprintln!(
                 "[WARN] Placement failed: Area for tile {:?} overlaps existing entity.",
@@ -304,10 +327,10 @@ pub fn apply_colony(
 /// Renders the preview for the colony tool.
 pub fn render_colony_preview(
     world_pos: Vec2,
+    radius: f32,
     is_removing: bool,
     current_player_index: Option<usize>,
 ) {
-    let radius = COLONY_NEST_SIZE / 2.0;
     // Preview follows mouse cursor directly, not snapped.
     let preview_center_x = world_pos.x;
     let preview_center_y = world_pos.y;
@@ -1,4 +1,7 @@
 pub mod colony_tool;
 pub mod food_tool;
 pub mod helpers;
+pub mod teleporter_tool;
 pub mod wall_tool;
+pub mod waypoint_tool;
+pub mod zone_tool;
@@ -0,0 +1,101 @@
+use crate::engine::TELEPORTER_COLOR;
+use crate::simulation::Simulation;
+use macroquad::prelude::{Color, Vec2};
+
+// TeleporterTool specific logic
+
+/// Cell under a world position, if inside the map.
+fn world_pos_to_cell(world_pos: Vec2, simulation: &Simulation) -> Option<(usize, usize)> {
+    let (x, y) = (world_pos.x.floor(), world_pos.y.floor());
+    if x < 0.0 || y < 0.0 || x >= simulation.map.width as f32 || y >= simulation.map.height as f32 {
+        return None;
+    }
+    Some((x as usize, y as usize))
+}
+
+fn cell_center(cell: (usize, usize)) -> Vec2 {
+    Vec2::new(cell.0 as f32 + 0.5, cell.1 as f32 + 0.5)
+}
+
+/// Applies the teleporter tool on the clicked cell and its symmetric cells (`world_positions`).
+/// The first click stores the entrances in `pending`, the second links each of them to the
+/// exit at the same index. Removing deletes the whole pairs with an end on the cells.
+/// Returns true if the map changed.
+pub fn apply_teleporter(
+    world_positions: &[Vec2],
+    is_removing: bool,
+    pending: &mut Option<Vec<(usize, usize)>>,
+    simulation: &mut Simulation,
+) -> bool {
+    let cells: Vec<(usize, usize)> = world_positions
+        .iter()
+        .filter_map(|&pos| world_pos_to_cell(pos, simulation))
+        .collect();
+
+    if is_removing {
+        *pending = None;
+        let mut removed = false;
+        for &(x, y) in &cells {
+            removed |= simulation.map.remove_teleporter_at(x, y);
+        }
+        return removed;
+    }
+
+    if cells.len() != world_positions.len()
+        || !cells
+            .iter()
+            .all(|&(x, y)| simulation.map.can_place_teleporter_at(x, y))
+    {
+        return false;
+    }
+
+    match pending.take() {
+        Some(entrances) if entrances.len() == cells.len() => {
+            let mut placed = false;
+            for (entrance, exit) in entrances.into_iter().zip(cells) {
+                placed |= simulation.map.add_teleporter(entrance, exit);
+            }
+            placed
+        }
+        // First click, or the symmetry mode changed since it: these cells are the entrances
+        _ => {
+            *pending = Some(cells);
+            false
+        }
+    }
+}
+
+/// Draws the cursor cell and, while an entrance is pending, its link to the cursor.
+pub fn render_teleporter_preview(
+    world_pos: Vec2,
+    is_removing: bool,
+    pending: Option<&[(usize, usize)]>,
+) {
+    let cursor = Vec2::new(world_pos.x.floor() + 0.5, world_pos.y.floor() + 0.5);
+    let color = if is_removing {
+        Color::new(0.8, 0.8, 0.8, 0.5)
+    } else {
+        Color::new(
+            TELEPORTER_COLOR.r,
+            TELEPORTER_COLOR.g,
+            TELEPORTER_COLOR.b,
+            0.6,
+        )
+    };
+
+    if let Some(entrances) = pending {
+        for &entrance in entrances {
+            let center = cell_center(entrance);
+            macroquad::shapes::draw_circle(center.x, center.y, 0.45, TELEPORTER_COLOR);
+        }
+        if let Some(&first) = entrances.first() {
+            let center = cell_center(first);
+            macroquad::shapes::draw_line(center.x, center.y, cursor.x, cursor.y, 0.1, color);
+        }
+    }
+    macroquad::shapes::draw_circle_lines(cursor.x, cursor.y, 0.4, 0.12, color);
+}
+
+pub fn is_teleporter_tool_draggable() -> bool {
+    false
+}
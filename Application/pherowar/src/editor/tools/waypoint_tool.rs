@@ -0,0 +1,38 @@
+use crate::engine::WAYPOINT_COLOR;
+use crate::simulation::Simulation;
+use macroquad::prelude::{Color, Vec2};
+
+// WaypointTool specific logic
+
+/// Names the clicked cell `name`, or removes the waypoint there when removing.
+pub fn apply_waypoint(
+    world_pos: Vec2,
+    is_removing: bool,
+    name: &str,
+    simulation: &mut Simulation,
+) -> bool {
+    let (x, y) = (world_pos.x.floor(), world_pos.y.floor());
+    if x < 0.0 || y < 0.0 {
+        return false;
+    }
+    let (x, y) = (x as usize, y as usize);
+    if is_removing {
+        simulation.map.remove_waypoint_at(x, y)
+    } else {
+        simulation.map.add_waypoint(name, x, y)
+    }
+}
+
+pub fn render_waypoint_preview(world_pos: Vec2, is_removing: bool) {
+    let cursor = Vec2::new(world_pos.x.floor() + 0.5, world_pos.y.floor() + 0.5);
+    let color = if is_removing {
+        Color::new(0.8, 0.8, 0.8, 0.5)
+    } else {
+        Color::new(WAYPOINT_COLOR.r, WAYPOINT_COLOR.g, WAYPOINT_COLOR.b, 0.6)
+    };
+    macroquad::shapes::draw_poly_lines(cursor.x, cursor.y, 4, 0.5, 45.0, 0.12, color);
+}
+
+pub fn is_waypoint_tool_draggable() -> bool {
+    false
+}
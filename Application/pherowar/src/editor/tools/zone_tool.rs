@@ -0,0 +1,35 @@
+use crate::editor::tools::helpers::apply_action_in_circular_area;
+use crate::engine::zone_color;
+use crate::simulation::{Simulation, Zone};
+use macroquad::prelude::{Color, Vec2, WHITE};
+
+// ZoneTool specific logic
+
+/// Paints `zone` in the tool area, or clears the zones there when removing.
+pub fn apply_zone(
+    world_pos: Vec2,
+    tool_size: f32,
+    is_removing: bool,
+    zone: Zone,
+    simulation: &mut Simulation,
+) -> bool {
+    let zone = if is_removing { Zone::None } else { zone };
+    apply_action_in_circular_area(world_pos, tool_size, simulation, |tile_x, tile_y, sim| {
+        sim.map.set_zone_at(tile_x, tile_y, zone)
+    })
+}
+
+pub fn render_zone_preview(world_pos: Vec2, tool_size: f32, is_removing: bool, zone: Zone) {
+    let color = if is_removing {
+        Color::new(0.8, 0.8, 0.8, 0.5)
+    } else {
+        let tint = zone_color(zone);
+        Color::new(tint.r, tint.g, tint.b, 0.5)
+    };
+    macroquad::shapes::draw_circle(world_pos.x, world_pos.y, tool_size / 2.0, color);
+    macroquad::shapes::draw_circle_lines(world_pos.x, world_pos.y, tool_size / 2.0, 0.4, WHITE);
+}
+
+pub fn is_zone_tool_draggable() -> bool {
+    true
+}
@@ -1,17 +1,27 @@
+use crate::config::CameraConfig;
 use macroquad::prelude::*;
+use std::ops::Range;
 
 // Camera configuration constants
-/// Minimum zoom level allowed (1.0 = full map view)
-const MIN_ZOOM: f32 = 1.0;
-/// Maximum zoom level allowed
-const MAX_ZOOM: f32 = 50.0;
 /// Speed multiplier for zoom operations
 const ZOOM_SPEED: f32 = 0.1;
+/// Relative zoom difference under which smoothing snaps to the target
+const ZOOM_SNAP_THRESHOLD: f32 = 0.001;
 
 /// A camera system for 2D game worlds that handles zooming and panning
 pub struct GameCamera {
-    /// Zoom level (minimum 1.0, higher values zoom in)
+    /// Zoom level (1.0 = full map view, higher values zoom in)
     zoom: f32,
+    /// Zoom level the camera is interpolating towards
+    target_zoom: f32,
+    /// Screen position and the world point under it to keep fixed while zooming
+    zoom_anchor: Option<(Vec2, Vec2)>,
+
+    /// Zoom limits
+    min_zoom: f32,
+    max_zoom: f32,
+    /// Time constant of the zoom interpolation in seconds (0.0 = instant)
+    zoom_smoothing: f32,
 
     /// Map dimensions
     pub map_width: u32,
@@ -23,9 +33,18 @@ pub struct GameCamera {
 
 impl GameCamera {
     /// Creates a new camera system for the given map dimensions
-    pub fn new(map_width: u32, map_height: u32) -> Self {
+    pub fn new(map_width: u32, map_height: u32, config: &CameraConfig) -> Self {
+        let min_zoom = config.min_zoom.max(f32::EPSILON);
+        let max_zoom = config.max_zoom.max(min_zoom);
+        let initial_zoom = 1.0_f32.clamp(min_zoom, max_zoom);
+
         let mut camera = Self {
-            zoom: 1.0,
+            zoom: initial_zoom,
+            target_zoom: initial_zoom,
+            zoom_anchor: None,
+            min_zoom,
+            max_zoom,
+            zoom_smoothing: config.zoom_smoothing.max(0.0),
             map_width,
             map_height,
             camera: Camera2D {
@@ -41,26 +60,56 @@ impl GameCamera {
     }
 
     pub fn adjust_zoom(&mut self, wheel_movement: f32) {
-        let old_zoom = self.zoom;
+        // Adjust target zoom level
+        self.target_zoom = (self.target_zoom - wheel_movement * self.target_zoom * ZOOM_SPEED)
+            .clamp(self.min_zoom, self.max_zoom);
 
-        // Store mouse position and convert to world coordinates before zoom change
+        // Store mouse position and the world point under it before zoom change
         let mouse_screen_pos = Vec2::from(mouse_position());
         let mouse_world_pos = self.camera.screen_to_world(mouse_screen_pos);
+        self.zoom_anchor = Some((mouse_screen_pos, mouse_world_pos));
 
-        // Adjust zoom level
-        self.zoom = (self.zoom - wheel_movement * self.zoom * ZOOM_SPEED).clamp(MIN_ZOOM, MAX_ZOOM);
+        if self.zoom_smoothing <= 0.0 {
+            self.apply_zoom(self.target_zoom);
+            self.zoom_anchor = None;
+        }
+    }
 
-        // If zoom level changed, update camera parameters
-        if old_zoom != self.zoom {
-            // Update the camera zoom values
-            self.update_camera_zoom();
+    /// Advances the zoom interpolation towards the target zoom level.
+    pub fn update(&mut self, dt: f32) {
+        if self.zoom == self.target_zoom {
+            self.zoom_anchor = None;
+            return;
+        }
+
+        let new_zoom =
+            if (self.target_zoom - self.zoom).abs() <= self.target_zoom * ZOOM_SNAP_THRESHOLD {
+                self.target_zoom
+            } else {
+                let t = 1.0 - (-dt / self.zoom_smoothing).exp();
+                self.zoom + (self.target_zoom - self.zoom) * t
+            };
+        self.apply_zoom(new_zoom);
+    }
 
+    /// Sets the zoom level, keeping the anchored world point under its screen position.
+    fn apply_zoom(&mut self, new_zoom: f32) {
+        if self.zoom == new_zoom {
+            return;
+        }
+
+        self.zoom = new_zoom;
+        self.update_camera_zoom();
+
+        if let Some((anchor_screen_pos, anchor_world_pos)) = self.zoom_anchor {
             // Get the new position of the same world point after zoom
-            let new_mouse_world_pos = self.camera.screen_to_world(mouse_screen_pos);
+            let new_anchor_world_pos = self.camera.screen_to_world(anchor_screen_pos);
 
             // Move the camera to keep the point under cursor
-            let position_delta = mouse_world_pos - new_mouse_world_pos;
+            let position_delta = anchor_world_pos - new_anchor_world_pos;
             self.move_by(position_delta);
+        } else {
+            self.adjust_camera_bounds();
         }
     }
 
@@ -69,9 +118,43 @@ impl GameCamera {
         self.adjust_camera_bounds();
     }
 
+    /// Restricts the camera to a screen rectangle (split-screen), or the whole screen if `None`.
+    pub fn set_viewport(&mut self, viewport: Option<Rect>) {
+        // Macroquad viewports are in GL coordinates, with the origin at the bottom left
+        let gl_viewport = viewport.map(|rect| {
+            (
+                rect.x as i32,
+                (screen_height() - rect.y - rect.h) as i32,
+                rect.w as i32,
+                rect.h as i32,
+            )
+        });
+        if self.camera.viewport != gl_viewport {
+            self.camera.viewport = gl_viewport;
+            self.handle_resize();
+        }
+    }
+
+    /// Returns true if the mouse is inside the camera's viewport.
+    pub fn contains_mouse(&self) -> bool {
+        let (x, y) = mouse_position();
+        self.camera.viewport.is_none_or(|(vx, vy, vw, vh)| {
+            let top = screen_height() - (vy + vh) as f32;
+            x >= vx as f32 && x < (vx + vw) as f32 && y >= top && y < top + vh as f32
+        })
+    }
+
+    /// Aspect ratio of the area the camera draws to.
+    fn view_ratio(&self) -> f32 {
+        match self.camera.viewport {
+            Some((_, _, w, h)) => w as f32 / h.max(1) as f32,
+            None => screen_width() / screen_height(),
+        }
+    }
+
     fn update_camera_zoom(&mut self) {
         let map_ratio = self.map_width as f32 / self.map_height as f32;
-        let screen_ratio = screen_width() / screen_height();
+        let screen_ratio = self.view_ratio();
 
         // Calculate aspect ratio adjustments to prevent distortion
         let (horizontal_adjustment, vertical_adjustment) = if map_ratio >= screen_ratio {
@@ -92,7 +175,7 @@ impl GameCamera {
     fn adjust_camera_bounds(&mut self) {
         // Calculate view dimensions based on zoom level
         let map_ratio = self.map_width as f32 / self.map_height as f32;
-        let screen_ratio = screen_width() / screen_height();
+        let screen_ratio = self.view_ratio();
 
         // Apply the same aspect ratio adjustments as in update_camera_zoom
         let horizontal_view = if map_ratio >= screen_ratio {
@@ -130,6 +213,41 @@ impl GameCamera {
         }
     }
 
+    /// World area shown by the camera, possibly extending past the map edges
+    pub fn visible_world_rect(&self) -> Rect {
+        let half_extent = vec2(
+            1.0 / self.camera.zoom.x.abs(),
+            1.0 / self.camera.zoom.y.abs(),
+        );
+        let top_left = self.camera.target - half_extent;
+        Rect::new(
+            top_left.x,
+            top_left.y,
+            half_extent.x * 2.0,
+            half_extent.y * 2.0,
+        )
+    }
+
+    /// Ranges of the grid cells of `cell_size` world units inside the view, clamped to
+    /// a `width` x `height` grid. Lets per-cell drawing skip what is off screen.
+    pub fn visible_cells(
+        &self,
+        cell_size: f32,
+        width: usize,
+        height: usize,
+    ) -> (Range<usize>, Range<usize>) {
+        let view = self.visible_world_rect();
+        let range = |start: f32, end: f32, len: usize| {
+            let first = ((start / cell_size).floor().max(0.0) as usize).min(len);
+            let last = ((end / cell_size).ceil().max(0.0) as usize).min(len);
+            first..last
+        };
+        (
+            range(view.left(), view.right(), width),
+            range(view.top(), view.bottom(), height),
+        )
+    }
+
     /// Converts the current mouse screen position to world coordinates
     pub fn get_mouse_world_pos(&self) -> Vec2 {
         self.camera.screen_to_world(Vec2::from(mouse_position()))
@@ -143,7 +261,9 @@ impl GameCamera {
 
     /// Resets the camera to its default position and zoom
     pub fn reset(&mut self) {
-        self.zoom = 1.0;
+        self.zoom = 1.0_f32.clamp(self.min_zoom, self.max_zoom);
+        self.target_zoom = self.zoom;
+        self.zoom_anchor = None;
         self.camera.target = vec2(self.map_width as f32 / 2.0, self.map_height as f32 / 2.0);
         self.update_camera_zoom();
         // Ensure bounds are correct after reset
@@ -0,0 +1,95 @@
+use crate::simulation::{SENSE_MAX_DISTANCE, Simulation};
+use macroquad::prelude::*;
+
+/// Cells revealed in a colony's fog view: those within vision range of its nest or one of its ants.
+/// Only used for rendering; brains get their own perception and never see this.
+pub struct FogMask {
+    width: usize,
+    height: usize,
+    visible: Vec<bool>,
+}
+
+impl FogMask {
+    /// Reveals a disc of `radius` cells around each source position.
+    pub fn new(
+        width: usize,
+        height: usize,
+        sources: impl IntoIterator<Item = (Vec2, f32)>,
+    ) -> Self {
+        let mut mask = Self {
+            width,
+            height,
+            visible: vec![false; width * height],
+        };
+        // Ants crowd the same cells: skip cells already revealed from with at least this radius
+        let mut revealed_from: Vec<f32> = vec![0.0; width * height];
+        for (pos, radius) in sources {
+            let (cx, cy) = (pos.x.floor() as isize, pos.y.floor() as isize);
+            if cx < 0 || cy < 0 || cx as usize >= width || cy as usize >= height {
+                continue;
+            }
+            let index = cy as usize * width + cx as usize;
+            if revealed_from[index] >= radius {
+                continue;
+            }
+            revealed_from[index] = radius;
+            mask.reveal_disc(cx, cy, radius);
+        }
+        mask
+    }
+
+    /// Fog mask of a colony, or `None` if the colony doesn't exist.
+    pub fn for_colony(simulation: &Simulation, colony_id: u32) -> Option<Self> {
+        let colony = simulation.colonies.get(&colony_id)?;
+        let nest = (colony.pos, colony.nest_radius + SENSE_MAX_DISTANCE);
+        let ants = colony
+            .ants
+            .values()
+            .map(|ant| (ant.pos, SENSE_MAX_DISTANCE));
+        Some(Self::new(
+            simulation.map.width as usize,
+            simulation.map.height as usize,
+            std::iter::once(nest).chain(ants),
+        ))
+    }
+
+    fn reveal_disc(&mut self, cx: isize, cy: isize, radius: f32) {
+        let r = radius.ceil() as isize;
+        let radius_sq = radius * radius;
+        for y in (cy - r).max(0)..=(cy + r).min(self.height as isize - 1) {
+            for x in (cx - r).max(0)..=(cx + r).min(self.width as isize - 1) {
+                let (dx, dy) = ((x - cx) as f32, (y - cy) as f32);
+                if dx * dx + dy * dy <= radius_sq {
+                    self.visible[y as usize * self.width + x as usize] = true;
+                }
+            }
+        }
+    }
+
+    pub fn is_visible(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.visible[y * self.width + x]
+    }
+
+    /// Returns true if the world position lies in a revealed cell.
+    pub fn is_visible_at(&self, pos: Vec2) -> bool {
+        pos.x >= 0.0 && pos.y >= 0.0 && self.is_visible(pos.x as usize, pos.y as usize)
+    }
+
+    /// Darkens the hidden cells, one rectangle per horizontal run.
+    pub fn draw(&self, color: Color) {
+        for y in 0..self.height {
+            let mut x = 0;
+            while x < self.width {
+                if self.is_visible(x, y) {
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                while x < self.width && !self.is_visible(x, y) {
+                    x += 1;
+                }
+                draw_rectangle(start as f32, y as f32, (x - start) as f32, 1.0, color);
+            }
+        }
+    }
+}
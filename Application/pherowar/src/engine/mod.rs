@@ -1,12 +1,15 @@
 mod camera;
+mod fog;
 mod rendering;
 
 pub use camera::GameCamera;
+pub use rendering::CameraAction;
 pub use rendering::Renderer;
-pub use rendering::CameraAction; // Add this line
 
 use macroquad::prelude::Color;
 
+use crate::simulation::Zone;
+
 // Define the constant for wall brightness variation
 pub const WALL_BRIGHTNESS_VARIATION: f32 = 1.0;
 
@@ -24,3 +27,15 @@ pub const CHANNEL_COLORS: [Color; 8] = [
     Color::new(1.0, 0.5, 0.0, 1.0), // orange
     Color::new(0.5, 0.0, 1.0, 1.0), // purple
 ];
+pub const TELEPORTER_COLOR: Color = Color::new(0.4, 0.8, 1.0, 1.0); // Teleporter ends and their links
+pub const WAYPOINT_COLOR: Color = Color::new(1.0, 0.85, 0.3, 1.0); // Waypoint markers and names
+
+/// Tint of the tiles of a map zone
+pub fn zone_color(zone: Zone) -> Color {
+    match zone {
+        Zone::None => Color::new(0.0, 0.0, 0.0, 0.0),
+        Zone::Mud => Color::new(0.45, 0.3, 0.15, 0.55),
+        Zone::Pad => Color::new(0.3, 0.85, 0.5, 0.3),
+        Zone::Scentless => Color::new(0.75, 0.75, 0.75, 0.2),
+    }
+}
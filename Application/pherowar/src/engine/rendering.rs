@@ -1,11 +1,18 @@
 use super::GameCamera;
-use crate::config::ASSETS_DIR;
+use super::fog::FogMask;
+use crate::config::{ASSETS_DIR, CameraConfig};
 use crate::simulation::{
-    ANT_LENGTH, AntRef, COLONY_NEST_SIZE, Colony, DEFAULT_FOOD_AMOUNT, GameMap,
-    MAX_PHEROMONE_AMOUNT, Simulation, Terrain,
+    ANT_LENGTH, AntRef, COMBAT_EFFECT_DURATION, Colony, DEFAULT_FOOD_AMOUNT, GameMap,
+    GhostPheromones, LAY_PULSE_DURATION, MAX_PHEROMONE_AMOUNT, PheromoneChannel, SENSE_MAX_ANGLE,
+    SENSE_MAX_DISTANCE, Simulation, Terrain, Zone,
 };
-use crate::ui::components::PheromoneDisplayMode;
+use crate::ui::components::{PheromoneDisplayMode, ViewMode};
 use macroquad::prelude::*;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Overlay drawn on cells hidden in a colony's fog view
+const FOG_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.65);
 
 /// Enum representing possible camera actions like dragging or zooming.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -22,6 +29,9 @@ pub enum CameraAction {
 pub struct Renderer {
     /// Texture for rendering ants.
     ant_texture: Texture2D,
+    /// Custom ant textures of the colonies, by colony id, along with the path they were
+    /// loaded from. `None` when loading failed, so it isn't retried every frame.
+    colony_ant_textures: HashMap<u32, (String, Option<Texture2D>)>,
     /// Texture for rendering food.
     food_texture: Texture2D,
     /// The main game camera.
@@ -38,8 +48,8 @@ pub struct Renderer {
 
 impl Renderer {
     /// Creates a new `Renderer` instance.
-    pub async fn new(map_width: u32, map_height: u32) -> Self {
-        let camera = GameCamera::new(map_width, map_height);
+    pub async fn new(map_width: u32, map_height: u32, camera_config: &CameraConfig) -> Self {
+        let camera = GameCamera::new(map_width, map_height, camera_config);
 
         let ant_texture = load_texture(&format!("{}ant.png", ASSETS_DIR))
             .await
@@ -60,6 +70,7 @@ impl Renderer {
 
         Self {
             ant_texture,
+            colony_ant_textures: HashMap::new(),
             food_texture,
             game_camera: camera,
             is_dragging: false,
@@ -115,22 +126,129 @@ impl Renderer {
     }
 
     /// Main rendering function, draws all game elements.
+    #[allow(clippy::too_many_arguments)]
     pub fn render(
         &mut self,
         simulation: &Simulation,
         pheromone_mode: PheromoneDisplayMode,
         selected_ant_ref: Option<&AntRef>,
         show_ants: bool,
+        show_sense_cone: bool,
+        show_lay_pulses: bool,
+        show_combat_effects: bool,
+        view_mode: ViewMode,
     ) {
         set_camera(&self.game_camera.camera);
 
+        let fog = match view_mode {
+            ViewMode::Spectator => None,
+            ViewMode::Colony { colony_id } => {
+                FogMask::for_colony(simulation, colony_id).map(|fog| (colony_id, fog))
+            }
+        };
+        // A colony view only shows the viewed colony's own pheromones
+        let pheromone_mode = match &fog {
+            Some((colony_id, _)) if pheromone_mode.colony_id() != Some(*colony_id) => {
+                PheromoneDisplayMode::None
+            }
+            _ => pheromone_mode,
+        };
+        let fog_view = fog.as_ref().map(|(colony_id, fog)| (*colony_id, fog));
+        self.sync_colony_ant_textures(simulation);
+
         self.draw_map(&simulation.map);
+        // Removed colonies belong to no colony view
+        if fog.is_none() && pheromone_mode != PheromoneDisplayMode::None {
+            self.draw_ghost_pheromones(&simulation.ghost_pheromones);
+        }
         self.draw_pheromones(&simulation.colonies, pheromone_mode);
-        self.draw_food(&simulation.map);
+        self.draw_food(&simulation.map, fog_view.map(|(_, fog)| fog));
+        self.draw_teleporters(&simulation.map);
+        self.draw_waypoints(&simulation.map);
+        // A colony view only shows the viewed colony's own beacons
+        self.draw_beacons(simulation, fog_view.map(|(colony_id, _)| colony_id));
+        if show_lay_pulses {
+            // A colony view only shows the viewed colony's own deposits
+            self.draw_lay_pulses(simulation, fog_view.map(|(colony_id, _)| colony_id));
+        }
+        if show_sense_cone && let Some(selected_ref) = selected_ant_ref {
+            self.draw_sense_cone(simulation, selected_ref);
+        }
         if show_ants {
-            self.draw_ants(simulation, selected_ant_ref);
+            self.draw_ants(simulation, selected_ant_ref, fog_view);
+        }
+        if show_combat_effects {
+            self.draw_combat_effects(simulation, fog_view.map(|(_, fog)| fog));
         }
         self.draw_colonies(simulation);
+        if let Some((_, fog)) = fog_view {
+            fog.draw(FOG_COLOR);
+        }
+    }
+
+    /// Draws the selected ant's sense cones and what its last perceive detected.
+    fn draw_sense_cone(&self, simulation: &Simulation, selected_ant_ref: &AntRef) {
+        let Some(ant) = simulation.get_ant(selected_ant_ref) else {
+            return;
+        };
+
+        // Communication (pheromone) cone, then vision cone on top
+        let comm_range = simulation.config.ant_comm_range;
+        if comm_range != SENSE_MAX_DISTANCE {
+            Self::draw_wedge(
+                ant.pos,
+                ant.rotation,
+                comm_range,
+                Color::new(0.4, 0.6, 1.0, 0.08),
+            );
+        }
+        Self::draw_wedge(
+            ant.pos,
+            ant.rotation,
+            SENSE_MAX_DISTANCE,
+            Color::new(1.0, 1.0, 1.0, 0.12),
+        );
+
+        let Some(input) = &ant.last_input else {
+            return;
+        };
+        let colony_color = simulation
+            .colonies
+            .get(&selected_ant_ref.colony_id)
+            .map_or(WHITE, |colony| colony.color);
+        let senses = [
+            (input.wall_sense, GRAY),
+            (input.food_sense, GREEN),
+            (input.enemy_sense, RED),
+            (input.friend_sense, SKYBLUE),
+            (input.colony_sense, colony_color),
+        ];
+        for ((angle_offset, dist), color) in senses {
+            if dist < 0.0 {
+                continue;
+            }
+            let angle = ant.last_input_rotation + angle_offset;
+            let marker = ant.pos + Vec2::from_angle(angle) * dist;
+            draw_line(ant.pos.x, ant.pos.y, marker.x, marker.y, 0.08, color);
+            draw_circle(marker.x, marker.y, 0.3, color);
+        }
+    }
+
+    /// Draws a translucent wedge of `SENSE_MAX_ANGLE` on each side of `rotation`.
+    fn draw_wedge(center: Vec2, rotation: f32, radius: f32, color: Color) {
+        const SEGMENTS: usize = 16;
+        let start_angle = rotation - SENSE_MAX_ANGLE;
+        let step = 2.0 * SENSE_MAX_ANGLE / SEGMENTS as f32;
+        for i in 0..SEGMENTS {
+            let a0 = start_angle + step * i as f32;
+            let a1 = a0 + step;
+            draw_triangle(
+                center,
+                center + Vec2::from_angle(a0) * radius,
+                center + Vec2::from_angle(a1) * radius,
+                color,
+            );
+        }
     }
 
     /// Draws the static map elements (e.g., walls) to an offscreen canvas.
@@ -145,6 +263,7 @@ impl Renderer {
 
             clear_background(Color::from_hex(0x222222));
 
+            self.draw_zones(map);
             self.draw_walls(map);
 
             pop_camera_state();
@@ -171,10 +290,17 @@ impl Renderer {
     }
 
     /// Draws food items on the map.
-    fn draw_food(&self, map: &GameMap) {
-        // Draw food textures dynamically each frame
-        for y in 0..map.height as usize {
-            for x in 0..map.width as usize {
+    /// Food in cells hidden by `fog` is not drawn.
+    fn draw_food(&self, map: &GameMap, fog: Option<&FogMask>) {
+        // Draw food textures dynamically each frame, skipping cells off screen
+        let (xs, ys) = self
+            .game_camera
+            .visible_cells(1.0, map.width as usize, map.height as usize);
+        for y in ys {
+            for x in xs.clone() {
+                if fog.is_some_and(|fog| !fog.is_visible(x, y)) {
+                    continue;
+                }
                 let pos_x = x as f32;
                 let pos_y = y as f32;
 
@@ -198,6 +324,163 @@ impl Renderer {
         }
     }
 
+    /// Draws teleporter pairs: a ring on each end, linked by a faint line.
+    fn draw_teleporters(&self, map: &GameMap) {
+        let link_color = Color::new(
+            super::TELEPORTER_COLOR.r,
+            super::TELEPORTER_COLOR.g,
+            super::TELEPORTER_COLOR.b,
+            0.25,
+        );
+        for pair in map.teleporters() {
+            let entrance = Vec2::new(pair.entrance.0 as f32 + 0.5, pair.entrance.1 as f32 + 0.5);
+            let exit = Vec2::new(pair.exit.0 as f32 + 0.5, pair.exit.1 as f32 + 0.5);
+            draw_line(entrance.x, entrance.y, exit.x, exit.y, 0.1, link_color);
+            draw_circle(entrance.x, entrance.y, 0.45, super::TELEPORTER_COLOR);
+            draw_circle_lines(exit.x, exit.y, 0.4, 0.12, super::TELEPORTER_COLOR);
+        }
+    }
+
+    /// Draws each waypoint as a diamond with its name above.
+    fn draw_waypoints(&self, map: &GameMap) {
+        for waypoint in map.waypoints() {
+            let center = waypoint.center();
+            draw_poly_lines(
+                center.x,
+                center.y,
+                4,
+                0.5,
+                45.0,
+                0.12,
+                super::WAYPOINT_COLOR,
+            );
+            // Rasterized at a readable size, then scaled down to ~1 tile
+            let params = TextParams {
+                font_size: 32,
+                font_scale: 1.0 / 32.0,
+                color: super::WAYPOINT_COLOR,
+                ..Default::default()
+            };
+            let text_dim = measure_text(&waypoint.name, None, params.font_size, params.font_scale);
+            draw_text_ex(
+                &waypoint.name,
+                center.x - text_dim.width / 2.0,
+                center.y - 0.8,
+                params,
+            );
+        }
+    }
+
+    /// Draws each colony beacon as a dot in its channel's color, ringed in the colony's color.
+    fn draw_beacons(&self, simulation: &Simulation, only_colony: Option<u32>) {
+        for (colony_id, colony) in &simulation.colonies {
+            if only_colony.is_some_and(|id| id != *colony_id) {
+                continue;
+            }
+            for beacon in colony.beacons.iter() {
+                let (x, y) = (beacon.cell.0 as f32 + 0.5, beacon.cell.1 as f32 + 0.5);
+                draw_circle(x, y, 0.3, super::CHANNEL_COLORS[beacon.channel as usize]);
+                draw_circle_lines(x, y, 0.45, 0.1, colony.color);
+            }
+        }
+    }
+
+    /// Draws a ring growing and fading out on each recent pheromone deposit, in the channel's color.
+    fn draw_lay_pulses(&self, simulation: &Simulation, only_colony: Option<u32>) {
+        for (colony_id, colony) in &simulation.colonies {
+            if only_colony.is_some_and(|id| id != *colony_id) {
+                continue;
+            }
+            for event in colony.lay_events.iter() {
+                let progress = (event.age / LAY_PULSE_DURATION).clamp(0.0, 1.0);
+                let mut color = super::CHANNEL_COLORS[event.channel as usize];
+                color.a = 1.0 - progress;
+                draw_circle_lines(
+                    event.cell.0 as f32 + 0.5,
+                    event.cell.1 as f32 + 0.5,
+                    0.3 + 0.7 * progress,
+                    0.1,
+                    color,
+                );
+            }
+        }
+    }
+
+    /// Draws a fading spark on each recent hit, with the damage floating up in the attacker's
+    /// color. Kills get a red ring. Hits in cells hidden by `fog` are not drawn.
+    fn draw_combat_effects(&self, simulation: &Simulation, fog: Option<&FogMask>) {
+        for colony in simulation.colonies.values() {
+            for event in colony.combat_events.iter() {
+                if fog.is_some_and(|fog| !fog.is_visible_at(event.pos)) {
+                    continue;
+                }
+                let progress = (event.age / COMBAT_EFFECT_DURATION).clamp(0.0, 1.0);
+                let fade = 1.0 - progress;
+                draw_circle(
+                    event.pos.x,
+                    event.pos.y,
+                    0.2 + 0.3 * progress,
+                    Color::new(1.0, 0.9, 0.4, 0.8 * fade),
+                );
+                if event.killed {
+                    draw_circle_lines(
+                        event.pos.x,
+                        event.pos.y,
+                        0.5 + progress,
+                        0.12,
+                        Color::new(1.0, 0.2, 0.2, fade),
+                    );
+                }
+
+                let text = format!("{:.0}", event.damage);
+                // Rasterized at a readable size, then scaled down to ~1 tile
+                let params = TextParams {
+                    font_size: 32,
+                    font_scale: 1.2 / 32.0,
+                    color: Color::new(colony.color.r, colony.color.g, colony.color.b, fade),
+                    ..Default::default()
+                };
+                let text_dim = measure_text(&text, None, params.font_size, params.font_scale);
+                draw_text_ex(
+                    &text,
+                    event.pos.x - text_dim.width / 2.0,
+                    event.pos.y - 0.6 - 1.2 * progress,
+                    params,
+                );
+            }
+        }
+    }
+
+    /// Draws the fading pheromones of removed colonies in their colors, fainter than living ones.
+    fn draw_ghost_pheromones(&self, ghosts: &[GhostPheromones]) {
+        for ghost in ghosts {
+            let Some(first) = ghost.channels.first() else {
+                continue;
+            };
+            let cell_size = first.resolution as f32;
+            let (xs, ys) = self.game_camera.visible_cells(
+                cell_size,
+                first.width as usize,
+                first.height as usize,
+            );
+            for (x, y) in visible_active_cells(&ghost.channels, &xs, &ys) {
+                let total: f32 = ghost
+                    .channels
+                    .iter()
+                    .map(|channel| channel.data[y][x])
+                    .sum();
+                let alpha = 0.5 * (total / MAX_PHEROMONE_AMOUNT).clamp(0.0, 1.0);
+                draw_rectangle(
+                    (x as f32 + 0.2) * cell_size,
+                    (y as f32 + 0.2) * cell_size,
+                    0.6 * cell_size,
+                    0.6 * cell_size,
+                    Color::new(ghost.color.r, ghost.color.g, ghost.color.b, alpha),
+                );
+            }
+        }
+    }
+
     /// Draws pheromone trails on the map based on the selected display mode.
     fn draw_pheromones(
         &self,
@@ -212,24 +495,21 @@ impl Renderer {
                     let base_color = colony.color;
                     let height = colony.pheromones[0].height as usize;
                     let width = colony.pheromones[0].width as usize;
-                    for y in 0..height {
-                        for x in 0..width {
-                            let mut total = 0.0;
-                            for channel in &colony.pheromones {
-                                total += channel.data[y][x];
-                            }
-                            if total < 0.01 {
-                                continue;
-                            }
-                            let alpha = (total / MAX_PHEROMONE_AMOUNT).clamp(0.0, 1.0);
-                            draw_rectangle(
-                                x as f32 + 0.2,
-                                y as f32 + 0.2,
-                                0.6,
-                                0.6,
-                                Color::new(base_color.r, base_color.g, base_color.b, alpha),
-                            );
+                    let cell_size = colony.pheromones[0].resolution as f32;
+                    let (xs, ys) = self.game_camera.visible_cells(cell_size, width, height);
+                    for (x, y) in visible_active_cells(&colony.pheromones, &xs, &ys) {
+                        let mut total = 0.0;
+                        for channel in &colony.pheromones {
+                            total += channel.data[y][x];
                         }
+                        let alpha = (total / MAX_PHEROMONE_AMOUNT).clamp(0.0, 1.0);
+                        draw_rectangle(
+                            (x as f32 + 0.2) * cell_size,
+                            (y as f32 + 0.2) * cell_size,
+                            0.6 * cell_size,
+                            0.6 * cell_size,
+                            Color::new(base_color.r, base_color.g, base_color.b, alpha),
+                        );
                     }
                 }
             }
@@ -238,40 +518,96 @@ impl Renderer {
                 if let Some(colony) = colonies.get(&colony_id) {
                     let height = colony.pheromones[0].height as usize;
                     let width = colony.pheromones[0].width as usize;
+                    let cell_size = colony.pheromones[0].resolution as f32;
+                    let (xs, ys) = self.game_camera.visible_cells(cell_size, width, height);
                     if channel_idx < colony.pheromones.len() {
                         let channel_data = &colony.pheromones[channel_idx];
                         let base_tint = channel_colors[channel_idx % channel_colors.len()];
-                        for y in 0..height {
-                            for x in 0..width {
-                                let val = channel_data.data[y][x];
-                                if val < 0.01 {
-                                    continue;
-                                }
-                                let intensity_ratio = (val / MAX_PHEROMONE_AMOUNT).clamp(0.0, 1.0);
-                                // Threshold
-                                // Sharper transition to white, more saturated base color
-                                let color_interpolation_factor = intensity_ratio.powf(3.0); // Adjust exponent for desired curve
-                                let r =
-                                    base_tint.r + (1.0 - base_tint.r) * color_interpolation_factor;
-                                let g =
-                                    base_tint.g + (1.0 - base_tint.g) * color_interpolation_factor;
-                                let b =
-                                    base_tint.b + (1.0 - base_tint.b) * color_interpolation_factor;
-                                draw_rectangle(
-                                    x as f32,
-                                    y as f32,
-                                    1.0,
-                                    1.0,
-                                    Color::new(r, g, b, intensity_ratio), // Opacity still based on raw intensity_ratio
-                                );
+                        let visible = |&(x, y): &(usize, usize)| xs.contains(&x) && ys.contains(&y);
+                        for (x, y) in channel_data.active_cells().filter(visible) {
+                            let val = channel_data.data[y][x];
+                            let intensity_ratio = (val / MAX_PHEROMONE_AMOUNT).clamp(0.0, 1.0);
+                            // Threshold
+                            // Sharper transition to white, more saturated base color
+                            let color_interpolation_factor = intensity_ratio.powf(3.0); // Adjust exponent for desired curve
+                            let r = base_tint.r + (1.0 - base_tint.r) * color_interpolation_factor;
+                            let g = base_tint.g + (1.0 - base_tint.g) * color_interpolation_factor;
+                            let b = base_tint.b + (1.0 - base_tint.b) * color_interpolation_factor;
+                            draw_rectangle(
+                                x as f32 * cell_size,
+                                y as f32 * cell_size,
+                                cell_size,
+                                cell_size,
+                                Color::new(r, g, b, intensity_ratio), // Opacity still based on raw intensity_ratio
+                            );
+                        }
+                    }
+                }
+            }
+            PheromoneDisplayMode::AllChannels { colony_id } => {
+                if let Some(colony) = colonies.get(&colony_id) {
+                    let height = colony.pheromones[0].height as usize;
+                    let width = colony.pheromones[0].width as usize;
+                    let cell_size = colony.pheromones[0].resolution as f32;
+                    let (xs, ys) = self.game_camera.visible_cells(cell_size, width, height);
+                    for (x, y) in visible_active_cells(&colony.pheromones, &xs, &ys) {
+                        // Accumulate each channel's tint weighted by its intensity
+                        let (mut r, mut g, mut b) = (0.0, 0.0, 0.0);
+                        let mut total_weight = 0.0;
+                        let mut transparency = 1.0;
+                        for (channel_idx, channel) in colony.pheromones.iter().enumerate() {
+                            let val = channel.data[y][x];
+                            if val <= 0.0 {
+                                continue;
                             }
+                            let intensity_ratio = (val / MAX_PHEROMONE_AMOUNT).clamp(0.0, 1.0);
+                            let tint = channel_colors[channel_idx % channel_colors.len()];
+                            r += tint.r * intensity_ratio;
+                            g += tint.g * intensity_ratio;
+                            b += tint.b * intensity_ratio;
+                            total_weight += intensity_ratio;
+                            // Alpha-blend channels as stacked layers
+                            transparency *= 1.0 - intensity_ratio;
+                        }
+                        if total_weight <= 0.0 {
+                            continue;
                         }
+                        draw_rectangle(
+                            x as f32 * cell_size,
+                            y as f32 * cell_size,
+                            cell_size,
+                            cell_size,
+                            Color::new(
+                                r / total_weight,
+                                g / total_weight,
+                                b / total_weight,
+                                1.0 - transparency,
+                            ),
+                        );
                     }
                 }
             }
         }
     }
 
+    /// Tints the tiles of map zones, one rectangle per horizontal run of the same zone.
+    fn draw_zones(&self, map: &GameMap) {
+        for y in 0..map.height as usize {
+            let mut x = 0;
+            while x < map.width as usize {
+                let zone = map.zone_at(x, y);
+                let start = x;
+                while x < map.width as usize && map.zone_at(x, y) == zone {
+                    x += 1;
+                }
+                if zone != Zone::None {
+                    let color = super::zone_color(zone);
+                    draw_rectangle(start as f32, y as f32, (x - start) as f32, 1.0, color);
+                }
+            }
+        }
+    }
+
     /// Draws wall tiles on the map with edge highlighting.
     fn draw_walls(&self, map: &GameMap) {
         let base_color_val = super::WALL_BASE_COLOR_VAL;
@@ -354,24 +690,89 @@ impl Renderer {
         }
     }
 
+    /// Loads the custom ant textures of new colonies and forgets those of removed ones.
+    /// A texture that fails to load is reported once; its colony uses the default texture.
+    fn sync_colony_ant_textures(&mut self, simulation: &Simulation) {
+        self.colony_ant_textures.retain(|colony_id, (path, _)| {
+            simulation.colonies.get(colony_id).is_some_and(|colony| {
+                colony.player_config.texture_path.as_deref() == Some(path.as_str())
+            })
+        });
+        for (colony_id, colony) in &simulation.colonies {
+            let Some(path) = &colony.player_config.texture_path else {
+                continue;
+            };
+            if self.colony_ant_textures.contains_key(colony_id) {
+                continue;
+            }
+            let texture = std::fs::read(path)
+                .map_err(|e| e.to_string())
+                .and_then(|bytes| {
+                    Image::from_file_with_format(&bytes, None).map_err(|e| e.to_string())
+                })
+                .map(|image| {
+                    let texture = Texture2D::from_image(&image);
+                    texture.set_filter(FilterMode::Linear);
+                    texture
+                });
+            let texture = match texture {
+                Ok(texture) => Some(texture),
+                Err(e) => {
+                    eprintln!(
+                        "Warning: Failed to load ant texture '{}': {}. Using the default one.",
+                        path, e
+                    );
+                    None
+                }
+            };
+            self.colony_ant_textures
+                .insert(*colony_id, (path.clone(), texture));
+        }
+    }
+
     /// Draws ants on the map, highlighting the selected ant if any.
-    fn draw_ants(&self, simulation: &Simulation, selected_ant_ref: Option<&AntRef>) {
-        for (_colony_id_map, colony_obj) in &simulation.colonies {
+    /// With a colony's fog view, other colonies' ants are only drawn in revealed cells.
+    fn draw_ants(
+        &self,
+        simulation: &Simulation,
+        selected_ant_ref: Option<&AntRef>,
+        fog_view: Option<(u32, &FogMask)>,
+    ) {
+        for (colony_id_map, colony_obj) in &simulation.colonies {
+            let ant_texture = self
+                .colony_ant_textures
+                .get(colony_id_map)
+                .and_then(|(_, texture)| texture.as_ref())
+                .unwrap_or(&self.ant_texture);
             for (_ant_key_map, ant_obj) in &colony_obj.ants {
+                if let Some((viewer_id, fog)) = fog_view
+                    && *colony_id_map != viewer_id
+                    && !fog.is_visible_at(ant_obj.pos)
+                {
+                    continue;
+                }
                 let mut current_ant_color = colony_obj.color;
-                if ant_obj.carrying_food {
+                if ant_obj.is_carrying_food() {
                     current_ant_color.r = (current_ant_color.r + 0.2).min(1.0);
                     current_ant_color.g = (current_ant_color.g + 0.2).min(1.0);
                     current_ant_color.b = (current_ant_color.b + 0.2).min(1.0);
                 }
 
+                // Emerging ants grow and fade in over the emergence delay
+                let mut size = ANT_LENGTH;
+                if ant_obj.is_emerging() && colony_obj.emergence_delay > 0.0 {
+                    let progress = 1.0 - ant_obj.emergence / colony_obj.emergence_delay;
+                    size *= 0.3 + 0.7 * progress;
+                    current_ant_color.a *= 0.2 + 0.6 * progress;
+                }
+
                 draw_texture_ex(
-                    &self.ant_texture,
-                    ant_obj.pos.x - ANT_LENGTH / 2.0,
-                    ant_obj.pos.y - ANT_LENGTH / 2.0,
+                    ant_texture,
+                    ant_obj.pos.x - size / 2.0,
+                    ant_obj.pos.y - size / 2.0,
                     current_ant_color,
                     DrawTextureParams {
-                        dest_size: Some(Vec2::new(ANT_LENGTH, ANT_LENGTH)),
+                        dest_size: Some(Vec2::new(size, size)),
                         rotation: ant_obj.rotation,
                         ..Default::default()
                     },
@@ -408,23 +809,35 @@ impl Renderer {
             );
 
             // Draw colony base and outline
-            draw_circle(
-                colony.pos.x,
-                colony.pos.y,
-                COLONY_NEST_SIZE / 2.0,
-                colony_color,
-            );
+            draw_circle(colony.pos.x, colony.pos.y, colony.nest_radius, colony_color);
             draw_circle_lines(
                 colony.pos.x,
                 colony.pos.y,
-                COLONY_NEST_SIZE / 2.0,
+                colony.nest_radius,
                 0.2,
                 outline_color,
             );
 
+            // Draw the food stock as a pile growing up to the nest's size
+            if colony.food_collected > 0 {
+                let fill = (colony.food_collected as f32 / simulation.config.colony_food_reference)
+                    .min(1.0);
+                let size = colony.nest_radius * (0.3 + 1.1 * fill.sqrt());
+                draw_texture_ex(
+                    &self.food_texture,
+                    colony.pos.x - size / 2.0,
+                    colony.pos.y - size / 2.0,
+                    WHITE,
+                    DrawTextureParams {
+                        dest_size: Some(Vec2::new(size, size)),
+                        ..Default::default()
+                    },
+                );
+            }
+
             // Draw skull emoji if dead
             if is_dead {
-                let font_size = COLONY_NEST_SIZE * 1.2;
+                let font_size = colony.nest_radius * 2.4;
                 let text = "x";
                 let text_dim = measure_text(text, None, font_size as u16, 1.0);
                 draw_text(
@@ -440,13 +853,14 @@ impl Renderer {
         // Draw placeholder colonies
         let placeholder_color = GRAY;
         let placeholder_outline_color = DARKGRAY;
-        // Iterate over simulation.map.placeholder_colony_locations directly
+        // Placeholders are drawn with the radius the colony spawned there will get
+        let placeholder_radius = simulation.new_nest_radius();
         for pos in &simulation.map.placeholder_colony_locations {
-            draw_circle(pos.x, pos.y, COLONY_NEST_SIZE / 2.0, placeholder_color);
+            draw_circle(pos.x, pos.y, placeholder_radius, placeholder_color);
             draw_circle_lines(
                 pos.x,
                 pos.y,
-                COLONY_NEST_SIZE / 2.0,
+                placeholder_radius,
                 0.2,
                 placeholder_outline_color,
             );
@@ -486,3 +900,20 @@ impl Renderer {
         self.is_wall_texture_dirty = true;
     }
 }
+
+/// Cells of the visible ranges holding pheromone on any of the channels, each once, row by row
+fn visible_active_cells(
+    channels: &[PheromoneChannel],
+    xs: &Range<usize>,
+    ys: &Range<usize>,
+) -> Vec<(usize, usize)> {
+    let mut cells: Vec<(usize, usize)> = channels
+        .iter()
+        .flat_map(|channel| channel.active_cells())
+        .filter(|(x, y)| xs.contains(x) && ys.contains(y))
+        .map(|(x, y)| (y, x))
+        .collect();
+    cells.sort_unstable();
+    cells.dedup();
+    cells.into_iter().map(|(y, x)| (x, y)).collect()
+}
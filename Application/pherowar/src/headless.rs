@@ -0,0 +1,418 @@
+use crate::app::{PWApp, format_placements};
+use crate::config::{AppConfig, PlayerConfig, SimulationConfig};
+use crate::player::{InProcessBrain, PlayerConnection};
+use crate::simulation::{Colony, GameMap, LossDiagnosis, MatchStats, Simulation, THINK_INTERVAL};
+use macroquad::prelude::{Vec2, WHITE};
+use shared::PlayerSetup;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Ticks after which `pherowar run` stops a match that has no winner yet.
+pub const DEFAULT_RUN_MAX_TICKS: u32 = 100_000;
+
+/// Match set up and stepped without a window, for scripted runs and experiments.
+pub struct HeadlessMatch {
+    pub simulation: Simulation,
+}
+
+impl HeadlessMatch {
+    /// Loads the map and spawns the players at its nest placeholders, as a match started
+    /// from the command line would.
+    pub fn new(app_config: &AppConfig) -> Result<Self, String> {
+        let map_name = app_config
+            .map_name
+            .as_ref()
+            .ok_or("A map is required to run a match")?;
+        let players = app_config
+            .cli_players
+            .as_ref()
+            .filter(|players| !players.is_empty())
+            .ok_or("Players are required to run a match")?;
+        PlayerConnection::preflight()
+            .map_err(|e| format!("Cannot start player containers: {}", e))?;
+
+        let mut simulation = Simulation::new(
+            &app_config.simulation,
+            app_config.player_configs.clone(),
+            None,
+        );
+        let mut map = GameMap::load_map(map_name)
+            .map_err(|e| format!("Failed to load map '{}': {}", map_name, e))?;
+        map.loaded_map_name = Some(map_name.clone());
+        simulation.map = map;
+        if let Some(path) = &app_config.population_log {
+            simulation
+                .start_population_log(path)
+                .map_err(|e| format!("Cannot create population log '{}': {}", path.display(), e))?;
+        }
+        if let Some(path) = &app_config.record {
+            simulation
+                .start_recording(path)
+                .map_err(|e| format!("Cannot create replay '{}': {}", path.display(), e))?;
+        }
+        PWApp::spawn_players_at_placeholders(&mut simulation, players)?;
+        if simulation.colonies.len() != players.len() {
+            return Err("Some colonies failed to spawn".to_string());
+        }
+
+        simulation.unpause();
+        Ok(Self { simulation })
+    }
+
+    /// Match on `map` without colonies, to fill with `add_in_process_colony`.
+    pub fn on_map(config: &SimulationConfig, map: GameMap) -> Self {
+        let mut simulation = Simulation::new(config, Vec::new(), None);
+        simulation.map = map;
+        simulation.unpause();
+        Self { simulation }
+    }
+
+    /// Adds a colony nested at `pos` whose ants think with `brain` on a thread of this
+    /// process. Returns its colony id, the lowest one free.
+    pub fn add_in_process_colony(
+        &mut self,
+        name: &str,
+        pos: Vec2,
+        setup: PlayerSetup,
+        brain: InProcessBrain,
+    ) -> Result<u32, String> {
+        let simulation = &mut self.simulation;
+        let colony_id = (0..)
+            .find(|id| !simulation.colonies.contains_key(id))
+            .expect("colony ids are never all taken");
+        let connection = PlayerConnection::in_process(colony_id, setup, brain)
+            .map_err(|e| format!("Cannot start brain '{}': {}", name, e))?;
+        let player_cfg = PlayerConfig {
+            name: name.to_string(),
+            so_path: String::new(),
+            texture_path: None,
+            build_command: None,
+        };
+        simulation.add_colony(Colony::with_connection(
+            colony_id,
+            pos,
+            simulation.map.width,
+            simulation.map.height,
+            WHITE,
+            &simulation.config,
+            player_cfg,
+            connection,
+        ));
+        Ok(colony_id)
+    }
+
+    /// Advances the match by `ticks` ticks of one think interval each.
+    pub fn step(&mut self, ticks: u32) {
+        for _ in 0..ticks {
+            self.simulation.update(THINK_INTERVAL);
+        }
+    }
+
+    /// Whether at most one colony is still alive.
+    pub fn is_over(&self) -> bool {
+        self.simulation
+            .colonies
+            .values()
+            .filter(|colony| !colony.is_dead())
+            .count()
+            <= 1
+    }
+}
+
+/// Runs a match until a single colony remains or `max_ticks` ticks passed, then prints
+/// the placements, as JSON when `json` is set. Returns the process exit code.
+pub fn run_match(app_config: &AppConfig, max_ticks: u32, json: bool) -> i32 {
+    let mut headless = match HeadlessMatch::new(app_config) {
+        Ok(headless) => headless,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return 1;
+        }
+    };
+
+    while !headless.is_over() && headless.simulation.tick < max_ticks {
+        headless.step(1);
+    }
+    if let Some(path) = &app_config.stats_out
+        && let Err(e) = write_stats(path, &headless.simulation.export_stats())
+    {
+        eprintln!("Error: Cannot write stats '{}': {}", path.display(), e);
+        return 1;
+    }
+    if json {
+        println!("{}", json_report(&headless));
+        return 0;
+    }
+
+    if headless.is_over() {
+        println!("Match over at tick {}", headless.simulation.tick);
+    } else {
+        println!("No winner after {} ticks", max_ticks);
+    }
+    println!(
+        "Placements:\n{}",
+        format_placements(&headless.simulation.placements())
+    );
+    println!(
+        "Think latency:\n{}",
+        headless.simulation.think_latency_report()
+    );
+    println!("Foraging:\n{}", headless.simulation.foraging_report());
+    println!("Losses:\n{}", headless.simulation.loss_report());
+    0
+}
+
+/// Final state of a match as a single JSON object: the tick, the winner (`null` without one),
+/// the placements, and each colony's stats by id.
+pub fn json_report(headless: &HeadlessMatch) -> String {
+    let simulation = &headless.simulation;
+    let placements = simulation.placements();
+    let winner = match placements.first() {
+        Some(first) if headless.is_over() => json_string(&first.name),
+        _ => "null".to_string(),
+    };
+    let placements: Vec<String> = placements
+        .iter()
+        .map(|placement| {
+            format!(
+                "{{\"place\":{},\"name\":{},\"eliminated_at\":{}}}",
+                placement.place,
+                json_string(&placement.name),
+                json_option(placement.eliminated_at)
+            )
+        })
+        .collect();
+
+    let mut colony_ids: Vec<u32> = simulation.colonies.keys().copied().collect();
+    colony_ids.sort_unstable();
+    let colonies: Vec<String> = colony_ids
+        .into_iter()
+        .map(|id| {
+            let colony = &simulation.colonies[&id];
+            let loss = colony
+                .is_dead()
+                .then(|| json_string(&LossDiagnosis::of(colony).cause().to_string()));
+            format!(
+                "{{\"id\":{},\"name\":{},\"ants\":{},\"food\":{},\"kills\":{},\"deaths\":{},\"timeouts\":{},\"thinks\":{},\"food_per_ant_minute\":{},\"avg_think_nanos\":{},\"loss_cause\":{}}}",
+                id,
+                json_string(&simulation.colony_display_name(id)),
+                colony.ants.len(),
+                colony.food_collected,
+                colony.kills,
+                colony.deaths,
+                colony.timeouts,
+                colony.think_count,
+                json_option(colony.foraging.food_per_ant_minute()),
+                json_option(colony.think_latency.avg_nanos()),
+                loss.unwrap_or_else(|| "null".to_string())
+            )
+        })
+        .collect();
+
+    format!(
+        "{{\"tick\":{},\"winner\":{},\"placements\":[{}],\"colonies\":[{}]}}",
+        simulation.tick,
+        winner,
+        placements.join(","),
+        colonies.join(",")
+    )
+}
+
+/// Per-colony match stats as a JSON object: the final `tick`, then per colony its `id`,
+/// `name`, `peak_ants`, `food_collected`, `kills`, `ants_lost` and `ticks_survived`.
+pub fn stats_json(stats: &MatchStats) -> String {
+    let colonies: Vec<String> = stats
+        .colonies
+        .iter()
+        .map(|colony| {
+            format!(
+                "{{\"id\":{},\"name\":{},\"peak_ants\":{},\"food_collected\":{},\"kills\":{},\"ants_lost\":{},\"ticks_survived\":{}}}",
+                colony.colony_id,
+                json_string(&colony.name),
+                colony.peak_ants,
+                colony.food_collected,
+                colony.kills,
+                colony.ants_lost,
+                colony.ticks_survived
+            )
+        })
+        .collect();
+    format!(
+        "{{\"tick\":{},\"colonies\":[{}]}}",
+        stats.tick,
+        colonies.join(",")
+    )
+}
+
+/// Writes the stats file of `--stats-out`, replacing an older one.
+pub fn write_stats(path: &Path, stats: &MatchStats) -> io::Result<()> {
+    fs::write(path, stats_json(stats) + "\n")
+}
+
+fn json_option<T: std::fmt::Display>(value: Option<T>) -> String {
+    value.map_or_else(|| "null".to_string(), |value| value.to_string())
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{AntInput, AntOutput, MEMORY_SIZE, PHEROMONE_CHANNEL_COUNT};
+
+    /// Walks straight, turning away from close walls
+    fn wander(input: &AntInput, output: &mut AntOutput) {
+        if input.wall_sense.1 >= 0.0 && input.wall_sense.1 < 2.0 {
+            output.turn_angle = std::f32::consts::FRAC_PI_2;
+        }
+    }
+
+    fn hunter(input: &AntInput, _memory: &mut [u8; MEMORY_SIZE], output: &mut AntOutput) {
+        if input.enemy_sense.1 >= 0.0 {
+            output.turn_angle = input.enemy_sense.0;
+            output.try_attack = true;
+        } else {
+            wander(input, output);
+        }
+    }
+
+    fn wanderer(input: &AntInput, _memory: &mut [u8; MEMORY_SIZE], output: &mut AntOutput) {
+        wander(input, output);
+    }
+
+    fn seeded_match() -> HeadlessMatch {
+        let config = SimulationConfig {
+            colony_initial_population: 10,
+            rng_seed: Some(7),
+            no_timeout: true,
+            ..SimulationConfig::default()
+        };
+        let mut map = GameMap::new(40, 20);
+        // Food inside each nest, delivered as soon as it is picked up, to spawn more ants
+        map.place_food_at(8, 13, 20);
+        map.place_food_at(31, 13, 20);
+        let setup = PlayerSetup {
+            decay_rates: [0.9; PHEROMONE_CHANNEL_COUNT],
+            initial_memory: [0; MEMORY_SIZE],
+            role_ratios: [0.0; shared::ROLE_COUNT],
+            channel_names: [[0; shared::CHANNEL_NAME_SIZE]; PHEROMONE_CHANNEL_COUNT],
+            diffusion_rates: [0.0; PHEROMONE_CHANNEL_COUNT],
+        };
+        let mut headless = HeadlessMatch::on_map(&config, map);
+        let brains: [(&str, f32, InProcessBrain); 2] =
+            [("hunter", 8.5, hunter), ("wanderer", 31.5, wanderer)];
+        for (name, x, brain) in brains {
+            headless
+                .add_in_process_colony(name, Vec2::new(x, 10.5), setup, brain)
+                .unwrap();
+        }
+        headless
+    }
+
+    #[test]
+    fn test_traced_ant_logs_each_think_tick() {
+        let mut headless = seeded_match();
+        headless.simulation.namespace = "test_trace".to_string();
+        let colony = &headless.simulation.colonies[&1];
+        let ant_ref = colony.ants.values().next().unwrap().ant_ref.clone();
+        let dir = std::env::temp_dir();
+        let file_name = headless.simulation.start_trace_in(&dir, &ant_ref).unwrap();
+        assert!(file_name.starts_with(&*dir.to_string_lossy()));
+        headless.step(100);
+        assert!(headless.simulation.get_ant(&ant_ref).unwrap().trace);
+        headless.simulation.stop_trace();
+        assert!(!headless.simulation.get_ant(&ant_ref).unwrap().trace);
+
+        let trace = std::fs::read_to_string(&file_name).unwrap();
+        std::fs::remove_file(&file_name).unwrap();
+        let ticks: Vec<u32> = trace
+            .lines()
+            .map(|line| {
+                assert!(line.contains("input=Some(AntInput"), "{line}");
+                assert!(line.contains("output=AntOutput"), "{line}");
+                let tick = line.strip_prefix("tick=").unwrap().split(' ').next();
+                tick.unwrap().parse().unwrap()
+            })
+            .collect();
+        // Headless steps last a think interval: one line per tick, and only for this ant
+        assert_eq!(ticks, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_population_log_samples_each_second() {
+        let mut headless = seeded_match();
+        let path = std::env::temp_dir().join("pherowar_test_population.tsv");
+        headless.simulation.start_population_log(&path).unwrap();
+        // 100 ticks of 0.375 s: one sample on the first tick, then one per elapsed second
+        headless.step(100);
+        headless.simulation.population_log = None;
+
+        let log = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut lines = log.lines();
+        // Columns for every colony id below `max_colonies` (5), unused ones at zero
+        let header = lines.next().unwrap();
+        assert!(header.starts_with("tick\tants_0\tfood_0\tants_1\tfood_1\tants_2"));
+        assert!(header.ends_with("\tants_4\tfood_4"));
+        let rows: Vec<Vec<&str>> = lines.map(|line| line.split('\t').collect()).collect();
+        assert_eq!(rows.len(), 38);
+        assert_eq!(
+            rows[0],
+            ["0", "10", "0", "10", "0", "0", "0", "0", "0", "0", "0"]
+        );
+        assert_eq!(rows[1][0], "2");
+        assert!(rows.iter().all(|row| row.len() == 11));
+    }
+
+    #[test]
+    fn test_json_report_of_a_finished_match() {
+        let mut headless = seeded_match();
+        while !headless.is_over() {
+            headless.step(1);
+        }
+        let json = json_report(&headless);
+        assert!(
+            json.starts_with("{\"tick\":965,\"winner\":\"wanderer\",\"placements\":[{\"place\":1,\"name\":\"wanderer\",\"eliminated_at\":null},"),
+            "{json}"
+        );
+        assert!(
+            json.contains("\"id\":0,\"name\":\"hunter\",\"ants\":0,"),
+            "{json}"
+        );
+        assert!(json.contains("\"kills\":9,"), "{json}");
+        assert!(json.ends_with("\"loss_cause\":null}]}"), "{json}");
+        assert_eq!(json_string("a \"b\"\\\n"), "\"a \\\"b\\\"\\\\\\u000a\"");
+    }
+
+    #[test]
+    fn test_stats_of_a_finished_match() {
+        let mut headless = seeded_match();
+        while !headless.is_over() {
+            headless.step(1);
+        }
+        let stats = headless.simulation.export_stats();
+        // The hunter is eliminated during the last tick, the wanderer survives it
+        assert_eq!(stats.colonies[0].ticks_survived, 964);
+        assert_eq!(stats.colonies[1].ticks_survived, 965);
+        assert_eq!(
+            stats_json(&stats),
+            "{\"tick\":965,\"colonies\":[\
+             {\"id\":0,\"name\":\"hunter\",\"peak_ants\":10,\"food_collected\":0,\"kills\":9,\"ants_lost\":10,\"ticks_survived\":964},\
+             {\"id\":1,\"name\":\"wanderer\",\"peak_ants\":11,\"food_collected\":21,\"kills\":10,\"ants_lost\":11,\"ticks_survived\":965}]}"
+        );
+    }
+}
@@ -0,0 +1,13 @@
+//! PheroWar's simulation, usable without the GUI: set up a `simulation::Simulation`, or a
+//! `headless::HeadlessMatch` with in-process brains, step it and query its state.
+//! The `pherowar` binary adds the window, the editor and the player containers on top.
+
+pub mod app;
+pub mod cli;
+pub mod config;
+mod editor;
+mod engine;
+pub mod headless;
+pub mod player;
+pub mod simulation;
+mod ui;
@@ -1,37 +1,47 @@
-mod app;
-mod config;
-mod editor;
-mod engine;
-mod player;
-mod simulation;
-mod ui;
-
 use std::path::PathBuf;
 
-use app::PWApp;
 use clap::Parser;
-use config::{SimulationConfig, window_conf};
-use toml;
-
-/// Command-line arguments for PheroWar.
-#[derive(Parser)]
-#[command(name = "PheroWar", version, about = "PheroWar Simulation")]
-pub struct Cli {
-    /// Path to the TOML configuration file.
-    #[arg(short, long, default_value = "./Application/config.toml")]
-    config: Option<PathBuf>,
-
-    /// Name of the map to load. Example: "Relic", "Labyrinth".
-    #[arg(short, long)]
-    map: Option<String>,
+use macroquad::prelude::Conf;
+use pherowar::app::PWApp;
+use pherowar::cli::{Cli, Command};
+use pherowar::config::{AppConfig, SimulationConfig, window_conf};
+use pherowar::headless::{self, DEFAULT_RUN_MAX_TICKS};
+use pherowar::simulation::GameMap;
 
-    /// List of colony players to spawn (player names separated by commas).
-    #[arg(short = 'p', long, value_delimiter = ',')]
-    players: Option<Vec<String>>,
+/// Runs a command line tool, returning the process exit code.
+fn run_command(command: Command, cli: Cli) -> i32 {
+    match command {
+        Command::DumpMap { name, max_width } => match GameMap::load_map(&name) {
+            Ok(map) => {
+                print!("{}", map.to_ascii(max_width));
+                0
+            }
+            Err(e) => {
+                eprintln!("Error loading map '{}': {}", name, e);
+                1
+            }
+        },
+        Command::Run { max_ticks, json } => match app_config(cli) {
+            Some(app_config) => headless::run_match(&app_config, max_ticks, json),
+            None => 1,
+        },
+    }
+}
 
-    /// Evaluate mode: auto-start and exit when there is a winner. Requires players to be set and >= 2.
-    #[arg(long)]
-    evaluate: bool,
+/// Called by macroquad before the window is created. Tools run and exit here, so they
+/// work without a display.
+fn conf() -> Conf {
+    let mut cli = Cli::parse();
+    if cli.headless && cli.command.is_none() {
+        cli.command = Some(Command::Run {
+            max_ticks: DEFAULT_RUN_MAX_TICKS,
+            json: true,
+        });
+    }
+    if let Some(command) = cli.command.take() {
+        std::process::exit(run_command(command, cli));
+    }
+    window_conf(&cli)
 }
 
 /// Loads the simulation configuration from a TOML file or uses defaults.
@@ -53,37 +63,46 @@ fn load_config(path: Option<PathBuf>) -> Result<SimulationConfig, Box<dyn std::e
                     return Err(Box::new(e));
                 }
             };
-            println!("Loaded config from '{}'", path.display());
-            println!("{:?}", config);
+            eprintln!("Loaded config from '{}'", path.display());
             Ok(config)
         }
         _ => {
-            println!("No config file provided, using defaults.");
+            eprintln!("No config file provided, using defaults.");
             Ok(SimulationConfig::default())
         }
     }
 }
 
-/// Main entry point for the PheroWar application.
-#[macroquad::main(window_conf)]
-async fn main() {
-    let cli = Cli::parse();
-
+/// Loads the configuration and validates it against the command line, reporting problems.
+fn app_config(cli: Cli) -> Option<AppConfig> {
     let config = match load_config(cli.config.clone()) {
         Ok(config) => config,
         Err(e) => {
             eprintln!("Error loading config: {}", e);
-            return;
+            return None;
         }
     };
 
     // Create app config with validation
-    let app_config = match config::AppConfig::from_cli_and_config(cli, config) {
-        Ok(app_config) => app_config,
-        Err(e) => {
-            eprintln!("Error: {}", e);
-            return;
+    match AppConfig::from_cli_and_config(cli, config) {
+        Ok(app_config) => Some(app_config),
+        Err(errors) => {
+            eprintln!("Invalid configuration ({} problem(s)):", errors.len());
+            for e in errors {
+                eprintln!("  - {}", e);
+            }
+            None
         }
+    }
+}
+
+/// Main entry point for the PheroWar application.
+#[macroquad::main(conf)]
+async fn main() {
+    let cli = Cli::parse();
+
+    let Some(app_config) = app_config(cli) else {
+        return;
     };
 
     let mut app = match PWApp::new(app_config).await {
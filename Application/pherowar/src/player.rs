@@ -1,16 +1,75 @@
 use anyhow::Result;
 use rkyv::{from_bytes, rancor::Error, to_bytes};
-use shared::{AntRequest, AntResponse, PlayerSetup};
+use shared::{AntRequest, AntResponse, MAX_MESSAGE_SIZE, MapInfo, PlayerSetup};
 use std::fs::{self, OpenOptions};
 use std::io::{Read, Write};
 use std::os::unix::net::UnixStream;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crate::config::PlayerConfig;
 
+/// Host directory holding the socket of a colony's container.
+/// `namespace` keeps simulations running side by side apart; empty for the main simulation.
+fn socket_dir(namespace: &str, colony_id: u32) -> PathBuf {
+    let base = PathBuf::from("/tmp/ant_sockets");
+    if namespace.is_empty() {
+        base.join(colony_id.to_string())
+    } else {
+        base.join(namespace).join(colony_id.to_string())
+    }
+}
+
+/// Image the player containers are created from, built by `build.sh`.
+const PLAYER_IMAGE: &str = "localhost/pherowar-player";
+
+/// First wait between attempts to connect to a starting container, doubled after each failure.
+const CONNECT_RETRY_START: Duration = Duration::from_millis(50);
+/// Longest wait between attempts to connect to a starting container.
+const CONNECT_RETRY_MAX: Duration = Duration::from_secs(1);
+
+/// Log file receiving the output of a colony's container, in the working directory.
+pub fn log_file_name(namespace: &str, player_name: &str, colony_id: u32) -> String {
+    if namespace.is_empty() {
+        format!("{}_{}.log", player_name, colony_id)
+    } else {
+        format!("{}_{}_{}.log", namespace, player_name, colony_id)
+    }
+}
+
+/// Runs the player's build command, if any, from the directory the brain really lives in
+/// (past symlinks). A failing build reports the command's output.
+fn build_brain(player_cfg: &PlayerConfig) -> Result<()> {
+    let Some(command) = &player_cfg.build_command else {
+        return Ok(());
+    };
+    let so_path = fs::canonicalize(&player_cfg.so_path)
+        .unwrap_or_else(|_| PathBuf::from(&player_cfg.so_path));
+    let brain_dir = so_path.parent().unwrap_or(Path::new("."));
+    eprintln!("Building {} with '{}'", player_cfg.name, command);
+    let output = Command::new("sh")
+        .args(["-c", command])
+        .current_dir(brain_dir)
+        .output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to build {} ('{}', {}):\n{}{}",
+            player_cfg.name,
+            command,
+            output.status,
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(())
+}
+
+/// Brain of an in-process connection: reads an ant's input, updates its memory and writes its output.
+pub type InProcessBrain =
+    fn(&shared::AntInput, &mut [u8; shared::MEMORY_SIZE], &mut shared::AntOutput);
+
 /// Represents a handle to a running Podman container.
 pub struct ContainerHandle {
     /// The ID of the Podman container.
@@ -28,7 +87,7 @@ impl ContainerHandle {
         {
             eprintln!("Failed to stop container {}: {}", self.container_id, e);
         } else {
-            println!("Container {} stopped", self.container_id);
+            eprintln!("Container {} stopped", self.container_id);
         }
     }
 }
@@ -50,9 +109,12 @@ impl Drop for ContainerHandle {
 pub struct PlayerConnection {
     /// The ID of the colony this player controls.
     pub colony_id: u32,
-    /// Handle to the Podman container running the player's AI.
+    /// Namespace of the simulation owning this connection, empty for the main simulation.
+    pub namespace: String,
+    /// Handle to the Podman container running the player's AI, None for in-process and
+    /// detached brains.
     #[allow(dead_code)]
-    pub container: ContainerHandle,
+    pub container: Option<ContainerHandle>,
     /// The Unix stream used to communicate with the player's AI.
     pub stream: UnixStream,
     /// Player setup information received from the AI upon connection.
@@ -62,7 +124,11 @@ pub struct PlayerConnection {
 impl Drop for PlayerConnection {
     /// Cleans up resources (socket file and directory) when the connection is dropped.
     fn drop(&mut self) {
-        let socket_dir = PathBuf::from(format!("/tmp/ant_sockets/{}", self.colony_id));
+        // In-process and detached brains have no socket file
+        if self.container.is_none() {
+            return;
+        }
+        let socket_dir = socket_dir(&self.namespace, self.colony_id);
         let socket_path = socket_dir.join("pherowar.sock"); // Corrected socket file name
         if socket_path.exists() {
             if let Err(e) = fs::remove_file(&socket_path) {
@@ -74,13 +140,13 @@ impl Drop for PlayerConnection {
             if let Err(e) = fs::remove_dir(&socket_dir) {
                 // It's common for this to fail if the directory isn't empty (e.g. logs still being written or other files)
                 // So, this might be more of a warning or debug log.
-                println!(
+                eprintln!(
                     "Attempted to remove socket dir {:?}, result: {:?}",
                     socket_dir, e
                 );
             }
         }
-        println!(
+        eprintln!(
             "Cleaned up socket and directory for colony {}",
             self.colony_id
         );
@@ -88,16 +154,62 @@ impl Drop for PlayerConnection {
 }
 
 impl PlayerConnection {
-    /// Starts a new player AI instance in a Podman container and establishes a connection.
-    pub fn start(colony_id: u32, player_cfg: &PlayerConfig) -> Result<Self> {
-        let socket_dir = PathBuf::from(format!("/tmp/ant_sockets/{}", colony_id));
+    /// Checks that Podman is installed and the player image is built, so that a missing
+    /// dependency is reported up front rather than as a container creation failure.
+    pub fn preflight() -> Result<()> {
+        match Command::new("podman").arg("--version").output() {
+            Ok(output) if output.status.success() => {}
+            Ok(output) => anyhow::bail!(
+                "podman is installed but not working: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(e) => anyhow::bail!(
+                "podman was not found ({e}). Player brains run in Podman containers: install podman, then run ./build.sh"
+            ),
+        }
+
+        let status = Command::new("podman")
+            .args(["image", "exists", PLAYER_IMAGE])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+        if !status.success() {
+            anyhow::bail!(
+                "podman image '{PLAYER_IMAGE}' not found. Build it first with ./build.sh"
+            );
+        }
+        Ok(())
+    }
+
+    /// Starts a new player AI instance in a Podman container and establishes a connection,
+    /// after building its brain when `build` is set and it has a build command.
+    /// Gives up if the container's socket can't be reached within `start_timeout`.
+    /// `map_info` is sent once the player's setup is received.
+    pub fn start(
+        namespace: &str,
+        colony_id: u32,
+        player_cfg: &PlayerConfig,
+        map_info: &MapInfo,
+        start_timeout: Duration,
+        build: bool,
+    ) -> Result<Self> {
+        if build {
+            build_brain(player_cfg)?;
+        } else if player_cfg.build_command.is_some() {
+            eprintln!(
+                "Not building {}: build commands run only with build_brains (--build-brains)",
+                player_cfg.name
+            );
+        }
+
+        let socket_dir = socket_dir(namespace, colony_id);
         fs::create_dir_all(&socket_dir)?;
         let socket_path = socket_dir.join("pherowar.sock");
         if socket_path.exists() {
             fs::remove_file(&socket_path)?;
         }
 
-        println!("Creating player container with socket at {:?}", socket_path);
+        eprintln!("Creating player container with socket at {:?}", socket_path);
 
         // Create container, mount the directory instead of the socket file
         let output = Command::new("podman")
@@ -113,7 +225,7 @@ impl PlayerConnection {
                 &format!("{}:/tmp/pherowar:z", socket_dir.to_string_lossy()),
                 "-v",
                 &format!("{}:/app/brain.so:z", player_cfg.so_path),
-                "localhost/pherowar-player",
+                PLAYER_IMAGE,
             ])
             .output()?;
 
@@ -127,7 +239,7 @@ impl PlayerConnection {
         let container_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
 
         // Start following logs into a file
-        let log_file_name = format!("{}_{}.log", player_cfg.name, colony_id);
+        let log_file_name = log_file_name(namespace, &player_cfg.name, colony_id);
 
         let log_file = OpenOptions::new()
             .create(true)
@@ -135,10 +247,10 @@ impl PlayerConnection {
             .truncate(true)
             .open(&log_file_name)?;
 
-        println!("Log file created: {}", log_file_name);
+        eprintln!("Log file created: {}", log_file_name);
         let file_for_stderr = log_file.try_clone()?;
 
-        println!(
+        eprintln!(
             "Starting player container {} with logs in {}",
             container_id, log_file_name
         );
@@ -165,34 +277,33 @@ impl PlayerConnection {
             );
         }
 
-        println!("Waiting for socket to become available...");
+        eprintln!("Waiting for socket to become available...");
 
-        // Wait for the socket file to appear and connect to it
-        let mut retries = 30; // wait up to ~3 seconds
+        // Wait for the socket file to appear and connect to it, backing off between attempts
+        let deadline = Instant::now() + start_timeout;
+        let mut delay = CONNECT_RETRY_START;
         let mut stream = loop {
-            if socket_path.exists() {
+            let error = if socket_path.exists() {
                 match UnixStream::connect(&socket_path) {
                     Ok(s) => break s,
-                    Err(e) => {
-                        retries -= 1;
-                        if retries == 0 {
-                            return Err(anyhow::anyhow!(
-                                "Failed to connect to player socket: {}",
-                                e
-                            ));
-                        }
-                    }
+                    Err(e) => format!("Failed to connect to player socket: {}", e),
                 }
             } else {
-                retries -= 1;
-                if retries == 0 {
-                    return Err(anyhow::anyhow!("Socket file not created by container"));
-                }
+                "Socket file not created by container".to_string()
+            };
+            let now = Instant::now();
+            if now >= deadline {
+                anyhow::bail!(
+                    "{} after {:.1}s (raise container_start_timeout on slow machines)",
+                    error,
+                    start_timeout.as_secs_f32()
+                );
             }
-            thread::sleep(Duration::from_millis(100));
+            thread::sleep(delay.min(deadline - now));
+            delay = (delay * 2).min(CONNECT_RETRY_MAX);
         };
 
-        println!("Connected to player container!");
+        eprintln!("Connected to player container!");
 
         // Send hello message to player
         stream.write_all(b"hello player")?;
@@ -201,7 +312,7 @@ impl PlayerConnection {
         let mut len_buf = [0u8; 4];
         stream.read_exact(&mut len_buf)?;
         let n = u32::from_le_bytes(len_buf) as usize;
-        if n > 256 {
+        if n > MAX_MESSAGE_SIZE {
             anyhow::bail!("player sent oversized setup ({n} bytes)");
         }
         let mut setup_buf = vec![0u8; n];
@@ -209,11 +320,73 @@ impl PlayerConnection {
 
         let setup: PlayerSetup = from_bytes::<PlayerSetup, Error>(&setup_buf)
             .map_err(|e| anyhow::anyhow!("invalid PlayerSetup: {e}"))?;
-        println!("Received PlayerSetup from player: {:?}", setup);
+        eprintln!("Received PlayerSetup from player: {:?}", setup);
+
+        // send length-prefixed MapInfo
+        let bytes = to_bytes::<Error>(map_info)?;
+        stream.write_all(&(bytes.len() as u32).to_le_bytes())?;
+        stream.write_all(&bytes)?;
 
         Ok(PlayerConnection {
             colony_id,
-            container,
+            namespace: namespace.to_string(),
+            container: Some(container),
+            stream,
+            setup,
+        })
+    }
+
+    /// Connection to a brain running on a thread of this process, speaking the same
+    /// protocol as the container wrapper. Used by tests and scripted experiments, without Podman.
+    pub fn in_process(colony_id: u32, setup: PlayerSetup, brain: InProcessBrain) -> Result<Self> {
+        let (stream, mut player_end) = UnixStream::pair()?;
+        thread::spawn(move || {
+            let mut len_buf = [0u8; 4];
+            // Ends when the host drops its end of the stream
+            while player_end.read_exact(&mut len_buf).is_ok() {
+                let mut buf = vec![0u8; u32::from_le_bytes(len_buf) as usize];
+                if player_end.read_exact(&mut buf).is_err() {
+                    break;
+                }
+                let Ok(req) = from_bytes::<AntRequest, Error>(&buf) else {
+                    break;
+                };
+                let mut memory = req.memory;
+                let mut output = shared::AntOutput {
+                    turn_angle: 0.0,
+                    pheromone_amounts: [0.0; shared::PHEROMONE_CHANNEL_COUNT],
+                    try_attack: false,
+                    move_offset: 0.0,
+                    beacon: shared::BEACON_NONE,
+                    lay_ahead: 0.0,
+                };
+                brain(&req.input, &mut memory, &mut output);
+                let Ok(bytes) = to_bytes::<Error>(&AntResponse { output, memory }) else {
+                    break;
+                };
+                let len = (bytes.len() as u32).to_le_bytes();
+                if player_end.write_all(&len).is_err() || player_end.write_all(&bytes).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(PlayerConnection {
+            colony_id,
+            namespace: String::new(),
+            container: None,
+            stream,
+            setup,
+        })
+    }
+
+    /// Connection without a brain on the other end, for colonies replayed from a recording.
+    /// Their ants must not think, as every request would fail.
+    pub fn detached(colony_id: u32, setup: PlayerSetup) -> Result<Self> {
+        let (stream, _) = UnixStream::pair()?;
+        Ok(PlayerConnection {
+            colony_id,
+            namespace: String::new(),
+            container: None,
             stream,
             setup,
         })
@@ -232,7 +405,7 @@ impl PlayerConnection {
         let mut len_buf = [0u8; 4];
         self.stream.read_exact(&mut len_buf)?;
         let resp_len = u32::from_le_bytes(len_buf) as usize;
-        if resp_len > 256 {
+        if resp_len > MAX_MESSAGE_SIZE {
             anyhow::bail!("player sent oversized response ({resp_len} bytes)");
         }
 
@@ -246,3 +419,49 @@ impl PlayerConnection {
         Ok(resp)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failing_build_reports_its_output() {
+        let mut player_cfg = PlayerConfig {
+            name: "builder".to_string(),
+            so_path: "/tmp/builder.so".to_string(),
+            texture_path: None,
+            build_command: None,
+        };
+        assert!(build_brain(&player_cfg).is_ok());
+
+        player_cfg.build_command = Some("true".to_string());
+        assert!(build_brain(&player_cfg).is_ok());
+
+        player_cfg.build_command = Some("echo missing semicolon >&2; exit 3".to_string());
+        let error = build_brain(&player_cfg).unwrap_err().to_string();
+        assert!(error.contains("missing semicolon"), "{error}");
+        assert!(error.contains("exit status: 3"), "{error}");
+    }
+
+    #[test]
+    fn test_build_runs_where_a_symlinked_brain_lives() {
+        let dir = std::env::temp_dir().join(format!("pherowar_build_{}", std::process::id()));
+        let real_dir = dir.join("real");
+        fs::create_dir_all(&real_dir).unwrap();
+        fs::write(real_dir.join("linked.so"), b"").unwrap();
+        let link = dir.join("linked.so");
+        let _ = fs::remove_file(&link);
+        std::os::unix::fs::symlink(real_dir.join("linked.so"), &link).unwrap();
+
+        let player_cfg = PlayerConfig {
+            name: "linked".to_string(),
+            so_path: link.to_string_lossy().to_string(),
+            texture_path: None,
+            build_command: Some("touch built".to_string()),
+        };
+        build_brain(&player_cfg).unwrap();
+        assert!(real_dir.join("built").exists());
+        assert!(!dir.join("built").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
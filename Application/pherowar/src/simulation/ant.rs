@@ -1,17 +1,26 @@
+use super::beacon::BeaconOrder;
+use super::combat::CombatEvent;
 use super::{
-    ANT_ATTACK_DAMAGE, ANT_LENGTH, ANT_SPEED, COLONY_NEST_SIZE, MAX_ANT_PROCESSING_TIME,
-    MAX_PHEROMONE_AMOUNT, SENSE_MAX_ANGLE, SENSE_MAX_DISTANCE, SENSE_NUM_SAMPLES,
-    pheromone::PheromoneChannel,
+    ANT_LENGTH, ANT_SPEED, MAX_PHEROMONE_AMOUNT, SENSE_MAX_ANGLE, SENSE_MAX_DISTANCE,
+    SENSE_NUM_SAMPLES, SUGGESTED_HEADING_RAYS,
+    pheromone::{PheromoneBudget, PheromoneChannel, TrailCells, trail_cells},
+};
+use super::{MAX_ANT_LONGEVITY, MAX_LAY_AHEAD, SeededRng, THINK_INTERVAL, Timer};
+use crate::config::{
+    EdgeBehavior, MovementMode, SimulationConfig, ThinkErrorFallback, ThinkEventsConfig,
+    TrophallaxisConfig,
 };
-use super::{MAX_ANT_LONGEVITY, THINK_INTERVAL, Timer};
 use crate::player::PlayerConnection;
 use crate::simulation::{Colony, GameMap, Terrain};
 
 use shared::PHEROMONE_CHANNEL_COUNT;
-use shared::{AntInput, AntOutput, MEMORY_SIZE, util::fast_sin_cos};
+use shared::{
+    AntInput, AntOutput, BEACON_NONE, ColonyState, MEMORY_SIZE, PROTOCOL_VERSION, ROLE_WORKER,
+    util::fast_sin_cos,
+};
 
 use anyhow::Result;
-use macroquad::prelude::{Vec2, rand};
+use macroquad::prelude::Vec2;
 use slotmap::{Key, new_key_type};
 use std::collections::HashMap;
 use std::f32;
@@ -35,67 +44,203 @@ pub struct FightOpponent {
     pub orientation: f32,
 }
 
+/// Wraps an angle into (-π, π], the range of the direct senses' angles.
+fn wrap_angle(angle: f32) -> f32 {
+    f32::consts::PI - (f32::consts::PI - angle).rem_euclid(f32::consts::TAU)
+}
+
+/// Adds Gaussian noise to the senses of `input`, with a standard deviation of `noise` times
+/// each value's range, clamped back into it. Senses that detected nothing stay unset.
+fn add_sense_noise(input: &mut AntInput, noise: f32, rng: &mut SeededRng) {
+    let angle_sd = noise * SENSE_MAX_ANGLE;
+    let distance_sd = noise * SENSE_MAX_DISTANCE;
+    let intensity_sd = noise * MAX_PHEROMONE_AMOUNT;
+
+    // Sensed within the vision cone
+    for sense in [
+        &mut input.wall_sense,
+        &mut input.food_sense,
+        &mut input.enemy_sense,
+        &mut input.friend_sense,
+    ] {
+        if sense.1 >= 0.0 {
+            sense.0 =
+                (sense.0 + angle_sd * rng.gaussian()).clamp(-SENSE_MAX_ANGLE, SENSE_MAX_ANGLE);
+            sense.1 = (sense.1 + distance_sd * rng.gaussian()).clamp(0.0, SENSE_MAX_DISTANCE);
+        }
+    }
+    // Sensed in any direction
+    for sense in [&mut input.colony_sense, &mut input.attacker_sense] {
+        if sense.1 >= 0.0 {
+            sense.0 = wrap_angle(sense.0 + angle_sd * rng.gaussian());
+            sense.1 = (sense.1 + distance_sd * rng.gaussian()).max(0.0);
+        }
+    }
+    for sense in input
+        .pheromone_senses
+        .iter_mut()
+        .chain(input.enemy_pheromone_senses.iter_mut())
+    {
+        if sense.1 > 0.0 {
+            sense.0 =
+                (sense.0 + angle_sd * rng.gaussian()).clamp(-SENSE_MAX_ANGLE, SENSE_MAX_ANGLE);
+            sense.1 = (sense.1 + intensity_sd * rng.gaussian()).clamp(0.0, MAX_PHEROMONE_AMOUNT);
+        }
+    }
+    for intensity in input
+        .cell_sense
+        .iter_mut()
+        .chain(input.enemy_cell_sense.iter_mut())
+    {
+        if *intensity > 0.0 {
+            *intensity =
+                (*intensity + intensity_sd * rng.gaussian()).clamp(0.0, MAX_PHEROMONE_AMOUNT);
+        }
+    }
+}
+
 /// State of an ant.
 pub struct Ant {
     pub ant_ref: AntRef,
 
     pub pos: Vec2,
     pub rotation: f32,
+    pub move_offset: f32, // Movement direction relative to the rotation, set at each think tick
     pub speed: f32,
     pub longevity: f32,
     pub is_on_colony: bool,
     pub is_on_food: bool,
-    pub carrying_food: bool,
+    carrying_food: bool, // Written through set_carrying_food only, which keeps the map in sync
     pub fight_opponents: Vec<FightOpponent>,
     pub memory: [u8; MEMORY_SIZE],
+    pub role: u8,
+    pub spawn_protection: f32, // Remaining seconds during which damage is ignored
+    pub emergence: f32, // Remaining seconds before a new ant leaves the nest, inactive and immune
+    pub last_input: Option<AntInput>, // Input from the last perceive, for debugging/visualization
+    pub last_input_rotation: f32, // Rotation at the last perceive (sense angles are relative to it)
+    pub last_output: Option<AntOutput>, // Last successful sanitized output, reused by the coast fallback
+    pub trail_start: Vec2, // Position at the last think tick, where the trail mode resumes laying
+    pub trip_time: f32,    // Seconds since the ant was last on its nest without food
+    pub since_discovery: f32, // Seconds since the ant last found food or met an enemy
 
     pub think_timer: Timer,
+    pub step_progress: f32, // Distance walked toward the next cell in grid movement
     pub try_attack: bool,
+    pub kills: u32,      // Enemies killed since the colony last collected the count
+    pub thinks: u32,     // Think ticks since the colony last collected the count
+    pub timed_out: bool, // Whether the ant died of a processing timeout, collected by the colony
+    pub think_nanos: Option<u64>, // Round-trip time of this tick's brain call, collected by the colony
+    pub laid_channels: u8, // Bitmask of the channels laid on this tick, collected by the colony
+    pub enemy_in_cell: bool, // Whether an enemy shared the ant's cell last tick (think on enemy event)
+    pub hit: Option<CombatEvent>, // Last successful hit of this tick, collected by the colony
+    pub beacon_order: Option<BeaconOrder>, // Beacon change asked on this tick, collected by the colony
+    pub last_attacker: Option<AntRef>,     // Last ant that damaged this one
+    pub killed_by_hit: bool, // Whether a hit, rather than age, a timeout or a wall, killed the ant
+    pub hit_from: Option<Vec2>, // Attacker position of the last hit since the previous think tick
+
+    // Fights against same-colony ants (friendly fire) can't be resolved during the ant's own
+    // update, as its colony's ants are borrowed. They are left here for the colony to resolve.
+    pub friendly_fight_request: Option<AntRef>,
+    pub friendly_strike: Option<AntRef>,
+    pub trace: bool, // Whether the ant's think ticks are logged to the simulation's trace file
+    pub trace_line: Option<String>, // This tick's trace line, collected by the simulation
 }
 
 impl Ant {
-    /// Create a new ant.
-    pub fn new(pos: Vec2, colony_id: u32) -> Self {
+    /// Create a new ant with its memory initialized from the colony's template.
+    /// Its initial rotation and think phase are drawn from the colony's `rng`.
+    pub fn new(
+        pos: Vec2,
+        colony_id: u32,
+        initial_memory: [u8; MEMORY_SIZE],
+        rng: &mut SeededRng,
+    ) -> Self {
         let ant_ref = AntRef {
             key: AntKey::null(),
             colony_id,
         };
 
         // Start think timer with a random value
-        let initial_think_timer_value = rand::gen_range(0.0, THINK_INTERVAL);
+        let initial_think_timer_value = rng.gen_range(0.0, THINK_INTERVAL);
         let think_timer = Timer::new(THINK_INTERVAL, initial_think_timer_value);
 
         Self {
             pos,
-            rotation: rand::gen_range(0.0, f32::consts::TAU),
+            rotation: rng.gen_range(0.0, f32::consts::TAU),
+            move_offset: 0.0,
             speed: ANT_SPEED,
             ant_ref,
             think_timer,
+            step_progress: 0.0,
             carrying_food: false,
             is_on_colony: true,
             is_on_food: false,
-            memory: [0u8; MEMORY_SIZE],   // zero-initialized
+            memory: initial_memory, // seeded by PlayerSetup
+            role: ROLE_WORKER,      // assigned by the colony at spawn
+            spawn_protection: 0.0,  // set by the colony at spawn
+            emergence: 0.0,         // set by the colony at spawn
+            last_input: None,       // filled on the first perceive
+            last_input_rotation: 0.0,
+            last_output: None,
+            trail_start: pos,
+            trip_time: 0.0,
+            since_discovery: 0.0,
             longevity: MAX_ANT_LONGEVITY, // start at max
             fight_opponents: Vec::new(),  // Initialize active_fights to an empty vector
             try_attack: false,            // initialize
+            kills: 0,
+            thinks: 0,
+            timed_out: false,
+            think_nanos: None,
+            laid_channels: 0,
+            enemy_in_cell: false,
+            hit: None,
+            beacon_order: None,
+            last_attacker: None,
+            killed_by_hit: false,
+            hit_from: None,
+            friendly_fight_request: None,
+            friendly_strike: None,
+            trace: false,
+            trace_line: None,
         }
     }
 
     /// Update ant state and behavior.
+    #[allow(clippy::too_many_arguments)]
     pub fn update(
         &mut self,
         colony_pos: &Vec2,
         map: &mut GameMap,
         pheromones: &mut [PheromoneChannel],
+        pheromone_budget: &mut PheromoneBudget,
         player_connection: &mut PlayerConnection,
+        max_processing_time: Option<u128>,
         other_colonies: &mut HashMap<u32, Colony>,
+        colony_state: &ColonyState,
+        config: &SimulationConfig,
+        rng: &mut SeededRng,
         dt: f32,
     ) {
         if self.is_dead() {
             return;
         }
 
+        self.update_spawn_protection(dt);
         self.think_timer.update(dt);
+        self.since_discovery += dt;
+
+        let events = &config.think_events;
+        if events.enabled && events.on_enemy {
+            // Force a think tick when an enemy shows up in the ant's cell
+            let x = self.pos.x.floor() as usize;
+            let y = self.pos.y.floor() as usize;
+            let enemy_in_cell = map.get_enemy_ant_at(x, y, self.ant_ref.colony_id).is_some();
+            if enemy_in_cell && !self.enemy_in_cell {
+                self.think_timer.force_ready();
+            }
+            self.enemy_in_cell = enemy_in_cell;
+        }
 
         if !self.think_timer.is_ready() {
             // Handle autopilot tick
@@ -107,11 +252,18 @@ impl Ant {
                 // Ant is not currently fighting and wanted to fight last think tick
                 // Check for ennemy in the current cell to initiate a fight
 
-                let x = self.pos.x.floor() as usize;
-                let y = self.pos.y.floor() as usize;
-                if let Some(opponent_ref) = map.get_enemy_ant_at(x, y, self.ant_ref.colony_id) {
+                if let Some(opponent_ref) = self.cell_attack_target(
+                    map,
+                    other_colonies,
+                    config.friendly_fire,
+                    config.max_attack_angle,
+                ) {
                     // Found an enemy ant in the same cell, initiate a fight
-                    if self.try_initiate_fight(&opponent_ref, other_colonies) {
+                    if self.try_initiate_fight(
+                        &opponent_ref,
+                        other_colonies,
+                        config.max_attack_angle,
+                    ) {
                         self.think_timer.force_ready();
                     }
                 }
@@ -123,52 +275,107 @@ impl Ant {
             // During this tick, the ant perceives the environment, thinks (player update call), and applies pheromones
 
             self.think_timer.wrap();
-
-            // Perceive the environment
-            let (ant_input, perceived) = self.perceive(map, pheromones, colony_pos);
+            self.thinks += 1;
+
+            // Perceive the environment, other colonies' pheromones included when enabled
+            let enemy_pheromones: Vec<&[PheromoneChannel]> = if config.sense_enemy_pheromones {
+                other_colonies
+                    .values()
+                    .map(|colony| colony.pheromones.as_slice())
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            let (mut ant_input, perceived) =
+                self.perceive(map, pheromones, &enemy_pheromones, colony_pos, config, rng);
+            ant_input.colony_food_level =
+                colony_food_level(colony_state.food_collected, config.colony_food_reference);
+            self.last_input = Some(ant_input);
+            self.last_input_rotation = self.rotation;
 
             // Call the player update function and sanitize the output
-            let sanitized_ouput = match self.think(ant_input, player_connection) {
-                Ok(mut output) => {
-                    self.sanitize_output(&mut output);
-                    output
-                }
-                Err(e) => {
-                    eprintln!(
-                        "Ignored think tick for {:?} because of error: {:?}",
-                        self.ant_ref.key, e
-                    );
-                    return;
-                }
+            let think_result = self.think(
+                ant_input,
+                *colony_state,
+                player_connection,
+                max_processing_time,
+            );
+            let Some(sanitized_ouput) = self.resolve_think_result(think_result, config) else {
+                return;
             };
 
-            // Apply pheromones
-            self.apply_pheromones(sanitized_ouput.pheromone_amounts, pheromones);
+            // Apply pheromones, ahead along the facing the ant turns to when asked
+            let ahead = (sanitized_ouput.lay_ahead > 0.0).then(|| {
+                lay_ahead_cell(
+                    map,
+                    self.pos,
+                    self.rotation + sanitized_ouput.turn_angle,
+                    sanitized_ouput.lay_ahead,
+                )
+            });
+            self.apply_pheromones(
+                sanitized_ouput.pheromone_amounts,
+                map,
+                pheromones,
+                pheromone_budget,
+                config.pheromone_trail,
+                ahead,
+            );
+            if config.beacons.enabled {
+                let cell = (self.pos.x as u32, self.pos.y as u32);
+                self.beacon_order = BeaconOrder::from_output(sanitized_ouput.beacon, cell);
+            }
             self.try_attack = sanitized_ouput.try_attack;
             if self.try_attack && !self.is_fighting() {
                 if let Some(mut perceived) = perceived {
-                    self.try_initiate_fight(&mut perceived, other_colonies);
+                    self.try_initiate_fight(
+                        &mut perceived,
+                        other_colonies,
+                        config.max_attack_angle,
+                    );
                 }
             }
 
             // Update orientation
             if self.is_fighting() {
                 // Fighting -> Handle fight
-                self.handle_fight(other_colonies);
+                self.handle_fight(other_colonies, config);
             } else {
                 // Not fighting -> Update rotation
                 self.rotation =
                     (self.rotation + sanitized_ouput.turn_angle).rem_euclid(f32::consts::TAU);
             }
+            self.move_offset = sanitized_ouput.move_offset;
+
+            // Host override the brain can't prevent: weak ants head home to rejuvenate
+            if self.longevity < config.flee_longevity {
+                self.flee_to(*colony_pos);
+            }
+
+            if self.trace {
+                self.trace_line = Some(self.trace_record(&sanitized_ouput));
+            }
         }
 
         if !self.is_fighting() {
-            // Not fighting -> Move
-            self.update_position(map, dt);
+            // Not fighting -> Move, and think next tick when bumping into a wall
+            let hit_wall = match config.movement {
+                MovementMode::Continuous => {
+                    self.update_position(map, config.backward_speed, config.edge_behavior, dt)
+                }
+                MovementMode::Grid => self.walk_grid(map, config.edge_behavior, dt),
+            };
+            if hit_wall && events.enabled && events.on_wall {
+                self.think_timer.force_ready();
+            }
         }
     }
 
-    fn handle_fight(&mut self, other_colonies: &mut HashMap<u32, Colony>) -> bool {
+    fn handle_fight(
+        &mut self,
+        other_colonies: &mut HashMap<u32, Colony>,
+        config: &SimulationConfig,
+    ) -> bool {
         // Handle fight logic here
         // For example, you can check if the ant is still alive and update its state accordingly
         // This is a placeholder for the actual fight handling logic
@@ -176,7 +383,7 @@ impl Ant {
         // Attack until either a hit succeeds or there are no more opponents.
         while !self.fight_opponents.is_empty() {
             let fight_opponent = self.fight_opponents[0].clone();
-            if self.try_attack(&fight_opponent, other_colonies) {
+            if self.try_attack(&fight_opponent, other_colonies, config) {
                 return true;
             }
         }
@@ -187,6 +394,39 @@ impl Ant {
         // Increase longevity by a certain amount, but not exceeding the maximum
         self.longevity = (self.longevity + amount).min(MAX_ANT_LONGEVITY);
     }
+    /// One line of the ant's trace: its state after a think tick, and that tick's input and output.
+    fn trace_record(&self, output: &AntOutput) -> String {
+        format!(
+            "pos=({:.2}, {:.2}) rotation={:.3} longevity={:.1} carrying_food={} fighting={} input={:?} output={:?} memory={:?}",
+            self.pos.x,
+            self.pos.y,
+            self.rotation,
+            self.longevity,
+            self.carrying_food,
+            self.is_fighting(),
+            self.last_input,
+            output,
+            self.memory,
+        )
+    }
+
+    /// Turns the ant straight toward its nest at `colony_pos`, moving forward, unless it is
+    /// fighting or already home.
+    fn flee_to(&mut self, colony_pos: Vec2) {
+        if self.is_fighting() || self.is_on_colony {
+            return;
+        }
+        let to_nest = colony_pos - self.pos;
+        self.rotation = to_nest.y.atan2(to_nest.x).rem_euclid(f32::consts::TAU);
+        self.move_offset = 0.0;
+    }
+
+    /// Changes the think interval, keeping the ant's phase within it.
+    pub fn set_think_interval(&mut self, interval: f32) {
+        let phase = self.think_timer.value / self.think_timer.max_value;
+        self.think_timer = Timer::new(interval, phase * interval);
+    }
+
     /// Restore ant longevity.
     pub fn rejuvenate(&mut self) {
         self.longevity = MAX_ANT_LONGEVITY;
@@ -196,7 +436,10 @@ impl Ant {
         &mut self,
         map: &mut GameMap,
         pheromones: &[PheromoneChannel],
+        enemy_pheromones: &[&[PheromoneChannel]],
         colony_pos: &Vec2,
+        config: &SimulationConfig,
+        rng: &mut SeededRng,
     ) -> (AntInput, Option<AntRef>) {
         // Initialize AntInput
         let mut ant_input = AntInput {
@@ -211,25 +454,62 @@ impl Ant {
             colony_sense: (0.0, -1.0),
             enemy_sense: (0.0, -1.0),
             is_fighting: self.is_fighting(),
+            role: self.role,
+            friend_sense: (0.0, -1.0),
+            enemy_carrying_food: false,
+            friend_carrying_food: false,
+            colony_food_level: 0.0,
+            attacker_sense: (0.0, -1.0),
+            time_since_discovery: 0.0,
+            enemy_pheromone_senses: [(0.0, 0.0); PHEROMONE_CHANNEL_COUNT],
+            enemy_cell_sense: [0.0; PHEROMONE_CHANNEL_COUNT],
+            suggested_heading: 0.0,
         };
 
+        if config.suggest_heading {
+            ant_input.suggested_heading = clear_heading(map, self.pos, self.rotation);
+        }
+
+        // Sense the ant that hit this one since the last think, where it stood when hitting
+        if let Some(attacker_pos) = self.hit_from.take() {
+            let to_attacker = attacker_pos - self.pos;
+            ant_input.attacker_sense = (
+                wrap_angle(to_attacker.y.atan2(to_attacker.x) - self.rotation),
+                to_attacker.length(),
+            );
+        }
+
         let x = self.pos.x.floor() as usize;
         let y = self.pos.y.floor() as usize;
 
         // Sense pheromones in current cell
         for channel in 0..PHEROMONE_CHANNEL_COUNT {
-            if y < pheromones[channel].data.len() && x < pheromones[channel].data[y].len() {
-                ant_input.cell_sense[channel] = pheromones[channel].data[y][x];
-            }
+            ant_input.cell_sense[channel] = pheromones[channel].get(x, y);
+            ant_input.enemy_cell_sense[channel] = strongest(enemy_pheromones, channel, x, y);
         }
 
+        let sense_carried_food = config.sense_carried_food;
         let mut attackable_enemy_ref: Option<AntRef> = None;
+        // With friendly fire, a friend in reach can be attacked when no enemy is
+        if config.friendly_fire
+            && let Some(ant_ref) =
+                map.get_friendly_ant_at(x, y, self.ant_ref.colony_id, &self.ant_ref)
+        {
+            attackable_enemy_ref = Some(ant_ref);
+        }
         // Sense enemy in current cell (without using other_colonies)
         if let Some(ant_ref) = map.get_enemy_ant_at(x, y, self.ant_ref.colony_id) {
             // Found an enemy ant in the same cell
             ant_input.enemy_sense = (0.0, 0.0);
+            ant_input.enemy_carrying_food = sense_carried_food && map.is_carrying_food(&ant_ref);
             attackable_enemy_ref = Some(ant_ref.clone());
         }
+        // Sense friend in current cell
+        if let Some(ant_ref) = map.get_friendly_ant_at(x, y, self.ant_ref.colony_id, &self.ant_ref)
+        {
+            ant_input.friend_sense = (0.0, 0.0);
+            ant_input.friend_carrying_food = sense_carried_food && map.is_carrying_food(&ant_ref);
+        }
 
         // Raycast to colony
         let dx = colony_pos.x - self.pos.x;
@@ -240,18 +520,43 @@ impl Ant {
             let (blocked, dist) =
                 map.raycast_angle(self.pos, angle_to_colony, dist_to_colony_sq.sqrt());
             if !blocked {
-                ant_input.colony_sense = (angle_to_colony - self.rotation, dist);
+                ant_input.colony_sense = (wrap_angle(angle_to_colony - self.rotation), dist);
             }
         }
 
-        // Sense the environment in the ant's perception cone by sampling at random angles and distances
+        // Communication reaches up to the comm range. Within the vision range, friendly
+        // signals are read on the vision samples, pulled in to scale with the comm range.
+        let comm_range = config.ant_comm_range.max(1.0);
+        let comm_scale =
+            (comm_range <= SENSE_MAX_DISTANCE).then(|| comm_range / SENSE_MAX_DISTANCE);
+
+        // Vision: sense walls, enemies and food in the ant's perception cone
+        // by sampling at random angles and distances up to the vision range
         for _ in 0..SENSE_NUM_SAMPLES {
-            let angle_offset = rand::gen_range(-SENSE_MAX_ANGLE, SENSE_MAX_ANGLE);
+            let angle_offset = rng.gen_range(-SENSE_MAX_ANGLE, SENSE_MAX_ANGLE);
             let angle = self.rotation + angle_offset;
-            let random_dist = rand::gen_range(1.0, SENSE_MAX_DISTANCE);
+            let random_dist = rng.gen_range(1.0, SENSE_MAX_DISTANCE);
 
             // Sense wall or map edge
             let (blocked, wall_dist) = map.raycast_angle(self.pos, angle, random_dist);
+
+            // The comm sample lies on the same ray, closer in, so it is clear up to the wall
+            if let Some(scale) = comm_scale {
+                let comm_dist = random_dist * scale;
+                if !blocked || comm_dist < wall_dist {
+                    sense_signals(
+                        &mut ant_input,
+                        map,
+                        pheromones,
+                        enemy_pheromones,
+                        self.pos,
+                        angle,
+                        angle_offset,
+                        comm_dist,
+                    );
+                }
+            }
+
             if blocked {
                 if wall_dist < ant_input.wall_sense.1 || ant_input.wall_sense.1 < 0.0 {
                     ant_input.wall_sense = (angle_offset, wall_dist);
@@ -270,20 +575,14 @@ impl Ant {
             let dist: f32 =
                 ((self.pos.x - sample_x).powi(2) + (self.pos.y - sample_y).powi(2)).sqrt();
 
-            // Sense pheromones
-            for channel in 0..PHEROMONE_CHANNEL_COUNT {
-                let intensity = pheromones[channel].data[yi as usize][xi as usize];
-                if intensity > ant_input.pheromone_senses[channel].1 {
-                    ant_input.pheromone_senses[channel] = (angle_offset, intensity);
-                }
-            }
-
             // Sense enemies
             if let Some(ant_ref) =
                 map.get_enemy_ant_at(xi as usize, yi as usize, self.ant_ref.colony_id)
             {
                 if dist < ant_input.enemy_sense.1 || ant_input.enemy_sense.1 < 0.0 {
                     ant_input.enemy_sense = (angle_offset, dist);
+                    ant_input.enemy_carrying_food =
+                        sense_carried_food && map.is_carrying_food(&ant_ref);
 
                     if dist <= ANT_LENGTH {
                         attackable_enemy_ref = Some(ant_ref.clone());
@@ -291,6 +590,27 @@ impl Ant {
                 }
             }
 
+            // Sense friends
+            if let Some(ant_ref) = map.get_friendly_ant_at(
+                xi as usize,
+                yi as usize,
+                self.ant_ref.colony_id,
+                &self.ant_ref,
+            ) && (dist < ant_input.friend_sense.1 || ant_input.friend_sense.1 < 0.0)
+            {
+                ant_input.friend_sense = (angle_offset, dist);
+                if config.friendly_fire
+                    && dist <= ANT_LENGTH
+                    && attackable_enemy_ref
+                        .as_ref()
+                        .is_none_or(|target| target.colony_id == self.ant_ref.colony_id)
+                {
+                    attackable_enemy_ref = Some(ant_ref.clone());
+                }
+                ant_input.friend_carrying_food =
+                    sense_carried_food && map.is_carrying_food(&ant_ref);
+            }
+
             match map.get_terrain_at(xi as usize, yi as usize) {
                 Some(Terrain::Food(_)) => {
                     if dist < ant_input.food_sense.1 || ant_input.food_sense.1 < 0.0 {
@@ -301,30 +621,71 @@ impl Ant {
             }
         }
 
+        // Past the vision range, signals get their own samples, traced for walls all the way
+        if comm_scale.is_none() {
+            for _ in 0..SENSE_NUM_SAMPLES {
+                let angle_offset = rng.gen_range(-SENSE_MAX_ANGLE, SENSE_MAX_ANGLE);
+                let angle = self.rotation + angle_offset;
+                let random_dist = rng.gen_range(1.0, comm_range);
+
+                let (blocked, _) = map.raycast_angle(self.pos, angle, random_dist);
+                if !blocked {
+                    sense_signals(
+                        &mut ant_input,
+                        map,
+                        pheromones,
+                        enemy_pheromones,
+                        self.pos,
+                        angle,
+                        angle_offset,
+                        random_dist,
+                    );
+                }
+            }
+        }
+
+        // A sensed enemy counts as met
+        if ant_input.enemy_sense.1 >= 0.0 {
+            self.since_discovery = 0.0;
+        }
+        ant_input.time_since_discovery = self.since_discovery;
+
+        if config.sense_noise > 0.0 {
+            add_sense_noise(&mut ant_input, config.sense_noise, rng);
+        }
+
         (ant_input, attackable_enemy_ref)
     }
 
     fn think(
         &mut self,
         ant_input: AntInput,
+        colony_state: ColonyState,
         player_connection: &mut PlayerConnection,
+        max_processing_time: Option<u128>,
     ) -> Result<AntOutput> {
         let req = shared::AntRequest {
+            version: PROTOCOL_VERSION,
             input: ant_input,
             memory: self.memory,
+            colony: colony_state,
         };
 
         let start_time = std::time::Instant::now();
         let resp_result = player_connection.player_update(req);
         let elapsed_time = start_time.elapsed().as_nanos();
+        self.think_nanos = Some(elapsed_time as u64);
 
-        if elapsed_time > MAX_ANT_PROCESSING_TIME {
+        if let Some(max_processing_time) = max_processing_time
+            && elapsed_time > max_processing_time
+        {
             self.die();
+            self.timed_out = true;
             return Err(anyhow::anyhow!(
                 "{:?} processing timed out. Took too long to process ({:}ns > {:}ns).",
                 self.ant_ref,
                 elapsed_time,
-                MAX_ANT_PROCESSING_TIME
+                max_processing_time
             ));
         }
 
@@ -333,17 +694,85 @@ impl Ant {
         Ok(resp.output)
     }
 
+    /// Sanitizes and caches a successful think output. On error, falls back according to
+    /// `think_error_fallback`; None means the think tick is skipped.
+    fn resolve_think_result(
+        &mut self,
+        think_result: Result<AntOutput>,
+        config: &SimulationConfig,
+    ) -> Option<AntOutput> {
+        match think_result {
+            Ok(mut output) => {
+                self.sanitize_output(&mut output, config.max_move_offset);
+                // Beacon orders are one-shot, coasting doesn't repeat them
+                self.last_output = Some(AntOutput {
+                    beacon: BEACON_NONE,
+                    ..output
+                });
+                Some(output)
+            }
+            Err(e) => {
+                let fallback = match config.think_error_fallback {
+                    ThinkErrorFallback::Coast if !self.is_dead() => self.last_output,
+                    _ => None,
+                };
+                if fallback.is_some() {
+                    eprintln!(
+                        "Reused the last output of {:?} because of error: {:?}",
+                        self.ant_ref.key, e
+                    );
+                } else {
+                    eprintln!(
+                        "Ignored think tick for {:?} because of error: {:?}",
+                        self.ant_ref.key, e
+                    );
+                }
+                fallback
+            }
+        }
+    }
+
     fn apply_pheromones(
         &mut self,
         pheromones_layed: [f32; PHEROMONE_CHANNEL_COUNT],
+        map: &GameMap,
         pheromones_channels: &mut [PheromoneChannel],
+        budget: &mut PheromoneBudget,
+        trail: bool,
+        ahead: Option<(usize, usize)>,
     ) {
-        let cell_x = self.pos.x.floor() as usize;
-        let cell_y = self.pos.y.floor() as usize;
+        // In trail mode, the deposit is spread over the cells crossed since the last think tick
+        let trail_start = std::mem::replace(&mut self.trail_start, self.pos);
+        let cells = if let Some(cell) = ahead {
+            TrailCells::from_slice(&[cell])
+        } else if trail {
+            trail_cells(trail_start, self.pos)
+        } else {
+            TrailCells::from_slice(&[(self.pos.x.floor() as usize, self.pos.y.floor() as usize)])
+        };
 
+        // Throttle the deposit when it would push the colony over its pheromone budget
+        let requested: f32 = pheromones_layed.iter().filter(|&&a| a > 0.0).sum();
+        let factor = budget.deposit_factor(requested);
+        if factor <= 0.0 {
+            return;
+        }
+
+        let share = factor / cells.len() as f32;
+        // The share of scentless cells is lost
+        let cells: TrailCells = cells
+            .into_iter()
+            .filter(|&(x, y)| map.zone_at(x, y).allows_pheromones())
+            .collect();
+        if cells.is_empty() {
+            return;
+        }
         for (idx, &amount) in pheromones_layed.iter().enumerate() {
             if amount > 0.0 && idx < PHEROMONE_CHANNEL_COUNT {
-                pheromones_channels[idx].lay(cell_x, cell_y, amount);
+                for &(x, y) in &cells {
+                    budget.total += pheromones_channels[idx].lay(x, y, amount * share);
+                }
+                self.laid_channels |= 1 << idx;
             }
         }
     }
@@ -354,6 +783,7 @@ impl Ant {
         &mut self,
         fight_opponent: &FightOpponent,
         other_colonies: &mut HashMap<u32, Colony>,
+        config: &SimulationConfig,
     ) -> bool {
         // Use stored orientation to face the opponent
         self.rotation = fight_opponent.orientation;
@@ -361,35 +791,65 @@ impl Ant {
         let target_colony_id = fight_opponent.ant_ref.colony_id;
         let target_key = fight_opponent.ant_ref.key;
 
-        let mut target_is_alive_and_found = false;
-        let mut hit_successful = false;
-
-        if let Some(target_colony_mut) = other_colonies.get_mut(&target_colony_id) {
-            if let Some(target) = target_colony_mut.ants.get_mut(target_key) {
-                let distance_sq = self.pos.distance_squared(target.pos);
-                if !target.is_dead() && distance_sq <= ANT_LENGTH * ANT_LENGTH {
-                    target_is_alive_and_found = true;
-
-                    // Attack the target
-                    target.take_damage(ANT_ATTACK_DAMAGE);
-                    hit_successful = true;
+        if target_colony_id == self.ant_ref.colony_id {
+            // Friendly fire: the colony strikes the target after this update
+            self.friendly_strike = Some(fight_opponent.ant_ref.clone());
+            return true;
+        }
 
-                    if target.is_dead() {
-                        // Killed the target
-                        self.rejuvenate_by(MAX_ANT_LONGEVITY - self.longevity / 2.0); // Rejuvenate half of the longevity
-                        self.remove_opponent(target_key); // Remove dead opponent
-                    }
-                }
+        match other_colonies
+            .get_mut(&target_colony_id)
+            .and_then(|colony| colony.ants.get_mut(target_key))
+        {
+            Some(target) => self.strike(target, config),
+            None => {
+                // Target is already dead (probably removed from map)
+                self.remove_opponent(target_key);
+                false
             }
         }
+    }
 
-        if !target_is_alive_and_found {
-            // Target is already dead (probably removed from map)
-            // or too far away (respawned when wall placed)
+    /// Hit the opponent if alive and within reach, otherwise drop it from the fight.
+    /// Returns true if the hit was successful.
+    pub fn strike(&mut self, target: &mut Ant, config: &SimulationConfig) -> bool {
+        let target_key = target.ant_ref.key;
+        let distance_sq = self.pos.distance_squared(target.pos);
+        if target.is_dead() || distance_sq > ANT_LENGTH * ANT_LENGTH {
+            // Target is already dead or too far away (respawned when wall placed)
             self.remove_opponent(target_key);
+            return false;
         }
 
-        return hit_successful;
+        // Attack the target
+        let damage = self.attack_damage(target, config);
+        target.take_damage(damage, &self.ant_ref, self.pos);
+        self.hit = Some(CombatEvent::new(target.pos, damage, target.is_dead()));
+
+        if target.is_dead() {
+            // Killed the target
+            if target.ant_ref.colony_id != self.ant_ref.colony_id {
+                self.kills += 1;
+            }
+            self.rejuvenate_by(MAX_ANT_LONGEVITY - self.longevity / 2.0); // Rejuvenate half of the longevity
+            self.remove_opponent(target_key); // Remove dead opponent
+        }
+        true
+    }
+
+    /// Damage of a hit on `target`, scaled by where the attacker stands relative to the target's
+    /// facing: up to `rear_attack_bonus` more from behind, `front_attack_reduction` less from
+    /// the front, flat from the sides.
+    fn attack_damage(&self, target: &Ant, config: &SimulationConfig) -> f32 {
+        let to_attacker = (self.pos - target.pos).normalize_or_zero();
+        // 1.0 when the target faces the attacker, -1.0 when it turns its back
+        let facing = to_attacker.dot(Vec2::from_angle(target.rotation));
+        let factor = if facing >= 0.0 {
+            1.0 - config.front_attack_reduction * facing
+        } else {
+            1.0 - config.rear_attack_bonus * facing
+        };
+        config.attack_damage * factor.max(0.0)
     }
 
     /// Moves the ant to a new position and updates its registration in the spatial index.
@@ -431,12 +891,27 @@ impl Ant {
         // The logic above handles changing cells. If it stays in the same cell, no map calls are made here.
     }
 
-    fn update_position(&mut self, map: &mut GameMap, dt: f32) {
-        let (dy, dx) = fast_sin_cos(self.rotation);
+    /// Moves the ant forward, turning away from walls. Returns true if a wall blocked the move,
+    /// or the ant bounced off the map's edge. The ant moves along `rotation + move_offset`,
+    /// slower when not moving forward.
+    fn update_position(
+        &mut self,
+        map: &mut GameMap,
+        backward_speed: f32,
+        edge_behavior: EdgeBehavior,
+        dt: f32,
+    ) -> bool {
+        let heading = self.rotation + self.move_offset;
+        let (dy, dx) = fast_sin_cos(heading);
         let mut speed = self.speed;
         if self.carrying_food {
             speed *= super::ANT_SLOWNESS_WITH_FOOD;
         }
+        let backwardness = self.move_offset.abs() / f32::consts::PI;
+        speed *= 1.0 + (backward_speed.clamp(0.0, 1.0) - 1.0) * backwardness;
+        speed *= map
+            .zone_at(self.pos.x.floor() as usize, self.pos.y.floor() as usize)
+            .speed_factor();
         let next_x_float = self.pos.x + dx * speed * dt;
         let next_y_float = self.pos.y + dy * speed * dt;
 
@@ -448,35 +923,46 @@ impl Ant {
             );
             // Ant's self.pos remains unchanged, and it stays in its current cell in ants_in_cell.
             // This effectively means the ant doesn't move this tick if its calculations result in NaN.
-            return;
+            return false;
         }
 
         let w = map.width as f32;
         let h = map.height as f32;
 
-        let next_cell_x_isize = next_x_float.floor() as isize;
-        let next_cell_y_isize = next_y_float.floor() as isize;
+        let off_x = next_x_float < 0.0 || next_x_float >= w;
+        let off_y = next_y_float < 0.0 || next_y_float >= h;
+        if off_x || off_y {
+            match edge_behavior {
+                // Handled with the walls below
+                EdgeBehavior::Wall => {}
+                EdgeBehavior::Death => {
+                    self.longevity = 0.0;
+                    return false;
+                }
+                EdgeBehavior::Bounce => {
+                    self.bounce(heading, off_x, off_y);
+                    return true;
+                }
+            }
+        }
 
-        let blocked = map
-            .get_terrain_at(next_cell_x_isize as usize, next_cell_y_isize as usize)
-            .map_or(true, |terrain| terrain == &Terrain::Wall);
+        // Sweep the path so that large steps can't tunnel through thin walls
+        let blocked = path_blocked(map, self.pos, Vec2::new(next_x_float, next_y_float));
 
         if !blocked {
             // Call the new centralized function to update position and spatial index
             self.move_to_pos(map, Vec2::new(next_x_float, next_y_float)); // Removed colony_id
+            false
         } else {
             // Collision handling logic (rotation)
             let try_rotate = |angle: f32| -> bool {
-                let (dy_r, dx_r) = fast_sin_cos(self.rotation + angle);
+                let (dy_r, dx_r) = fast_sin_cos(heading + angle);
                 let tx = self.pos.x + dx_r * self.speed * dt;
                 let ty = self.pos.y + dy_r * self.speed * dt;
                 if tx < 0.0 || tx >= w || ty < 0.0 || ty >= h {
                     return false;
                 }
-                let mx = tx.floor() as isize;
-                let my = ty.floor() as isize;
-                map.get_terrain_at(mx as usize, my as usize)
-                    .map_or(false, |terrain| terrain != &Terrain::Wall)
+                !path_blocked(map, self.pos, Vec2::new(tx, ty))
             };
 
             let cw_clear = try_rotate(f32::consts::FRAC_PI_4);
@@ -492,53 +978,202 @@ impl Ant {
                 // Both blocked, rotate 180
                 self.rotation = (self.rotation + f32::consts::PI) % f32::consts::TAU;
             }
+            true
+        }
+    }
+
+    /// Mirrors the heading on the crossed edges, keeping the move offset.
+    fn bounce(&mut self, heading: f32, off_x: bool, off_y: bool) {
+        let mut bounced = heading;
+        if off_x {
+            bounced = f32::consts::PI - bounced;
+        }
+        if off_y {
+            bounced = -bounced;
+        }
+        self.rotation = (bounced - self.move_offset).rem_euclid(f32::consts::TAU);
+    }
+
+    /// Grid movement: walks at the ant's speed, independently of think ticks, stepping each
+    /// time the distance to the next cell center (1, or √2 diagonally) is covered.
+    /// Returns true if a wall blocked a step or the ant bounced, which drops the distance left.
+    fn walk_grid(&mut self, map: &mut GameMap, edge_behavior: EdgeBehavior, dt: f32) -> bool {
+        self.step_progress += self.speed * dt;
+        loop {
+            let (dx, dy) = self.grid_step();
+            let length = if dx != 0.0 && dy != 0.0 {
+                f32::consts::SQRT_2
+            } else {
+                1.0
+            };
+            if self.step_progress < length {
+                return false;
+            }
+            self.step_progress -= length;
+            if self.step_cell(map, edge_behavior) {
+                self.step_progress = 0.0;
+                return true;
+            }
+            if self.longevity <= 0.0 {
+                return false;
+            }
         }
     }
 
-    pub fn check_colony(&mut self, colony_pos: &Vec2) {
+    /// Offset of the neighboring cell closest to the heading, diagonals included.
+    fn grid_step(&self) -> (f32, f32) {
+        let heading = self.rotation + self.move_offset;
+        let snapped = (heading / f32::consts::FRAC_PI_4).round() * f32::consts::FRAC_PI_4;
+        let (dy, dx) = snapped.sin_cos();
+        (dx.round(), dy.round())
+    }
+
+    /// Grid movement: steps to the center of the neighboring cell closest to the heading.
+    /// The rotation itself isn't snapped, so small turns add up across think ticks.
+    /// Returns true if a wall blocked the step, turning the ant around, or it bounced.
+    fn step_cell(&mut self, map: &mut GameMap, edge_behavior: EdgeBehavior) -> bool {
+        let heading = self.rotation + self.move_offset;
+        let (dx, dy) = self.grid_step();
+        let x = self.pos.x.floor() + dx;
+        let y = self.pos.y.floor() + dy;
+
+        let off_x = x < 0.0 || x >= map.width as f32;
+        let off_y = y < 0.0 || y >= map.height as f32;
+        if off_x || off_y {
+            match edge_behavior {
+                EdgeBehavior::Wall => {}
+                EdgeBehavior::Death => {
+                    self.longevity = 0.0;
+                    return false;
+                }
+                EdgeBehavior::Bounce => {
+                    self.bounce(heading, off_x, off_y);
+                    return true;
+                }
+            }
+        }
+
+        let target = Vec2::new(x + 0.5, y + 0.5);
+        // Ants fresh from the nest may not stand on a cell center yet
+        let center = self.pos.floor() + Vec2::splat(0.5);
+        if path_blocked(map, center, target) {
+            self.rotation = (self.rotation + f32::consts::PI) % f32::consts::TAU;
+            return true;
+        }
+        self.move_to_pos(map, target);
+        false
+    }
+
+    pub fn check_colony(
+        &mut self,
+        colony_pos: &Vec2,
+        nest_radius: f32,
+        events: &ThinkEventsConfig,
+    ) {
         let dx = self.pos.x - colony_pos.x;
         let dy = self.pos.y - colony_pos.y;
-        if (dx * dx + dy * dy) <= COLONY_NEST_SIZE * COLONY_NEST_SIZE / 4.0 {
-            if !self.is_on_colony {
-                // Force a think tick when the ant enters colony
-                self.think_timer.force_ready();
-            }
-            self.is_on_colony = true;
-        } else {
-            self.is_on_colony = false;
+        let on_colony = (dx * dx + dy * dy) <= nest_radius * nest_radius;
+        if on_colony != self.is_on_colony && events.forces_think(events.on_colony, on_colony) {
+            // Force a think tick when the ant enters (or, in event mode, leaves) the colony
+            self.think_timer.force_ready();
         }
+        self.is_on_colony = on_colony;
     }
 
-    pub fn check_food(&mut self, map: &mut GameMap) {
+    /// Picks up the nearest food within `pickup_range` if the ant carries none.
+    pub fn check_food(&mut self, map: &mut GameMap, events: &ThinkEventsConfig, pickup_range: f32) {
         let x = self.pos.x.floor() as usize;
         let y = self.pos.y.floor() as usize;
-        match map.get_terrain_at(x, y) {
-            Some(Terrain::Food(_)) => {
-                if !self.is_on_food {
-                    // Force a think tick when the ant enters food
-                    self.think_timer.force_ready();
-                }
-                if !self.carrying_food {
-                    map.take_food_at(x, y);
-                    self.carrying_food = true;
-                    self.rejuvenate();
-                }
+        let on_food = matches!(map.get_terrain_at(x, y), Some(Terrain::Food(_)));
+        if on_food != self.is_on_food && events.forces_think(events.on_food, on_food) {
+            // Force a think tick when the ant enters (or, in event mode, leaves) food
+            self.think_timer.force_ready();
+        }
+        if on_food {
+            self.since_discovery = 0.0;
+        }
+        if !self.carrying_food
+            && let Some((food_x, food_y)) = map.nearest_food_within(self.pos, pickup_range)
+        {
+            map.take_food_at(food_x, food_y);
+            self.set_carrying_food(true, map);
+            self.rejuvenate();
+            self.since_discovery = 0.0;
+        }
 
-                // Re-check terrain after taking food to correctly set is_on_food
-                if let Some(Terrain::Food(_)) = map.get_terrain_at(x, y) {
-                    self.is_on_food = true;
-                } else {
-                    self.is_on_food = false;
-                }
-            }
-            _ => {
-                self.is_on_food = false;
+        // Re-check terrain after taking food to correctly set is_on_food
+        self.is_on_food = matches!(map.get_terrain_at(x, y), Some(Terrain::Food(_)));
+    }
+
+    /// Whether the ant carries food.
+    pub fn is_carrying_food(&self) -> bool {
+        self.carrying_food
+    }
+
+    /// Set whether the ant carries food, keeping the map's record of carriers in sync.
+    /// This is the only way to change it.
+    pub fn set_carrying_food(&mut self, carrying: bool, map: &mut GameMap) {
+        self.carrying_food = carrying;
+        map.set_carrying_food(&self.ant_ref, carrying);
+    }
+
+    /// Trophallaxis with a friendly ant of the same cell: a carrying ant hands its food to a
+    /// non-carrying one, and both memories move toward their average by `memory_blend`.
+    pub fn exchange_with(
+        &mut self,
+        other: &mut Ant,
+        config: &TrophallaxisConfig,
+        map: &mut GameMap,
+    ) {
+        if config.share_food && self.carrying_food != other.carrying_food {
+            let self_carrying = self.carrying_food;
+            self.set_carrying_food(!self_carrying, map);
+            other.set_carrying_food(self_carrying, map);
+        }
+        if config.memory_blend > 0.0 {
+            for (a, b) in self.memory.iter_mut().zip(other.memory.iter_mut()) {
+                let average = (*a as f32 + *b as f32) / 2.0;
+                *a = (*a as f32 + (average - *a as f32) * config.memory_blend).round() as u8;
+                *b = (*b as f32 + (average - *b as f32) * config.memory_blend).round() as u8;
             }
         }
     }
 
-    pub fn take_damage(&mut self, damage: f32) {
+    /// Units of food the ant carries. Ants carry at most one unit.
+    pub fn carried_food(&self) -> u32 {
+        self.carrying_food as u32
+    }
+
+    /// Apply damage dealt by `attacker`, standing at `attacker_pos`.
+    /// Ignored while the ant is spawn protected.
+    pub fn take_damage(&mut self, damage: f32, attacker: &AntRef, attacker_pos: Vec2) {
+        if self.is_spawn_protected() || self.is_emerging() {
+            return;
+        }
         self.longevity = (self.longevity - damage).max(0.0);
+        self.killed_by_hit = self.is_dead();
+        self.last_attacker = Some(attacker.clone());
+        self.hit_from = Some(attacker_pos);
+        self.since_discovery = 0.0;
+    }
+
+    /// Count down the spawn protection window.
+    fn update_spawn_protection(&mut self, dt: f32) {
+        self.spawn_protection = (self.spawn_protection - dt).max(0.0);
+    }
+
+    /// Returns true if the ant is still within its spawn protection window.
+    pub fn is_spawn_protected(&self) -> bool {
+        self.spawn_protection > 0.0
+    }
+
+    pub fn update_emergence(&mut self, dt: f32) {
+        self.emergence = (self.emergence - dt).max(0.0);
+    }
+
+    /// Whether the ant hasn't left the nest yet (`emergence_delay`).
+    pub fn is_emerging(&self) -> bool {
+        self.emergence > 0.0
     }
 
     /// Returns true if ant is dead.
@@ -568,11 +1203,39 @@ impl Ant {
         true
     }
 
+    /// Ant of the ant's cell it may start a fight with. Enemies it can't fight, emerging or
+    /// outside its frontal arc, are passed over so they don't shield the others of the cell.
+    fn cell_attack_target(
+        &self,
+        map: &GameMap,
+        other_colonies: &HashMap<u32, Colony>,
+        friendly_fire: bool,
+        max_attack_angle: f32,
+    ) -> Option<AntRef> {
+        let x = self.pos.x.floor() as usize;
+        let y = self.pos.y.floor() as usize;
+        map.get_attackable_ant_at(x, y, &self.ant_ref, friendly_fire, |candidate| {
+            // Same-colony ants are checked by the colony, which starts friendly fights
+            candidate.colony_id == self.ant_ref.colony_id
+                || other_colonies
+                    .get(&candidate.colony_id)
+                    .and_then(|colony| colony.ants.get(candidate.key))
+                    .is_some_and(|ant| self.can_fight(ant, max_attack_angle))
+        })
+    }
+
     pub fn try_initiate_fight(
         &mut self,
         opponent_ref: &AntRef,
         other_colonies: &mut HashMap<u32, Colony>,
+        max_attack_angle: f32,
     ) -> bool {
+        if opponent_ref.colony_id == self.ant_ref.colony_id {
+            // Friendly fire: the colony starts the fight after this update
+            self.friendly_fight_request = Some(opponent_ref.clone());
+            return false;
+        }
+
         let opponent = match get_ant_by_ref(&opponent_ref, other_colonies) {
             Some(opponent) => opponent,
             None => {
@@ -580,16 +1243,42 @@ impl Ant {
                 return false;
             }
         };
+        self.initiate_fight_with(opponent, max_attack_angle)
+    }
+
+    /// Whether the ant may start a fight with `opponent`: alive, out of the nest, within
+    /// reach, and within `max_attack_angle` of the ant's facing.
+    fn can_fight(&self, opponent: &Ant, max_attack_angle: f32) -> bool {
+        // Ants still emerging can't be fought
+        if opponent.is_dead() || opponent.is_emerging() {
+            return false;
+        }
 
-        // Compute the angle to the opponent
         let dx = opponent.pos.x - self.pos.x;
         let dy = opponent.pos.y - self.pos.y;
-        let orientation_to_opponent = dy.atan2(dx);
         let distance_sq = dx * dx + dy * dy;
         if distance_sq > ANT_LENGTH * ANT_LENGTH {
             // Too far to initiate a fight
             return false;
         }
+        // An opponent on the exact same spot has no direction to be behind
+        let off_facing = (dy.atan2(dx) - self.rotation + f32::consts::PI)
+            .rem_euclid(f32::consts::TAU)
+            - f32::consts::PI;
+        distance_sq == 0.0 || off_facing.abs() <= max_attack_angle
+    }
+
+    /// Start a fight with the given opponent if it is within reach, and within
+    /// `max_attack_angle` of the ant's facing.
+    pub fn initiate_fight_with(&mut self, opponent: &mut Ant, max_attack_angle: f32) -> bool {
+        if !self.can_fight(opponent, max_attack_angle) {
+            return false;
+        }
+
+        // Compute the angle to the opponent
+        let dx = opponent.pos.x - self.pos.x;
+        let dy = opponent.pos.y - self.pos.y;
+        let orientation_to_opponent = dy.atan2(dx);
 
         if !self.try_add_opponent(&opponent.ant_ref, orientation_to_opponent) {
             eprintln!(
@@ -626,7 +1315,7 @@ impl Ant {
         self.longevity = 0.0;
     }
 
-    fn sanitize_output(&self, output: &mut AntOutput) {
+    fn sanitize_output(&self, output: &mut AntOutput, max_move_offset: f32) {
         // Sanitize pheromone amounts
         for amount in &mut output.pheromone_amounts {
             if amount.is_nan() {
@@ -640,6 +1329,17 @@ impl Ant {
             }
         }
 
+        // Sanitize lay distance
+        if output.lay_ahead.is_nan() {
+            output.lay_ahead = 0.0; // Default to the ant's own cell
+            eprintln!(
+                "Warning: Ant {:?} received NaN lay_ahead. Defaulting to 0.0.",
+                self.ant_ref
+            );
+        } else {
+            output.lay_ahead = output.lay_ahead.clamp(0.0, MAX_LAY_AHEAD);
+        }
+
         // Sanitize turn angle
         if output.turn_angle.is_nan() {
             output.turn_angle = 0.0; // Default to no rotation
@@ -650,6 +1350,21 @@ impl Ant {
         } else {
             output.turn_angle = output.turn_angle.rem_euclid(f32::consts::TAU);
         }
+
+        // Sanitize move offset: wrapped to [-PI, PI], then clamped to the allowed offset
+        if output.move_offset.is_nan() {
+            output.move_offset = 0.0; // Default to moving forward
+            eprintln!(
+                "Warning: Ant {:?} received NaN move_offset. Defaulting to 0.0.",
+                self.ant_ref
+            );
+        } else {
+            let max_offset = max_move_offset.clamp(0.0, f32::consts::PI);
+            output.move_offset = ((output.move_offset + f32::consts::PI)
+                .rem_euclid(f32::consts::TAU)
+                - f32::consts::PI)
+                .clamp(-max_offset, max_offset);
+        }
     }
 }
 
@@ -666,3 +1381,798 @@ fn get_ant_by_ref<'a>(
     }
     None
 }
+
+/// Whether a move from `from` to `to` hits a wall or leaves the map, sampled every
+/// `MOVE_SWEEP_STEP` cells along the path and at the destination.
+fn path_blocked(map: &GameMap, from: Vec2, to: Vec2) -> bool {
+    let steps = (from.distance(to) / super::MOVE_SWEEP_STEP).ceil().max(1.0) as usize;
+    (1..=steps).any(|step| {
+        let point = from.lerp(to, step as f32 / steps as f32);
+        if point.x < 0.0 || point.y < 0.0 {
+            return true;
+        }
+        map.get_terrain_at(point.x.floor() as usize, point.y.floor() as usize)
+            .map_or(true, |terrain| terrain == &Terrain::Wall)
+    })
+}
+
+/// Offset from `rotation`, within the sense cone, of the fixed ray reaching farthest before a
+/// wall or the map's edge. Ties go to the ray closest to straight ahead.
+fn clear_heading(map: &mut GameMap, pos: Vec2, rotation: f32) -> f32 {
+    let mut best: (f32, f32) = (0.0, -1.0);
+    for ray in 0..SUGGESTED_HEADING_RAYS {
+        let offset =
+            SENSE_MAX_ANGLE * (2.0 * ray as f32 / (SUGGESTED_HEADING_RAYS - 1) as f32 - 1.0);
+        let (_, distance) = map.raycast_angle(pos, rotation + offset, SENSE_MAX_DISTANCE);
+        if distance > best.1 || (distance == best.1 && offset.abs() < best.0.abs()) {
+            best = (offset, distance);
+        }
+    }
+    best.0
+}
+
+/// Cell `distance` tiles ahead of `pos` along `heading`, or the last cell before a wall or
+/// the map's edge on the way.
+fn lay_ahead_cell(map: &GameMap, pos: Vec2, heading: f32, distance: f32) -> (usize, usize) {
+    let direction = Vec2::from_angle(heading);
+    let steps = (distance / super::MOVE_SWEEP_STEP).ceil() as usize;
+    let mut cell = (pos.x.floor() as usize, pos.y.floor() as usize);
+    for step in 1..=steps {
+        let point = pos + direction * (distance * step as f32 / steps as f32);
+        if point.x < 0.0 || point.y < 0.0 {
+            break;
+        }
+        let next = (point.x.floor() as usize, point.y.floor() as usize);
+        match map.get_terrain_at(next.0, next.1) {
+            Some(terrain) if terrain != &Terrain::Wall => cell = next,
+            _ => break,
+        }
+    }
+    cell
+}
+
+/// Sense friendly and enemy pheromone signals at `dist` along `angle` from `pos`,
+/// keeping the strongest per channel.
+#[allow(clippy::too_many_arguments)]
+fn sense_signals(
+    ant_input: &mut AntInput,
+    map: &GameMap,
+    pheromones: &[PheromoneChannel],
+    enemy_pheromones: &[&[PheromoneChannel]],
+    pos: Vec2,
+    angle: f32,
+    angle_offset: f32,
+    dist: f32,
+) {
+    let (sin_a, cos_a) = fast_sin_cos(angle);
+    let xi = (pos.x + cos_a * dist) as isize;
+    let yi = (pos.y + sin_a * dist) as isize;
+    if !(xi >= 0 && yi >= 0 && xi < map.width as isize && yi < map.height as isize) {
+        return;
+    }
+
+    for channel in 0..PHEROMONE_CHANNEL_COUNT {
+        let intensity = pheromones[channel].get(xi as usize, yi as usize);
+        if intensity > ant_input.pheromone_senses[channel].1 {
+            ant_input.pheromone_senses[channel] = (angle_offset, intensity);
+        }
+        let intensity = strongest(enemy_pheromones, channel, xi as usize, yi as usize);
+        if intensity > ant_input.enemy_pheromone_senses[channel].1 {
+            ant_input.enemy_pheromone_senses[channel] = (angle_offset, intensity);
+        }
+    }
+}
+
+/// Strongest intensity of `channel` on the cell among the given colonies' pheromones
+fn strongest(colonies: &[&[PheromoneChannel]], channel: usize, x: usize, y: usize) -> f32 {
+    colonies
+        .iter()
+        .map(|pheromones| pheromones[channel].get(x, y))
+        .fold(0.0, f32::max)
+}
+
+/// Colony food stock normalized by the `colony_food_reference` setting, capped at 1.0
+fn colony_food_level(food_collected: u32, reference: f32) -> f32 {
+    (food_collected as f32 / reference).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::DEFAULT_ANT_ATTACK_DAMAGE;
+
+    #[test]
+    fn test_new_ant_carries_initial_memory() {
+        let mut template = [0u8; MEMORY_SIZE];
+        for (i, byte) in template.iter_mut().enumerate() {
+            *byte = i as u8 + 1;
+        }
+
+        let ant = Ant::new(Vec2::new(4.5, 4.5), 2, template, &mut SeededRng::new(0));
+
+        assert_eq!(ant.memory, template);
+        assert_eq!(ant.ant_ref.colony_id, 2);
+    }
+
+    #[test]
+    fn test_new_ant_zero_memory_template() {
+        let ant = Ant::new(Vec2::ZERO, 0, [0u8; MEMORY_SIZE], &mut SeededRng::new(0));
+        assert!(ant.memory.iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_spawn_protection_ignores_damage_until_expired() {
+        let attacker = AntRef {
+            key: AntKey::null(),
+            colony_id: 1,
+        };
+        let mut ant = Ant::new(Vec2::ZERO, 0, [0u8; MEMORY_SIZE], &mut SeededRng::new(0));
+        ant.spawn_protection = 1.0;
+
+        ant.take_damage(DEFAULT_ANT_ATTACK_DAMAGE, &attacker, Vec2::ONE);
+        assert_eq!(ant.longevity, MAX_ANT_LONGEVITY);
+
+        ant.update_spawn_protection(0.6);
+        assert!(ant.is_spawn_protected());
+        ant.update_spawn_protection(0.6);
+        assert!(!ant.is_spawn_protected());
+        assert_eq!(ant.spawn_protection, 0.0);
+
+        ant.take_damage(DEFAULT_ANT_ATTACK_DAMAGE, &attacker, Vec2::ONE);
+        assert_eq!(ant.longevity, MAX_ANT_LONGEVITY - DEFAULT_ANT_ATTACK_DAMAGE);
+    }
+
+    #[test]
+    fn test_emerging_ants_cannot_be_fought_or_hurt() {
+        let mut rng = SeededRng::new(0);
+        let attacker_ref = AntRef {
+            key: AntKey::null(),
+            colony_id: 1,
+        };
+        let mut attacker = Ant::new(Vec2::new(1.0, 1.0), 1, [0u8; MEMORY_SIZE], &mut rng);
+        let mut ant = Ant::new(Vec2::new(1.2, 1.0), 0, [0u8; MEMORY_SIZE], &mut rng);
+        ant.emergence = 1.0;
+
+        ant.take_damage(DEFAULT_ANT_ATTACK_DAMAGE, &attacker_ref, Vec2::ONE);
+        assert_eq!(ant.longevity, MAX_ANT_LONGEVITY);
+        assert!(!attacker.initiate_fight_with(&mut ant, f32::consts::PI));
+
+        ant.update_emergence(0.6);
+        assert!(ant.is_emerging());
+        ant.update_emergence(0.6);
+        assert!(!ant.is_emerging());
+        assert!(attacker.initiate_fight_with(&mut ant, f32::consts::PI));
+    }
+
+    #[test]
+    fn test_emerging_ants_do_not_shield_their_cell() {
+        let map_config = SimulationConfig::default();
+        let mut map = GameMap::new(10, 10);
+        let mut defenders = Colony::detached(0, Vec2::new(1.5, 1.5), &map, &map_config);
+        defenders.spawn_ants(&mut map, 2);
+        // The lowest key, picked first among the cell's ants, is still emerging
+        let mut keys: Vec<AntKey> = defenders.ants.keys().collect();
+        keys.sort();
+        defenders.ants[keys[0]].emergence = 1.0;
+        let mut other_colonies = HashMap::from([(0, defenders)]);
+
+        let attacker = Ant::new(
+            Vec2::new(1.4, 1.5),
+            1,
+            [0u8; MEMORY_SIZE],
+            &mut SeededRng::new(0),
+        );
+        let target = attacker.cell_attack_target(&map, &other_colonies, false, f32::consts::PI);
+        assert_eq!(target.map(|ant_ref| ant_ref.key), Some(keys[1]));
+
+        other_colonies.get_mut(&0).unwrap().ants[keys[1]].emergence = 1.0;
+        assert_eq!(
+            attacker.cell_attack_target(&map, &other_colonies, false, f32::consts::PI),
+            None
+        );
+    }
+
+    #[test]
+    fn test_attacks_start_only_within_the_frontal_arc() {
+        let mut rng = SeededRng::new(0);
+        let mut attacker = Ant::new(Vec2::new(1.0, 1.0), 1, [0u8; MEMORY_SIZE], &mut rng);
+        let mut target = Ant::new(Vec2::new(1.5, 1.0), 0, [0u8; MEMORY_SIZE], &mut rng);
+
+        // Target behind the attacker: only the default full circle reaches it
+        attacker.rotation = f32::consts::PI;
+        assert!(!attacker.initiate_fight_with(&mut target, f32::consts::FRAC_PI_2));
+        assert!(!attacker.is_fighting());
+        assert!(attacker.initiate_fight_with(&mut target, f32::consts::PI));
+
+        // Target slightly off the attacker's facing, across the 0/TAU wrap
+        let mut attacker = Ant::new(Vec2::new(1.0, 1.0), 1, [0u8; MEMORY_SIZE], &mut rng);
+        let mut target = Ant::new(Vec2::new(1.5, 1.0), 0, [0u8; MEMORY_SIZE], &mut rng);
+        attacker.rotation = f32::consts::TAU - 0.3;
+        assert!(attacker.initiate_fight_with(&mut target, f32::consts::FRAC_PI_4));
+
+        // An enemy behind the attacker doesn't hide one ahead of it in the same cell
+        let mut map = GameMap::new(10, 10);
+        let config = SimulationConfig::default();
+        let mut defenders = Colony::detached(0, Vec2::new(1.5, 1.5), &map, &config);
+        defenders.spawn_ants(&mut map, 2);
+        let mut keys: Vec<AntKey> = defenders.ants.keys().collect();
+        keys.sort();
+        defenders.ants[keys[0]].pos = Vec2::new(1.1, 1.5);
+        defenders.ants[keys[1]].pos = Vec2::new(1.9, 1.5);
+        let other_colonies = HashMap::from([(0, defenders)]);
+        let mut attacker = Ant::new(Vec2::new(1.5, 1.5), 1, [0u8; MEMORY_SIZE], &mut rng);
+        attacker.rotation = 0.0;
+        let ahead =
+            attacker.cell_attack_target(&map, &other_colonies, false, f32::consts::FRAC_PI_2);
+        assert_eq!(ahead.map(|ant_ref| ant_ref.key), Some(keys[1]));
+    }
+
+    #[test]
+    fn test_spawn_protection_off_by_default() {
+        let attacker = AntRef {
+            key: AntKey::null(),
+            colony_id: 1,
+        };
+        let mut ant = Ant::new(Vec2::ZERO, 0, [0u8; MEMORY_SIZE], &mut SeededRng::new(0));
+        assert!(!ant.is_spawn_protected());
+
+        ant.take_damage(DEFAULT_ANT_ATTACK_DAMAGE, &attacker, Vec2::ONE);
+        assert_eq!(ant.longevity, MAX_ANT_LONGEVITY - DEFAULT_ANT_ATTACK_DAMAGE);
+    }
+
+    #[test]
+    fn test_leaving_colony_forces_think_only_in_event_mode() {
+        let colony_pos = Vec2::new(5.0, 5.0);
+        let mut events = ThinkEventsConfig::default();
+        let mut ant = Ant::new(colony_pos, 0, [0u8; MEMORY_SIZE], &mut SeededRng::new(0));
+        ant.set_think_interval(events.interval);
+        ant.think_timer.value = 0.0;
+
+        ant.pos = Vec2::new(20.0, 5.0);
+        ant.check_colony(&colony_pos, 4.0, &events);
+        assert!(!ant.is_on_colony);
+        assert!(!ant.think_timer.is_ready());
+
+        ant.pos = colony_pos;
+        ant.check_colony(&colony_pos, 4.0, &events);
+        assert!(ant.think_timer.is_ready());
+
+        events.enabled = true;
+        ant.think_timer.value = 0.0;
+        ant.pos = Vec2::new(20.0, 5.0);
+        ant.check_colony(&colony_pos, 4.0, &events);
+        assert!(ant.think_timer.is_ready());
+        assert_eq!(ant.think_timer.max_value, events.interval);
+    }
+
+    #[test]
+    fn test_edge_behavior_when_walking_off_the_map() {
+        let mut map = GameMap::new(10, 10);
+        let walk_west = |edge_behavior: EdgeBehavior, map: &mut GameMap| {
+            let mut ant = Ant::new(
+                Vec2::new(0.1, 5.5),
+                0,
+                [0u8; MEMORY_SIZE],
+                &mut SeededRng::new(0),
+            );
+            ant.rotation = f32::consts::PI;
+            let bumped = ant.update_position(map, 1.0, edge_behavior, 0.1);
+            (ant, bumped)
+        };
+
+        let (ant, bumped) = walk_west(EdgeBehavior::Wall, &mut map);
+        assert!(bumped);
+        assert_eq!(ant.pos, Vec2::new(0.1, 5.5));
+        assert!(!ant.is_dead());
+
+        let (ant, bumped) = walk_west(EdgeBehavior::Death, &mut map);
+        assert!(!bumped);
+        assert!(ant.is_dead());
+
+        let (ant, bumped) = walk_west(EdgeBehavior::Bounce, &mut map);
+        assert!(bumped);
+        assert!(!ant.is_dead());
+        assert!(ant.rotation.abs() < 1e-5 || (ant.rotation - f32::consts::TAU).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_large_steps_do_not_tunnel_through_thin_walls() {
+        let mut map = GameMap::new(20, 10);
+        for y in 0..10 {
+            map.place_wall_at(10, y);
+        }
+        let mut ant = Ant::new(
+            Vec2::new(8.5, 5.5),
+            0,
+            [0u8; MEMORY_SIZE],
+            &mut SeededRng::new(0),
+        );
+        ant.rotation = 0.0;
+        ant.move_offset = 0.0;
+        // One step lands on the far side of the wall at x = 10
+        let bumped = ant.update_position(&mut map, 1.0, EdgeBehavior::Wall, 4.0 / ant.speed);
+        assert!(bumped);
+        assert_eq!(ant.pos, Vec2::new(8.5, 5.5));
+
+        // The same step with no wall in the way goes through
+        map.remove_terrain_at(10, 5);
+        ant.rotation = 0.0;
+        let bumped = ant.update_position(&mut map, 1.0, EdgeBehavior::Wall, 4.0 / ant.speed);
+        assert!(!bumped);
+        assert!((ant.pos.x - 12.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_grid_steps_go_cell_center_to_cell_center() {
+        let mut map = GameMap::new(10, 10);
+        map.place_wall_at(7, 6);
+        let mut ant = Ant::new(
+            Vec2::new(5.2, 5.7),
+            0,
+            [0u8; MEMORY_SIZE],
+            &mut SeededRng::new(0),
+        );
+        map.register_ant_in_cell(&ant.ant_ref, ant.pos);
+        ant.move_offset = 0.0;
+
+        // Slightly off east snaps to the east neighbor's center, keeping the rotation
+        ant.rotation = 0.2;
+        assert!(!ant.step_cell(&mut map, EdgeBehavior::Wall));
+        assert_eq!(ant.pos, Vec2::new(6.5, 5.5));
+        assert_eq!(ant.rotation, 0.2);
+
+        // South-east is a wall: the ant stays and turns around
+        ant.rotation = f32::consts::FRAC_PI_4;
+        assert!(ant.step_cell(&mut map, EdgeBehavior::Wall));
+        assert_eq!(ant.pos, Vec2::new(6.5, 5.5));
+        assert!((ant.rotation - 5.0 * f32::consts::FRAC_PI_4).abs() < 1e-5);
+        assert!(!ant.step_cell(&mut map, EdgeBehavior::Wall));
+        assert_eq!(ant.pos, Vec2::new(5.5, 4.5));
+        assert!(map.unregister_ant_from_cell(&ant.ant_ref, ant.pos));
+    }
+
+    #[test]
+    fn test_grid_walk_paces_steps_by_their_length() {
+        let mut map = GameMap::new(10, 10);
+        let mut ant = Ant::new(
+            Vec2::new(2.5, 2.5),
+            0,
+            [0u8; MEMORY_SIZE],
+            &mut SeededRng::new(0),
+        );
+        map.register_ant_in_cell(&ant.ant_ref, ant.pos);
+        ant.move_offset = 0.0;
+        let dt = 0.2 / ant.speed;
+
+        // A straight step covers one cell: 5 walks of 0.2 cells
+        ant.rotation = 0.0;
+        for _ in 0..4 {
+            assert!(!ant.walk_grid(&mut map, EdgeBehavior::Wall, dt));
+            assert_eq!(ant.pos, Vec2::new(2.5, 2.5));
+        }
+        ant.walk_grid(&mut map, EdgeBehavior::Wall, dt);
+        assert_eq!(ant.pos, Vec2::new(3.5, 2.5));
+
+        // A diagonal one covers √2 cells: 8 walks, the progress carried over from the last step
+        ant.rotation = f32::consts::FRAC_PI_4;
+        for _ in 0..7 {
+            ant.walk_grid(&mut map, EdgeBehavior::Wall, dt);
+            assert_eq!(ant.pos, Vec2::new(3.5, 2.5));
+        }
+        ant.walk_grid(&mut map, EdgeBehavior::Wall, dt);
+        assert_eq!(ant.pos, Vec2::new(4.5, 3.5));
+
+        // A long tick covers several cells at once
+        ant.rotation = 0.0;
+        ant.walk_grid(&mut map, EdgeBehavior::Wall, 3.0 / ant.speed);
+        assert_eq!(ant.pos, Vec2::new(7.5, 3.5));
+        assert!(map.unregister_ant_from_cell(&ant.ant_ref, ant.pos));
+    }
+
+    #[test]
+    fn test_trophallaxis_hands_food_over_and_blends_memory() {
+        let mut map = GameMap::new(10, 10);
+        let mut rng = SeededRng::new(0);
+        let mut giver = Ant::new(Vec2::new(4.5, 4.5), 0, [0u8; MEMORY_SIZE], &mut rng);
+        let mut receiver = Ant::new(Vec2::new(4.5, 4.5), 0, [200u8; MEMORY_SIZE], &mut rng);
+        giver.set_carrying_food(true, &mut map);
+        let config = TrophallaxisConfig {
+            enabled: true,
+            share_food: true,
+            memory_blend: 0.5,
+        };
+
+        giver.exchange_with(&mut receiver, &config, &mut map);
+        assert!(!giver.carrying_food);
+        assert!(receiver.carrying_food);
+        assert_eq!(giver.memory[0], 50);
+        assert_eq!(receiver.memory[0], 150);
+
+        // Food goes back only through another meeting, from the new carrier
+        receiver.exchange_with(&mut giver, &config, &mut map);
+        assert!(giver.carrying_food);
+        assert_eq!(giver.memory[0], 75);
+        assert_eq!(receiver.memory[0], 125);
+    }
+
+    #[test]
+    fn test_move_offset_is_wrapped_and_clamped() {
+        let ant = Ant::new(Vec2::ZERO, 0, [0u8; MEMORY_SIZE], &mut SeededRng::new(0));
+        let sanitized = |move_offset: f32, max_move_offset: f32| {
+            let mut output = AntOutput {
+                turn_angle: 0.0,
+                pheromone_amounts: [0.0; PHEROMONE_CHANNEL_COUNT],
+                try_attack: false,
+                move_offset,
+                beacon: BEACON_NONE,
+                lay_ahead: 0.0,
+            };
+            ant.sanitize_output(&mut output, max_move_offset);
+            output.move_offset
+        };
+
+        assert!(
+            (sanitized(1.5 * f32::consts::PI, f32::consts::PI) + 0.5 * f32::consts::PI).abs()
+                < 1e-5
+        );
+        assert_eq!(sanitized(-3.0, 1.0), -1.0);
+        assert_eq!(sanitized(2.0, 0.0), 0.0);
+        assert_eq!(sanitized(f32::NAN, f32::consts::PI), 0.0);
+    }
+
+    #[test]
+    fn test_clear_heading_steers_around_walls() {
+        let mut map = GameMap::new(20, 20);
+        assert_eq!(clear_heading(&mut map, Vec2::new(9.5, 9.5), 0.0), 0.0);
+
+        // A wall ahead and to the north: the first ray passing south of it is suggested
+        for y in 0..=10 {
+            map.place_wall_at(12, y);
+        }
+        let heading = clear_heading(&mut map, Vec2::new(9.5, 9.5), 0.0);
+        assert!((heading - 0.75 * SENSE_MAX_ANGLE).abs() < 1e-5, "{heading}");
+    }
+
+    #[test]
+    fn test_lay_ahead_stops_before_walls_and_edges() {
+        let mut map = GameMap::new(20, 10);
+        map.place_wall_at(10, 5);
+        let pos = Vec2::new(5.5, 5.5);
+        assert_eq!(lay_ahead_cell(&map, pos, 0.0, 3.0), (8, 5));
+        assert_eq!(lay_ahead_cell(&map, pos, 0.0, MAX_LAY_AHEAD), (9, 5));
+        assert_eq!(
+            lay_ahead_cell(&map, pos, f32::consts::FRAC_PI_2, 2.0),
+            (5, 7)
+        );
+        assert_eq!(
+            lay_ahead_cell(&map, Vec2::new(1.5, 5.5), f32::consts::PI, 4.0),
+            (0, 5)
+        );
+    }
+
+    #[test]
+    fn test_sense_noise_stays_in_range_and_keeps_unset_senses() {
+        let mut rng = SeededRng::new(0);
+        let mut map = GameMap::new(20, 20);
+        let channels: Vec<PheromoneChannel> = (0..PHEROMONE_CHANNEL_COUNT)
+            .map(|_| PheromoneChannel::new(20, 20, 0.0, 1, 0.0))
+            .collect();
+        let mut ant = Ant::new(Vec2::new(5.5, 10.5), 0, [0u8; MEMORY_SIZE], &mut rng);
+        let config = SimulationConfig::default();
+        let (mut input, _) = ant.perceive(&mut map, &channels, &[], &Vec2::ZERO, &config, &mut rng);
+        input.food_sense = (0.1, 2.0);
+        input.wall_sense = (SENSE_MAX_ANGLE, SENSE_MAX_DISTANCE - 0.1);
+        input.friend_sense = (0.0, -1.0);
+        input.colony_sense = (f32::consts::PI - 0.01, 2.0);
+        input.attacker_sense = (-f32::consts::PI + 0.01, 1.0);
+        input.cell_sense = [0.0; PHEROMONE_CHANNEL_COUNT];
+        input.cell_sense[0] = 0.5;
+        let noisy = |seed: u64| {
+            let mut noisy = input;
+            add_sense_noise(&mut noisy, 0.5, &mut SeededRng::new(seed));
+            noisy
+        };
+
+        let first = noisy(1);
+        assert_eq!(first.food_sense, noisy(1).food_sense);
+        assert_ne!(first.food_sense, input.food_sense);
+        for seed in 0..100 {
+            let noisy = noisy(seed);
+            assert!(noisy.wall_sense.0.abs() <= SENSE_MAX_ANGLE);
+            // Direct senses near π wrap around instead of leaving (-π, π]
+            for angle in [noisy.colony_sense.0, noisy.attacker_sense.0] {
+                assert!(angle > -f32::consts::PI && angle <= f32::consts::PI);
+            }
+            assert!((0.0..=SENSE_MAX_DISTANCE).contains(&noisy.wall_sense.1));
+            assert!((0.0..=MAX_PHEROMONE_AMOUNT).contains(&noisy.cell_sense[0]));
+            assert_eq!(noisy.friend_sense, (0.0, -1.0));
+            assert_eq!(noisy.cell_sense[1], 0.0);
+        }
+    }
+
+    #[test]
+    fn test_colony_sense_angle_is_wrapped() {
+        let mut rng = SeededRng::new(0);
+        let mut map = GameMap::new(20, 20);
+        let channels: Vec<PheromoneChannel> = (0..PHEROMONE_CHANNEL_COUNT)
+            .map(|_| PheromoneChannel::new(20, 20, 0.0, 1, 0.0))
+            .collect();
+        let mut ant = Ant::new(Vec2::new(5.5, 5.5), 0, [0u8; MEMORY_SIZE], &mut rng);
+        // Facing just short of a full turn, with the nest straight east
+        ant.rotation = f32::consts::TAU - 0.2;
+        let config = SimulationConfig::default();
+        let colony_pos = Vec2::new(8.5, 5.5);
+        let (input, _) = ant.perceive(&mut map, &channels, &[], &colony_pos, &config, &mut rng);
+        assert!((input.colony_sense.0 - 0.2).abs() < 1e-4);
+        assert_eq!(wrap_angle(-f32::consts::PI), f32::consts::PI);
+    }
+
+    #[test]
+    fn test_think_phase_comes_from_the_colony_stream() {
+        let phases = |seed: u64| -> Vec<f32> {
+            let mut rng = SeededRng::for_colony(seed, 0);
+            (0..8)
+                .map(|_| {
+                    Ant::new(Vec2::ZERO, 0, [0u8; MEMORY_SIZE], &mut rng)
+                        .think_timer
+                        .value
+                })
+                .collect()
+        };
+        assert_eq!(phases(3), phases(3));
+        assert_ne!(phases(3), phases(4));
+        // Spawns stay spread over the think interval
+        assert!(
+            phases(3)
+                .iter()
+                .all(|&phase| (0.0..THINK_INTERVAL).contains(&phase))
+        );
+        assert!(phases(3).windows(2).any(|w| w[0] != w[1]));
+    }
+
+    #[test]
+    fn test_fleeing_ants_head_straight_home() {
+        let mut ant = Ant::new(
+            Vec2::new(10.5, 5.5),
+            0,
+            [0u8; MEMORY_SIZE],
+            &mut SeededRng::new(0),
+        );
+        ant.is_on_colony = false;
+        ant.rotation = 1.0;
+        ant.move_offset = f32::consts::PI;
+        ant.flee_to(Vec2::new(10.5, 15.5));
+        assert!((ant.rotation - f32::consts::FRAC_PI_2).abs() < 1e-5);
+        assert_eq!(ant.move_offset, 0.0);
+
+        // Ants already home are left alone
+        ant.is_on_colony = true;
+        ant.rotation = 1.0;
+        ant.flee_to(Vec2::new(10.5, 15.5));
+        assert_eq!(ant.rotation, 1.0);
+    }
+
+    #[test]
+    fn test_same_colony_ants_can_fight_when_paired() {
+        let mut keys: slotmap::SlotMap<AntKey, ()> = slotmap::SlotMap::with_key();
+        let mut ant = Ant::new(
+            Vec2::new(1.5, 1.5),
+            0,
+            [0u8; MEMORY_SIZE],
+            &mut SeededRng::new(0),
+        );
+        let mut ally = Ant::new(
+            Vec2::new(1.6, 1.5),
+            0,
+            [0u8; MEMORY_SIZE],
+            &mut SeededRng::new(0),
+        );
+        ant.ant_ref.key = keys.insert(());
+        ally.ant_ref.key = keys.insert(());
+
+        assert!(ant.initiate_fight_with(&mut ally, f32::consts::PI));
+        assert!(ant.is_fighting() && ally.is_fighting());
+
+        assert!(ant.strike(&mut ally, &SimulationConfig::default()));
+        assert_eq!(
+            ally.longevity,
+            MAX_ANT_LONGEVITY - DEFAULT_ANT_ATTACK_DAMAGE
+        );
+    }
+
+    #[test]
+    fn test_rear_attacks_deal_more_damage_than_front_attacks() {
+        let mut rng = SeededRng::new(0);
+        let attacker = Ant::new(Vec2::new(5.0, 5.0), 0, [0u8; MEMORY_SIZE], &mut rng);
+        let mut target = Ant::new(Vec2::new(5.5, 5.0), 1, [0u8; MEMORY_SIZE], &mut rng);
+        let mut config = SimulationConfig::default();
+
+        // Flat damage by default, whatever the facing
+        target.rotation = 0.0;
+        assert_eq!(
+            attacker.attack_damage(&target, &config),
+            DEFAULT_ANT_ATTACK_DAMAGE
+        );
+
+        config.rear_attack_bonus = 0.5;
+        config.front_attack_reduction = 0.4;
+        // Target facing away from the attacker
+        assert!(
+            (attacker.attack_damage(&target, &config) - 1.5 * DEFAULT_ANT_ATTACK_DAMAGE).abs()
+                < 1e-4
+        );
+        // Target facing the attacker
+        target.rotation = f32::consts::PI;
+        assert!(
+            (attacker.attack_damage(&target, &config) - 0.6 * DEFAULT_ANT_ATTACK_DAMAGE).abs()
+                < 1e-4
+        );
+        // Attacked from the side
+        target.rotation = f32::consts::FRAC_PI_2;
+        assert!(
+            (attacker.attack_damage(&target, &config) - DEFAULT_ANT_ATTACK_DAMAGE).abs() < 1e-4
+        );
+    }
+
+    #[test]
+    fn test_hit_ant_senses_and_records_its_attacker() {
+        let mut rng = SeededRng::new(0);
+        let mut attacker = Ant::new(Vec2::new(5.0, 5.5), 0, [0u8; MEMORY_SIZE], &mut rng);
+        let mut target = Ant::new(Vec2::new(5.0, 5.0), 1, [0u8; MEMORY_SIZE], &mut rng);
+        target.rotation = 0.0;
+        let config = SimulationConfig::default();
+        let mut map = GameMap::new(10, 10);
+        let pheromones: Vec<PheromoneChannel> = (0..PHEROMONE_CHANNEL_COUNT)
+            .map(|_| PheromoneChannel::new(10, 10, 0.0, 1, 0.0))
+            .collect();
+
+        assert!(attacker.strike(&mut target, &config));
+        assert_eq!(target.last_attacker, Some(attacker.ant_ref.clone()));
+        assert!(!target.killed_by_hit);
+
+        // The attacker stands a quarter turn from the target's facing, sensed on the next think only
+        let (input, _) =
+            target.perceive(&mut map, &pheromones, &[], &Vec2::ZERO, &config, &mut rng);
+        assert!((input.attacker_sense.0 - f32::consts::FRAC_PI_2).abs() < 1e-4);
+        assert!((input.attacker_sense.1 - 0.5).abs() < 1e-4);
+        let (input, _) =
+            target.perceive(&mut map, &pheromones, &[], &Vec2::ZERO, &config, &mut rng);
+        assert_eq!(input.attacker_sense.1, -1.0);
+
+        // The last attacker names the killer
+        target.longevity = DEFAULT_ANT_ATTACK_DAMAGE;
+        assert!(attacker.strike(&mut target, &config));
+        assert!(target.is_dead() && target.killed_by_hit);
+        assert_eq!(target.last_attacker, Some(attacker.ant_ref.clone()));
+        assert_eq!(attacker.kills, 1);
+    }
+
+    #[test]
+    fn test_food_pickup_resets_time_since_discovery() {
+        let mut rng = SeededRng::new(0);
+        let mut map = GameMap::new(10, 10);
+        map.place_food_at(7, 5, 3);
+        let pheromones: Vec<PheromoneChannel> = (0..PHEROMONE_CHANNEL_COUNT)
+            .map(|_| PheromoneChannel::new(10, 10, 0.0, 1, 0.0))
+            .collect();
+        let config = SimulationConfig::default();
+        let mut ant = Ant::new(Vec2::new(2.5, 5.5), 0, [0u8; MEMORY_SIZE], &mut rng);
+        ant.since_discovery = 12.0;
+
+        // Nothing in reach: the timer keeps running
+        ant.check_food(&mut map, &config.think_events, config.food_pickup_range);
+        assert_eq!(ant.since_discovery, 12.0);
+
+        ant.pos = Vec2::new(6.5, 5.5);
+        ant.check_food(&mut map, &config.think_events, config.food_pickup_range);
+        assert!(ant.carrying_food);
+        assert_eq!(ant.since_discovery, 0.0);
+        ant.since_discovery = 3.0;
+        let (input, _) = ant.perceive(&mut map, &pheromones, &[], &Vec2::ZERO, &config, &mut rng);
+        assert_eq!(input.time_since_discovery, 3.0);
+    }
+
+    #[test]
+    fn test_full_ant_leaves_food_in_place() {
+        let mut rng = SeededRng::new(0);
+        let mut map = GameMap::new(10, 10);
+        map.place_food_at(5, 5, 3);
+        let config = SimulationConfig::default();
+        let mut ant = Ant::new(Vec2::new(5.5, 5.5), 0, [0u8; MEMORY_SIZE], &mut rng);
+        ant.set_carrying_food(true, &mut map);
+        ant.since_discovery = 4.0;
+        ant.longevity = 100.0;
+
+        ant.check_food(&mut map, &config.think_events, config.food_pickup_range);
+        assert_eq!(ant.carried_food(), 1);
+        assert_eq!(map.get_terrain_at(5, 5), Some(&Terrain::Food(3)));
+        // Standing on food still counts as finding it, but doesn't rejuvenate
+        assert!(ant.is_on_food);
+        assert_eq!(ant.since_discovery, 0.0);
+        assert_eq!(ant.longevity, 100.0);
+    }
+
+    #[test]
+    fn test_enemy_pheromones_are_sensed_apart_from_own() {
+        let mut rng = SeededRng::new(0);
+        let mut map = GameMap::new(20, 20);
+        let channels = || -> Vec<PheromoneChannel> {
+            (0..PHEROMONE_CHANNEL_COUNT)
+                .map(|_| PheromoneChannel::new(20, 20, 0.0, 1, 0.0))
+                .collect()
+        };
+        let own = channels();
+        let (mut weak, mut strong) = (channels(), channels());
+        weak[2].lay(5, 10, 30.0);
+        strong[2].lay(5, 10, 80.0);
+        // A field ahead of the ant, facing east
+        for y in 5..16 {
+            for x in 6..20 {
+                strong[5].lay(x, y, 50.0);
+            }
+        }
+        let config = SimulationConfig::default();
+        let mut ant = Ant::new(Vec2::new(5.5, 10.5), 0, [0u8; MEMORY_SIZE], &mut rng);
+        ant.rotation = 0.0;
+
+        let enemies = [weak.as_slice(), strong.as_slice()];
+        let (input, _) = ant.perceive(&mut map, &own, &enemies, &Vec2::ZERO, &config, &mut rng);
+        assert_eq!(input.cell_sense[2], 0.0);
+        assert_eq!(input.enemy_cell_sense[2], 80.0);
+        assert_eq!(input.pheromone_senses[5].1, 0.0);
+        assert_eq!(input.enemy_pheromone_senses[5].1, 50.0);
+
+        // Without enemy pheromones, as when the setting is off
+        let (input, _) = ant.perceive(&mut map, &own, &[], &Vec2::ZERO, &config, &mut rng);
+        assert_eq!(input.enemy_cell_sense[2], 0.0);
+        assert_eq!(input.enemy_pheromone_senses[5].1, 0.0);
+    }
+
+    #[test]
+    fn test_coast_fallback_reuses_last_output_on_think_error() {
+        let mut ant = Ant::new(Vec2::ZERO, 0, [0u8; MEMORY_SIZE], &mut SeededRng::new(0));
+        let mut config = SimulationConfig::default();
+        let output = AntOutput {
+            turn_angle: 0.3,
+            pheromone_amounts: [0.0; PHEROMONE_CHANNEL_COUNT],
+            try_attack: false,
+            move_offset: 0.0,
+            beacon: BEACON_NONE,
+            lay_ahead: 0.0,
+        };
+        let transient_error = || Err(anyhow::anyhow!("connection reset"));
+
+        // Nothing to coast on before the first successful think
+        config.think_error_fallback = ThinkErrorFallback::Coast;
+        assert!(
+            ant.resolve_think_result(transient_error(), &config)
+                .is_none()
+        );
+
+        assert!(ant.resolve_think_result(Ok(output), &config).is_some());
+        let coasted = ant.resolve_think_result(transient_error(), &config);
+        assert_eq!(coasted.map(|o| o.turn_angle), Some(0.3));
+
+        // Dead ants (e.g. timed out) don't coast
+        ant.die();
+        assert!(
+            ant.resolve_think_result(transient_error(), &config)
+                .is_none()
+        );
+
+        let mut ant = Ant::new(Vec2::ZERO, 0, [0u8; MEMORY_SIZE], &mut SeededRng::new(0));
+        config.think_error_fallback = ThinkErrorFallback::Skip;
+        ant.resolve_think_result(Ok(output), &config);
+        assert!(
+            ant.resolve_think_result(transient_error(), &config)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_colony_food_level_is_normalized_and_capped() {
+        assert_eq!(colony_food_level(0, 100.0), 0.0);
+        assert_eq!(colony_food_level(25, 100.0), 0.25);
+        assert_eq!(colony_food_level(500, 100.0), 1.0);
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::VecDeque;
+
+use shared::{BEACON_NONE, BEACON_REMOVE, PHEROMONE_CHANNEL_COUNT};
+
+use super::pheromone::{PheromoneBudget, PheromoneChannel};
+use super::{GameMap, Terrain};
+
+/// Persistent point emitting a pheromone channel every tick, planted by an ant of the colony
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Beacon {
+    pub cell: (u32, u32),
+    pub channel: u8,
+}
+
+/// Beacon change requested by an ant through its `AntOutput::beacon`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BeaconOrder {
+    Plant(Beacon),
+    Remove((u32, u32)),
+}
+
+impl BeaconOrder {
+    /// Order for the `beacon` output of an ant standing on `cell`, None for no or an invalid order
+    pub fn from_output(beacon: u8, cell: (u32, u32)) -> Option<Self> {
+        match beacon {
+            BEACON_NONE => None,
+            BEACON_REMOVE => Some(BeaconOrder::Remove(cell)),
+            channel if (channel as usize) <= PHEROMONE_CHANNEL_COUNT => {
+                Some(BeaconOrder::Plant(Beacon {
+                    cell,
+                    channel: channel - 1,
+                }))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Beacons of a colony, oldest first. At most one per cell; planting past the colony's
+/// limit replaces the oldest one.
+#[derive(Debug, Clone, Default)]
+pub struct Beacons {
+    beacons: VecDeque<Beacon>,
+}
+
+impl Beacons {
+    pub fn apply(&mut self, order: BeaconOrder, max_count: usize) {
+        match order {
+            BeaconOrder::Plant(beacon) => {
+                self.remove(beacon.cell);
+                if max_count == 0 {
+                    return;
+                }
+                while self.beacons.len() >= max_count {
+                    self.beacons.pop_front();
+                }
+                self.beacons.push_back(beacon);
+            }
+            BeaconOrder::Remove(cell) => self.remove(cell),
+        }
+    }
+
+    fn remove(&mut self, cell: (u32, u32)) {
+        self.beacons.retain(|beacon| beacon.cell != cell);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Beacon> {
+        self.beacons.iter()
+    }
+
+    /// Lays `amount` on the cell of every beacon, within the colony's pheromone budget.
+    /// Beacons on walls or scentless zones stay silent.
+    pub fn emit(
+        &self,
+        amount: f32,
+        map: &GameMap,
+        pheromones: &mut [PheromoneChannel],
+        budget: &mut PheromoneBudget,
+    ) {
+        let emitting: Vec<&Beacon> = self
+            .beacons
+            .iter()
+            .filter(|beacon| {
+                let (x, y) = (beacon.cell.0 as usize, beacon.cell.1 as usize);
+                map.zone_at(x, y).allows_pheromones()
+                    && !matches!(map.get_terrain_at(x, y), Some(Terrain::Wall))
+            })
+            .collect();
+        if emitting.is_empty() || amount <= 0.0 {
+            return;
+        }
+        let factor = budget.deposit_factor(amount * emitting.len() as f32);
+        for beacon in emitting {
+            let (x, y) = (beacon.cell.0 as usize, beacon.cell.1 as usize);
+            budget.total += pheromones[beacon.channel as usize].lay(x, y, amount * factor);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PheromoneOverflow;
+    use crate::simulation::Zone;
+
+    #[test]
+    fn test_beacons_are_capped_and_emit_within_budget() {
+        assert_eq!(BeaconOrder::from_output(BEACON_NONE, (1, 1)), None);
+        assert_eq!(BeaconOrder::from_output(9, (1, 1)), None);
+        let plant = |x: u32, channel: u8| BeaconOrder::from_output(channel + 1, (x, 0)).unwrap();
+
+        let mut beacons = Beacons::default();
+        beacons.apply(plant(0, 0), 2);
+        beacons.apply(plant(1, 0), 2);
+        // Replanting on a cell moves the beacon to another channel instead of adding one
+        beacons.apply(plant(1, 3), 2);
+        assert_eq!(beacons.iter().count(), 2);
+        // Past the limit, the oldest beacon goes
+        beacons.apply(plant(2, 0), 2);
+        let cells: Vec<(u32, u32)> = beacons.iter().map(|beacon| beacon.cell).collect();
+        assert_eq!(cells, [(1, 0), (2, 0)]);
+
+        let mut map = GameMap::new(4, 4);
+        map.set_zone_at(2, 0, Zone::Scentless);
+        let mut pheromones: Vec<PheromoneChannel> = (0..PHEROMONE_CHANNEL_COUNT)
+            .map(|_| PheromoneChannel::new(4, 4, 0.5, 1, 0.0))
+            .collect();
+        let mut budget = PheromoneBudget::new(3.0, PheromoneOverflow::Scale);
+        beacons.emit(2.0, &map, &mut pheromones, &mut budget);
+        assert_eq!(pheromones[3].get(1, 0), 2.0);
+        assert_eq!(pheromones[0].get(2, 0), 0.0);
+        beacons.emit(2.0, &map, &mut pheromones, &mut budget);
+        assert_eq!(pheromones[3].get(1, 0), 3.0);
+
+        beacons.apply(BeaconOrder::from_output(BEACON_REMOVE, (1, 0)).unwrap(), 2);
+        assert_eq!(beacons.iter().count(), 1);
+    }
+}
@@ -1,47 +1,154 @@
 use super::ant::{Ant, AntKey};
-use super::pheromone::PheromoneChannel;
-use super::{PHEROMONE_DECAY_INTERVAL, Timer};
-use crate::config::PlayerConfig;
+use super::beacon::Beacons;
+use super::combat::CombatEvents;
+use super::pheromone::{LayEvent, LayEvents, PheromoneBudget, PheromoneChannel};
+use super::{
+    ForagingStats, MIN_NEST_RADIUS, PHEROMONE_DECAY_INTERVAL, SeededRng, THINK_INTERVAL,
+    ThinkLatency, Timer,
+};
+use crate::config::{DiffusionNeighborhood, PlayerConfig, SimulationConfig, TrophallaxisConfig};
 use crate::player::PlayerConnection;
-use crate::simulation::Terrain;
 use crate::simulation::{ANT_SPAWN_INTERVAL, GameMap};
+use ahash::AHashSet;
 use anyhow::Result;
 use macroquad::prelude::*;
-use shared::PHEROMONE_CHANNEL_COUNT;
+use shared::{ColonyState, MapInfo, PHEROMONE_CHANNEL_COUNT, ROLE_COUNT, ROLE_WORKER};
 use slotmap::SlotMap;
 use std::collections::HashMap;
+use std::time::Duration;
+
+/// Background think interval of the ants, longer in event-driven mode
+fn think_interval(config: &SimulationConfig) -> f32 {
+    if config.think_events.enabled {
+        config.think_events.interval.max(THINK_INTERVAL)
+    } else {
+        THINK_INTERVAL
+    }
+}
 
 pub struct Colony {
     pub colony_id: u32,
     pub ants: SlotMap<AntKey, Ant>,
     pub pheromones: Vec<PheromoneChannel>,
+    /// Tracks the colony's total pheromone mass against the configured cap
+    pub pheromone_budget: PheromoneBudget,
     pub color: Color,
     pub pos: Vec2,
+    /// Radius of the nest around `pos`, in tiles
+    pub nest_radius: f32,
     pub food_collected: u32,
+    /// Food brought back over the match, before spawns spend it
+    pub total_food: u32,
+    /// Most ants the colony had alive at once
+    pub peak_ants: usize,
+    /// Simulation tick at which the colony was added
+    pub spawned_at: u32,
+    /// Enemy ants killed by this colony's ants
+    pub kills: u32,
+    /// Ants lost, how many of them died with an enemy's hit on them, and how many were
+    /// crushed by walls
+    pub deaths: u32,
+    pub combat_deaths: u32,
+    pub wall_deaths: u32,
+    /// Ants killed by exceeding the processing time
+    pub timeouts: u32,
+    /// Think ticks (player round-trips) of this colony's ants so far
+    pub think_count: u64,
+    /// Whether collected food turns into new ants (off during sudden death)
+    pub spawns_ants: bool,
+    /// Nanoseconds a brain call may take before the ant dies, None without timeout
+    pub max_processing_time: Option<u128>,
+    /// Round-trip times of this colony's brain calls
+    pub think_latency: ThinkLatency,
+    /// Food round trips of this colony's ants
+    pub foraging: ForagingStats,
+    /// Pheromone beacons planted by the colony's ants
+    pub beacons: Beacons,
+    /// Recent pheromone deposits, for the lay pulse debug view
+    pub lay_events: LayEvents,
+    /// Recent hits of this colony's ants, for the combat effects view
+    pub combat_events: CombatEvents,
     pub player_connection: PlayerConnection,
     pub player_config: PlayerConfig,
     pub pheromone_decay_timer: Timer,
-    pub ant_spawn_timer: f32,
+    pub ant_spawn_timer: Timer,
+    pub role_counts: [u32; ROLE_COUNT],
+    /// Seconds of invulnerability given to newly spawned ants
+    pub spawn_protection: f32,
+    /// Seconds newly spawned ants stay in the nest before becoming active
+    pub emergence_delay: f32,
+    /// Food units left by each dying ant, on top of the food it carried
+    pub corpse_food: u32,
+    /// Food units turned into each new ant
+    pub spawn_food_cost: u32,
+    /// Background think interval of the colony's ants
+    pub think_interval: f32,
+    /// Random stream of this colony's ants (perception sampling, spawns)
+    pub rng: SeededRng,
 }
 
 impl Colony {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
+        namespace: &str,
         colony_id: u32,
         pos: Vec2,
         map_width: u32,
         map_height: u32,
         color: Color,
-        ant_count: u32,
+        config: &SimulationConfig,
         player_cfg: PlayerConfig,
+        map_info: &MapInfo,
     ) -> Result<Self> {
-        let ants = SlotMap::with_capacity_and_key(ant_count as usize);
+        let player_connection = PlayerConnection::start(
+            namespace,
+            colony_id,
+            &player_cfg,
+            map_info,
+            Duration::from_secs_f32(config.container_start_timeout),
+            config.build_brains,
+        )?;
+        Ok(Self::with_connection(
+            colony_id,
+            pos,
+            map_width,
+            map_height,
+            color,
+            config,
+            player_cfg,
+            player_connection,
+        ))
+    }
+
+    /// Colony driven by an already started player connection, taking its decay rates from
+    /// the player's setup.
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_connection(
+        colony_id: u32,
+        pos: Vec2,
+        map_width: u32,
+        map_height: u32,
+        color: Color,
+        config: &SimulationConfig,
+        player_cfg: PlayerConfig,
+        player_connection: PlayerConnection,
+    ) -> Self {
+        let ants = SlotMap::with_capacity_and_key(config.colony_initial_population as usize);
 
-        // Start player connection and get decay rates from setup
-        let player_connection = PlayerConnection::start(colony_id, &player_cfg)?;
         let decay_rates = player_connection.setup.decay_rates;
+        let diffusion_rates = player_connection.setup.diffusion_rates;
         let mut pheromones = Vec::with_capacity(PHEROMONE_CHANNEL_COUNT);
         for i in 0..PHEROMONE_CHANNEL_COUNT {
-            pheromones.push(PheromoneChannel::new(map_width, map_height, decay_rates[i]));
+            let mut channel = PheromoneChannel::new(
+                map_width,
+                map_height,
+                decay_rates[i],
+                config.pheromone_resolution,
+                config.pheromone_threshold,
+            );
+            // Player rates are kept within 0..=1, `max` turning a NaN into 0.0
+            channel.diffusion_rate = diffusion_rates[i].clamp(0.0, 1.0).max(0.0);
+            pheromones.push(channel);
         }
 
         // Check for all channels to make sure they are initialized correctly with 0.0 on all cells
@@ -58,38 +165,116 @@ impl Colony {
             }
         }
 
-        Ok(Self {
+        Self {
             pos,
+            nest_radius: config.nest_radius.max(MIN_NEST_RADIUS),
             ants,
             color,
             food_collected: 0,
+            total_food: 0,
+            peak_ants: 0,
+            spawned_at: 0,
+            kills: 0,
+            deaths: 0,
+            combat_deaths: 0,
+            wall_deaths: 0,
+            timeouts: 0,
+            think_count: 0,
+            think_latency: ThinkLatency::new(config.think_budget_for(&player_cfg.name)),
+            foraging: ForagingStats::default(),
+            max_processing_time: config.max_processing_time_for(&player_cfg.name),
+            spawns_ants: true,
+            beacons: Beacons::default(),
+            lay_events: LayEvents::default(),
+            combat_events: CombatEvents::default(),
             pheromones,
+            pheromone_budget: PheromoneBudget::new(
+                config.pheromone_budget,
+                config.pheromone_overflow,
+            ),
             colony_id,
             player_connection,
             player_config: player_cfg,
             pheromone_decay_timer: Timer::new(PHEROMONE_DECAY_INTERVAL, 0.0),
-            ant_spawn_timer: 0.0,
-        })
+            ant_spawn_timer: Timer::new(ANT_SPAWN_INTERVAL, 0.0),
+            role_counts: [0; ROLE_COUNT],
+            spawn_protection: config.spawn_protection.max(0.0),
+            emergence_delay: config.emergence_delay.max(0.0),
+            corpse_food: config.corpse_food,
+            spawn_food_cost: config.spawn_food_cost.max(1),
+            think_interval: think_interval(config),
+            rng: SeededRng::for_colony(
+                config.rng_seed.unwrap_or_else(|| rand::rand() as u64),
+                colony_id,
+            ),
+        }
+    }
+
+    /// Applies reloaded settings to the values derived from them, keeping the colony's
+    /// ants, pheromones and stock.
+    pub fn apply_config(&mut self, config: &SimulationConfig) {
+        self.nest_radius = config.nest_radius.max(MIN_NEST_RADIUS);
+        self.max_processing_time = config.max_processing_time_for(&self.player_config.name);
+        self.think_latency
+            .set_budget(config.think_budget_for(&self.player_config.name));
+        self.pheromone_budget.cap = config.pheromone_budget.max(0.0);
+        self.pheromone_budget.overflow = config.pheromone_overflow;
+        for channel in &mut self.pheromones {
+            channel.threshold = config.pheromone_threshold;
+        }
+        self.spawn_protection = config.spawn_protection.max(0.0);
+        self.emergence_delay = config.emergence_delay.max(0.0);
+        self.corpse_food = config.corpse_food;
+        self.spawn_food_cost = config.spawn_food_cost.max(1);
+        let interval = think_interval(config);
+        if interval != self.think_interval {
+            self.think_interval = interval;
+            for ant in self.ants.values_mut() {
+                ant.set_think_interval(interval);
+            }
+        }
     }
 
     pub fn update(
         &mut self,
         map: &mut GameMap,
         other_colonies: &mut HashMap<u32, Colony>,
+        config: &SimulationConfig,
         dt: f32,
     ) {
         self.pheromone_decay_timer.update(dt);
         if self.pheromone_decay_timer.is_ready() {
-            self.decay_pheromones();
+            self.decay_pheromones(config.diffusion_neighborhood, map);
             self.pheromone_decay_timer.wrap();
         }
 
-        let (pheromones, player_connection, pos) =
-            (&mut self.pheromones, &mut self.player_connection, self.pos);
+        self.lay_events.update(dt);
+        self.combat_events.update(dt);
+
+        self.foraging.record_ants(self.ants.len(), dt);
+
+        // Stats sent to the brains along with each ant's input
+        let colony_state = self.state();
+        let (pheromones, pheromone_budget, player_connection, rng, pos, max_processing_time) = (
+            &mut self.pheromones,
+            &mut self.pheromone_budget,
+            &mut self.player_connection,
+            &mut self.rng,
+            self.pos,
+            self.max_processing_time,
+        );
 
         let mut ants_to_despawn: Vec<AntKey> = Vec::new();
+        // Ants that entered a new cell this tick, and may meet a friend there
+        let mut arrived_ants: Vec<AntKey> = Vec::new();
 
         for (key, ant) in self.ants.iter_mut() {
+            // Ants still in the nest neither age, forage nor move
+            if ant.is_emerging() {
+                ant.update_emergence(dt);
+                continue;
+            }
+
             // Lose longevity (aging)
             ant.longevity -= dt; // longevity decreases
             if ant.longevity < 0.0 {
@@ -103,41 +288,217 @@ impl Colony {
             }
 
             // Update is_on_colony status
-            ant.check_colony(&self.pos);
+            ant.check_colony(&self.pos, self.nest_radius, &config.think_events);
             // Update is_on_food status
-            ant.check_food(map);
+            ant.check_food(map, &config.think_events, config.food_pickup_range);
+            // Raid the stock of an enemy nest the ant stands on
+            if config.food_stealing && !ant.is_carrying_food() {
+                steal_food(ant, map, other_colonies);
+            }
 
             // Try drop food on colony
-            if ant.is_on_colony && ant.carrying_food {
-                ant.carrying_food = false;
-                self.food_collected += 1;
+            ant.trip_time += dt;
+            if ant.is_on_colony && ant.is_carrying_food() {
+                // Everything carried is delivered at once
+                let delivered = ant.carried_food();
+                self.food_collected += delivered;
+                self.total_food += delivered;
+                ant.set_carrying_food(false, map);
+                self.foraging.record_delivery(ant.trip_time);
+                ant.trip_time = 0.0;
                 ant.rejuvenate();
+            } else if ant.is_on_colony {
+                // Trips start when leaving the nest
+                ant.trip_time = 0.0;
             }
 
+            let old_cell = (ant.pos.x as u32, ant.pos.y as u32);
             // Updates the ant's position, pheromone laying, and fighting logic
-            ant.update(&pos, map, pheromones, player_connection, other_colonies, dt);
+            ant.update(
+                &pos,
+                map,
+                pheromones,
+                pheromone_budget,
+                player_connection,
+                max_processing_time,
+                other_colonies,
+                &colony_state,
+                config,
+                rng,
+                dt,
+            );
+            self.kills += std::mem::take(&mut ant.kills);
+            self.timeouts += std::mem::take(&mut ant.timed_out) as u32;
+            self.think_count += std::mem::take(&mut ant.thinks) as u64;
+            if let Some(nanos) = ant.think_nanos.take() {
+                self.think_latency.record(nanos);
+            }
+            let laid_channels = std::mem::take(&mut ant.laid_channels);
+            if laid_channels != 0 {
+                let cell = (ant.pos.x as u32, ant.pos.y as u32);
+                for channel in 0..PHEROMONE_CHANNEL_COUNT as u8 {
+                    if laid_channels & (1 << channel) != 0 {
+                        self.lay_events.push(LayEvent::new(cell, channel));
+                    }
+                }
+            }
+            if let Some(hit) = ant.hit.take() {
+                self.combat_events.push(hit);
+            }
+            if let Some(order) = ant.beacon_order.take() {
+                self.beacons.apply(order, config.beacons.max_count);
+            }
+            if config.trophallaxis.enabled && old_cell != (ant.pos.x as u32, ant.pos.y as u32) {
+                arrived_ants.push(key);
+            }
+        }
+
+        if config.beacons.enabled {
+            self.beacons.emit(
+                config.beacons.emission_rate * dt,
+                map,
+                &mut self.pheromones,
+                &mut self.pheromone_budget,
+            );
+        }
+
+        if config.friendly_fire {
+            self.resolve_friendly_fights(config);
+        }
+
+        if !arrived_ants.is_empty() {
+            self.resolve_trophallaxis(&arrived_ants, map, &config.trophallaxis);
         }
 
         for key in ants_to_despawn {
             self.despawn_ant(key, map);
         }
 
-        self.ant_spawn_timer += dt;
-        while self.ant_spawn_timer >= ANT_SPAWN_INTERVAL
-            && self.food_collected >= crate::simulation::ANT_SPAWN_FOOD_COST
-        {
-            self.spawn_ant(map);
-            self.food_collected -= crate::simulation::ANT_SPAWN_FOOD_COST;
-            self.ant_spawn_timer -= ANT_SPAWN_INTERVAL;
+        if self.spawns_ants {
+            // One ant per elapsed spawn interval, as long as the food lasts
+            self.ant_spawn_timer.update(dt);
+            let spawns = self
+                .ant_spawn_timer
+                .elapsed_intervals()
+                .min(self.food_collected / self.spawn_food_cost);
+            for _ in 0..spawns {
+                self.spawn_ant(map);
+            }
+            self.food_collected -= spawns * self.spawn_food_cost;
+            self.ant_spawn_timer.value -= spawns as f32 * ANT_SPAWN_INTERVAL;
         }
     }
 
-    fn decay_pheromones(&mut self) {
-        for pheromone in &mut self.pheromones {
-            pheromone.decay();
+    /// Progress toward the next spawn, limited by both the spawn interval and the food collected.
+    pub fn spawn_progress(&self) -> f32 {
+        let food_progress = self.food_collected as f32 / self.spawn_food_cost as f32;
+        self.ant_spawn_timer.progress().min(food_progress)
+    }
+
+    /// Colony-wide stats exposed to the player's brain.
+    pub fn state(&self) -> ColonyState {
+        ColonyState {
+            food_collected: self.food_collected,
+            population: self.ants.len() as u32,
+            kills: self.kills,
+        }
+    }
+
+    /// Let each ant that entered a new cell exchange with a friend found there.
+    /// Like friendly fights, this can't happen during the ants' own update.
+    /// Each ant takes part in at most one exchange per tick.
+    fn resolve_trophallaxis(
+        &mut self,
+        arrived_ants: &[AntKey],
+        map: &mut GameMap,
+        config: &TrophallaxisConfig,
+    ) {
+        let can_exchange = |ant: &Ant| !ant.is_dead() && !ant.is_fighting();
+        let mut exchanged: AHashSet<AntKey> = AHashSet::new();
+        for &key in arrived_ants {
+            let Some(ant) = self.ants.get(key) else {
+                continue;
+            };
+            if exchanged.contains(&key) || !can_exchange(ant) {
+                continue;
+            }
+            let partner = map
+                .ants_at(ant.pos.x as usize, ant.pos.y as usize)
+                .iter()
+                .filter(|ant_ref| ant_ref.colony_id == self.colony_id && ant_ref.key != key)
+                .map(|ant_ref| ant_ref.key)
+                .find(|partner| {
+                    !exchanged.contains(partner)
+                        && self.ants.get(*partner).is_some_and(can_exchange)
+                });
+            if let Some(partner) = partner
+                && let Some([ant, partner_ant]) = self.ants.get_disjoint_mut([key, partner])
+            {
+                ant.exchange_with(partner_ant, config, map);
+                exchanged.insert(key);
+                exchanged.insert(partner);
+            }
+        }
+    }
+
+    /// Resolve the fights between ants of this colony requested during their update.
+    fn resolve_friendly_fights(&mut self, config: &SimulationConfig) {
+        let keys: Vec<AntKey> = self
+            .ants
+            .iter()
+            .filter(|(_, ant)| {
+                ant.friendly_fight_request.is_some() || ant.friendly_strike.is_some()
+            })
+            .map(|(key, _)| key)
+            .collect();
+
+        for key in keys {
+            let ant = &mut self.ants[key];
+            let (fight_request, strike) = (
+                ant.friendly_fight_request.take(),
+                ant.friendly_strike.take(),
+            );
+
+            if let Some(target_ref) = fight_request
+                && !ant.is_fighting()
+                && let Some([ant, target]) = self.ants.get_disjoint_mut([key, target_ref.key])
+            {
+                ant.initiate_fight_with(target, config.max_attack_angle);
+            }
+
+            if let Some(target_ref) = strike {
+                match self.ants.get_disjoint_mut([key, target_ref.key]) {
+                    Some([ant, target]) => {
+                        ant.strike(target, config);
+                        if let Some(hit) = ant.hit.take() {
+                            self.combat_events.push(hit);
+                        }
+                    }
+                    // Opponent was despawned
+                    None => self.ants[key].remove_opponent(target_ref.key),
+                }
+            }
         }
     }
 
+    /// Clears the colony's pheromones, leaving its ants, nest and stock untouched.
+    pub fn clear_pheromones(&mut self) {
+        self.pheromones.iter_mut().for_each(PheromoneChannel::clear);
+        self.pheromone_budget.total = 0.0;
+    }
+
+    /// Diffuses then decays every channel, so the faint cells diffusion creates are cleared.
+    fn decay_pheromones(&mut self, neighborhood: DiffusionNeighborhood, map: &GameMap) {
+        self.pheromone_budget.total = self
+            .pheromones
+            .iter_mut()
+            .map(|pheromone| {
+                pheromone.diffuse(neighborhood, map);
+                pheromone.decay()
+            })
+            .sum();
+    }
+
     pub fn spawn_ants(&mut self, map: &mut GameMap, count: u32) {
         for _ in 0..count {
             self.spawn_ant(map);
@@ -145,11 +506,22 @@ impl Colony {
     }
 
     pub fn spawn_ant(&mut self, map: &mut GameMap) {
-        let mut ant_instance = Ant::new(self.pos, self.colony_id);
+        let mut ant_instance = Ant::new(
+            self.pos,
+            self.colony_id,
+            self.player_connection.setup.initial_memory,
+            &mut self.rng,
+        );
+        ant_instance.role = self.next_role();
+        ant_instance.spawn_protection = self.spawn_protection;
+        ant_instance.emergence = self.emergence_delay;
+        ant_instance.set_think_interval(self.think_interval);
+        self.role_counts[ant_instance.role as usize] += 1;
         let key = self.ants.insert_with_key(|k| {
             ant_instance.ant_ref.key = k;
             ant_instance
         });
+        self.peak_ants = self.peak_ants.max(self.ants.len());
 
         // Register the newly spawned ant in the map at its initial position.
         if let Some(new_ant) = self.ants.get(key) {
@@ -163,23 +535,60 @@ impl Colony {
         }
     }
 
+    /// Picks the role of the next spawned ant, the one furthest below its target share
+    /// of `PlayerSetup::role_ratios`. Falls back to worker when no ratio is configured.
+    fn next_role(&self) -> u8 {
+        let ratios = self.player_connection.setup.role_ratios;
+        let ratio_sum: f32 = ratios.iter().filter(|r| r.is_finite() && **r > 0.0).sum();
+        if ratio_sum <= 0.0 {
+            return ROLE_WORKER;
+        }
+
+        let population = self.role_counts.iter().sum::<u32>() + 1;
+        let mut best_role = ROLE_WORKER;
+        let mut best_deficit = f32::NEG_INFINITY;
+        for (role, &ratio) in ratios.iter().enumerate() {
+            if !ratio.is_finite() || ratio <= 0.0 {
+                continue;
+            }
+            let target = ratio / ratio_sum * population as f32;
+            let deficit = target - self.role_counts[role] as f32;
+            if deficit > best_deficit {
+                best_deficit = deficit;
+                best_role = role as u8;
+            }
+        }
+        best_role
+    }
+
+    /// Removes a dead or killed ant, counting it among the colony's deaths.
     pub fn despawn_ant(&mut self, key: AntKey, map: &mut GameMap) {
-        if let Some(ant_to_despawn) = self.ants.get(key) {
+        if let Some(ant) = self.ants.get(key) {
+            self.deaths += 1;
+            if ant.killed_by_hit {
+                self.combat_deaths += 1;
+            }
+        }
+        self.remove_ant(key, map);
+    }
+
+    /// Removes an ant from the colony and the map, dropping its food.
+    fn remove_ant(&mut self, key: AntKey, map: &mut GameMap) {
+        if let Some(ant_to_despawn) = self.ants.get_mut(key) {
             let ant_ref_clone = ant_to_despawn.ant_ref.clone();
             let ant_pos = ant_to_despawn.pos;
-            // If the ant was carrying food, drop it on the terrain
-            if ant_to_despawn.carrying_food {
-                let x = ant_pos.x.floor() as usize;
-                let y = ant_pos.y.floor() as usize;
-                // Only place food if the cell is empty or already food
-                if let Some(terrain) = map.get_terrain_at(x, y) {
-                    match terrain {
-                        Terrain::Empty => map.place_food_at(x, y, 1),
-                        Terrain::Food(amount) => map.place_food_at(x, y, amount + 1),
-                        _ => {}
-                    }
-                }
-            }
+            // Drop the carried food, and the corpse food if configured, on the terrain
+            let dropped_food = ant_to_despawn.carried_food() + self.corpse_food;
+            map.drop_food_at(
+                ant_pos.x.floor() as usize,
+                ant_pos.y.floor() as usize,
+                dropped_food,
+            );
+            let role = ant_to_despawn.role as usize;
+            self.role_counts[role] = self.role_counts[role].saturating_sub(1);
+
+            ant_to_despawn.set_carrying_food(false, map);
+
             // Unregister the ant from the map at its last known position.
             if !map.unregister_ant_from_cell(&ant_ref_clone, ant_pos) {
                 eprintln!(
@@ -203,8 +612,8 @@ impl Colony {
     pub fn respawn_ant(&mut self, ant_key: AntKey, _new_pos: Vec2, map: &mut GameMap) {
         // First, ensure the ant to be "respawned" (i.e., replaced) exists in this colony.
         if self.ants.contains_key(ant_key) {
-            // Despawn the old ant.
-            self.despawn_ant(ant_key, map);
+            // Remove the old ant, replaced rather than lost.
+            self.remove_ant(ant_key, map);
             // Spawn a new ant at the colony's nest position.
             self.spawn_ant(map);
         } else {
@@ -215,18 +624,195 @@ impl Colony {
         }
     }
 
+    /// Moves an ant to `pos` without changing its state, keeping the map's cell registry in sync.
+    pub fn relocate_ant(&mut self, ant_key: AntKey, pos: Vec2, map: &mut GameMap) {
+        let Some(ant) = self.ants.get_mut(ant_key) else {
+            eprintln!(
+                "Warning: AntKey {:?} not found in colony {} during relocation.",
+                ant_key, self.colony_id
+            );
+            return;
+        };
+        map.unregister_ant_from_cell(&ant.ant_ref, ant.pos);
+        ant.pos = pos;
+        ant.trail_start = pos;
+        map.register_ant_in_cell(&ant.ant_ref, pos);
+    }
+
     /// Get the pheromone level for a specific channel at a specific tile coordinate.
     pub fn get_pheromone_channel_at(&self, x: usize, y: usize, channel_index: usize) -> f32 {
-        if channel_index < self.pheromones.len() {
-            let channel = &self.pheromones[channel_index];
-            if x < channel.width as usize && y < channel.height as usize {
-                return channel.data[y][x];
-            }
+        // 0 if channel index or coordinates are out of bounds
+        self.pheromones
+            .get(channel_index)
+            .map_or(0.0, |channel| channel.get(x, y))
+    }
+
+    /// Display label of a pheromone channel (0-based), using the name set by the player if any.
+    pub fn channel_label(&self, channel_index: usize) -> String {
+        match self.player_connection.setup.channel_name(channel_index) {
+            Some(name) => format!("Channel {} ({})", channel_index + 1, name),
+            None => format!("Channel {}", channel_index + 1),
         }
-        0.0 // Return 0 if channel index or coordinates are out of bounds
+    }
+
+    /// Get the total pheromone mass of a channel, summed over all cells.
+    /// This is O(cells holding pheromone), callers displaying it should throttle the queries.
+    pub fn total_pheromone(&self, channel_index: usize) -> f32 {
+        self.pheromones
+            .get(channel_index)
+            .map_or(0.0, |channel| channel.total())
     }
 
     pub fn is_dead(&self) -> bool {
         self.ants.is_empty()
     }
 }
+
+/// Takes one unit from the stock of an enemy colony whose nest the ant stands on, the one with
+/// the lowest id where nests overlap.
+fn steal_food(ant: &mut Ant, map: &mut GameMap, other_colonies: &mut HashMap<u32, Colony>) {
+    let raided = other_colonies
+        .iter_mut()
+        .filter(|(_, colony)| {
+            colony.food_collected > 0
+                && colony.pos.distance_squared(ant.pos) <= colony.nest_radius * colony.nest_radius
+        })
+        .min_by_key(|(colony_id, _)| **colony_id);
+    if let Some((_, colony)) = raided {
+        colony.food_collected -= 1;
+        ant.set_carrying_food(true, map);
+    }
+}
+
+#[cfg(test)]
+impl Colony {
+    /// Colony without a brain behind it, for tests driving its ants directly.
+    pub fn detached(colony_id: u32, pos: Vec2, map: &GameMap, config: &SimulationConfig) -> Self {
+        let setup = shared::PlayerSetup {
+            decay_rates: [0.9; PHEROMONE_CHANNEL_COUNT],
+            initial_memory: [0; shared::MEMORY_SIZE],
+            role_ratios: [0.0; ROLE_COUNT],
+            channel_names: [[0; shared::CHANNEL_NAME_SIZE]; PHEROMONE_CHANNEL_COUNT],
+            diffusion_rates: [0.0; PHEROMONE_CHANNEL_COUNT],
+        };
+        Self::with_connection(
+            colony_id,
+            pos,
+            map.width,
+            map.height,
+            WHITE,
+            config,
+            PlayerConfig {
+                name: format!("player{}", colony_id),
+                so_path: String::new(),
+                texture_path: None,
+                build_command: None,
+            },
+            PlayerConnection::detached(colony_id, setup).unwrap(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::Terrain;
+    use shared::MEMORY_SIZE;
+
+    #[test]
+    fn test_steal_food_raids_the_lowest_id_of_overlapping_nests() {
+        let mut map = GameMap::new(20, 20);
+        let config = SimulationConfig::default();
+        // Fresh maps iterate in different orders: the raided colony must not depend on it
+        for _ in 0..8 {
+            let mut others = HashMap::new();
+            for colony_id in [3, 1, 2] {
+                let mut colony = Colony::detached(colony_id, Vec2::new(5.5, 5.5), &map, &config);
+                colony.food_collected = 4;
+                others.insert(colony_id, colony);
+            }
+            let mut ant = Ant::new(
+                Vec2::new(5.5, 5.5),
+                0,
+                [0u8; MEMORY_SIZE],
+                &mut SeededRng::new(0),
+            );
+            map.register_ant_in_cell(&ant.ant_ref, ant.pos);
+
+            steal_food(&mut ant, &mut map, &mut others);
+
+            assert!(ant.is_carrying_food());
+            assert_eq!(others[&1].food_collected, 3);
+            assert_eq!(others[&2].food_collected, 4);
+            assert_eq!(others[&3].food_collected, 4);
+            ant.set_carrying_food(false, &mut map);
+            assert!(map.unregister_ant_from_cell(&ant.ant_ref, ant.pos));
+        }
+    }
+
+    #[test]
+    fn test_only_ants_killed_by_a_hit_are_combat_deaths() {
+        let mut map = GameMap::new(20, 20);
+        let config = SimulationConfig::default();
+        let mut colony = Colony::detached(1, Vec2::new(5.5, 5.5), &map, &config);
+        colony.spawn_protection = 0.0;
+        colony.spawn_ants(&mut map, 3);
+        let mut enemy = Ant::new(
+            Vec2::new(5.5, 5.5),
+            0,
+            [0u8; MEMORY_SIZE],
+            &mut SeededRng::new(0),
+        );
+        let keys: Vec<AntKey> = colony.ants.keys().collect();
+        for &key in &keys {
+            let ant = &mut colony.ants[key];
+            ant.emergence = 0.0;
+            assert!(enemy.strike(ant, &config));
+            assert!(!ant.is_dead());
+        }
+        // Hit earlier, then dies of old age, of a timeout, or of a second hit
+        colony.ants[keys[0]].longevity = 0.01;
+        colony.ants[keys[1]].longevity = 0.0;
+        colony.ants[keys[1]].timed_out = true;
+        colony.ants[keys[2]].longevity = config.attack_damage;
+        assert!(enemy.strike(&mut colony.ants[keys[2]], &config));
+
+        colony.update(&mut map, &mut HashMap::new(), &config, 0.1);
+
+        assert!(colony.ants.is_empty());
+        assert_eq!((colony.deaths, colony.combat_deaths), (3, 1));
+    }
+
+    #[test]
+    fn test_dying_ants_leave_their_food_and_corpse_on_free_cells() {
+        let mut map = GameMap::new(20, 20);
+        let config = SimulationConfig {
+            corpse_food: 2,
+            ..SimulationConfig::default()
+        };
+        let mut colony = Colony::detached(1, Vec2::new(5.5, 5.5), &map, &config);
+        map.place_colony_at(5, 5, 1);
+        map.place_wall_at(13, 10);
+        colony.spawn_ants(&mut map, 4);
+        // Carriers dying on an empty cell, next to a wall and on the nest, then a non-carrier
+        let cells = [(12, 3), (12, 10), (5, 5), (15, 15)];
+        let keys: Vec<AntKey> = colony.ants.keys().collect();
+        for (i, (&key, (x, y))) in keys.iter().zip(cells).enumerate() {
+            colony.relocate_ant(key, Vec2::new(x as f32 + 0.5, y as f32 + 0.5), &mut map);
+            let ant = &mut colony.ants[key];
+            ant.emergence = 0.0;
+            ant.longevity = 0.0;
+            ant.set_carrying_food(i < 3, &mut map);
+        }
+
+        colony.update(&mut map, &mut HashMap::new(), &config, 0.1);
+
+        assert!(colony.ants.is_empty());
+        assert_eq!(map.get_terrain_at(12, 3), Some(&Terrain::Food(3)));
+        assert_eq!(map.get_terrain_at(12, 10), Some(&Terrain::Food(3)));
+        assert_eq!(map.get_terrain_at(13, 10), Some(&Terrain::Wall));
+        // Food is never dropped on a nest
+        assert_eq!(map.get_terrain_at(5, 5), Some(&Terrain::Nest(1)));
+        assert_eq!(map.get_terrain_at(15, 15), Some(&Terrain::Food(2)));
+    }
+}
@@ -0,0 +1,39 @@
+use macroquad::prelude::Vec2;
+
+use super::fading::{FadingEvent, FadingEvents};
+use super::{COMBAT_EFFECT_DURATION, MAX_COMBAT_EVENTS};
+
+/// Successful hit of an ant, kept a moment for the combat effects view
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CombatEvent {
+    /// Position of the ant that was hit
+    pub pos: Vec2,
+    pub damage: f32,
+    /// Whether the hit killed the target
+    pub killed: bool,
+    /// Seconds since the hit
+    pub age: f32,
+}
+
+impl CombatEvent {
+    pub fn new(pos: Vec2, damage: f32, killed: bool) -> Self {
+        Self {
+            pos,
+            damage,
+            killed,
+            age: 0.0,
+        }
+    }
+}
+
+impl FadingEvent for CombatEvent {
+    const DURATION: f32 = COMBAT_EFFECT_DURATION;
+    const CAPACITY: usize = MAX_COMBAT_EVENTS;
+
+    fn age_mut(&mut self) -> &mut f32 {
+        &mut self.age
+    }
+}
+
+/// Recent hits dealt by a colony's ants, oldest first
+pub type CombatEvents = FadingEvents<CombatEvent>;
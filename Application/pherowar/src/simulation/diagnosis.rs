@@ -0,0 +1,145 @@
+use std::fmt;
+
+use super::Colony;
+
+/// Food per ant-minute under which a colony is considered starved
+const STARVED_FOOD_PER_ANT_MINUTE: f32 = 0.1;
+/// Share of a colony's deaths caused by brain timeouts for it to have timed out
+const TIMED_OUT_DEATH_SHARE: f32 = 0.25;
+/// Ants lost in fights per enemy killed for a colony to have been overwhelmed
+const OVERWHELMED_LOSS_RATIO: f32 = 2.0;
+/// Share of a colony's deaths caused by walls (sudden death ring, editor) for it to have
+/// been crushed
+const CRUSHED_DEATH_SHARE: f32 = 0.5;
+
+/// Most likely reason a colony lost, picked by simple heuristics on its match stats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LossCause {
+    /// Its brain kept exceeding the processing time, killing its ants
+    TimedOut,
+    /// Most of its ants were crushed by walls, like the sudden death ring
+    Crushed,
+    /// Its ants died in fights far more than they killed
+    Overwhelmed,
+    /// Its ants barely brought food back
+    Starved,
+    /// None of the above stands out
+    Outplayed,
+}
+
+impl fmt::Display for LossCause {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            LossCause::TimedOut => "timed out",
+            LossCause::Crushed => "crushed",
+            LossCause::Overwhelmed => "overwhelmed",
+            LossCause::Starved => "starved",
+            LossCause::Outplayed => "outplayed",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Match stats of a colony explaining its loss.
+#[derive(Debug, Clone, Copy)]
+pub struct LossDiagnosis {
+    pub deaths: u32,
+    pub combat_deaths: u32,
+    pub wall_deaths: u32,
+    pub timeouts: u32,
+    pub kills: u32,
+    pub food_per_ant_minute: Option<f32>,
+}
+
+impl LossDiagnosis {
+    pub fn of(colony: &Colony) -> Self {
+        Self {
+            deaths: colony.deaths,
+            combat_deaths: colony.combat_deaths,
+            wall_deaths: colony.wall_deaths,
+            timeouts: colony.timeouts,
+            kills: colony.kills,
+            food_per_ant_minute: colony.foraging.food_per_ant_minute(),
+        }
+    }
+
+    /// Timeouts take precedence, as they also make the colony lose fights and food.
+    pub fn cause(&self) -> LossCause {
+        if self.timeouts > 0 && self.timeouts as f32 >= self.deaths as f32 * TIMED_OUT_DEATH_SHARE {
+            LossCause::TimedOut
+        } else if self.wall_deaths > 0
+            && self.wall_deaths as f32 >= self.deaths as f32 * CRUSHED_DEATH_SHARE
+        {
+            LossCause::Crushed
+        } else if self.combat_deaths as f32 >= self.deaths as f32 / 2.0
+            && self.combat_deaths as f32 > self.kills as f32 * OVERWHELMED_LOSS_RATIO
+        {
+            LossCause::Overwhelmed
+        } else if self
+            .food_per_ant_minute
+            .is_none_or(|efficiency| efficiency < STARVED_FOOD_PER_ANT_MINUTE)
+        {
+            LossCause::Starved
+        } else {
+            LossCause::Outplayed
+        }
+    }
+}
+
+/// Summary, e.g. `overwhelmed (40 deaths, 35 in fights, 3 kills, 0 timeouts, 0.42 food/ant-min)`
+impl fmt::Display for LossDiagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} ({} deaths, {} in fights, {} kills, {} timeouts, ",
+            self.cause(),
+            self.deaths,
+            self.combat_deaths,
+            self.kills,
+            self.timeouts
+        )?;
+        match self.food_per_ant_minute {
+            Some(efficiency) => write!(f, "{:.2} food/ant-min)", efficiency),
+            None => write!(f, "no food/ant-min)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnosis(deaths: u32, combat_deaths: u32, timeouts: u32, kills: u32) -> LossDiagnosis {
+        LossDiagnosis {
+            deaths,
+            combat_deaths,
+            wall_deaths: 0,
+            timeouts,
+            kills,
+            food_per_ant_minute: Some(0.5),
+        }
+    }
+
+    #[test]
+    fn test_loss_causes() {
+        assert_eq!(diagnosis(20, 10, 5, 0).cause(), LossCause::TimedOut);
+        // A few timeouts among many deaths don't explain the loss
+        assert_eq!(diagnosis(40, 30, 1, 5).cause(), LossCause::Overwhelmed);
+        // Fights lost evenly
+        assert_eq!(diagnosis(40, 30, 0, 20).cause(), LossCause::Outplayed);
+        // Wiped out by the sudden death ring
+        let mut crushed = diagnosis(40, 5, 0, 5);
+        crushed.wall_deaths = 30;
+        assert_eq!(crushed.cause(), LossCause::Crushed);
+
+        let mut starved = diagnosis(40, 5, 0, 5);
+        starved.food_per_ant_minute = Some(0.05);
+        assert_eq!(starved.cause(), LossCause::Starved);
+        starved.food_per_ant_minute = None;
+        assert_eq!(starved.cause(), LossCause::Starved);
+        assert_eq!(
+            starved.to_string(),
+            "starved (40 deaths, 5 in fights, 5 kills, 0 timeouts, no food/ant-min)"
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::VecDeque;
+
+/// Event kept a moment for a debug view, until it fades
+pub trait FadingEvent {
+    /// Seconds the event stays visible
+    const DURATION: f32;
+    /// Events kept per colony
+    const CAPACITY: usize;
+
+    /// Seconds since the event
+    fn age_mut(&mut self) -> &mut f32;
+}
+
+/// Recent events of a colony, oldest first.
+/// Events are dropped once faded, or earliest first when `T::CAPACITY` is reached.
+#[derive(Debug, Clone)]
+pub struct FadingEvents<T> {
+    events: VecDeque<T>,
+}
+
+impl<T> Default for FadingEvents<T> {
+    fn default() -> Self {
+        Self {
+            events: VecDeque::new(),
+        }
+    }
+}
+
+impl<T: FadingEvent> FadingEvents<T> {
+    pub fn push(&mut self, event: T) {
+        if self.events.len() == T::CAPACITY {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    /// Ages the events by `dt` and drops the faded ones.
+    pub fn update(&mut self, dt: f32) {
+        for event in self.events.iter_mut() {
+            *event.age_mut() += dt;
+        }
+        while self
+            .events
+            .front_mut()
+            .is_some_and(|event| *event.age_mut() >= T::DURATION)
+        {
+            self.events.pop_front();
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.events.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Pulse {
+        id: usize,
+        age: f32,
+    }
+
+    impl Pulse {
+        fn new(id: usize) -> Self {
+            Self { id, age: 0.0 }
+        }
+    }
+
+    impl FadingEvent for Pulse {
+        const DURATION: f32 = 1.0;
+        const CAPACITY: usize = 4;
+
+        fn age_mut(&mut self) -> &mut f32 {
+            &mut self.age
+        }
+    }
+
+    #[test]
+    fn test_fading_events_fade_and_stay_bounded() {
+        let mut events = FadingEvents::default();
+        for id in 0..Pulse::CAPACITY + 3 {
+            events.push(Pulse::new(id));
+        }
+        assert_eq!(events.iter().count(), Pulse::CAPACITY);
+        assert_eq!(events.iter().next().unwrap().id, 3);
+
+        events.update(Pulse::DURATION / 2.0);
+        events.push(Pulse::new(10));
+        events.update(Pulse::DURATION / 2.0);
+        let remaining: Vec<usize> = events.iter().map(|pulse| pulse.id).collect();
+        assert_eq!(remaining, [10]);
+    }
+}
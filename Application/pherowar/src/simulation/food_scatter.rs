@@ -0,0 +1,127 @@
+use crate::editor::symmetry_mode::SymmetryMode;
+
+use super::rng::SeededRng;
+use super::{DEFAULT_FOOD_AMOUNT, GameMap, Terrain};
+
+/// Max placement attempts per requested food tile, so crowded maps terminate
+const MAX_ATTEMPTS_PER_TILE: usize = 8;
+
+/// Procedural food settings of a new map
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FoodScatter {
+    /// Fraction of the free tiles covered with food (0.0 to 1.0)
+    pub density: f32,
+    /// 0.0 scatters single tiles uniformly, 1.0 grows a few large patches
+    pub clustering: f32,
+    pub seed: u64,
+}
+
+impl GameMap {
+    /// Scatters food on empty tiles. Each new tile either starts a patch or, with
+    /// probability `clustering`, grows next to an already placed one.
+    /// Food is only placed in the authoritative part of `symmetry` and mirrored onto the rest.
+    /// Returns the number of food tiles placed.
+    pub fn scatter_food(
+        &mut self,
+        density: f32,
+        clustering: f32,
+        seed: u64,
+        symmetry: SymmetryMode,
+    ) -> usize {
+        let (width, height) = (self.width as usize, self.height as usize);
+        if width == 0 || height == 0 {
+            return 0;
+        }
+        let is_source = |x: usize, y: usize| symmetry.source_cell(x, y, width, height) == (x, y);
+        let is_empty =
+            |map: &GameMap, x: usize, y: usize| map.get_terrain_at(x, y) == Some(&Terrain::Empty);
+        let free_sources = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| is_source(x, y) && is_empty(self, x, y))
+            .count();
+        let target = (free_sources as f32 * density.clamp(0.0, 1.0)).round() as usize;
+        let clustering = clustering.clamp(0.0, 1.0);
+
+        let mut rng = SeededRng::new(seed);
+        let mut placed: Vec<(usize, usize)> = Vec::with_capacity(target);
+        let mut attempts = 0;
+        while placed.len() < target && attempts < target * MAX_ATTEMPTS_PER_TILE {
+            attempts += 1;
+            let (x, y) = if !placed.is_empty() && rng.next_f32() < clustering {
+                let (px, py) = placed[rng.below(placed.len())];
+                let nx = (px + rng.below(3)).checked_sub(1);
+                let ny = (py + rng.below(3)).checked_sub(1);
+                match (nx, ny) {
+                    (Some(nx), Some(ny)) if nx < width && ny < height => (nx, ny),
+                    _ => continue,
+                }
+            } else {
+                (rng.below(width), rng.below(height))
+            };
+            if is_source(x, y) && is_empty(self, x, y) {
+                self.place_food_at(x, y, DEFAULT_FOOD_AMOUNT);
+                placed.push((x, y));
+            }
+        }
+
+        if symmetry != SymmetryMode::None {
+            for y in 0..height {
+                for x in 0..width {
+                    let (sx, sy) = symmetry.source_cell(x, y, width, height);
+                    if (sx, sy) != (x, y)
+                        && is_empty(self, x, y)
+                        && matches!(self.get_terrain_at(sx, sy), Some(Terrain::Food(_)))
+                    {
+                        self.place_food_at(x, y, DEFAULT_FOOD_AMOUNT);
+                    }
+                }
+            }
+        }
+        placed.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn food_tiles(map: &GameMap) -> Vec<(usize, usize)> {
+        let mut tiles = Vec::new();
+        for y in 0..map.height as usize {
+            for x in 0..map.width as usize {
+                if matches!(map.get_terrain_at(x, y), Some(Terrain::Food(_))) {
+                    tiles.push((x, y));
+                }
+            }
+        }
+        tiles
+    }
+
+    #[test]
+    fn test_scatter_food_is_seeded_symmetric_and_avoids_walls() {
+        let mut map = GameMap::new(32, 32);
+        for y in 0..32 {
+            map.place_wall_at(10, y);
+        }
+        let placed = map.scatter_food(0.1, 0.5, 42, SymmetryMode::MirrorHorizontal);
+        assert_eq!(placed, 48); // 10% of the 15 * 32 free tiles of the left half
+
+        let food = food_tiles(&map);
+        assert_eq!(food.len(), 2 * placed);
+        for &(x, y) in &food {
+            assert_ne!(x, 10);
+            assert_ne!(x, 21);
+            assert!(matches!(
+                map.get_terrain_at(31 - x, y),
+                Some(Terrain::Food(_))
+            ));
+        }
+
+        let mut same_seed = GameMap::new(32, 32);
+        for y in 0..32 {
+            same_seed.place_wall_at(10, y);
+        }
+        same_seed.scatter_food(0.1, 0.5, 42, SymmetryMode::MirrorHorizontal);
+        assert_eq!(food_tiles(&same_seed), food);
+    }
+}
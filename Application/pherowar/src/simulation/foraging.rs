@@ -0,0 +1,73 @@
+use std::fmt;
+
+/// Food deliveries of a colony's ants, to judge a brain's foraging beyond wins and losses.
+#[derive(Debug, Clone, Default)]
+pub struct ForagingStats {
+    deliveries: u32,
+    /// Summed round trips of the deliveries, from leaving the nest to dropping the food there
+    trip_seconds: f64,
+    /// Summed lifetime of all the colony's ants
+    ant_seconds: f64,
+}
+
+impl ForagingStats {
+    /// Counts `ants` living ants over `dt` seconds.
+    pub fn record_ants(&mut self, ants: usize, dt: f32) {
+        self.ant_seconds += ants as f64 * dt as f64;
+    }
+
+    pub fn record_delivery(&mut self, trip_seconds: f32) {
+        self.deliveries += 1;
+        self.trip_seconds += trip_seconds as f64;
+    }
+
+    /// Average round trip of a delivery, in seconds
+    pub fn avg_trip_seconds(&self) -> Option<f32> {
+        (self.deliveries > 0).then(|| (self.trip_seconds / self.deliveries as f64) as f32)
+    }
+
+    /// Food delivered per minute of ant life
+    pub fn food_per_ant_minute(&self) -> Option<f32> {
+        (self.ant_seconds > 0.0).then(|| (self.deliveries as f64 * 60.0 / self.ant_seconds) as f32)
+    }
+}
+
+/// Summary, e.g. `0.42 food/ant-min, avg trip 38.5 s (120 deliveries)`
+impl fmt::Display for ForagingStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (Some(efficiency), Some(trip)) = (self.food_per_ant_minute(), self.avg_trip_seconds())
+        else {
+            return write!(f, "no deliveries yet");
+        };
+        write!(
+            f,
+            "{:.2} food/ant-min, avg trip {:.1} s ({} deliveries)",
+            efficiency, trip, self.deliveries
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_foraging_stats() {
+        let mut stats = ForagingStats::default();
+        assert_eq!(stats.to_string(), "no deliveries yet");
+
+        // 10 ants for 30 seconds, 5 of which bring food back
+        for _ in 0..30 {
+            stats.record_ants(10, 1.0);
+        }
+        for trip in [20.0, 25.0, 30.0, 25.0, 25.0] {
+            stats.record_delivery(trip);
+        }
+        assert_eq!(stats.avg_trip_seconds(), Some(25.0));
+        assert_eq!(stats.food_per_ant_minute(), Some(1.0));
+        assert_eq!(
+            stats.to_string(),
+            "1.00 food/ant-min, avg trip 25.0 s (5 deliveries)"
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt;
+
+use super::MAX_ANT_PROCESSING_TIME;
+
+/// Buckets of the histogram percentiles are read from
+const LATENCY_BUCKETS: usize = 300;
+/// Histogram range in think time budgets; slower calls share the last bucket
+const LATENCY_RANGE_BUDGETS: u64 = 2;
+
+/// Round-trip times of a colony's brain calls: running min/avg/max and a histogram for percentiles.
+#[derive(Debug, Clone)]
+pub struct ThinkLatency {
+    count: u64,
+    total_nanos: u64,
+    min_nanos: u64,
+    max_nanos: u64,
+    /// Width of the histogram buckets, scaled by the colony's think time budget
+    bucket_nanos: u64,
+    buckets: Vec<u32>,
+}
+
+impl Default for ThinkLatency {
+    fn default() -> Self {
+        Self::new(MAX_ANT_PROCESSING_TIME)
+    }
+}
+
+impl ThinkLatency {
+    /// Latency of a colony whose brain calls may take `budget_nanos`.
+    pub fn new(budget_nanos: u128) -> Self {
+        let range = LATENCY_RANGE_BUDGETS.saturating_mul(budget_nanos.min(u64::MAX as u128) as u64);
+        Self {
+            count: 0,
+            total_nanos: 0,
+            min_nanos: u64::MAX,
+            max_nanos: 0,
+            bucket_nanos: (range / LATENCY_BUCKETS as u64).max(1),
+            buckets: vec![0; LATENCY_BUCKETS],
+        }
+    }
+
+    /// Rescales the histogram to a new budget, moving the recorded calls to the new buckets.
+    pub fn set_budget(&mut self, budget_nanos: u128) {
+        let mut rescaled = Self::new(budget_nanos);
+        if rescaled.bucket_nanos == self.bucket_nanos {
+            return;
+        }
+        for (bucket, &calls) in self.buckets.iter().enumerate() {
+            let nanos = bucket as u64 * self.bucket_nanos;
+            let rescaled_bucket = rescaled.bucket_of(nanos);
+            rescaled.buckets[rescaled_bucket] += calls;
+        }
+        self.bucket_nanos = rescaled.bucket_nanos;
+        self.buckets = rescaled.buckets;
+    }
+
+    fn bucket_of(&self, nanos: u64) -> usize {
+        ((nanos / self.bucket_nanos) as usize).min(LATENCY_BUCKETS - 1)
+    }
+
+    pub fn record(&mut self, nanos: u64) {
+        self.count += 1;
+        self.total_nanos = self.total_nanos.saturating_add(nanos);
+        self.min_nanos = self.min_nanos.min(nanos);
+        self.max_nanos = self.max_nanos.max(nanos);
+        let bucket = self.bucket_of(nanos);
+        self.buckets[bucket] += 1;
+    }
+
+    pub fn min_nanos(&self) -> Option<u64> {
+        (self.count > 0).then_some(self.min_nanos)
+    }
+
+    pub fn max_nanos(&self) -> Option<u64> {
+        (self.count > 0).then_some(self.max_nanos)
+    }
+
+    pub fn avg_nanos(&self) -> Option<u64> {
+        (self.count > 0).then(|| self.total_nanos / self.count)
+    }
+
+    /// Upper bound of the bucket holding the `percentile` (0-100) call, capped by the max.
+    /// Precise to the bucket width, a 150th of the budget.
+    pub fn percentile_nanos(&self, percentile: f32) -> Option<u64> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank =
+            ((percentile as f64 * self.count as f64 / 100.0).ceil() as u64).clamp(1, self.count);
+        let mut seen = 0;
+        for (bucket, &calls) in self.buckets.iter().enumerate() {
+            seen += calls as u64;
+            if seen >= rank && bucket < LATENCY_BUCKETS - 1 {
+                let upper = (bucket as u64 + 1) * self.bucket_nanos;
+                return Some(upper.min(self.max_nanos));
+            }
+        }
+        // In the open-ended last bucket
+        Some(self.max_nanos)
+    }
+}
+
+/// Summary in microseconds, e.g. `min 12 / avg 30 / p99 90 / max 140 µs`
+impl fmt::Display for ThinkLatency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (Some(min), Some(avg), Some(p99), Some(max)) = (
+            self.min_nanos(),
+            self.avg_nanos(),
+            self.percentile_nanos(99.0),
+            self.max_nanos(),
+        ) else {
+            return write!(f, "no thinks yet");
+        };
+        write!(
+            f,
+            "min {} / avg {} / p99 {} / max {} µs",
+            min / 1000,
+            avg / 1000,
+            p99 / 1000,
+            max / 1000
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_think_latency_stats() {
+        let mut latency = ThinkLatency::default();
+        assert_eq!(latency.percentile_nanos(99.0), None);
+        assert_eq!(latency.to_string(), "no thinks yet");
+
+        for _ in 0..99 {
+            latency.record(25_000);
+        }
+        latency.record(900_000);
+        assert_eq!(latency.min_nanos(), Some(25_000));
+        assert_eq!(latency.max_nanos(), Some(900_000));
+        assert_eq!(latency.avg_nanos(), Some(33_750));
+        // The 99th call is still a fast one, the slow outlier is the 100th
+        assert_eq!(latency.percentile_nanos(99.0), Some(30_000));
+        assert_eq!(latency.percentile_nanos(100.0), Some(900_000));
+
+        // Calls over the histogram's range land in the last bucket
+        latency.record(10 * MAX_ANT_PROCESSING_TIME as u64);
+        assert_eq!(
+            latency.percentile_nanos(100.0),
+            Some(10 * MAX_ANT_PROCESSING_TIME as u64)
+        );
+        assert_eq!(
+            latency.to_string(),
+            "min 25 / avg 181 / p99 910 / max 15000 µs"
+        );
+    }
+
+    #[test]
+    fn test_think_latency_range_follows_the_budget() {
+        // 50 calls at 5 ms and 50 at 9 ms, past the default budget's range
+        let record = |latency: &mut ThinkLatency| {
+            for nanos in [5_000_000, 9_000_000] {
+                for _ in 0..50 {
+                    latency.record(nanos);
+                }
+            }
+        };
+        let mut default = ThinkLatency::default();
+        record(&mut default);
+        assert_eq!(default.percentile_nanos(50.0), Some(9_000_000));
+
+        // A 15 ms budget has 100 µs buckets
+        let mut slow = ThinkLatency::new(15_000_000);
+        record(&mut slow);
+        assert_eq!(slow.percentile_nanos(50.0), Some(5_100_000));
+
+        // A reloaded budget moves the recorded calls to the new buckets
+        let mut reloaded = ThinkLatency::default();
+        reloaded.record(1_000_000);
+        reloaded.record(2_000_000);
+        reloaded.set_budget(15_000_000);
+        assert_eq!(reloaded.percentile_nanos(50.0), Some(1_100_000));
+    }
+}
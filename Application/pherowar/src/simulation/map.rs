@@ -1,15 +1,22 @@
-use crate::config::MAPS_DIR;
+use crate::config::{AUTOSAVE_MAP_NAME, MAPS_DIR};
+use crate::editor::symmetry_mode::SymmetryMode;
 use crate::simulation::ant::AntRef;
+use ahash::{AHashMap, AHashSet};
 use bincode::{decode_from_slice, encode_to_vec};
 use bincode_derive::{Decode, Encode};
 use macroquad::math::Vec2;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use smallvec::SmallVec;
 use std::fs;
 use std::io::{self, Write};
 use std::path::Path;
 
-use super::{DEFAULT_FOOD_AMOUNT, RaycastCache};
+use super::map_thumbnail::THUMBNAIL_EXTENSION;
+use super::raycast::trace_ray;
+use super::teleporter::TeleporterPair;
+use super::waypoint::MapWaypoint;
+use super::zone::{Zone, ZoneCell};
+use super::{DEFAULT_FOOD_AMOUNT, RaycastCache, SENSE_MAX_DISTANCE};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Encode, Decode)]
 pub enum Terrain {
@@ -20,6 +27,23 @@ pub enum Terrain {
     PlaceholderColony,
 }
 
+impl Terrain {
+    /// Whether two terrains are the same kind of tile, ignoring food amounts and nest owners
+    fn same_kind(&self, other: &Terrain) -> bool {
+        let normalize = |terrain: &Terrain| match terrain {
+            Terrain::Nest(_) => std::mem::discriminant(&Terrain::PlaceholderColony),
+            _ => std::mem::discriminant(terrain),
+        };
+        normalize(self) == normalize(other)
+    }
+
+    /// Whether this terrain blocks line of sight for raycasts
+    #[inline(always)]
+    pub fn blocks_sight(&self) -> bool {
+        matches!(self, Terrain::Wall)
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, Encode, Decode)]
 pub struct Tile {
     pub terrain: Terrain,
@@ -33,14 +57,28 @@ impl Default for Tile {
     }
 }
 
+/// Ants registered in a single cell. Most occupied cells hold only a few ants.
+pub type CellAnts = SmallVec<[AntRef; 4]>;
+
 pub struct GameMap {
     pub width: u32,
     pub height: u32,
     tiles: Vec<Vec<Tile>>,
     pub placeholder_colony_locations: Vec<Vec2>,
-    pub ants_in_cell: Vec<Vec<HashSet<AntRef>>>,
+    /// Linked entrance/exit cells placed with the editor's teleporter tool
+    pub(super) teleporters: Vec<TeleporterPair>,
+    /// Zone of each cell, row by row
+    pub(super) zones: Vec<Zone>,
+    /// Named cells authored with the editor's waypoint tool, handed to brains
+    pub(super) waypoints: Vec<MapWaypoint>,
+    /// Sparse spatial index of ants, keyed by cell (x, y). Only occupied cells have an entry.
+    ants_in_cell: AHashMap<(u32, u32), CellAnts>,
+    /// Ants currently carrying food, so sensing ants can tell carriers apart
+    food_carriers: AHashSet<AntRef>,
     pub loaded_map_name: Option<String>,
     pub rc_cache: RaycastCache,
+    /// Cells walled by sudden death, with their terrain before, restored on a reset
+    pub(super) sudden_death_cells: Vec<(usize, usize, Terrain)>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Encode, Decode)]
@@ -48,6 +86,97 @@ pub struct SerializedMap {
     pub width: u32,
     pub height: u32,
     pub tiles: Vec<Vec<Tile>>,
+    pub teleporters: Vec<TeleporterPair>,
+    pub zones: Vec<ZoneCell>,
+    pub waypoints: Vec<MapWaypoint>,
+}
+
+/// Map file format from before waypoints, still accepted when loading.
+#[derive(Decode)]
+struct PreWaypointSerializedMap {
+    width: u32,
+    height: u32,
+    tiles: Vec<Vec<Tile>>,
+    teleporters: Vec<TeleporterPair>,
+    zones: Vec<ZoneCell>,
+}
+
+impl From<PreWaypointSerializedMap> for SerializedMap {
+    fn from(legacy: PreWaypointSerializedMap) -> Self {
+        SerializedMap {
+            width: legacy.width,
+            height: legacy.height,
+            tiles: legacy.tiles,
+            teleporters: legacy.teleporters,
+            zones: legacy.zones,
+            waypoints: Vec::new(),
+        }
+    }
+}
+
+/// Map file format from before zones, still accepted when loading.
+#[derive(Decode)]
+struct PreZoneSerializedMap {
+    width: u32,
+    height: u32,
+    tiles: Vec<Vec<Tile>>,
+    teleporters: Vec<TeleporterPair>,
+}
+
+impl From<PreZoneSerializedMap> for SerializedMap {
+    fn from(legacy: PreZoneSerializedMap) -> Self {
+        SerializedMap {
+            width: legacy.width,
+            height: legacy.height,
+            tiles: legacy.tiles,
+            teleporters: legacy.teleporters,
+            zones: Vec::new(),
+            waypoints: Vec::new(),
+        }
+    }
+}
+
+/// Map file format from before teleporters, still accepted when loading.
+#[derive(Decode)]
+struct LegacySerializedMap {
+    width: u32,
+    height: u32,
+    tiles: Vec<Vec<Tile>>,
+}
+
+impl From<LegacySerializedMap> for SerializedMap {
+    fn from(legacy: LegacySerializedMap) -> Self {
+        SerializedMap {
+            width: legacy.width,
+            height: legacy.height,
+            tiles: legacy.tiles,
+            teleporters: Vec::new(),
+            zones: Vec::new(),
+            waypoints: Vec::new(),
+        }
+    }
+}
+
+impl SerializedMap {
+    /// Decodes a map file, in the current or a legacy format.
+    pub fn decode(data: &[u8]) -> io::Result<Self> {
+        let config = bincode::config::standard();
+        if let Ok((serialized, _len)) = decode_from_slice::<SerializedMap, _>(data, config) {
+            return Ok(serialized);
+        }
+        // Maps saved before waypoints end right after the zones
+        if let Ok((legacy, _len)) = decode_from_slice::<PreWaypointSerializedMap, _>(data, config) {
+            return Ok(SerializedMap::from(legacy));
+        }
+        // Maps saved before zones end right after the teleporter pairs
+        if let Ok((legacy, _len)) = decode_from_slice::<PreZoneSerializedMap, _>(data, config) {
+            return Ok(SerializedMap::from(legacy));
+        }
+        // Maps saved before teleporters end right after the tiles
+        decode_from_slice::<LegacySerializedMap, _>(data, config)
+            .map(|(legacy, _len)| SerializedMap::from(legacy))
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+    }
 }
 
 impl From<&GameMap> for SerializedMap {
@@ -73,6 +202,9 @@ impl From<&GameMap> for SerializedMap {
             width: map.width,
             height: map.height,
             tiles,
+            teleporters: map.teleporters.clone(),
+            zones: map.zone_cells(),
+            waypoints: map.waypoints.clone(),
         }
     }
 }
@@ -105,10 +237,42 @@ impl From<SerializedMap> for GameMap {
             }
         }
 
+        for pair in smap.teleporters {
+            let entrance = (pair.entrance.0 as usize, pair.entrance.1 as usize);
+            let exit = (pair.exit.0 as usize, pair.exit.1 as usize);
+            if !game_map.add_teleporter(entrance, exit) {
+                eprintln!(
+                    "Warning: Dropped invalid teleporter pair {:?} -> {:?} in loaded map data.",
+                    pair.entrance, pair.exit
+                );
+            }
+        }
+
+        for cell in smap.zones {
+            if cell.x < game_map.width && cell.y < game_map.height {
+                game_map.set_zone_at(cell.x as usize, cell.y as usize, cell.zone);
+            } else {
+                eprintln!(
+                    "Warning: Dropped invalid zone cell ({}, {}) in loaded map data.",
+                    cell.x, cell.y
+                );
+            }
+        }
+
+        for waypoint in smap.waypoints {
+            let (x, y) = (waypoint.cell.0 as usize, waypoint.cell.1 as usize);
+            if !game_map.add_waypoint(&waypoint.name, x, y) {
+                eprintln!(
+                    "Warning: Dropped invalid waypoint '{}' at {:?} in loaded map data.",
+                    waypoint.name, waypoint.cell
+                );
+            }
+        }
+
         game_map.rc_cache.clear();
         game_map.rc_cache.recompute_all_cache(&|gx, gy| {
             if gx < game_map.width as usize && gy < game_map.height as usize {
-                matches!(game_map.tiles[gy][gx].terrain, Terrain::Wall)
+                game_map.tiles[gy][gx].terrain.blocks_sight()
             } else {
                 true // Treat out-of-bounds as a wall for raycasting purposes
             }
@@ -126,9 +290,14 @@ impl GameMap {
             height,
             tiles: vec![vec![Tile::default(); width as usize]; height as usize],
             placeholder_colony_locations: Vec::new(), // Initialize new field
-            ants_in_cell: vec![vec![HashSet::new(); width as usize]; height as usize],
+            teleporters: Vec::new(),
+            zones: vec![Zone::None; (width * height) as usize],
+            waypoints: Vec::new(),
+            ants_in_cell: AHashMap::new(),
+            food_carriers: AHashSet::new(),
             loaded_map_name: None,
             rc_cache: RaycastCache::new(width as usize, height as usize),
+            sudden_death_cells: Vec::new(),
         }
     }
 
@@ -147,6 +316,21 @@ impl GameMap {
         }
     }
 
+    /// Adds food to a cell if it is empty or already food. Returns true if food was dropped.
+    pub fn drop_food_at(&mut self, x: usize, y: usize, amount: u32) -> bool {
+        if amount == 0 {
+            return false;
+        }
+        match self.get_terrain_at(x, y) {
+            Some(Terrain::Empty) => self.place_food_at(x, y, amount),
+            Some(&Terrain::Food(current)) => {
+                self.place_food_at(x, y, current.saturating_add(amount))
+            }
+            _ => return false,
+        }
+        true
+    }
+
     #[inline(always)]
     pub fn place_colony_at(&mut self, x: usize, y: usize, colony_id: u32) {
         if x < self.width as usize && y < self.height as usize {
@@ -174,24 +358,40 @@ impl GameMap {
         if x < self.width as usize && y < self.height as usize {
             self.tiles[y][x].terrain = Terrain::Wall;
             self.rc_cache.invalidate_area_around(x, y);
+            // A wall over a teleporter end breaks its pair, and removes a waypoint
+            self.remove_teleporter_at(x, y);
+            self.remove_waypoint_at(x, y);
             return true;
         }
         false
     }
 
+    /// Turns the outermost ring of cells into walls.
+    pub fn add_border_walls(&mut self) {
+        let (width, height) = (self.width as usize, self.height as usize);
+        for x in 0..width {
+            self.place_wall_at(x, 0);
+            self.place_wall_at(x, height.saturating_sub(1));
+        }
+        for y in 0..height {
+            self.place_wall_at(0, y);
+            self.place_wall_at(width.saturating_sub(1), y);
+        }
+    }
+
     #[inline(always)]
     pub fn remove_terrain_at(&mut self, x: usize, y: usize) {
         if x < self.width as usize && y < self.height as usize {
-            let was_wall = matches!(self.tiles[y][x].terrain, Terrain::Wall);
+            let blocked_sight = self.tiles[y][x].terrain.blocks_sight();
             self.tiles[y][x].terrain = Terrain::Empty;
-            // If we removed a wall, invalidate raycast cache around this position
-            if was_wall {
+            // If we removed a sight-blocking tile, invalidate raycast cache around this position
+            if blocked_sight {
                 self.rc_cache.invalidate_area_around(x, y);
 
-                // This cell itself is no longer a wall, so its own outgoing rays need recomputation.
+                // This cell itself no longer blocks sight, so its own outgoing rays need recomputation.
                 let is_wall_check_fn = |gx: usize, gy: usize| {
                     if gx < self.width as usize && gy < self.height as usize {
-                        matches!(self.tiles[gy][gx].terrain, Terrain::Wall)
+                        self.tiles[gy][gx].terrain.blocks_sight()
                     } else {
                         true
                     }
@@ -236,7 +436,13 @@ impl GameMap {
             && (cell_x as usize) < self.width as usize
             && (cell_y as usize) < self.height as usize
         {
-            self.ants_in_cell[cell_y as usize][cell_x as usize].insert(ant_ref.clone());
+            let cell_ants = self
+                .ants_in_cell
+                .entry((cell_x as u32, cell_y as u32))
+                .or_default();
+            if !cell_ants.contains(ant_ref) {
+                cell_ants.push(ant_ref.clone());
+            }
         } else {
             eprintln!(
                 "Warning: Ant {:?} attempted to register at out-of-bounds pos ({:.2},{:.2}). Not registered.",
@@ -256,7 +462,18 @@ impl GameMap {
             && (cell_x as usize) < self.width as usize
             && (cell_y as usize) < self.height as usize
         {
-            return self.ants_in_cell[cell_y as usize][cell_x as usize].remove(ant_ref);
+            let cell = (cell_x as u32, cell_y as u32);
+            let Some(cell_ants) = self.ants_in_cell.get_mut(&cell) else {
+                return false;
+            };
+            let Some(index) = cell_ants.iter().position(|r| r == ant_ref) else {
+                return false;
+            };
+            cell_ants.swap_remove(index);
+            if cell_ants.is_empty() {
+                self.ants_in_cell.remove(&cell);
+            }
+            return true;
         }
         eprintln!(
             "Warning: Ant {:?} attempted to unregister from out-of-bounds pos ({:.2},{:.2}). Not unregistered.",
@@ -267,17 +484,42 @@ impl GameMap {
 
     /// Save the map
     pub fn save_map<P: AsRef<Path>>(&mut self, name: P) -> io::Result<()> {
+        self.write_map(name.as_ref())?;
+        self.loaded_map_name = Some(name.as_ref().to_string_lossy().to_string());
+        Ok(())
+    }
+
+    /// Save the map to the autosave file, keeping its name
+    pub fn autosave(&self) -> io::Result<()> {
+        self.write_map(AUTOSAVE_MAP_NAME)
+    }
+
+    /// Whether an autosave is waiting to be recovered
+    pub fn autosave_exists() -> bool {
+        Path::new(MAPS_DIR).join(AUTOSAVE_MAP_NAME).is_file()
+    }
+
+    /// Delete the autosave file, if any
+    pub fn remove_autosave() {
+        let path = Path::new(MAPS_DIR).join(AUTOSAVE_MAP_NAME);
+        if let Err(e) = fs::remove_file(&path)
+            && e.kind() != io::ErrorKind::NotFound
+        {
+            eprintln!("Warning: Failed to remove {}: {}", path.display(), e);
+        }
+    }
+
+    fn write_map<P: AsRef<Path>>(&self, name: P) -> io::Result<()> {
         let dir = std::path::Path::new(MAPS_DIR);
         if !dir.exists() {
             fs::create_dir_all(&dir)?;
         }
         let file_path = dir.join(name.as_ref());
-        let serialized = SerializedMap::from(&*self); // Changed to pass an immutable reference
+        let serialized = SerializedMap::from(self);
         let data = encode_to_vec(&serialized, bincode::config::standard())
             .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
         let mut file = fs::File::create(file_path)?;
         file.write_all(&data)?;
-        self.loaded_map_name = Some(name.as_ref().to_string_lossy().to_string());
         Ok(())
     }
 
@@ -286,11 +528,9 @@ impl GameMap {
         let name_str = name.as_ref().to_string_lossy().to_string();
         let file_path = std::path::Path::new(MAPS_DIR).join(&name_str);
         let data = fs::read(file_path)?;
-        let (serialized, _len): (SerializedMap, _) =
-            decode_from_slice(&data, bincode::config::standard())
-                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        let serialized = SerializedMap::decode(&data)?;
         let mut map: GameMap = serialized.into();
-        println!("Loaded map {}", name_str);
+        eprintln!("Loaded map {}", name_str);
         map.loaded_map_name = Some(name_str);
         Ok(map)
     }
@@ -304,6 +544,18 @@ impl GameMap {
         let mut maps = vec![];
         for entry in fs::read_dir(maps_dir_path)? {
             let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            // Skip cached thumbnails stored next to the maps
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) == Some(THUMBNAIL_EXTENSION) {
+                continue;
+            }
+            // and the editor's autosave
+            if entry.file_name() == AUTOSAVE_MAP_NAME {
+                continue;
+            }
             if let Some(name) = entry.file_name().to_str() {
                 maps.push(name.to_string());
             }
@@ -327,38 +579,273 @@ impl GameMap {
         }
     }
 
-    /// Get enemy ant at the given coordinates (x, y) that is not from the given colony
-    pub fn get_enemy_ant_at(&self, x: usize, y: usize, friendly_colony_id: u32) -> Option<AntRef> {
-        if let Some(ants_set) = self.ants_in_cell.get(y).and_then(|row| row.get(x)) {
-            for ant_ref in ants_set {
-                if ant_ref.colony_id != friendly_colony_id {
-                    return Some(ant_ref.clone());
+    /// Cell nearest to (`x`, `y`) that is not a wall, excluding (`x`, `y`) itself.
+    /// Searches rings of growing distance; `None` if the whole map is walled.
+    pub fn nearest_free_cell(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        let (width, height) = (self.width as isize, self.height as isize);
+        let (cx, cy) = (x as isize, y as isize);
+        for ring in 1..width.max(height) {
+            let mut nearest: Option<((usize, usize), isize)> = None;
+            for ny in cy - ring..=cy + ring {
+                for nx in cx - ring..=cx + ring {
+                    let on_ring = (nx - cx).abs() == ring || (ny - cy).abs() == ring;
+                    if !on_ring || nx < 0 || ny < 0 || nx >= width || ny >= height {
+                        continue;
+                    }
+                    if self.get_terrain_at(nx as usize, ny as usize) == Some(&Terrain::Wall) {
+                        continue;
+                    }
+                    let dist_sq = (nx - cx).pow(2) + (ny - cy).pow(2);
+                    if nearest.is_none_or(|(_, best)| dist_sq < best) {
+                        nearest = Some(((nx as usize, ny as usize), dist_sq));
+                    }
                 }
             }
+            if let Some((cell, _)) = nearest {
+                return Some(cell);
+            }
         }
         None
     }
 
+    /// Food cell nearest to `pos` whose center lies within `radius`. The cell under `pos`
+    /// wins whenever it holds food, so a radius of 0 only looks under the position.
+    pub fn nearest_food_within(&self, pos: Vec2, radius: f32) -> Option<(usize, usize)> {
+        let is_food = |x: isize, y: isize| {
+            x >= 0
+                && y >= 0
+                && matches!(
+                    self.get_terrain_at(x as usize, y as usize),
+                    Some(Terrain::Food(_))
+                )
+        };
+        let (cx, cy) = (pos.x.floor() as isize, pos.y.floor() as isize);
+        if is_food(cx, cy) {
+            return Some((cx as usize, cy as usize));
+        }
+
+        let reach = radius.ceil() as isize;
+        let mut nearest = None;
+        let mut nearest_dist_sq = radius * radius;
+        for y in cy - reach..=cy + reach {
+            for x in cx - reach..=cx + reach {
+                if !is_food(x, y) {
+                    continue;
+                }
+                let dist_sq = pos.distance_squared(Vec2::new(x as f32 + 0.5, y as f32 + 0.5));
+                if dist_sq <= nearest_dist_sq {
+                    nearest_dist_sq = dist_sq;
+                    nearest = Some((x as usize, y as usize));
+                }
+            }
+        }
+        nearest
+    }
+
+    /// Fraction of mirrored tiles (0.0 to 1.0) that match their authoritative tile under `mode`.
+    pub fn symmetry_score(&self, mode: SymmetryMode) -> f32 {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let mut mirrored = 0;
+        let mut matching = 0;
+        for y in 0..height {
+            for x in 0..width {
+                let (sx, sy) = mode.source_cell(x, y, width, height);
+                if (sx, sy) == (x, y) {
+                    continue;
+                }
+                mirrored += 1;
+                if self.tiles[y][x]
+                    .terrain
+                    .same_kind(&self.tiles[sy][sx].terrain)
+                {
+                    matching += 1;
+                }
+            }
+        }
+        if mirrored == 0 {
+            1.0
+        } else {
+            matching as f32 / mirrored as f32
+        }
+    }
+
+    /// Detects the symmetry mode the map is closest to, with its score.
+    /// On ties, stricter symmetries are preferred.
+    pub fn detect_symmetry(&self) -> (SymmetryMode, f32) {
+        let candidates = [
+            SymmetryMode::MirrorBoth,
+            SymmetryMode::Center,
+            SymmetryMode::MirrorVertical,
+            SymmetryMode::MirrorHorizontal,
+        ];
+        let mut best = (SymmetryMode::None, 0.0);
+        for mode in candidates {
+            let score = self.symmetry_score(mode);
+            if score > best.1 {
+                best = (mode, score);
+            }
+        }
+        best
+    }
+
+    /// Returns a copy of the map with its top/left part mirrored onto the rest under `mode`.
+    /// Like a saved map, nests become placeholders and food is reset. Teleporter pairs and
+    /// waypoints are mirrored along with the tiles.
+    pub fn mirrored(&self, mode: SymmetryMode) -> GameMap {
+        let mut serialized = SerializedMap::from(self);
+        let (width, height) = (self.width as usize, self.height as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let (sx, sy) = mode.source_cell(x, y, width, height);
+                if (sx, sy) != (x, y) {
+                    serialized.tiles[y][x] = serialized.tiles[sy][sx].clone();
+                }
+            }
+        }
+        // Teleporter ends and waypoints on the overwritten part go, those of the authoritative
+        // part are copied onto each of its mirror images below
+        let is_authoritative = |(x, y): (u32, u32)| {
+            let cell = (x as usize, y as usize);
+            mode.source_cell(cell.0, cell.1, width, height) == cell
+        };
+        serialized
+            .teleporters
+            .retain(|pair| is_authoritative(pair.entrance) && is_authoritative(pair.exit));
+        serialized
+            .waypoints
+            .retain(|waypoint| is_authoritative(waypoint.cell));
+        let mut mirrored_map = GameMap::from(serialized);
+        for y in 0..height {
+            for x in 0..width {
+                let (sx, sy) = mode.source_cell(x, y, width, height);
+                mirrored_map.set_zone_at(x, y, self.zone_at(sx, sy));
+            }
+        }
+        let mirror_cells =
+            |(x, y): (u32, u32)| mode.mirror_cells(x as usize, y as usize, width, height);
+        for pair in mirrored_map.teleporters.clone() {
+            let images = mirror_cells(pair.entrance)
+                .into_iter()
+                .zip(mirror_cells(pair.exit));
+            for (entrance, exit) in images {
+                // Fails on an image sharing a cell with an existing end, e.g. on a mirror axis
+                mirrored_map.add_teleporter(entrance, exit);
+            }
+        }
+        for waypoint in mirrored_map.waypoints.clone() {
+            for (x, y) in mirror_cells(waypoint.cell) {
+                mirrored_map.add_waypoint(&waypoint.name, x, y);
+            }
+        }
+        mirrored_map.loaded_map_name = self.loaded_map_name.clone();
+        mirrored_map
+    }
+
+    /// Get the ants registered in the cell at the given coordinates (x, y)
+    pub fn ants_at(&self, x: usize, y: usize) -> &[AntRef] {
+        if x >= self.width as usize || y >= self.height as usize {
+            return &[];
+        }
+        self.ants_in_cell
+            .get(&(x as u32, y as u32))
+            .map_or(&[], |cell_ants| cell_ants.as_slice())
+    }
+
+    /// Get enemy ant at the given coordinates (x, y) that is not from the given colony.
+    /// Among several, the one with the lowest key, whatever order the ants entered the cell in.
+    pub fn get_enemy_ant_at(&self, x: usize, y: usize, friendly_colony_id: u32) -> Option<AntRef> {
+        self.ants_at(x, y)
+            .iter()
+            .filter(|ant_ref| ant_ref.colony_id != friendly_colony_id)
+            .min_by_key(|ant_ref| (ant_ref.key, ant_ref.colony_id))
+            .cloned()
+    }
+
+    /// Get a friendly ant at the given coordinates (x, y) from the given colony, other than `exclude`.
+    /// Among several, the one with the lowest key.
+    pub fn get_friendly_ant_at(
+        &self,
+        x: usize,
+        y: usize,
+        friendly_colony_id: u32,
+        exclude: &AntRef,
+    ) -> Option<AntRef> {
+        self.ants_at(x, y)
+            .iter()
+            .filter(|ant_ref| ant_ref.colony_id == friendly_colony_id && *ant_ref != exclude)
+            .min_by_key(|ant_ref| ant_ref.key)
+            .cloned()
+    }
+
+    /// Get an ant at the given coordinates (x, y) that `attacker` may fight, among those
+    /// passing `can_fight`. Enemies are preferred; same-colony ants are only returned with
+    /// friendly fire. Among several, the one with the lowest key.
+    pub fn get_attackable_ant_at(
+        &self,
+        x: usize,
+        y: usize,
+        attacker: &AntRef,
+        friendly_fire: bool,
+        can_fight: impl Fn(&AntRef) -> bool,
+    ) -> Option<AntRef> {
+        let candidates = self
+            .ants_at(x, y)
+            .iter()
+            .filter(|ant_ref| can_fight(ant_ref));
+        candidates
+            .clone()
+            .filter(|ant_ref| ant_ref.colony_id != attacker.colony_id)
+            .min_by_key(|ant_ref| (ant_ref.key, ant_ref.colony_id))
+            .or_else(|| {
+                if friendly_fire {
+                    candidates
+                        .filter(|ant_ref| {
+                            ant_ref.colony_id == attacker.colony_id && *ant_ref != attacker
+                        })
+                        .min_by_key(|ant_ref| ant_ref.key)
+                } else {
+                    None
+                }
+            })
+            .cloned()
+    }
+
+    /// Record whether the given ant is carrying food. Only `Ant::set_carrying_food` calls this,
+    /// so the record never drifts from the ant.
+    pub(super) fn set_carrying_food(&mut self, ant_ref: &AntRef, carrying: bool) {
+        if carrying {
+            self.food_carriers.insert(ant_ref.clone());
+        } else {
+            self.food_carriers.remove(ant_ref);
+        }
+    }
+
+    /// Whether the given ant is carrying food
+    pub fn is_carrying_food(&self, ant_ref: &AntRef) -> bool {
+        self.food_carriers.contains(ant_ref)
+    }
+
     /// Only reset the ants data
     pub fn soft_reset(&mut self) {
-        self.ants_in_cell
-            .iter_mut()
-            .for_each(|row| row.iter_mut().for_each(|cell_set| cell_set.clear()));
+        self.ants_in_cell.clear();
+        self.food_carriers.clear();
     }
 
     /// Remove a colony from the map
     /// This will remove all ants from the map and clear the pheromone channels.
     pub fn remove_colony_ants(&mut self, colony_id: u32) {
-        // Clear all ants of this colony from the map
-        for y in 0..self.height as usize {
-            for x in 0..self.width as usize {
-                self.ants_in_cell[y][x].retain(|ant_ref| ant_ref.colony_id != colony_id);
-            }
-        }
+        // Clear all ants of this colony from the map, dropping cells left empty
+        self.ants_in_cell.retain(|_, cell_ants| {
+            cell_ants.retain(|ant_ref| ant_ref.colony_id != colony_id);
+            !cell_ants.is_empty()
+        });
+        self.food_carriers
+            .retain(|ant_ref| ant_ref.colony_id != colony_id);
     }
 
     /// Perform a raycast from the given position at the given angle.
-    /// The ray is traced up to `SENSE_MAX_DISTANCE` by the underlying cache.
+    /// The ray is traced up to `SENSE_MAX_DISTANCE` by the underlying cache,
+    /// and past it without caching when `max_distance_for_query` is longer.
     /// This function then interprets the result based on the provided `max_distance_for_query`.
     ///
     /// Returns:
@@ -378,7 +865,7 @@ impl GameMap {
         // This is used both for an early exit check and for the cache query.
         let is_wall_fn = |gx: usize, gy: usize| {
             if gx < self.width as usize && gy < self.height as usize {
-                matches!(self.tiles[gy][gx].terrain, Terrain::Wall)
+                self.tiles[gy][gx].terrain.blocks_sight()
             } else {
                 true // Treat out-of-bounds as a wall for raycasting purposes.
             }
@@ -396,7 +883,22 @@ impl GameMap {
             .rc_cache
             .get_distance_at_angle(&is_wall_fn, grid_x, grid_y, angle)
         {
-            Some(cached_distance_to_obstacle) => {
+            Some(mut cached_distance_to_obstacle) => {
+                // The cache sees no wall up to SENSE_MAX_DISTANCE; trace the rest of a longer query.
+                if cached_distance_to_obstacle.is_infinite()
+                    && max_distance_for_query > SENSE_MAX_DISTANCE
+                {
+                    cached_distance_to_obstacle = trace_ray(
+                        &is_wall_fn,
+                        self.width as usize,
+                        self.height as usize,
+                        grid_x,
+                        grid_y,
+                        angle,
+                        max_distance_for_query,
+                    );
+                }
+
                 // cached_distance_to_obstacle is the distance to a wall if found by cache (up to SENSE_MAX_DISTANCE),
                 // or f32::INFINITY if no wall was hit by the cache within its sensing range.
 
@@ -424,3 +926,381 @@ impl GameMap {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::ant::AntKey;
+    use slotmap::SlotMap;
+
+    fn ant_refs(count: usize, colony_id: u32) -> Vec<AntRef> {
+        let mut keys: SlotMap<AntKey, ()> = SlotMap::with_key();
+        (0..count)
+            .map(|_| AntRef {
+                key: keys.insert(()),
+                colony_id,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_border_walls_keep_the_inside_open() {
+        let mut map = GameMap::new(5, 4);
+        map.add_border_walls();
+        for y in 0..4 {
+            for x in 0..5 {
+                let border = x == 0 || y == 0 || x == 4 || y == 3;
+                assert_eq!(map.get_terrain_at(x, y) == Some(&Terrain::Wall), border);
+            }
+        }
+        assert!(!map.place_nest_placeholder_at(0, 2));
+        assert!(map.place_nest_placeholder_at(1, 2));
+    }
+
+    #[test]
+    fn test_mirrored_map_is_symmetric() {
+        let mut map = GameMap::new(9, 6);
+        map.place_wall_at(1, 1);
+        map.place_wall_at(2, 4);
+        map.place_food_at(3, 0, 10);
+        map.place_nest_placeholder_at(2, 2);
+        assert!(map.add_teleporter((0, 0), (3, 2)));
+        assert!(map.add_waypoint("gate", 4, 0));
+        // Noise on the non-authoritative side, overwritten by mirroring
+        map.place_wall_at(7, 2);
+        assert!(map.add_teleporter((6, 4), (7, 5)));
+        assert!(map.add_waypoint("noise", 8, 5));
+
+        for mode in [
+            SymmetryMode::MirrorVertical,
+            SymmetryMode::MirrorHorizontal,
+            SymmetryMode::MirrorBoth,
+            SymmetryMode::Center,
+        ] {
+            let mirrored = map.mirrored(mode);
+            assert_eq!(mirrored.symmetry_score(mode), 1.0);
+            let expected_placeholders = match mode {
+                SymmetryMode::MirrorBoth => 4,
+                _ => 2,
+            };
+            assert_eq!(
+                mirrored.placeholder_colony_locations.len(),
+                expected_placeholders
+            );
+            // One teleporter pair per nest, as for the nests
+            assert_eq!(mirrored.teleporters().len(), expected_placeholders);
+            assert!(mirrored.teleporter_at(6, 4).is_none());
+            assert!(mirrored.waypoint_at(8, 5).is_none());
+        }
+
+        let mirrored = map.mirrored(SymmetryMode::MirrorHorizontal);
+        assert_eq!(mirrored.get_terrain_at(7, 1), Some(&Terrain::Wall));
+        assert_eq!(mirrored.get_terrain_at(7, 2), Some(&Terrain::Empty));
+        assert_eq!(mirrored.detect_symmetry().0, SymmetryMode::MirrorHorizontal);
+        let pair = |entrance, exit| TeleporterPair { entrance, exit };
+        assert_eq!(
+            mirrored.teleporters(),
+            [pair((0, 0), (3, 2)), pair((8, 0), (5, 2))]
+        );
+        // On the mirror axis, the waypoint is its own image
+        let cells: Vec<(u32, u32)> = mirrored.waypoints().iter().map(|w| w.cell).collect();
+        assert_eq!(cells, [(4, 0)]);
+
+        let mirrored = map.mirrored(SymmetryMode::Center);
+        assert_eq!(
+            mirrored.teleporters(),
+            [pair((0, 0), (3, 2)), pair((8, 5), (5, 3))]
+        );
+        let waypoint = mirrored.waypoint_at(4, 5).unwrap();
+        assert_eq!(waypoint.name, "gate");
+        assert_eq!(mirrored.waypoints().len(), 2);
+    }
+
+    #[test]
+    fn test_register_and_unregister_ants_in_cell() {
+        let mut map = GameMap::new(16, 16);
+        let ants = ant_refs(3, 0);
+        let pos = Vec2::new(3.5, 7.5);
+
+        for ant_ref in &ants {
+            map.register_ant_in_cell(ant_ref, pos);
+        }
+        // Registering twice must not duplicate the ant
+        map.register_ant_in_cell(&ants[0], pos);
+        assert_eq!(map.ants_at(3, 7).len(), 3);
+        assert!(map.ants_at(4, 7).is_empty());
+
+        assert!(map.unregister_ant_from_cell(&ants[1], pos));
+        assert!(!map.unregister_ant_from_cell(&ants[1], pos));
+        assert_eq!(map.ants_at(3, 7).len(), 2);
+
+        map.soft_reset();
+        assert!(map.ants_at(3, 7).is_empty());
+        assert!(map.ants_in_cell.is_empty());
+    }
+
+    /// Compares the sparse ant index with the dense per-cell grid it replaced.
+    /// Run with `cargo test --release -- --ignored --nocapture bench_sparse_ant_index`.
+    #[test]
+    #[ignore]
+    fn bench_sparse_ant_index() {
+        use crate::simulation::{SENSE_MAX_DISTANCE, SeededRng};
+        use std::collections::HashSet;
+        use std::mem::size_of;
+        use std::time::Instant;
+
+        const SIZE: usize = 1024;
+        const LOOKUPS_PER_ANT: usize = 20;
+        let ants = [ant_refs(25_000, 0), ant_refs(25_000, 1)].concat();
+        let mut rng = SeededRng::new(7);
+        let mut positions = Vec::new();
+        let mut probes = Vec::new();
+        for _ in &ants {
+            let pos = Vec2::new(
+                rng.gen_range(0.0, SIZE as f32),
+                rng.gen_range(0.0, SIZE as f32),
+            );
+            for _ in 0..LOOKUPS_PER_ANT {
+                let x = pos.x + rng.gen_range(-SENSE_MAX_DISTANCE, SENSE_MAX_DISTANCE);
+                let y = pos.y + rng.gen_range(-SENSE_MAX_DISTANCE, SENSE_MAX_DISTANCE);
+                probes.push((
+                    x.clamp(0.0, SIZE as f32 - 1.0) as usize,
+                    y.clamp(0.0, SIZE as f32 - 1.0) as usize,
+                ));
+            }
+            positions.push(pos);
+        }
+
+        let mut dense = vec![vec![HashSet::<AntRef>::new(); SIZE]; SIZE];
+        let start = Instant::now();
+        for (ant_ref, pos) in ants.iter().zip(&positions) {
+            dense[pos.y as usize][pos.x as usize].insert(ant_ref.clone());
+        }
+        let dense_build = start.elapsed();
+        let start = Instant::now();
+        let dense_found = probes
+            .iter()
+            .filter(|&&(x, y)| dense[y][x].iter().any(|r| r.colony_id != 0))
+            .count();
+        let dense_lookups = start.elapsed();
+        let dense_bytes = SIZE * SIZE * size_of::<HashSet<AntRef>>()
+            + dense
+                .iter()
+                .flatten()
+                .map(|set| set.capacity() * size_of::<AntRef>())
+                .sum::<usize>();
+
+        let mut map = GameMap::new(SIZE as u32, SIZE as u32);
+        let start = Instant::now();
+        for (ant_ref, pos) in ants.iter().zip(&positions) {
+            map.register_ant_in_cell(ant_ref, *pos);
+        }
+        let sparse_build = start.elapsed();
+        let start = Instant::now();
+        let sparse_found = probes
+            .iter()
+            .filter(|&&(x, y)| map.get_enemy_ant_at(x, y, 0).is_some())
+            .count();
+        let sparse_lookups = start.elapsed();
+        let sparse_bytes = map.ants_in_cell.capacity() * size_of::<((u32, u32), CellAnts)>()
+            + map
+                .ants_in_cell
+                .values()
+                .filter(|cell_ants| cell_ants.spilled())
+                .map(|cell_ants| cell_ants.capacity() * size_of::<AntRef>())
+                .sum::<usize>();
+
+        println!(
+            "{SIZE}x{SIZE} map, {} ants, {} lookups",
+            ants.len(),
+            probes.len()
+        );
+        println!(
+            "dense grid:   ~{:.1} MB, built in {dense_build:?}, looked up in {dense_lookups:?}",
+            dense_bytes as f64 / 1e6
+        );
+        println!(
+            "sparse index: ~{:.1} MB, built in {sparse_build:?}, looked up in {sparse_lookups:?}",
+            sparse_bytes as f64 / 1e6
+        );
+        assert_eq!(sparse_found, dense_found);
+        assert!(sparse_bytes < dense_bytes);
+    }
+
+    #[test]
+    fn test_cell_ant_selection_ignores_registration_order() {
+        let enemies = [ant_refs(3, 1), ant_refs(3, 2)].concat();
+        let pos = Vec2::new(2.5, 2.5);
+        let mut selected = Vec::new();
+        for order in [[0, 1, 2, 3, 4, 5], [5, 3, 1, 4, 2, 0], [4, 0, 5, 2, 3, 1]] {
+            let mut map = GameMap::new(8, 8);
+            for i in order {
+                map.register_ant_in_cell(&enemies[i], pos);
+            }
+            // Swap removal reorders the cell
+            map.unregister_ant_from_cell(&enemies[order[0]], pos);
+            map.register_ant_in_cell(&enemies[order[0]], pos);
+            selected.push((
+                map.get_enemy_ant_at(2, 2, 0),
+                map.get_friendly_ant_at(2, 2, 1, &enemies[0]),
+            ));
+        }
+        assert_eq!(selected[0].0, Some(enemies[0].clone()));
+        assert_eq!(selected[0].1, Some(enemies[1].clone()));
+        assert!(selected.iter().all(|s| *s == selected[0]));
+    }
+
+    #[test]
+    fn test_remove_colony_ants_keeps_other_colonies() {
+        let mut map = GameMap::new(16, 16);
+        let friends = ant_refs(2, 0);
+        let enemies = ant_refs(2, 1);
+        map.register_ant_in_cell(&friends[0], Vec2::new(1.5, 1.5));
+        map.register_ant_in_cell(&enemies[0], Vec2::new(1.5, 1.5));
+        map.register_ant_in_cell(&friends[1], Vec2::new(5.5, 5.5));
+        map.register_ant_in_cell(&enemies[1], Vec2::new(9.5, 9.5));
+
+        assert_eq!(map.get_enemy_ant_at(1, 1, 0), Some(enemies[0].clone()));
+
+        map.remove_colony_ants(1);
+        assert_eq!(map.get_enemy_ant_at(1, 1, 0), None);
+        assert_eq!(map.ants_at(1, 1), &[friends[0].clone()]);
+        assert!(map.ants_at(9, 9).is_empty());
+        assert_eq!(map.ants_in_cell.len(), 2);
+    }
+
+    #[test]
+    fn test_allies_only_attackable_with_friendly_fire() {
+        let mut map = GameMap::new(16, 16);
+        let friends = ant_refs(2, 0);
+        let enemies = ant_refs(1, 1);
+        let pos = Vec2::new(4.5, 4.5);
+        map.register_ant_in_cell(&friends[0], pos);
+        map.register_ant_in_cell(&friends[1], pos);
+
+        // Allies are ignored by default, and an ant never targets itself
+        assert_eq!(
+            map.get_attackable_ant_at(4, 4, &friends[0], false, |_| true),
+            None
+        );
+        assert_eq!(
+            map.get_attackable_ant_at(4, 4, &friends[0], true, |_| true),
+            Some(friends[1].clone())
+        );
+        map.unregister_ant_from_cell(&friends[1], pos);
+        assert_eq!(
+            map.get_attackable_ant_at(4, 4, &friends[0], true, |_| true),
+            None
+        );
+
+        // Enemies are preferred over allies
+        map.register_ant_in_cell(&friends[1], pos);
+        map.register_ant_in_cell(&enemies[0], pos);
+        assert_eq!(
+            map.get_attackable_ant_at(4, 4, &friends[0], true, |_| true),
+            Some(enemies[0].clone())
+        );
+        // Ants failing `can_fight` are passed over, whatever their key
+        assert_eq!(
+            map.get_attackable_ant_at(4, 4, &friends[0], true, |ant_ref| *ant_ref != enemies[0]),
+            Some(friends[1].clone())
+        );
+    }
+
+    #[test]
+    fn test_drop_food_only_on_empty_or_food() {
+        let mut map = GameMap::new(8, 8);
+        // A dying carrier with several units leaves all of them
+        assert!(map.drop_food_at(1, 1, 3));
+        assert_eq!(map.get_terrain_at(1, 1), Some(&Terrain::Food(3)));
+        assert!(map.drop_food_at(1, 1, 2));
+        assert_eq!(map.get_terrain_at(1, 1), Some(&Terrain::Food(5)));
+
+        map.place_wall_at(2, 2);
+        assert!(!map.drop_food_at(2, 2, 3));
+        assert_eq!(map.get_terrain_at(2, 2), Some(&Terrain::Wall));
+        assert!(!map.drop_food_at(3, 3, 0));
+        assert_eq!(map.get_terrain_at(3, 3), Some(&Terrain::Empty));
+    }
+
+    #[test]
+    fn test_raycast_traces_walls_past_the_cache() {
+        let mut map = GameMap::new(40, 3);
+        map.place_wall_at(25, 1);
+        let start = Vec2::new(2.5, 1.5);
+        assert_eq!(
+            map.raycast_angle(start, 0.0, SENSE_MAX_DISTANCE),
+            (false, 10.0)
+        );
+        assert_eq!(map.raycast_angle(start, 0.0, 20.0), (false, 20.0));
+        assert_eq!(map.raycast_angle(start, 0.0, 30.0), (true, 22.5));
+    }
+
+    #[test]
+    fn test_nearest_free_cell() {
+        let mut map = GameMap::new(5, 5);
+        map.place_wall_at(2, 2);
+        map.place_wall_at(2, 1);
+        map.place_wall_at(1, 2);
+        // Straight neighbors come before diagonal ones
+        assert_eq!(map.nearest_free_cell(2, 2), Some((3, 2)));
+
+        let mut walled = GameMap::new(2, 2);
+        for (x, y) in [(0, 0), (1, 0), (0, 1)] {
+            walled.place_wall_at(x, y);
+        }
+        assert_eq!(walled.nearest_free_cell(0, 0), Some((1, 1)));
+        walled.place_wall_at(1, 1);
+        assert_eq!(walled.nearest_free_cell(0, 0), None);
+    }
+
+    #[test]
+    fn test_nearest_food_within() {
+        let mut map = GameMap::new(10, 10);
+        map.place_food_at(5, 3, 1);
+        map.place_food_at(3, 3, 1);
+        let pos = Vec2::new(4.3, 3.5);
+
+        assert_eq!(map.nearest_food_within(pos, 0.0), None);
+        assert_eq!(map.nearest_food_within(pos, 1.0), Some((3, 3)));
+        map.take_food_at(3, 3);
+        assert_eq!(map.nearest_food_within(pos, 1.0), None);
+        assert_eq!(map.nearest_food_within(pos, 1.5), Some((5, 3)));
+
+        // Food under the ant wins over closer cell centers
+        map.place_food_at(4, 3, 1);
+        assert_eq!(
+            map.nearest_food_within(Vec2::new(4.9, 3.5), 1.0),
+            Some((4, 3))
+        );
+        assert_eq!(map.nearest_food_within(Vec2::new(-0.5, 0.5), 1.0), None);
+    }
+
+    #[test]
+    fn test_friendly_ant_lookup_and_food_carriers() {
+        let mut map = GameMap::new(16, 16);
+        let friends = ant_refs(2, 0);
+        let enemies = ant_refs(1, 1);
+        let pos = Vec2::new(2.5, 2.5);
+        map.register_ant_in_cell(&friends[0], pos);
+        map.register_ant_in_cell(&enemies[0], pos);
+
+        // An ant never senses itself as a friend
+        assert_eq!(map.get_friendly_ant_at(2, 2, 0, &friends[0]), None);
+        map.register_ant_in_cell(&friends[1], pos);
+        assert_eq!(
+            map.get_friendly_ant_at(2, 2, 0, &friends[0]),
+            Some(friends[1].clone())
+        );
+
+        map.set_carrying_food(&enemies[0], true);
+        assert!(map.is_carrying_food(&enemies[0]));
+        assert!(!map.is_carrying_food(&friends[0]));
+        map.set_carrying_food(&enemies[0], false);
+        assert!(!map.is_carrying_food(&enemies[0]));
+
+        map.set_carrying_food(&enemies[0], true);
+        map.remove_colony_ants(1);
+        assert!(!map.is_carrying_food(&enemies[0]));
+    }
+}
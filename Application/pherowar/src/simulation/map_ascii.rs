@@ -0,0 +1,60 @@
+use super::map_thumbnail::terrain_priority;
+use super::{GameMap, Terrain};
+
+/// Default width limit of ASCII map dumps, in characters
+pub const DEFAULT_ASCII_MAX_WIDTH: usize = 120;
+
+fn terrain_char(terrain: &Terrain) -> char {
+    match terrain {
+        Terrain::Empty => '.',
+        Terrain::Wall => '#',
+        Terrain::Food(_) => '*',
+        Terrain::Nest(_) | Terrain::PlaceholderColony => '@',
+    }
+}
+
+impl GameMap {
+    /// Renders the map as text, one line per row: `#` walls, `*` food, `@` colonies, `.` empty.
+    /// Maps wider than `max_width` are downsampled by square blocks of tiles, each block
+    /// showing its most notable tile.
+    pub fn to_ascii(&self, max_width: usize) -> String {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let block = width.div_ceil(max_width.max(1)).max(1);
+
+        let mut ascii = String::with_capacity((width / block + 1) * height.div_ceil(block));
+        for y0 in (0..height).step_by(block) {
+            for x0 in (0..width).step_by(block) {
+                let mut terrain = &Terrain::Empty;
+                for y in y0..(y0 + block).min(height) {
+                    for x in x0..(x0 + block).min(width) {
+                        if let Some(tile_terrain) = self.get_terrain_at(x, y)
+                            && terrain_priority(tile_terrain) > terrain_priority(terrain)
+                        {
+                            terrain = tile_terrain;
+                        }
+                    }
+                }
+                ascii.push(terrain_char(terrain));
+            }
+            ascii.push('\n');
+        }
+        ascii
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_ascii_full_size_and_downsampled() {
+        let mut map = GameMap::new(6, 4);
+        map.place_wall_at(0, 0);
+        map.place_food_at(3, 1, 10);
+        map.place_wall_at(2, 0);
+
+        assert_eq!(map.to_ascii(10), "#.#...\n...*..\n......\n......\n");
+        // 2x2 blocks: food wins over the wall sharing its block
+        assert_eq!(map.to_ascii(3), "#*.\n...\n");
+    }
+}
@@ -0,0 +1,122 @@
+use crate::config::MAPS_DIR;
+use image::{ImageFormat, Rgba, RgbaImage};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::{GameMap, Terrain};
+
+/// File extension of cached map thumbnails, stored next to the map files
+pub const THUMBNAIL_EXTENSION: &str = "png";
+
+const EMPTY_COLOR: Rgba<u8> = Rgba([0x22, 0x22, 0x22, 0xff]);
+const WALL_COLOR: Rgba<u8> = Rgba([0x50, 0x49, 0x45, 0xff]);
+const FOOD_COLOR: Rgba<u8> = Rgba([0x98, 0xc3, 0x79, 0xff]);
+const NEST_COLOR: Rgba<u8> = Rgba([0xd0, 0xd0, 0xd0, 0xff]);
+
+/// Drawing priority of a terrain when several tiles fall into the same pixel,
+/// so that small features (food, nests) survive downscaling.
+pub(super) fn terrain_priority(terrain: &Terrain) -> u8 {
+    match terrain {
+        Terrain::Empty => 0,
+        Terrain::Wall => 1,
+        Terrain::Food(_) => 2,
+        Terrain::Nest(_) | Terrain::PlaceholderColony => 3,
+    }
+}
+
+fn terrain_color(terrain: &Terrain) -> Rgba<u8> {
+    match terrain {
+        Terrain::Empty => EMPTY_COLOR,
+        Terrain::Wall => WALL_COLOR,
+        Terrain::Food(_) => FOOD_COLOR,
+        Terrain::Nest(_) | Terrain::PlaceholderColony => NEST_COLOR,
+    }
+}
+
+impl GameMap {
+    /// Rasterizes the map tiles into an image whose longest side is `size` pixels.
+    /// Works without a window or GPU.
+    pub fn render_thumbnail(&self, size: u32) -> RgbaImage {
+        let size = size.max(1);
+        let longest_side = self.width.max(self.height).max(1) as f32;
+        let scale = size as f32 / longest_side;
+        let img_width = ((self.width as f32 * scale).round() as u32).max(1);
+        let img_height = ((self.height as f32 * scale).round() as u32).max(1);
+
+        let mut image = RgbaImage::new(img_width, img_height);
+        for (px, py, pixel) in image.enumerate_pixels_mut() {
+            // Tiles covered by this pixel
+            let x0 = (px as f32 / scale) as usize;
+            let y0 = (py as f32 / scale) as usize;
+            let x1 = (((px + 1) as f32 / scale).ceil() as usize).clamp(x0 + 1, self.width as usize);
+            let y1 =
+                (((py + 1) as f32 / scale).ceil() as usize).clamp(y0 + 1, self.height as usize);
+
+            let mut terrain = &Terrain::Empty;
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    if let Some(tile_terrain) = self.get_terrain_at(x, y)
+                        && terrain_priority(tile_terrain) > terrain_priority(terrain)
+                    {
+                        terrain = tile_terrain;
+                    }
+                }
+            }
+            *pixel = terrain_color(terrain);
+        }
+        image
+    }
+
+    /// Path of the cached thumbnail of a map file
+    pub fn thumbnail_path(map_name: &str) -> PathBuf {
+        Path::new(MAPS_DIR).join(format!("{}.{}", map_name, THUMBNAIL_EXTENSION))
+    }
+
+    /// Loads the cached thumbnail of a map file, regenerating it if it is missing,
+    /// older than the map or of a different size.
+    pub fn load_thumbnail(map_name: &str, size: u32) -> io::Result<RgbaImage> {
+        let map_path = Path::new(MAPS_DIR).join(map_name);
+        let thumbnail_path = Self::thumbnail_path(map_name);
+
+        let map_modified = fs::metadata(&map_path)?.modified()?;
+        let cache_is_fresh = fs::metadata(&thumbnail_path)
+            .and_then(|meta| meta.modified())
+            .is_ok_and(|modified| modified >= map_modified);
+        if cache_is_fresh && let Ok(cached) = image::open(&thumbnail_path) {
+            let cached = cached.to_rgba8();
+            if cached.width().max(cached.height()) == size {
+                return Ok(cached);
+            }
+        }
+
+        let thumbnail = Self::load_map(map_name)?.render_thumbnail(size);
+        if let Err(e) = thumbnail.save_with_format(&thumbnail_path, ImageFormat::Png) {
+            eprintln!(
+                "Warning: Failed to cache thumbnail {}: {}",
+                thumbnail_path.display(),
+                e
+            );
+        }
+        Ok(thumbnail)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumbnail_keeps_aspect_and_small_features() {
+        let mut map = GameMap::new(40, 20);
+        map.place_wall_at(0, 0);
+        map.place_food_at(21, 11, 10);
+
+        let thumbnail = map.render_thumbnail(10);
+        assert_eq!((thumbnail.width(), thumbnail.height()), (10, 5));
+        assert_eq!(*thumbnail.get_pixel(0, 0), WALL_COLOR);
+        // A single food tile still shows when 4x4 tiles share a pixel
+        assert_eq!(*thumbnail.get_pixel(5, 2), FOOD_COLOR);
+        assert_eq!(*thumbnail.get_pixel(9, 4), EMPTY_COLOR);
+    }
+}
@@ -0,0 +1,91 @@
+use super::Simulation;
+
+/// Per-colony results of a match, for tournament tools (`--stats-out`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchStats {
+    /// Tick the stats were taken at, the match's last one
+    pub tick: u32,
+    /// Colonies of the match, by id
+    pub colonies: Vec<ColonyStats>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColonyStats {
+    pub colony_id: u32,
+    pub name: String,
+    /// Most ants alive at once
+    pub peak_ants: usize,
+    /// Food brought back to the nest over the match, spent or not
+    pub food_collected: u32,
+    pub kills: u32,
+    pub ants_lost: u32,
+    /// Ticks from the colony's spawn to its elimination, or to now if still alive
+    pub ticks_survived: u32,
+}
+
+impl Simulation {
+    /// Results of the colonies still on the map, eliminated ones included.
+    pub fn export_stats(&self) -> MatchStats {
+        let mut colony_ids: Vec<u32> = self.colonies.keys().copied().collect();
+        colony_ids.sort_unstable();
+        let colonies = colony_ids
+            .into_iter()
+            .map(|id| {
+                let colony = &self.colonies[&id];
+                let end_tick = self
+                    .elimination_order
+                    .iter()
+                    .find(|elimination| elimination.colony_id == id)
+                    .map_or(self.tick, |elimination| elimination.tick);
+                ColonyStats {
+                    colony_id: id,
+                    name: self.colony_display_name(id),
+                    peak_ants: colony.peak_ants,
+                    food_collected: colony.total_food,
+                    kills: colony.kills,
+                    ants_lost: colony.deaths,
+                    ticks_survived: end_tick.saturating_sub(colony.spawned_at),
+                }
+            })
+            .collect();
+        MatchStats {
+            tick: self.tick,
+            colonies,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::prelude::Vec2;
+
+    use super::*;
+    use crate::config::{SimulationConfig, WallCrushPolicy};
+    use crate::simulation::{Colony, GameMap};
+
+    /// Ants lost by a colony whose first ant is walled in under `wall_crush`.
+    fn ants_lost_to_a_wall(wall_crush: WallCrushPolicy) -> u32 {
+        let config = SimulationConfig {
+            colony_initial_population: 2,
+            wall_crush,
+            ..Default::default()
+        };
+        let mut simulation = Simulation::new(&config, Vec::new(), None);
+        simulation.map = GameMap::new(20, 20);
+        let colony = Colony::detached(0, Vec2::new(5.5, 5.5), &simulation.map, &config);
+        simulation.add_colony(colony);
+        let colony = simulation.colonies.get_mut(&0).unwrap();
+        let key = colony.ants.keys().next().unwrap();
+        colony.relocate_ant(key, Vec2::new(12.5, 5.5), &mut simulation.map);
+
+        simulation.place_wall_at(12, 5);
+        simulation.export_stats().colonies[0].ants_lost
+    }
+
+    #[test]
+    fn test_wall_crush_kills_are_ants_lost() {
+        assert_eq!(ants_lost_to_a_wall(WallCrushPolicy::Kill), 1);
+        // Respawned ants are replaced, not lost
+        assert_eq!(ants_lost_to_a_wall(WallCrushPolicy::Respawn), 0);
+    }
+}
@@ -0,0 +1,87 @@
+use shared::PHEROMONE_CHANNEL_COUNT;
+use std::mem::size_of;
+
+use super::Simulation;
+use super::ant::{Ant, AntRef};
+use super::map::Tile;
+use super::raycast::ANGLE_COUNT;
+use crate::config::SimulationConfig;
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+/// Rough bytes used by a match of `colonies` colonies on a `width` x `height` map: the map
+/// tiles and raycast cache, each colony's pheromone grids, and the initial ants with their
+/// spatial index entries.
+pub fn estimated_memory(
+    config: &SimulationConfig,
+    width: u32,
+    height: u32,
+    colonies: usize,
+) -> u64 {
+    let tiles = width as u64 * height as u64;
+    let map = tiles * (size_of::<Tile>() + ANGLE_COUNT * size_of::<f32>()) as u64;
+
+    let resolution = config.pheromone_resolution.max(1);
+    let cells = width.div_ceil(resolution) as u64 * height.div_ceil(resolution) as u64;
+    let pheromones = cells * (PHEROMONE_CHANNEL_COUNT * size_of::<f32>()) as u64;
+    let ants =
+        config.colony_initial_population as u64 * (size_of::<Ant>() + size_of::<AntRef>()) as u64;
+
+    map + colonies as u64 * (pheromones + ants)
+}
+
+/// Checks the estimated memory of `colonies` colonies on `width` x `height` against
+/// `max_memory_mb`, explaining how to fit when over it.
+pub fn check_memory_budget(
+    config: &SimulationConfig,
+    width: u32,
+    height: u32,
+    colonies: usize,
+) -> Result<(), String> {
+    if config.max_memory_mb == 0 {
+        return Ok(());
+    }
+    let estimate_mb = estimated_memory(config, width, height, colonies).div_ceil(BYTES_PER_MB);
+    if estimate_mb <= config.max_memory_mb as u64 {
+        return Ok(());
+    }
+    Err(format!(
+        "{} colonies on a {}x{} map would need about {} MB, over the max_memory_mb budget of {} MB. \
+         Raise pheromone_resolution (now {}) for coarser pheromone grids, lower \
+         colony_initial_population, use a smaller map or fewer colonies, or raise max_memory_mb.",
+        colonies, width, height, estimate_mb, config.max_memory_mb, config.pheromone_resolution
+    ))
+}
+
+impl Simulation {
+    /// Checks that the current map can hold `colonies` colonies within `max_memory_mb`.
+    pub fn check_memory_budget(&self, colonies: usize) -> Result<(), String> {
+        check_memory_budget(&self.config, self.map.width, self.map.height, colonies)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_budget_suggests_coarser_pheromones() {
+        let mut config = SimulationConfig {
+            colony_initial_population: 0,
+            ..SimulationConfig::default()
+        };
+        // 100 MB left once the map is loaded, while 8 full 2000x2000 grids take 122 MB
+        let map_mb = estimated_memory(&config, 2000, 2000, 0).div_ceil(BYTES_PER_MB);
+        config.max_memory_mb = map_mb as u32 + 100;
+        let error = check_memory_budget(&config, 2000, 2000, 1).unwrap_err();
+        assert!(error.contains("pheromone_resolution (now 1)"), "{error}");
+
+        // Each resolution step divides the grids by its square
+        config.pheromone_resolution = 2;
+        assert!(check_memory_budget(&config, 2000, 2000, 1).is_ok());
+        assert!(check_memory_budget(&config, 2000, 2000, 4).is_err());
+
+        config.max_memory_mb = 0;
+        assert!(check_memory_budget(&config, 2000, 2000, 4).is_ok());
+    }
+}
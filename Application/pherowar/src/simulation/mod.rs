@@ -1,19 +1,48 @@
 pub mod ant;
+mod beacon;
 mod colony;
+mod combat;
+mod diagnosis;
+mod fading;
+mod food_scatter;
+mod foraging;
+mod latency;
 mod map;
+mod map_ascii;
+mod map_thumbnail;
+mod match_stats;
+mod memory;
 mod pheromone;
+mod population_log;
 mod raycast;
+mod replay;
+mod rng;
 mod sim;
+mod sudden_death;
+mod teleporter;
 mod timer;
+mod trace;
+mod waypoint;
+mod zone;
 
 // Re-export key types for easier imports
 pub use ant::AntRef;
 pub use colony::Colony;
+pub use diagnosis::LossDiagnosis;
+pub use food_scatter::FoodScatter;
+pub use foraging::ForagingStats;
+pub use latency::ThinkLatency;
 pub use map::GameMap;
 pub use map::Terrain;
+pub use map_ascii::DEFAULT_ASCII_MAX_WIDTH;
+pub use match_stats::MatchStats;
+pub use pheromone::{GhostPheromones, PheromoneChannel};
 pub use raycast::RaycastCache;
-pub use sim::Simulation;
+pub use replay::Replay;
+pub use rng::SeededRng;
+pub use sim::{Placement, Simulation};
 pub use timer::Timer;
+pub use zone::Zone;
 
 // Time constants
 pub const MIN_TIME_MULTIPLIER: f32 = 0.1;
@@ -22,10 +51,18 @@ pub const ANT_SPAWN_INTERVAL: f32 = 0.3;
 
 // Simulation constants
 pub const DEFAULT_FOOD_AMOUNT: u32 = 50;
-pub const COLONY_NEST_SIZE: f32 = 8.0;
-pub const MAX_COLONIES: usize = 5;
-pub const ANT_SPAWN_FOOD_COST: u32 = 5;
+pub const COLONY_NEST_SIZE: f32 = 8.0; // Default nest diameter
+pub const MIN_NEST_RADIUS: f32 = 0.5;
+pub const MAP_THUMBNAIL_SIZE: u32 = 128; // Longest side of map thumbnails, in pixels
+pub const MAX_COLONIES: usize = 5; // Default of the `max_colonies` setting
+pub const MAX_BEACONS: usize = 16; // Upper bound of the `beacons.max_count` setting
+pub const MAX_BEACON_EMISSION_RATE: f32 = 50.0; // Upper bound of `beacons.emission_rate`, per second
+pub const MAX_WAYPOINT_NAME_LEN: usize = shared::WAYPOINT_NAME_SIZE - 1; // Longer waypoint names are cut, in bytes
+pub const DEFAULT_ANT_SPAWN_FOOD_COST: u32 = 5; // Food units turned into each new ant
 pub const MAX_PHEROMONE_AMOUNT: f32 = 255.0;
+pub const DEFAULT_PHEROMONE_THRESHOLD: f32 = 0.01; // Pheromone amounts below this are cleared on decay
+pub const DEFAULT_COLONY_FOOD_REFERENCE: f32 = 20.0 * DEFAULT_ANT_SPAWN_FOOD_COST as f32; // Stock worth 20 spawns
+pub const DEFAULT_FOOD_PICKUP_RANGE: f32 = 1.0; // Tiles from the ant to a food cell center
 
 // Map size defaults
 pub const DEFAULT_MAP_WIDTH: u32 = 360;
@@ -33,16 +70,30 @@ pub const DEFAULT_MAP_HEIGHT: u32 = 200;
 
 // Ant behavior constants
 pub const THINK_INTERVAL: f32 = 1.5 / ANT_SPEED; // How often the ant thinks (in seconds) : Once per cell
+pub const DEFAULT_EVENT_THINK_INTERVAL: f32 = 4.0 * THINK_INTERVAL; // Background think interval of the event-driven mode
 pub const ANT_LENGTH: f32 = 1.0;
 pub const ANT_SPEED: f32 = 4.0; // How much the ant moves in 1 second at 1x speed
+pub const MAX_LAY_AHEAD: f32 = 5.0; // Farthest an ant lays pheromone ahead of itself, in cells
+pub const MOVE_SWEEP_STEP: f32 = 0.25; // Cells between wall checks along an ant's move, against tunneling
 pub const ANT_SLOWNESS_WITH_FOOD: f32 = 0.9; // Ants are 10% slower when carrying food
+pub const DEFAULT_MAX_MOVE_OFFSET: f32 = std::f32::consts::PI; // Ants may move in any direction relative to their facing
+pub const DEFAULT_BACKWARD_SPEED: f32 = 0.5; // Speed factor of ants moving straight backward
+pub const MUD_SPEED_FACTOR: f32 = 0.5; // Speed factor of ants on mud zones
+pub const PAD_SPEED_FACTOR: f32 = 1.5; // Speed factor of ants on speed pad zones
 pub const SENSE_MAX_ANGLE: f32 = std::f32::consts::FRAC_PI_4; // 45 degrees
 pub const SENSE_MAX_DISTANCE: f32 = 10.0;
 pub const SENSE_NUM_SAMPLES: usize = 32;
+pub const SUGGESTED_HEADING_RAYS: usize = 9; // Fixed rays across the sense cone, straight ahead included
+pub const DEFAULT_ANT_COMM_RANGE: f32 = SENSE_MAX_DISTANCE; // How far an ant can sense friendly pheromone signals
 // pub const MAX_ANT_AGE: f32 = 200.0; // in seconds, 200 is enough for 1.5 map length walk
 pub const MAX_ANT_LONGEVITY: f32 = 300.0; // in seconds, 200 is enough for 1.5 map length walk
-pub const ANT_ATTACK_DAMAGE: f32 = 5.0;
-pub const MAX_ANT_PROCESSING_TIME: u128 = 1500000; // Max time in nanos for an ant to be processed by the player connection
+pub const DEFAULT_ANT_ATTACK_DAMAGE: f32 = 5.0; // Longevity taken by a hit, before the facing modifiers
+pub const MAX_ANT_PROCESSING_TIME: u128 = 1500000; // Default max time in nanos for an ant to be processed by the player connection
+pub const MAX_PROCESSING_TIME_LIMIT: f32 = 100.0; // Upper bound of the configurable max processing time, in milliseconds
 
 // Pheromone decay interval (seconds)
 pub const PHEROMONE_DECAY_INTERVAL: f32 = 1.0; // 1 time every 1 seconds
+pub const LAY_PULSE_DURATION: f32 = 0.4; // Seconds a pheromone lay pulse stays visible
+pub const MAX_LAY_EVENTS: usize = 256; // Lay events kept per colony for the lay pulse view
+pub const COMBAT_EFFECT_DURATION: f32 = 0.8; // Seconds a hit stays visible in the combat effects view
+pub const MAX_COMBAT_EVENTS: usize = 256; // Hits kept per colony for the combat effects view
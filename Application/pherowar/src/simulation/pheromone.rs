@@ -2,44 +2,507 @@ use bincode_derive::{Decode, Encode};
 use macroquad::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use super::MAX_PHEROMONE_AMOUNT;
+use smallvec::SmallVec;
 
+use super::fading::{FadingEvent, FadingEvents};
+use super::{
+    GameMap, LAY_PULSE_DURATION, MAX_LAY_EVENTS, MAX_PHEROMONE_AMOUNT, PHEROMONE_DECAY_INTERVAL,
+    Terrain, Timer,
+};
+use crate::config::{DiffusionNeighborhood, PheromoneOverflow};
+
+/// Cells of a pheromone trail, usually a handful per think tick
+pub type TrailCells = SmallVec<[(usize, usize); 4]>;
+
+/// Pheromone grid of one channel. Each grid cell covers a `resolution` x `resolution`
+/// block of map tiles; `lay` and `get` take tile coordinates.
 #[derive(Encode, Decode, Clone, Serialize, Deserialize)]
 pub struct PheromoneChannel {
+    /// Grid size, in cells
     pub width: u32,
     pub height: u32,
+    /// Map tiles per grid cell side (1 = one value per tile)
+    pub resolution: u32,
     pub data: Vec<Vec<f32>>,
     pub decay_rate: f32,
+    /// Share of each cell's amount spread to its neighbors on decay (0.0 = no diffusion)
+    pub diffusion_rate: f32,
+    /// Amounts below this are cleared to 0.0 on decay
+    pub threshold: f32,
+    /// Cells holding pheromone, each listed once, so decay and drawing skip the empty ones
+    active: Vec<(u32, u32)>,
 }
 
 impl PheromoneChannel {
-    pub fn new(width: u32, height: u32, decay_rate: f32) -> Self {
+    /// Grid covering a `map_width` x `map_height` map with one cell per `resolution`² tiles.
+    pub fn new(
+        map_width: u32,
+        map_height: u32,
+        decay_rate: f32,
+        resolution: u32,
+        threshold: f32,
+    ) -> Self {
+        let resolution = resolution.max(1);
+        let width = map_width.div_ceil(resolution);
+        let height = map_height.div_ceil(resolution);
         Self {
             width,
             height,
+            resolution,
             data: vec![vec![0.0; width as usize]; height as usize],
             decay_rate,
+            diffusion_rate: 0.0,
+            threshold,
+            active: Vec::new(),
         }
     }
 
+    /// Grid cell holding the tile, if inside the grid
     #[inline(always)]
-    pub fn lay(&mut self, x: usize, y: usize, amount: f32) {
-        let cell = &mut self.data[y][x];
+    fn cell_of(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        let resolution = self.resolution as usize;
+        let (cx, cy) = (x / resolution, y / resolution);
+        (cx < self.width as usize && cy < self.height as usize).then_some((cx, cy))
+    }
+
+    /// Pheromone amount on the tile, 0.0 outside the grid
+    #[inline(always)]
+    pub fn get(&self, x: usize, y: usize) -> f32 {
+        self.cell_of(x, y).map_or(0.0, |(cx, cy)| self.data[cy][cx])
+    }
+
+    /// Lay pheromone on the tile's cell, clamped to `MAX_PHEROMONE_AMOUNT`.
+    /// Returns the amount actually added.
+    #[inline(always)]
+    pub fn lay(&mut self, x: usize, y: usize, amount: f32) -> f32 {
+        let Some((cx, cy)) = self.cell_of(x, y) else {
+            return 0.0;
+        };
+        let cell = &mut self.data[cy][cx];
+        let before = *cell;
         *cell = (*cell + amount).min(MAX_PHEROMONE_AMOUNT);
+        if before <= 0.0 && *cell > 0.0 {
+            self.active.push((cx as u32, cy as u32));
+        }
+        *cell - before
+    }
+
+    /// Grid cells holding pheromone, in no particular order
+    pub fn active_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.active.iter().map(|&(x, y)| (x as usize, y as usize))
+    }
+
+    /// Sum of the pheromone amounts over all cells of the channel.
+    pub fn total(&self) -> f32 {
+        self.active_cells().map(|(x, y)| self.data[y][x]).sum()
     }
 
-    pub fn decay(&mut self) {
-        let width = self.width as usize;
-        let height = self.height as usize;
-        for y in 0..height {
-            for x in 0..width {
-                if self.data[y][x] > 0.0 {
-                    self.data[y][x] *= self.decay_rate;
-                }
-                if self.data[y][x] < 0.01 {
-                    self.data[y][x] = 0.0;
-                }
+    /// Clears every cell, as on a new channel.
+    pub fn clear(&mut self) {
+        for (x, y) in self.active.drain(..) {
+            self.data[y as usize][x as usize] = 0.0;
+        }
+    }
+
+    /// Whether every tile of the grid cell is a wall of `map`, so no pheromone can sit there
+    fn is_wall_cell(&self, map: &GameMap, cx: usize, cy: usize) -> bool {
+        let resolution = self.resolution as usize;
+        let tiles_x = cx * resolution..((cx + 1) * resolution).min(map.width as usize);
+        let tiles_y = cy * resolution..((cy + 1) * resolution).min(map.height as usize);
+        tiles_y
+            .flat_map(|y| tiles_x.clone().map(move |x| (x, y)))
+            .all(|(x, y)| {
+                map.get_terrain_at(x, y)
+                    .is_none_or(|terrain| terrain == &Terrain::Wall)
+            })
+    }
+
+    /// Spreads `diffusion_rate` of each cell's amount evenly over its neighbors inside the
+    /// grid, keeping the total. Like deposits, pheromone doesn't spread onto walls of `map`,
+    /// which aren't counted as neighbors. Every share is computed from the amounts before
+    /// the pass, so the result doesn't depend on the scan order.
+    pub fn diffuse(&mut self, neighborhood: DiffusionNeighborhood, map: &GameMap) {
+        if self.diffusion_rate <= 0.0 {
+            return;
+        }
+        let offsets: &[(i32, i32)] = match neighborhood {
+            DiffusionNeighborhood::Four => &[(1, 0), (-1, 0), (0, 1), (0, -1)],
+            DiffusionNeighborhood::Eight => &[
+                (1, 0),
+                (-1, 0),
+                (0, 1),
+                (0, -1),
+                (1, 1),
+                (1, -1),
+                (-1, 1),
+                (-1, -1),
+            ],
+        };
+        let (width, height) = (self.width as i32, self.height as i32);
+        let mut inflow = Vec::with_capacity(self.active.len() * offsets.len());
+        let mut outflow = Vec::with_capacity(self.active.len());
+        for &(x, y) in &self.active {
+            let neighbors = offsets
+                .iter()
+                .map(|&(dx, dy)| (x as i32 + dx, y as i32 + dy))
+                .filter(|&(nx, ny)| nx >= 0 && ny >= 0 && nx < width && ny < height)
+                .filter(|&(nx, ny)| !self.is_wall_cell(map, nx as usize, ny as usize));
+            let count = neighbors.clone().count();
+            if count == 0 {
+                continue;
             }
+            let spread = self.data[y as usize][x as usize] * self.diffusion_rate;
+            let share = spread / count as f32;
+            inflow.extend(neighbors.map(|(nx, ny)| ((nx as u32, ny as u32), share)));
+            outflow.push(((x, y), spread));
         }
+
+        // Inflows first, so a cell emptied by its outflow isn't listed as active twice
+        for ((x, y), share) in inflow {
+            let cell = &mut self.data[y as usize][x as usize];
+            if *cell <= 0.0 {
+                self.active.push((x, y));
+            }
+            *cell = (*cell + share).min(MAX_PHEROMONE_AMOUNT);
+        }
+        for ((x, y), spread) in outflow {
+            let cell = &mut self.data[y as usize][x as usize];
+            *cell = (*cell - spread).max(0.0);
+        }
+    }
+
+    /// Decay the cells holding pheromone, clearing those that fall below the threshold.
+    /// Returns the remaining total.
+    pub fn decay(&mut self) -> f32 {
+        let (data, decay_rate, threshold) = (&mut self.data, self.decay_rate, self.threshold);
+        let mut total = 0.0;
+        self.active.retain(|&(x, y)| {
+            let cell = &mut data[y as usize][x as usize];
+            *cell *= decay_rate;
+            if *cell < threshold || *cell <= 0.0 {
+                *cell = 0.0;
+                return false;
+            }
+            total += *cell;
+            true
+        });
+        total
+    }
+}
+
+/// Pheromones left by a colony removed mid-match, decaying until none remain.
+pub struct GhostPheromones {
+    pub color: Color,
+    pub channels: Vec<PheromoneChannel>,
+    decay_timer: Timer,
+}
+
+impl GhostPheromones {
+    pub fn new(color: Color, channels: Vec<PheromoneChannel>) -> Self {
+        Self {
+            color,
+            channels,
+            decay_timer: Timer::new(PHEROMONE_DECAY_INTERVAL, 0.0),
+        }
+    }
+
+    /// Decays the pheromones like a living colony's. Returns whether any remain.
+    pub fn update(&mut self, dt: f32) -> bool {
+        self.decay_timer.update(dt);
+        if self.decay_timer.is_ready() {
+            self.channels.iter_mut().for_each(|channel| {
+                channel.decay();
+            });
+            self.decay_timer.wrap();
+        }
+        self.channels
+            .iter()
+            .any(|channel| channel.active_cells().next().is_some())
+    }
+}
+
+/// Cells crossed by the segment from `from` to `to`, in order, excluding the cell of `from`
+/// (it got the previous deposit). A segment within a single cell yields that cell.
+pub fn trail_cells(from: Vec2, to: Vec2) -> TrailCells {
+    let (mut x, mut y) = (from.x.floor() as isize, from.y.floor() as isize);
+    let (end_x, end_y) = (to.x.floor() as isize, to.y.floor() as isize);
+    let mut cells = TrailCells::new();
+    if (x, y) == (end_x, end_y) || x < 0 || y < 0 || end_x < 0 || end_y < 0 {
+        cells.push((end_x.max(0) as usize, end_y.max(0) as usize));
+        return cells;
+    }
+
+    // Grid traversal: step to whichever cell border the segment reaches first
+    let delta = to - from;
+    let step_x = delta.x.signum() as isize;
+    let step_y = delta.y.signum() as isize;
+    let t_delta_x = (1.0 / delta.x).abs();
+    let t_delta_y = (1.0 / delta.y).abs();
+    let border_x = if step_x > 0 { x as f32 + 1.0 } else { x as f32 };
+    let border_y = if step_y > 0 { y as f32 + 1.0 } else { y as f32 };
+    let mut t_max_x = if delta.x != 0.0 {
+        (border_x - from.x) / delta.x
+    } else {
+        f32::INFINITY
+    };
+    let mut t_max_y = if delta.y != 0.0 {
+        (border_y - from.y) / delta.y
+    } else {
+        f32::INFINITY
+    };
+
+    let max_steps = (end_x - x).unsigned_abs() + (end_y - y).unsigned_abs();
+    for _ in 0..max_steps {
+        if t_max_x < t_max_y {
+            x += step_x;
+            t_max_x += t_delta_x;
+        } else {
+            y += step_y;
+            t_max_y += t_delta_y;
+        }
+        if x < 0 || y < 0 {
+            break;
+        }
+        cells.push((x as usize, y as usize));
+        if (x, y) == (end_x, end_y) {
+            break;
+        }
+    }
+    cells
+}
+
+/// Budget on the total pheromone mass a colony may have on the map, across all channels.
+#[derive(Debug, Clone)]
+pub struct PheromoneBudget {
+    /// Maximum total mass (0.0 = unlimited)
+    pub cap: f32,
+    pub overflow: PheromoneOverflow,
+    /// Current total mass of the colony's pheromones
+    pub total: f32,
+}
+
+impl PheromoneBudget {
+    pub fn new(cap: f32, overflow: PheromoneOverflow) -> Self {
+        Self {
+            cap: cap.max(0.0),
+            overflow,
+            total: 0.0,
+        }
+    }
+
+    /// Factor to apply to a deposit of `requested` total mass to stay within the budget.
+    pub fn deposit_factor(&self, requested: f32) -> f32 {
+        if self.cap <= 0.0 || self.total + requested <= self.cap {
+            return 1.0;
+        }
+        match self.overflow {
+            PheromoneOverflow::Reject => 0.0,
+            PheromoneOverflow::Scale => ((self.cap - self.total) / requested).max(0.0),
+        }
+    }
+}
+
+/// Pheromone deposit of an ant, kept a moment for the lay pulse debug view
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayEvent {
+    pub cell: (u32, u32),
+    pub channel: u8,
+    /// Seconds since the deposit
+    pub age: f32,
+}
+
+impl LayEvent {
+    pub fn new(cell: (u32, u32), channel: u8) -> Self {
+        Self {
+            cell,
+            channel,
+            age: 0.0,
+        }
+    }
+}
+
+impl FadingEvent for LayEvent {
+    const DURATION: f32 = LAY_PULSE_DURATION;
+    const CAPACITY: usize = MAX_LAY_EVENTS;
+
+    fn age_mut(&mut self) -> &mut f32 {
+        &mut self.age
+    }
+}
+
+/// Recent lay events of a colony, oldest first
+pub type LayEvents = FadingEvents<LayEvent>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::DEFAULT_PHEROMONE_THRESHOLD;
+
+    #[test]
+    fn test_diffusion_keeps_the_total_and_is_symmetric() {
+        let map = GameMap::new(5, 5);
+        let mut channel = PheromoneChannel::new(5, 5, 1.0, 1, 0.0);
+        channel.lay(2, 2, 80.0);
+        channel.diffusion_rate = 0.5;
+        channel.diffuse(DiffusionNeighborhood::Four, &map);
+        assert_eq!(channel.get(2, 2), 40.0);
+        for (x, y) in [(1, 2), (3, 2), (2, 1), (2, 3)] {
+            assert_eq!(channel.get(x, y), 10.0);
+        }
+        assert_eq!(channel.get(1, 1), 0.0);
+
+        // Shares are taken from the amounts before the pass, whatever the scan order
+        channel.diffuse(DiffusionNeighborhood::Four, &map);
+        assert_eq!(channel.get(1, 2), channel.get(3, 2));
+        assert_eq!(channel.get(2, 1), channel.get(2, 3));
+        assert_eq!(channel.get(1, 1), channel.get(3, 3));
+        assert!((channel.total() - 80.0).abs() < 1e-3);
+        // Each cell is listed once, even those refilled after spreading everything
+        let mut active: Vec<_> = channel.active_cells().collect();
+        active.sort_unstable();
+        active.dedup();
+        assert_eq!(active.len(), channel.active_cells().count());
+
+        // A corner spreads over its 3 neighbors only
+        let mut corner = PheromoneChannel::new(5, 5, 1.0, 1, 0.0);
+        corner.lay(0, 0, 30.0);
+        corner.diffusion_rate = 0.3;
+        corner.diffuse(DiffusionNeighborhood::Eight, &map);
+        assert_eq!(corner.get(1, 1), 3.0);
+        assert_eq!(corner.get(0, 0), 21.0);
+        assert_eq!(corner.total(), 30.0);
+    }
+
+    #[test]
+    fn test_diffusion_stops_at_walls() {
+        // A 1-tile wall right of the trail
+        let mut map = GameMap::new(5, 5);
+        map.place_wall_at(3, 2);
+        let mut channel = PheromoneChannel::new(5, 5, 1.0, 1, 0.0);
+        channel.lay(2, 2, 30.0);
+        channel.diffusion_rate = 0.3;
+        channel.diffuse(DiffusionNeighborhood::Four, &map);
+        assert_eq!(channel.get(3, 2), 0.0);
+        // The share the wall would get goes to the 3 open neighbors
+        assert_eq!(channel.get(1, 2), 3.0);
+        assert_eq!(channel.get(2, 2), 21.0);
+        channel.diffuse(DiffusionNeighborhood::Four, &map);
+        assert_eq!(channel.get(3, 2), 0.0);
+        assert_eq!(channel.get(4, 2), 0.0);
+        assert!((channel.total() - 30.0).abs() < 1e-3);
+
+        // A coarse cell is only a wall when all its tiles are
+        let coarse = PheromoneChannel::new(5, 5, 1.0, 2, 0.0);
+        assert!(!coarse.is_wall_cell(&map, 1, 1));
+        map.place_wall_at(2, 2);
+        map.place_wall_at(2, 3);
+        map.place_wall_at(3, 3);
+        assert!(coarse.is_wall_cell(&map, 1, 1));
+    }
+
+    #[test]
+    fn test_coarse_channel_shares_a_cell_per_tile_block() {
+        let mut channel = PheromoneChannel::new(10, 7, 0.9, 4, DEFAULT_PHEROMONE_THRESHOLD);
+        assert_eq!((channel.width, channel.height), (3, 2));
+
+        assert_eq!(channel.lay(5, 6, 10.0), 10.0);
+        assert_eq!(channel.lay(7, 4, 5.0), 5.0);
+        // Every tile of the 4x4 block reads the same value
+        assert_eq!(channel.get(4, 4), 15.0);
+        assert_eq!(channel.get(3, 4), 0.0);
+        // The last, partial block still covers the map's edge
+        assert_eq!(channel.lay(9, 6, 1.0), 1.0);
+        assert_eq!(channel.get(8, 4), 1.0);
+        assert_eq!(channel.lay(12, 0, 1.0), 0.0);
+        assert_eq!(channel.total(), 16.0);
+    }
+
+    #[test]
+    fn test_ghost_pheromones_decay_until_gone() {
+        let mut channel = PheromoneChannel::new(4, 4, 0.5, 1, DEFAULT_PHEROMONE_THRESHOLD);
+        channel.lay(1, 1, 1.0);
+        let mut ghost = GhostPheromones::new(WHITE, vec![channel]);
+        // Decays once per interval, not on every update
+        assert!(ghost.update(PHEROMONE_DECAY_INTERVAL / 2.0));
+        assert_eq!(ghost.channels[0].get(1, 1), 1.0);
+        let mut updates = 0;
+        while ghost.update(PHEROMONE_DECAY_INTERVAL) {
+            updates += 1;
+        }
+        // 1.0 halves below 0.01 on the 7th decay
+        assert_eq!(updates, 6);
+    }
+
+    #[test]
+    fn test_decaying_trail_reaches_exactly_zero() {
+        let mut channel = PheromoneChannel::new(4, 4, 0.5, 1, DEFAULT_PHEROMONE_THRESHOLD);
+        channel.lay(1, 1, MAX_PHEROMONE_AMOUNT);
+        channel.lay(2, 1, 1.0);
+        channel.lay(2, 1, 1.0);
+        assert_eq!(channel.active_cells().count(), 2);
+
+        assert_eq!(channel.decay(), MAX_PHEROMONE_AMOUNT / 2.0 + 1.0);
+        let mut decays = 1;
+        loop {
+            decays += 1;
+            if channel.decay() == 0.0 {
+                break;
+            }
+        }
+        // 255 halves below 0.01 on the 15th decay, which clears the cell instead of leaving a residue
+        assert_eq!(decays, 15);
+        assert_eq!(channel.get(1, 1), 0.0);
+        assert_eq!(channel.get(2, 1), 0.0);
+        assert_eq!(channel.active_cells().count(), 0);
+
+        // A cleared cell is tracked again when laid on
+        channel.lay(1, 1, 3.0);
+        assert_eq!(channel.active_cells().collect::<Vec<_>>(), [(1, 1)]);
+        assert_eq!(channel.total(), 3.0);
+    }
+
+    #[test]
+    fn test_cleared_channel_is_empty_and_reusable() {
+        let mut channel = PheromoneChannel::new(4, 4, 0.5, 1, DEFAULT_PHEROMONE_THRESHOLD);
+        channel.lay(1, 1, 10.0);
+        channel.lay(3, 2, 5.0);
+        channel.clear();
+        assert!(channel.data.iter().flatten().all(|&v| v == 0.0));
+        assert_eq!(channel.active_cells().count(), 0);
+
+        channel.lay(3, 2, 2.0);
+        assert_eq!(channel.get(3, 2), 2.0);
+        assert_eq!(channel.active_cells().count(), 1);
+    }
+
+    #[test]
+    fn test_budget_overflow_policies() {
+        let mut budget = PheromoneBudget::new(100.0, PheromoneOverflow::Scale);
+        budget.total = 80.0;
+        assert_eq!(budget.deposit_factor(10.0), 1.0);
+        assert_eq!(budget.deposit_factor(40.0), 0.5);
+        budget.total = 120.0;
+        assert_eq!(budget.deposit_factor(40.0), 0.0);
+
+        budget.total = 80.0;
+        budget.overflow = PheromoneOverflow::Reject;
+        assert_eq!(budget.deposit_factor(10.0), 1.0);
+        assert_eq!(budget.deposit_factor(40.0), 0.0);
+
+        let unlimited = PheromoneBudget::new(0.0, PheromoneOverflow::Reject);
+        assert_eq!(unlimited.deposit_factor(1.0e9), 1.0);
+    }
+
+    #[test]
+    fn test_trail_cells_follow_the_segment() {
+        let cells = trail_cells(vec2(0.5, 0.5), vec2(0.9, 0.2));
+        assert_eq!(cells.as_slice(), &[(0, 0)]);
+
+        let cells = trail_cells(vec2(0.5, 0.5), vec2(3.5, 0.5));
+        assert_eq!(cells.as_slice(), &[(1, 0), (2, 0), (3, 0)]);
+
+        // A diagonal crosses every cell it touches, never jumping corners
+        let cells = trail_cells(vec2(2.2, 2.4), vec2(0.6, 0.1));
+        assert_eq!(cells.as_slice(), &[(1, 2), (1, 1), (1, 0), (0, 0)]);
     }
 }
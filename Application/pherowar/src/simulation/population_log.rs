@@ -0,0 +1,141 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use super::{Simulation, Timer};
+
+/// Simulation seconds between two population samples
+const POPULATION_SAMPLE_INTERVAL: f32 = 1.0;
+
+/// Tab-separated samples of each colony's ants and food, for offline analysis.
+/// Every colony id a match can use (below `max_colonies`) has its columns from the first
+/// header on, so colonies added mid-match keep the column count fixed.
+/// A reset rewinds the tick, announced by a repeated header line starting with `#`.
+pub struct PopulationLog {
+    pub file_name: String,
+    writer: BufWriter<File>,
+    sample_timer: Timer,
+    /// Number of colony ids with columns
+    colony_count: u32,
+    /// Header still to write before the next row: `""` for the first one, `"# "` after a reset
+    pending_header: Option<&'static str>,
+}
+
+impl PopulationLog {
+    /// Creates the log file, truncating an older one. The first sample is taken on the next tick.
+    fn create(path: &Path, max_colonies: usize) -> io::Result<Self> {
+        Ok(Self {
+            file_name: path.display().to_string(),
+            writer: BufWriter::new(File::create(path)?),
+            sample_timer: Timer::new(POPULATION_SAMPLE_INTERVAL, POPULATION_SAMPLE_INTERVAL),
+            colony_count: max_colonies as u32,
+            pending_header: Some(""),
+        })
+    }
+
+    /// Marks the rewind of the tick: the next tick is sampled, after a `#` header line.
+    pub(super) fn restart(&mut self) {
+        self.sample_timer.force_ready();
+        self.pending_header.get_or_insert("# ");
+    }
+
+    fn write_header(&mut self, prefix: &str) -> io::Result<()> {
+        write!(self.writer, "{}tick", prefix)?;
+        for id in 0..self.colony_count {
+            write!(self.writer, "\tants_{}\tfood_{}", id, id)?;
+        }
+        writeln!(self.writer)
+    }
+}
+
+impl Drop for PopulationLog {
+    /// Flushes the last samples when the log stops.
+    fn drop(&mut self) {
+        if let Err(e) = self.writer.flush() {
+            eprintln!(
+                "Warning: Failed to write population log {}: {}",
+                self.file_name, e
+            );
+        }
+    }
+}
+
+impl Simulation {
+    /// Starts sampling the colonies' populations to `path`, replacing any current log.
+    pub fn start_population_log(&mut self, path: &Path) -> io::Result<()> {
+        self.population_log = Some(PopulationLog::create(path, self.config.max_colonies)?);
+        Ok(())
+    }
+
+    /// Writes a sample row once per sample interval, and stops the log on a write error.
+    pub(super) fn sample_population(&mut self, dt: f32) {
+        let Some(mut log) = self.population_log.take() else {
+            return;
+        };
+        log.sample_timer.update(dt);
+        if log.sample_timer.is_ready() {
+            log.sample_timer.wrap();
+            if let Err(e) = write_sample(&mut log, self) {
+                eprintln!(
+                    "Warning: Failed to write population log {}: {}",
+                    log.file_name, e
+                );
+                return;
+            }
+        }
+        self.population_log = Some(log);
+    }
+}
+
+/// Writes the current tick's row, after the pending header if any.
+/// Absent, removed and eliminated colonies are at zero.
+fn write_sample(log: &mut PopulationLog, simulation: &Simulation) -> io::Result<()> {
+    if let Some(prefix) = log.pending_header.take() {
+        log.write_header(prefix)?;
+    }
+
+    write!(log.writer, "{}", simulation.tick)?;
+    for id in 0..log.colony_count {
+        let (ants, food) = simulation
+            .colonies
+            .get(&id)
+            .filter(|colony| !colony.is_dead())
+            .map_or((0, 0), |colony| (colony.ants.len(), colony.food_collected));
+        write!(log.writer, "\t{}\t{}", ants, food)?;
+    }
+    writeln!(log.writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimulationConfig;
+
+    #[test]
+    fn test_reset_is_sampled_after_a_marked_header() {
+        let config = SimulationConfig {
+            max_colonies: 2,
+            ..SimulationConfig::default()
+        };
+        let mut simulation = Simulation::new(&config, Vec::new(), None);
+        let path = std::env::temp_dir().join("pherowar_test_population_reset.tsv");
+        simulation.start_population_log(&path).unwrap();
+        simulation.sample_population(0.1);
+        simulation.tick = 40;
+        simulation.sample_population(POPULATION_SAMPLE_INTERVAL);
+        simulation.reset();
+        // The rewound tick is sampled at once, not a sample interval later
+        simulation.sample_population(0.1);
+        simulation.population_log = None;
+
+        let log = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let header = "tick\tants_0\tfood_0\tants_1\tfood_1";
+        let expected = [header, "0\t0\t0\t0\t0", "40\t0\t0\t0\t0"];
+        let mut lines = log.lines();
+        assert!(lines.by_ref().take(3).eq(expected));
+        assert_eq!(lines.next(), Some(format!("# {}", header).as_str()));
+        assert_eq!(lines.next(), Some("0\t0\t0\t0\t0"));
+        assert_eq!(lines.next(), None);
+    }
+}
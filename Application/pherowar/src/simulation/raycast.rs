@@ -103,78 +103,16 @@ impl RaycastCache {
     where
         F: Fn(usize, usize) -> bool,
     {
-        let ox = x as f32 + 0.5;
-        let oy = y as f32 + 0.5;
         let theta = Self::ray_index_to_angle(ray_idx);
-        let (sin_a, cos_a) = fast_sin_cos(theta);
-
-        let mut current_hit_dist = f32::INFINITY;
-
-        let mut current_map_x = x as isize;
-        let mut current_map_y = y as isize;
-
-        let step_x: isize = if cos_a > 0.0 { 1 } else { -1 };
-        let step_y: isize = if sin_a > 0.0 { 1 } else { -1 };
-
-        let t_delta_x = if cos_a.abs() < 1e-6 {
-            f32::INFINITY
-        } else {
-            (1.0 / cos_a).abs()
-        };
-        let t_delta_y = if sin_a.abs() < 1e-6 {
-            f32::INFINITY
-        } else {
-            (1.0 / sin_a).abs()
-        };
-
-        let mut t_max_x = if cos_a.abs() < 1e-6 {
-            f32::INFINITY
-        } else if cos_a > 0.0 {
-            ((x as f32 + 1.0) - ox) / cos_a
-        } else {
-            (x as f32 - ox) / cos_a
-        };
-
-        let mut t_max_y = if sin_a.abs() < 1e-6 {
-            f32::INFINITY
-        } else if sin_a > 0.0 {
-            ((y as f32 + 1.0) - oy) / sin_a
-        } else {
-            (y as f32 - oy) / sin_a
-        };
-
-        loop {
-            let dist_to_boundary;
-
-            if t_max_x < t_max_y {
-                if t_max_x > SENSE_MAX_DISTANCE {
-                    break;
-                }
-                dist_to_boundary = t_max_x;
-                current_map_x += step_x;
-                t_max_x += t_delta_x;
-            } else {
-                if t_max_y > SENSE_MAX_DISTANCE {
-                    break;
-                }
-                dist_to_boundary = t_max_y;
-                current_map_y += step_y;
-                t_max_y += t_delta_y;
-            }
-
-            if current_map_x < 0
-                || current_map_x as usize >= self.width
-                || current_map_y < 0
-                || current_map_y as usize >= self.height
-            {
-                break;
-            }
-
-            if is_wall_fn(current_map_x as usize, current_map_y as usize) {
-                current_hit_dist = dist_to_boundary;
-                break;
-            }
-        }
+        let current_hit_dist = trace_ray(
+            is_wall_fn,
+            self.width,
+            self.height,
+            x,
+            y,
+            theta,
+            SENSE_MAX_DISTANCE,
+        );
         let cache_idx = self.idx(x, y, ray_idx); // Calculate index before mutable borrow
         self.cache[cache_idx] = current_hit_dist;
     }
@@ -213,3 +151,91 @@ impl RaycastCache {
         }
     }
 }
+
+/// Trace a ray from the center of cell (x,y) with DDA, uncached.
+/// Returns the distance to the first wall within `max_distance`, or ∞ if none.
+pub fn trace_ray<F>(
+    is_wall_fn: &F,
+    width: usize,
+    height: usize,
+    x: usize,
+    y: usize,
+    theta: f32,
+    max_distance: f32,
+) -> f32
+where
+    F: Fn(usize, usize) -> bool,
+{
+    let ox = x as f32 + 0.5;
+    let oy = y as f32 + 0.5;
+    let (sin_a, cos_a) = fast_sin_cos(theta);
+
+    let mut current_hit_dist = f32::INFINITY;
+
+    let mut current_map_x = x as isize;
+    let mut current_map_y = y as isize;
+
+    let step_x: isize = if cos_a > 0.0 { 1 } else { -1 };
+    let step_y: isize = if sin_a > 0.0 { 1 } else { -1 };
+
+    let t_delta_x = if cos_a.abs() < 1e-6 {
+        f32::INFINITY
+    } else {
+        (1.0 / cos_a).abs()
+    };
+    let t_delta_y = if sin_a.abs() < 1e-6 {
+        f32::INFINITY
+    } else {
+        (1.0 / sin_a).abs()
+    };
+
+    let mut t_max_x = if cos_a.abs() < 1e-6 {
+        f32::INFINITY
+    } else if cos_a > 0.0 {
+        ((x as f32 + 1.0) - ox) / cos_a
+    } else {
+        (x as f32 - ox) / cos_a
+    };
+
+    let mut t_max_y = if sin_a.abs() < 1e-6 {
+        f32::INFINITY
+    } else if sin_a > 0.0 {
+        ((y as f32 + 1.0) - oy) / sin_a
+    } else {
+        (y as f32 - oy) / sin_a
+    };
+
+    loop {
+        let dist_to_boundary;
+
+        if t_max_x < t_max_y {
+            if t_max_x > max_distance {
+                break;
+            }
+            dist_to_boundary = t_max_x;
+            current_map_x += step_x;
+            t_max_x += t_delta_x;
+        } else {
+            if t_max_y > max_distance {
+                break;
+            }
+            dist_to_boundary = t_max_y;
+            current_map_y += step_y;
+            t_max_y += t_delta_y;
+        }
+
+        if current_map_x < 0
+            || current_map_x as usize >= width
+            || current_map_y < 0
+            || current_map_y as usize >= height
+        {
+            break;
+        }
+
+        if is_wall_fn(current_map_x as usize, current_map_y as usize) {
+            current_hit_dist = dist_to_boundary;
+            break;
+        }
+    }
+    current_hit_dist
+}
@@ -0,0 +1,737 @@
+use ahash::{AHashMap, AHashSet};
+use bincode::error::DecodeError;
+use bincode::{decode_from_std_read, encode_into_std_write};
+use bincode_derive::{Decode, Encode};
+use macroquad::prelude::{Color, Vec2};
+use shared::{CHANNEL_NAME_SIZE, MEMORY_SIZE, PHEROMONE_CHANNEL_COUNT, PlayerSetup, ROLE_COUNT};
+use slotmap::Key;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use super::ant::{Ant, AntKey};
+use super::map::{GameMap, SerializedMap};
+use super::pheromone::LayEvent;
+use super::{Colony, SeededRng, Simulation, Terrain, Timer};
+use crate::config::{PlayerConfig, SimulationConfig};
+use crate::player::PlayerConnection;
+
+/// Bumped when the recording format changes, older recordings are refused
+const REPLAY_FORMAT_VERSION: u32 = 3;
+/// Simulation seconds between two recorded wall, food and pheromone snapshots
+const KEYFRAME_INTERVAL: f32 = 1.0;
+
+/// Map and colonies of a recording, as they were on its first frame.
+#[derive(Encode, Decode)]
+struct ReplayHeader {
+    version: u32,
+    map: SerializedMap,
+    pheromone_resolution: u32,
+    colonies: Vec<ReplayColony>,
+}
+
+#[derive(Encode, Decode)]
+struct ReplayColony {
+    colony_id: u32,
+    name: String,
+    texture_path: Option<String>,
+    color: [f32; 4],
+    pos: (f32, f32),
+    nest_radius: f32,
+    decay_rates: [f32; PHEROMONE_CHANNEL_COUNT],
+    channel_names: [[u8; CHANNEL_NAME_SIZE]; PHEROMONE_CHANNEL_COUNT],
+}
+
+/// State of the recorded colonies after one tick.
+#[derive(Encode, Decode)]
+struct ReplayFrame {
+    tick: u32,
+    dt: f32,
+    colonies: Vec<ColonyFrame>,
+    keyframe: Option<Keyframe>,
+}
+
+#[derive(Encode, Decode)]
+struct ColonyFrame {
+    colony_id: u32,
+    food_collected: u32,
+    kills: u32,
+    ants: Vec<AntFrame>,
+    /// Cells and channels the colony's ants laid on during the tick
+    lays: Vec<(u32, u32, u8)>,
+}
+
+#[derive(Encode, Decode)]
+struct AntFrame {
+    /// Identifies the ant across frames, unique within its colony
+    id: u64,
+    x: f32,
+    y: f32,
+    rotation: f32,
+    longevity: f32,
+    role: u8,
+    carrying_food: bool,
+}
+
+/// Walls, food cells and pheromone grids, recorded once per `KEYFRAME_INTERVAL`.
+#[derive(Encode, Decode)]
+struct Keyframe {
+    /// Every wall cell, when the walls changed since the last keyframe (sudden death, editor)
+    walls: Option<Vec<(u32, u32)>>,
+    /// Cell and amount of every food tile
+    food: Vec<(u32, u32, u32)>,
+    pheromones: Vec<ChannelCells>,
+}
+
+/// Grid cells of a colony's channel holding pheromone, with their amount
+#[derive(Encode, Decode)]
+struct ChannelCells {
+    colony_id: u32,
+    channel: u8,
+    cells: Vec<(u32, u32, f32)>,
+}
+
+/// Records a match tick by tick (`--record`), for `Replay` to show it again without the
+/// players. Colonies spawned after the first recorded tick are left out.
+pub struct ReplayRecorder {
+    pub file_name: String,
+    writer: BufWriter<File>,
+    keyframe_timer: Timer,
+    /// Colonies of the header, None before the first frame
+    colony_ids: Option<Vec<u32>>,
+    /// Wall cells of the header or the last keyframe recording them
+    walls: Vec<(u32, u32)>,
+}
+
+impl ReplayRecorder {
+    /// Creates the recording file, truncating an older one. The header is written with the
+    /// first frame, once the colonies are in place.
+    fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            file_name: path.display().to_string(),
+            writer: BufWriter::new(File::create(path)?),
+            keyframe_timer: Timer::new(KEYFRAME_INTERVAL, KEYFRAME_INTERVAL),
+            colony_ids: None,
+            walls: Vec::new(),
+        })
+    }
+
+    fn write_frame(&mut self, simulation: &Simulation, dt: f32) -> io::Result<()> {
+        let colony_ids = match &self.colony_ids {
+            Some(colony_ids) => colony_ids.clone(),
+            None => {
+                let header = header_of(simulation);
+                encode(&mut self.writer, &header)?;
+                self.walls = wall_cells(&simulation.map);
+                let colony_ids: Vec<u32> = header.colonies.iter().map(|c| c.colony_id).collect();
+                self.colony_ids = Some(colony_ids.clone());
+                colony_ids
+            }
+        };
+
+        self.keyframe_timer.update(dt);
+        let keyframe = self.keyframe_timer.is_ready().then(|| {
+            self.keyframe_timer.wrap();
+            let walls = wall_cells(&simulation.map);
+            let changed_walls = (walls != self.walls).then(|| walls.clone());
+            self.walls = walls;
+            keyframe_of(simulation, &colony_ids, changed_walls)
+        });
+        let frame = ReplayFrame {
+            tick: simulation.tick,
+            dt,
+            colonies: colony_ids
+                .iter()
+                .filter_map(|id| simulation.colonies.get(id))
+                .map(colony_frame_of)
+                .collect(),
+            keyframe,
+        };
+        encode(&mut self.writer, &frame)
+    }
+}
+
+impl Drop for ReplayRecorder {
+    /// Flushes the last frames when the recording stops.
+    fn drop(&mut self) {
+        if let Err(e) = self.writer.flush() {
+            eprintln!("Warning: Failed to write replay {}: {}", self.file_name, e);
+        }
+    }
+}
+
+fn encode<T: bincode::Encode>(writer: &mut BufWriter<File>, value: &T) -> io::Result<()> {
+    encode_into_std_write(value, writer, bincode::config::standard())
+        .map(|_| ())
+        .map_err(io::Error::other)
+}
+
+fn header_of(simulation: &Simulation) -> ReplayHeader {
+    // Nests are recreated from the colonies, not read back as placeholders
+    let mut map = SerializedMap::from(&simulation.map);
+    for tile in map.tiles.iter_mut().flatten() {
+        if matches!(tile.terrain, Terrain::Nest(_)) {
+            tile.terrain = Terrain::Empty;
+        }
+    }
+
+    let mut colonies: Vec<ReplayColony> = simulation
+        .colonies
+        .values()
+        .map(|colony| {
+            let setup = &colony.player_connection.setup;
+            ReplayColony {
+                colony_id: colony.colony_id,
+                name: colony.player_config.name.clone(),
+                texture_path: colony.player_config.texture_path.clone(),
+                color: colony.color.into(),
+                pos: (colony.pos.x, colony.pos.y),
+                nest_radius: colony.nest_radius,
+                decay_rates: setup.decay_rates,
+                channel_names: setup.channel_names,
+            }
+        })
+        .collect();
+    colonies.sort_unstable_by_key(|colony| colony.colony_id);
+
+    ReplayHeader {
+        version: REPLAY_FORMAT_VERSION,
+        map,
+        pheromone_resolution: simulation.config.pheromone_resolution,
+        colonies,
+    }
+}
+
+fn colony_frame_of(colony: &Colony) -> ColonyFrame {
+    ColonyFrame {
+        colony_id: colony.colony_id,
+        food_collected: colony.food_collected,
+        kills: colony.kills,
+        ants: colony
+            .ants
+            .values()
+            .map(|ant| AntFrame {
+                id: ant.ant_ref.key.data().as_ffi(),
+                x: ant.pos.x,
+                y: ant.pos.y,
+                rotation: ant.rotation,
+                longevity: ant.longevity,
+                role: ant.role,
+                carrying_food: ant.is_carrying_food(),
+            })
+            .collect(),
+        // Events of this tick haven't aged yet
+        lays: colony
+            .lay_events
+            .iter()
+            .filter(|event| event.age == 0.0)
+            .map(|event| (event.cell.0, event.cell.1, event.channel))
+            .collect(),
+    }
+}
+
+/// Wall cells of the map, row by row.
+fn wall_cells(map: &GameMap) -> Vec<(u32, u32)> {
+    let mut walls = Vec::new();
+    for y in 0..map.height as usize {
+        for x in 0..map.width as usize {
+            if map.get_terrain_at(x, y) == Some(&Terrain::Wall) {
+                walls.push((x as u32, y as u32));
+            }
+        }
+    }
+    walls
+}
+
+fn keyframe_of(
+    simulation: &Simulation,
+    colony_ids: &[u32],
+    walls: Option<Vec<(u32, u32)>>,
+) -> Keyframe {
+    let map = &simulation.map;
+    let mut food = Vec::new();
+    for y in 0..map.height as usize {
+        for x in 0..map.width as usize {
+            if let Some(&Terrain::Food(amount)) = map.get_terrain_at(x, y) {
+                food.push((x as u32, y as u32, amount));
+            }
+        }
+    }
+
+    let mut pheromones = Vec::new();
+    for colony in colony_ids
+        .iter()
+        .filter_map(|id| simulation.colonies.get(id))
+    {
+        for (channel_index, channel) in colony.pheromones.iter().enumerate() {
+            let cells: Vec<(u32, u32, f32)> = channel
+                .active_cells()
+                .map(|(x, y)| (x as u32, y as u32, channel.data[y][x]))
+                .filter(|&(_, _, amount)| amount > 0.0)
+                .collect();
+            if !cells.is_empty() {
+                pheromones.push(ChannelCells {
+                    colony_id: colony.colony_id,
+                    channel: channel_index as u8,
+                    cells,
+                });
+            }
+        }
+    }
+    Keyframe {
+        walls,
+        food,
+        pheromones,
+    }
+}
+
+impl Simulation {
+    /// Starts recording the match to `path`, replacing any current recording.
+    pub fn start_recording(&mut self, path: &Path) -> io::Result<()> {
+        self.replay_recorder = Some(ReplayRecorder::create(path)?);
+        Ok(())
+    }
+
+    /// Writes this tick's frame, and stops the recording on a write error.
+    pub(super) fn record_frame(&mut self, dt: f32) {
+        let Some(mut recorder) = self.replay_recorder.take() else {
+            return;
+        };
+        if let Err(e) = recorder.write_frame(self, dt) {
+            eprintln!(
+                "Warning: Failed to write replay {}: {}",
+                recorder.file_name, e
+            );
+            return;
+        }
+        self.replay_recorder = Some(recorder);
+    }
+}
+
+/// Plays a recording back (`--replay`) on a simulation without players: its colonies have
+/// no brain and their ants are rebuilt from each frame. Food and pheromones are updated once
+/// per recorded keyframe.
+pub struct Replay {
+    pub file_name: String,
+    reader: BufReader<File>,
+    /// Next frame to show, None once the recording is over
+    next_frame: Option<ReplayFrame>,
+    /// Simulation seconds played since the last shown frame
+    elapsed: f32,
+    /// Key of each recorded ant id, per colony, so replayed ants keep their key (and stay
+    /// selected) from frame to frame
+    ant_keys: HashMap<u32, AHashMap<u64, AntKey>>,
+    /// Stream drawn by the creation of replayed ants, apart from the colonies' own
+    rng: SeededRng,
+}
+
+impl Replay {
+    /// Opens a recording and builds the paused simulation showing its first frame.
+    /// `config` is used for the settings the recording doesn't hold.
+    pub fn open(path: &Path, config: &SimulationConfig) -> io::Result<(Self, Simulation)> {
+        let file_name = path.display().to_string();
+        let mut reader = BufReader::new(File::open(path)?);
+        let header: ReplayHeader = decode_from_std_read(&mut reader, bincode::config::standard())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if header.version != REPLAY_FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "recording format {} is not supported (expected {})",
+                    header.version, REPLAY_FORMAT_VERSION
+                ),
+            ));
+        }
+
+        let mut config = config.clone();
+        config.pheromone_resolution = header.pheromone_resolution;
+        let mut simulation = Simulation::new(&config, Vec::new(), None);
+        simulation.map = GameMap::from(header.map);
+        for recorded in header.colonies {
+            let colony = replayed_colony(recorded, &simulation.map, &config)?;
+            let (x, y) = (colony.pos.x as usize, colony.pos.y as usize);
+            simulation.map.place_colony_at(x, y, colony.colony_id);
+            simulation.colonies.insert(colony.colony_id, colony);
+        }
+
+        let mut replay = Self {
+            file_name,
+            reader,
+            next_frame: None,
+            elapsed: 0.0,
+            ant_keys: HashMap::new(),
+            rng: SeededRng::new(0),
+        };
+        if let Some(frame) = replay.read_frame() {
+            replay.apply_frame(&mut simulation, frame);
+        }
+        replay.next_frame = replay.read_frame();
+        Ok((replay, simulation))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_frame.is_none()
+    }
+
+    /// Plays `dt` seconds of the recording, showing each frame reached on the way.
+    /// Nothing plays while the simulation is paused, and it pauses when the recording ends.
+    pub fn advance(&mut self, simulation: &mut Simulation, dt: f32) {
+        if simulation.is_paused {
+            return;
+        }
+        self.elapsed += dt;
+        while let Some(frame) = self.next_frame.take_if(|frame| frame.dt <= self.elapsed) {
+            self.elapsed -= frame.dt;
+            self.apply_frame(simulation, frame);
+            self.next_frame = self.read_frame();
+        }
+        if self.is_finished() {
+            eprintln!(
+                "Replay {} finished at tick {}",
+                self.file_name, simulation.tick
+            );
+            simulation.pause();
+            self.elapsed = 0.0;
+        }
+    }
+
+    /// Shows a frame: moves the recorded ants, adds the new ones and removes those gone.
+    /// Colonies missing from the frame were removed from the recorded match, and lose all
+    /// their ants.
+    fn apply_frame(&mut self, simulation: &mut Simulation, frame: ReplayFrame) {
+        // Same tick count as after the recorded `update`
+        simulation.tick = frame.tick + 1;
+        let map = &mut simulation.map;
+        for colony in simulation.colonies.values_mut() {
+            let ant_keys = self.ant_keys.entry(colony.colony_id).or_default();
+            let Some(colony_frame) = frame
+                .colonies
+                .iter()
+                .find(|colony_frame| colony_frame.colony_id == colony.colony_id)
+            else {
+                map.remove_colony_ants(colony.colony_id);
+                colony.ants.clear();
+                colony.role_counts = [0; ROLE_COUNT];
+                ant_keys.clear();
+                continue;
+            };
+            colony.food_collected = colony_frame.food_collected;
+            colony.kills = colony_frame.kills;
+            colony.lay_events.update(frame.dt);
+            for &(x, y, channel) in &colony_frame.lays {
+                colony.lay_events.push(LayEvent::new((x, y), channel));
+            }
+            update_ants(colony, &colony_frame.ants, ant_keys, map, &mut self.rng);
+        }
+
+        if let Some(keyframe) = frame.keyframe {
+            apply_keyframe(simulation, keyframe);
+        }
+    }
+
+    /// Next frame of the recording, None at its end.
+    fn read_frame(&mut self) -> Option<ReplayFrame> {
+        match decode_from_std_read(&mut self.reader, bincode::config::standard()) {
+            Ok(frame) => Some(frame),
+            // A recording cut short (e.g. by a crash) ends on its last whole frame
+            Err(DecodeError::Io { inner, .. }) if inner.kind() == io::ErrorKind::UnexpectedEof => {
+                None
+            }
+            Err(e) => {
+                eprintln!("Warning: Failed to read replay {}: {}", self.file_name, e);
+                None
+            }
+        }
+    }
+}
+
+/// Colony of a replay, with a detached player connection instead of a brain.
+fn replayed_colony(
+    recorded: ReplayColony,
+    map: &GameMap,
+    config: &SimulationConfig,
+) -> io::Result<Colony> {
+    let setup = PlayerSetup {
+        decay_rates: recorded.decay_rates,
+        initial_memory: [0; MEMORY_SIZE],
+        role_ratios: [0.0; ROLE_COUNT],
+        channel_names: recorded.channel_names,
+        diffusion_rates: [0.0; PHEROMONE_CHANNEL_COUNT],
+    };
+    let connection =
+        PlayerConnection::detached(recorded.colony_id, setup).map_err(io::Error::other)?;
+    let player_config = PlayerConfig {
+        name: recorded.name,
+        so_path: String::new(),
+        texture_path: recorded.texture_path,
+        build_command: None,
+    };
+    let [r, g, b, a] = recorded.color;
+    let mut colony = Colony::with_connection(
+        recorded.colony_id,
+        Vec2::new(recorded.pos.0, recorded.pos.1),
+        map.width,
+        map.height,
+        Color::new(r, g, b, a),
+        config,
+        player_config,
+        connection,
+    );
+    colony.nest_radius = recorded.nest_radius;
+    Ok(colony)
+}
+
+/// Brings the colony's ants to their recorded state, keeping the key of each ant already
+/// shown.
+fn update_ants(
+    colony: &mut Colony,
+    recorded_ants: &[AntFrame],
+    ant_keys: &mut AHashMap<u64, AntKey>,
+    map: &mut GameMap,
+    rng: &mut SeededRng,
+) {
+    let mut recorded_ids = AHashSet::with_capacity(recorded_ants.len());
+    for recorded in recorded_ants {
+        recorded_ids.insert(recorded.id);
+        let pos = Vec2::new(recorded.x, recorded.y);
+        let known_key = ant_keys
+            .get(&recorded.id)
+            .copied()
+            .filter(|&key| colony.ants.contains_key(key));
+        let key = match known_key {
+            Some(key) => {
+                if colony.ants[key].pos != pos {
+                    colony.relocate_ant(key, pos, map);
+                }
+                key
+            }
+            None => {
+                let mut ant = Ant::new(pos, colony.colony_id, [0; MEMORY_SIZE], rng);
+                let key = colony.ants.insert_with_key(|key| {
+                    ant.ant_ref.key = key;
+                    ant
+                });
+                map.register_ant_in_cell(&colony.ants[key].ant_ref, pos);
+                ant_keys.insert(recorded.id, key);
+                key
+            }
+        };
+        let ant = &mut colony.ants[key];
+        ant.rotation = recorded.rotation;
+        ant.longevity = recorded.longevity;
+        ant.role = recorded.role;
+        ant.set_carrying_food(recorded.carrying_food, map);
+    }
+
+    // Ants that died or were removed since the last frame
+    ant_keys.retain(|id, &mut key| {
+        if recorded_ids.contains(id) {
+            return true;
+        }
+        if let Some(mut ant) = colony.ants.remove(key) {
+            map.unregister_ant_from_cell(&ant.ant_ref, ant.pos);
+            ant.set_carrying_food(false, map);
+        }
+        false
+    });
+
+    colony.role_counts = [0; ROLE_COUNT];
+    for ant in colony.ants.values() {
+        if let Some(count) = colony.role_counts.get_mut(ant.role as usize) {
+            *count += 1;
+        }
+    }
+}
+
+fn apply_keyframe(simulation: &mut Simulation, keyframe: Keyframe) {
+    let map = &mut simulation.map;
+    if let Some(walls) = keyframe.walls {
+        for y in 0..map.height as usize {
+            for x in 0..map.width as usize {
+                if map.get_terrain_at(x, y) == Some(&Terrain::Wall) {
+                    map.remove_terrain_at(x, y);
+                }
+            }
+        }
+        for (x, y) in walls {
+            map.place_wall_at(x as usize, y as usize);
+        }
+        simulation.walls_changed = true;
+    }
+
+    for y in 0..map.height as usize {
+        for x in 0..map.width as usize {
+            if matches!(map.get_terrain_at(x, y), Some(Terrain::Food(_))) {
+                map.remove_terrain_at(x, y);
+            }
+        }
+    }
+    for (x, y, amount) in keyframe.food {
+        map.place_food_at(x as usize, y as usize, amount);
+    }
+
+    for colony in simulation.colonies.values_mut() {
+        for channel in colony.pheromones.iter_mut() {
+            channel.clear();
+        }
+    }
+    for recorded in keyframe.pheromones {
+        let Some(channel) = simulation
+            .colonies
+            .get_mut(&recorded.colony_id)
+            .and_then(|colony| colony.pheromones.get_mut(recorded.channel as usize))
+        else {
+            continue;
+        };
+        let resolution = channel.resolution as usize;
+        for (x, y, amount) in recorded.cells {
+            channel.lay(x as usize * resolution, y as usize * resolution, amount);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::THINK_INTERVAL;
+
+    #[test]
+    fn test_replay_shows_the_recorded_ticks() {
+        let path = std::env::temp_dir().join(format!("pherowar_replay_{}.bin", std::process::id()));
+        let config = SimulationConfig {
+            colony_initial_population: 5,
+            ..SimulationConfig::default()
+        };
+        let mut recorded = Simulation::new(&config, Vec::new(), None);
+        recorded.map = GameMap::new(40, 30);
+        recorded.map.place_food_at(5, 6, 12);
+        recorded.add_colony(test_colony(0, Vec2::new(10.5, 10.5), &recorded));
+        recorded.add_colony(test_colony(1, Vec2::new(30.5, 20.5), &recorded));
+        recorded.colonies.get_mut(&1).unwrap().pheromones[3].lay(20, 8, 7.0);
+        recorded.start_recording(&path).unwrap();
+
+        // Ants are moved by hand, as these colonies have no brain to think with
+        let mut positions = Vec::new();
+        for step in 0..3 {
+            if step == 2 {
+                // An ant dies and colony 0 is removed from the match
+                let ants = &mut recorded.colonies.get_mut(&1).unwrap().ants;
+                let dead = ants.keys().next().unwrap();
+                ants.remove(dead);
+                recorded.colonies.remove(&0);
+            }
+            for colony in recorded.colonies.values_mut() {
+                for ant in colony.ants.values_mut() {
+                    ant.pos.x += 1.0;
+                    ant.rotation = step as f32;
+                }
+            }
+            recorded.record_frame(THINK_INTERVAL);
+            recorded.tick += 1;
+            positions.push(ant_positions(&recorded, 1));
+        }
+        recorded.replay_recorder = None;
+
+        let (mut replay, mut replayed) = Replay::open(&path, &recorded.config).unwrap();
+        assert_eq!(replayed.tick, 1);
+        assert_eq!(replayed.colonies.len(), 2);
+        assert_eq!(ant_positions(&replayed, 1), positions[0]);
+        assert_eq!(replayed.map.get_terrain_at(5, 6), Some(&Terrain::Food(12)));
+        assert_eq!(replayed.map.get_terrain_at(30, 20), Some(&Terrain::Nest(1)));
+        assert_eq!(replayed.colonies[&1].pheromones[3].get(20, 8), 7.0);
+
+        // Paused replays don't move
+        replay.advance(&mut replayed, THINK_INTERVAL);
+        assert_eq!(replayed.tick, 1);
+
+        // Ants keep their key from frame to frame, for selection to follow them
+        let keys = ant_keys(&replayed, 1);
+        replayed.unpause();
+        replay.advance(&mut replayed, THINK_INTERVAL);
+        assert_eq!(ant_positions(&replayed, 1), positions[1]);
+        assert_eq!(ant_keys(&replayed, 1), keys);
+        replay.advance(&mut replayed, 10.0 * THINK_INTERVAL);
+        assert_eq!(replayed.tick, 3);
+        assert_eq!(ant_positions(&replayed, 1), positions[2]);
+        let remaining = ant_keys(&replayed, 1);
+        assert_eq!(remaining.len(), 4);
+        assert!(remaining.iter().all(|key| keys.contains(key)));
+        assert!(replayed.colonies[&0].ants.is_empty());
+        assert!(replay.is_finished() && replayed.is_paused);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_replay_shows_walls_placed_during_the_match() {
+        let path =
+            std::env::temp_dir().join(format!("pherowar_replay_walls_{}.bin", std::process::id()));
+        let config = SimulationConfig {
+            colony_initial_population: 2,
+            ..SimulationConfig::default()
+        };
+        let mut recorded = Simulation::new(&config, Vec::new(), None);
+        recorded.map = GameMap::new(20, 20);
+        recorded.map.place_wall_at(1, 1);
+        recorded.add_colony(test_colony(0, Vec2::new(10.5, 10.5), &recorded));
+        recorded.start_recording(&path).unwrap();
+        recorded.record_frame(KEYFRAME_INTERVAL);
+        // A sudden death ring closing in, and the editor removing a wall
+        recorded.map.place_wall_at(3, 4);
+        recorded.map.remove_terrain_at(1, 1);
+        recorded.tick += 1;
+        recorded.record_frame(KEYFRAME_INTERVAL);
+        recorded.replay_recorder = None;
+
+        let (mut replay, mut replayed) = Replay::open(&path, &recorded.config).unwrap();
+        assert_eq!(replayed.map.get_terrain_at(1, 1), Some(&Terrain::Wall));
+        assert_eq!(replayed.map.get_terrain_at(3, 4), Some(&Terrain::Empty));
+        replayed.walls_changed = false;
+
+        replayed.unpause();
+        replay.advance(&mut replayed, KEYFRAME_INTERVAL);
+        assert_eq!(replayed.map.get_terrain_at(1, 1), Some(&Terrain::Empty));
+        assert_eq!(replayed.map.get_terrain_at(3, 4), Some(&Terrain::Wall));
+        assert!(replayed.walls_changed);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    fn test_colony(colony_id: u32, pos: Vec2, simulation: &Simulation) -> Colony {
+        let setup = PlayerSetup {
+            decay_rates: [0.9; PHEROMONE_CHANNEL_COUNT],
+            initial_memory: [0; MEMORY_SIZE],
+            role_ratios: [0.0; ROLE_COUNT],
+            channel_names: [[0; CHANNEL_NAME_SIZE]; PHEROMONE_CHANNEL_COUNT],
+            diffusion_rates: [0.0; PHEROMONE_CHANNEL_COUNT],
+        };
+        Colony::with_connection(
+            colony_id,
+            pos,
+            simulation.map.width,
+            simulation.map.height,
+            Color::new(1.0, 0.0, 0.0, 1.0),
+            &simulation.config,
+            PlayerConfig {
+                name: format!("player{}", colony_id),
+                so_path: String::new(),
+                texture_path: None,
+                build_command: None,
+            },
+            PlayerConnection::detached(colony_id, setup).unwrap(),
+        )
+    }
+
+    fn ant_keys(simulation: &Simulation, colony_id: u32) -> Vec<AntKey> {
+        simulation.colonies[&colony_id].ants.keys().collect()
+    }
+
+    fn ant_positions(simulation: &Simulation, colony_id: u32) -> Vec<(f32, f32, f32)> {
+        let mut positions: Vec<_> = simulation.colonies[&colony_id]
+            .ants
+            .values()
+            .map(|ant| (ant.pos.x, ant.pos.y, ant.rotation))
+            .collect();
+        positions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        positions
+    }
+}
@@ -1,13 +1,34 @@
 use macroquad::prelude::*;
-use macroquad::rand;
 use std::collections::HashMap;
 
-use crate::config::{PlayerConfig, SimulationConfig};
+use crate::config::{ColonyOrder, ConfigError, PlayerConfig, SimulationConfig, WallCrushPolicy};
 
 use super::ant::{Ant, AntRef};
 use super::colony::Colony;
+use super::diagnosis::LossDiagnosis;
 use super::map::GameMap;
-use super::{DEFAULT_MAP_HEIGHT, DEFAULT_MAP_WIDTH, MAX_COLONIES, Terrain};
+use super::population_log::PopulationLog;
+use super::replay::ReplayRecorder;
+use super::rng::SeededRng;
+use super::trace::AntTrace;
+use super::{DEFAULT_MAP_HEIGHT, DEFAULT_MAP_WIDTH, GhostPheromones, MIN_NEST_RADIUS, Terrain};
+
+/// A colony that died or was removed during a match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Elimination {
+    pub colony_id: u32,
+    pub name: String,
+    pub tick: u32,
+}
+
+/// Final standing of a colony. Colonies eliminated on the same tick share a place.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placement {
+    pub place: usize,
+    pub name: String,
+    /// Tick of elimination, `None` for surviving colonies
+    pub eliminated_at: Option<u32>,
+}
 
 pub struct Simulation {
     pub tick: u32,
@@ -16,6 +37,99 @@ pub struct Simulation {
     pub player_configs: Vec<PlayerConfig>,
     pub is_paused: bool,
     pub config: SimulationConfig,
+    /// Colonies eliminated during the current match, first eliminated first
+    pub elimination_order: Vec<Elimination>,
+    /// Namespace of the player containers' sockets and logs, empty for the main simulation
+    pub namespace: String,
+    /// Set when the simulation itself placed walls, for the renderer to redraw them
+    pub walls_changed: bool,
+    /// Why the last colony failed to spawn (e.g. its brain didn't build), for the UI to show
+    pub spawn_error: Option<String>,
+    /// Trace file of the ant whose think ticks are being logged
+    pub trace: Option<AntTrace>,
+    /// Periodic samples of the colonies' ants and food (`--population-log`)
+    pub population_log: Option<PopulationLog>,
+    /// Tick by tick recording of the match (`--record`)
+    pub replay_recorder: Option<ReplayRecorder>,
+    /// Pheromones of colonies removed mid-match, kept until they decay (`ghost_pheromones`)
+    pub ghost_pheromones: Vec<GhostPheromones>,
+    /// Stream shuffling the colony processing order, apart from the colonies' own streams
+    order_rng: SeededRng,
+}
+
+/// Random stream of the colony processing order, seeded by `rng_seed` when set.
+fn order_rng(config: &SimulationConfig) -> SeededRng {
+    SeededRng::new(
+        config
+            .rng_seed
+            .unwrap_or_else(|| macroquad::rand::rand() as u64),
+    )
+}
+
+/// Spawn positions of colonies placed at `placeholders`, each moved up to `radius` tiles away.
+/// Every colony moves by the same distance and angle relative to the direction of the map
+/// center, so rotationally symmetric maps stay fair. A position landing on a wall, food or
+/// another nest moves to the nearest empty cell, or stays on the placeholder if there is none.
+fn jittered_spawns(
+    map: &GameMap,
+    placeholders: &[Vec2],
+    radius: f32,
+    rng: &mut SeededRng,
+) -> Vec<Vec2> {
+    if radius <= 0.0 {
+        return placeholders.to_vec();
+    }
+    let distance = rng.gen_range(0.0, radius);
+    let angle = rng.gen_range(-std::f32::consts::PI, std::f32::consts::PI);
+    let center = Vec2::new(map.width as f32, map.height as f32) / 2.0;
+    placeholders
+        .iter()
+        .map(|&placeholder| {
+            let to_center = center - placeholder;
+            let base = if to_center.length_squared() > 0.0 {
+                to_center.y.atan2(to_center.x)
+            } else {
+                0.0
+            };
+            let target = placeholder + Vec2::from_angle(base + angle) * distance;
+            nearest_free_cell(map, target, placeholder, radius.ceil() as i32).unwrap_or(placeholder)
+        })
+        .collect()
+}
+
+/// Center of the empty cell nearest to `target` within `reach` cells, the placeholder's own cell included.
+fn nearest_free_cell(map: &GameMap, target: Vec2, placeholder: Vec2, reach: i32) -> Option<Vec2> {
+    let (tx, ty) = (target.x.floor() as i32, target.y.floor() as i32);
+    let own_cell = (placeholder.x.floor() as i32, placeholder.y.floor() as i32);
+    (-reach..=reach)
+        .flat_map(|dy| (-reach..=reach).map(move |dx| (tx + dx, ty + dy)))
+        .filter(|&(x, y)| {
+            x >= 0
+                && y >= 0
+                && match map.get_terrain_at(x as usize, y as usize) {
+                    Some(Terrain::Empty) => true,
+                    Some(Terrain::PlaceholderColony) => (x, y) == own_cell,
+                    _ => false,
+                }
+        })
+        .map(|(x, y)| Vec2::new(x as f32 + 0.5, y as f32 + 0.5))
+        .min_by(|a, b| {
+            a.distance_squared(target)
+                .total_cmp(&b.distance_squared(target))
+        })
+}
+
+/// Order in which the colonies are updated this tick
+fn processing_order(mut colony_ids: Vec<u32>, order: ColonyOrder, rng: &mut SeededRng) -> Vec<u32> {
+    // Sorted first, as map iteration order is not reproducible
+    colony_ids.sort_unstable();
+    if order == ColonyOrder::Shuffle {
+        for i in (1..colony_ids.len()).rev() {
+            let j = rng.below(i + 1);
+            colony_ids.swap(i, j);
+        }
+    }
+    colony_ids
 }
 
 impl Simulation {
@@ -42,10 +156,19 @@ impl Simulation {
         Self {
             tick: 0,
             map,
-            colonies: HashMap::with_capacity(MAX_COLONIES),
+            colonies: HashMap::with_capacity(config.max_colonies),
             player_configs,
             is_paused: true,
             config: config.clone(),
+            elimination_order: Vec::new(),
+            namespace: String::new(),
+            walls_changed: false,
+            spawn_error: None,
+            trace: None,
+            population_log: None,
+            replay_recorder: None,
+            ghost_pheromones: Vec::new(),
+            order_rng: order_rng(config),
         }
     }
 
@@ -71,22 +194,175 @@ impl Simulation {
     }
 
     pub fn tick(&mut self, dt: f32) {
-        let mut colony_ids: Vec<u32> = self.colonies.keys().cloned().collect();
-        // Shuffle colony processing order
-        let n = colony_ids.len();
-        for i in (1..n).rev() {
-            let j = rand::gen_range(0, i + 1);
-            colony_ids.swap(i, j);
-        }
+        let colony_ids = processing_order(
+            self.colonies.keys().cloned().collect(),
+            self.config.colony_order,
+            &mut self.order_rng,
+        );
 
         for colony_id in &colony_ids {
             // Temporarily remove the current colony to pass the rest as &mut all_colonies
             if let Some(mut current_colony) = self.colonies.remove(colony_id) {
-                current_colony.update(&mut self.map, &mut self.colonies, dt);
+                current_colony.update(&mut self.map, &mut self.colonies, &self.config, dt);
                 // Put the colony back after its update
                 self.colonies.insert(*colony_id, current_colony);
             }
         }
+
+        self.ghost_pheromones.retain_mut(|ghost| ghost.update(dt));
+        self.collect_trace();
+        self.sample_population(dt);
+        self.record_frame(dt);
+        self.update_sudden_death();
+        self.record_eliminations();
+    }
+
+    /// Records colonies that died this tick, and forgets those that respawned ants since.
+    fn record_eliminations(&mut self) {
+        let colonies = &self.colonies;
+        self.elimination_order.retain(|elimination| {
+            colonies
+                .get(&elimination.colony_id)
+                .is_none_or(|colony| colony.is_dead())
+        });
+
+        // Sort so that simultaneous eliminations are recorded in a stable order
+        let mut dead_ids: Vec<u32> = self
+            .colonies
+            .iter()
+            .filter(|(_, colony)| colony.is_dead())
+            .map(|(&colony_id, _)| colony_id)
+            .collect();
+        dead_ids.sort_unstable();
+        for colony_id in dead_ids {
+            self.record_elimination(colony_id);
+        }
+    }
+
+    fn record_elimination(&mut self, colony_id: u32) {
+        if self
+            .elimination_order
+            .iter()
+            .any(|elimination| elimination.colony_id == colony_id)
+        {
+            return;
+        }
+        self.elimination_order.push(Elimination {
+            colony_id,
+            name: self.colony_display_name(colony_id),
+            tick: self.tick,
+        });
+    }
+
+    /// Ranks surviving colonies first, then eliminated colonies from last to first eliminated.
+    pub fn placements(&self) -> Vec<Placement> {
+        let mut survivors: Vec<(usize, String)> = self
+            .colonies
+            .iter()
+            .filter(|(_, colony)| !colony.is_dead())
+            .map(|(&colony_id, colony)| (colony.ants.len(), self.colony_display_name(colony_id)))
+            .collect();
+        // Most ants first when the match is not over yet
+        survivors.sort_by_key(|(ants, _)| std::cmp::Reverse(*ants));
+        let survivors: Vec<String> = survivors.into_iter().map(|(_, name)| name).collect();
+        rank_placements(&survivors, &self.elimination_order)
+    }
+
+    /// Think latency of each colony's brain, one `name: stats` line per colony by id.
+    pub fn think_latency_report(&self) -> String {
+        self.colony_report(|colony| colony.think_latency.to_string())
+    }
+
+    /// Foraging efficiency of each colony, one `name: stats` line per colony by id.
+    pub fn foraging_report(&self) -> String {
+        self.colony_report(|colony| colony.foraging.to_string())
+    }
+
+    /// Likely cause of each eliminated colony's loss, one `name: diagnosis` line per colony by id.
+    pub fn loss_report(&self) -> String {
+        self.colony_report_of(
+            |colony| colony.is_dead(),
+            |colony| LossDiagnosis::of(colony).to_string(),
+        )
+    }
+
+    fn colony_report(&self, stats: impl Fn(&Colony) -> String) -> String {
+        self.colony_report_of(|_| true, stats)
+    }
+
+    fn colony_report_of(
+        &self,
+        filter: impl Fn(&Colony) -> bool,
+        stats: impl Fn(&Colony) -> String,
+    ) -> String {
+        let mut colony_ids: Vec<u32> = self
+            .colonies
+            .iter()
+            .filter(|(_, colony)| filter(colony))
+            .map(|(&id, _)| id)
+            .collect();
+        colony_ids.sort_unstable();
+        colony_ids
+            .into_iter()
+            .map(|id| {
+                format!(
+                    "{}: {}",
+                    self.colony_display_name(id),
+                    stats(&self.colonies[&id])
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Nest radius given to newly spawned colonies, also used for nest placeholders.
+    pub fn new_nest_radius(&self) -> f32 {
+        self.config.nest_radius.max(MIN_NEST_RADIUS)
+    }
+
+    /// Applies reloaded settings to the running match without touching the map or the ants.
+    /// Match settings keep their running values. Returns the changed settings, or every
+    /// problem of the new config, which is then not applied.
+    pub fn reload_config(
+        &mut self,
+        config: SimulationConfig,
+    ) -> Result<Vec<String>, Vec<ConfigError>> {
+        let errors = config.validate();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let changes = self.config.changes_to(&config);
+        self.config = self.config.reloaded(config);
+        for colony in self.colonies.values_mut() {
+            colony.apply_config(&self.config);
+        }
+        Ok(changes)
+    }
+
+    /// Clears the pheromones of every colony, ghosts included, keeping everything else.
+    pub fn clear_all_pheromones(&mut self) {
+        for colony in self.colonies.values_mut() {
+            colony.clear_pheromones();
+        }
+        self.ghost_pheromones.clear();
+    }
+
+    /// Clears all colonies and the elimination record.
+    pub fn clear_colonies(&mut self) {
+        self.stop_trace();
+        self.ghost_pheromones.clear();
+        self.colonies.clear();
+        self.elimination_order.clear();
+    }
+
+    /// Spawn positions for colonies placed at `placeholders`, moved by the `spawn_jitter` setting.
+    pub fn spawn_positions(&self, placeholders: &[Vec2]) -> Vec<Vec2> {
+        let mut rng = SeededRng::new(
+            self.config
+                .rng_seed
+                .unwrap_or_else(|| macroquad::rand::rand() as u64),
+        );
+        jittered_spawns(&self.map, placeholders, self.config.spawn_jitter, &mut rng)
     }
 
     pub fn spawn_colony(
@@ -96,7 +372,8 @@ impl Simulation {
         player_cfg: PlayerConfig,
         id: Option<u32>,
     ) {
-        if self.colonies.len() >= MAX_COLONIES {
+        let max_colonies = self.config.max_colonies;
+        if self.colonies.len() >= max_colonies {
             eprintln!("Max colonies reached. Cannot spawn new colony.");
             return;
         }
@@ -109,7 +386,7 @@ impl Simulation {
             id
         } else {
             let mut colony_id: Option<u32> = None;
-            for i in 0..MAX_COLONIES as u32 {
+            for i in 0..max_colonies as u32 {
                 if !self.colonies.contains_key(&i) {
                     colony_id = Some(i);
                     break;
@@ -120,13 +397,19 @@ impl Simulation {
                 Some(id) => id,
                 None => {
                     eprintln!(
-                        "No available colony ID found (this should not happen if MAX_COLONIES check passed)."
+                        "No available colony ID found (this should not happen if the max colonies check passed)."
                     );
                     return;
                 }
             }
         };
 
+        if let Err(e) = self.check_memory_budget(self.colonies.len() + 1) {
+            eprintln!("Failed to create colony: {}", e);
+            self.spawn_error = Some(e);
+            return;
+        }
+
         // Attempt to remove any placeholder status at this position first.
         // The position for remove_placeholder_colony should be the tile coordinates,
         // while 'pos' for spawn_colony is usually the center of the tile.
@@ -134,44 +417,74 @@ impl Simulation {
         self.map.remove_placeholder_colony(tile_pos);
 
         match Colony::new(
+            &self.namespace,
             current_colony_id,
             pos,
             self.map.width,
             self.map.height,
             color,
-            self.config.colony_initial_population,
+            &self.config,
             player_cfg.clone(),
+            &self.map.map_info_for(pos),
         ) {
-            Ok(mut new_colony) => {
-                let x = pos.x.floor() as usize;
-                let y = pos.y.floor() as usize;
-                self.map.place_colony_at(x, y, current_colony_id);
-
-                new_colony.spawn_ants(&mut self.map, self.config.colony_initial_population);
-                self.colonies.insert(current_colony_id, new_colony);
-            }
+            Ok(new_colony) => self.add_colony(new_colony),
             Err(e) => {
                 eprintln!("Failed to create colony: {}", e);
+                self.spawn_error = Some(e.to_string());
             }
         }
     }
 
+    /// Places the nest of a created colony on the map and spawns its initial ants.
+    pub fn add_colony(&mut self, mut colony: Colony) {
+        let x = colony.pos.x.floor() as usize;
+        let y = colony.pos.y.floor() as usize;
+        self.map.place_colony_at(x, y, colony.colony_id);
+        colony.spawned_at = self.tick;
+
+        colony.spawn_ants(&mut self.map, self.config.colony_initial_population);
+        self.colonies.insert(colony.colony_id, colony);
+    }
+
+    /// Places a wall, handling the ants on the cell according to the `wall_crush` policy.
     pub fn place_wall_at(&mut self, x: usize, y: usize) {
-        let ants_to_respawn: Vec<AntRef> = self.map.ants_in_cell[y][x].iter().cloned().collect();
+        let crushed_ants: Vec<AntRef> = self.map.ants_at(x, y).to_vec();
 
         if !(self.map.place_wall_at(x, y)) {
             return;
         }
 
-        for ant_ref_to_respawn in ants_to_respawn {
-            let colony_id = ant_ref_to_respawn.colony_id;
-            if let Some(colony) = self.colonies.get_mut(&colony_id) {
-                colony.respawn_ant(ant_ref_to_respawn.key, colony.pos, &mut self.map);
-            } else {
+        let policy = self.config.wall_crush;
+        // Killed ants drop their food next to the wall rather than under it
+        let free_cell = match policy {
+            WallCrushPolicy::Respawn => None,
+            WallCrushPolicy::Kill | WallCrushPolicy::Relocate => self.map.nearest_free_cell(x, y),
+        };
+        for ant_ref in crushed_ants {
+            let colony_id = ant_ref.colony_id;
+            let Some(colony) = self.colonies.get_mut(&colony_id) else {
                 eprintln!(
                     "Warning: Colony {} for AntKey {:?} (from cell {},{} being walled) not found. Ant cannot be respawned.",
-                    colony_id, ant_ref_to_respawn.key, x, y
+                    colony_id, ant_ref.key, x, y
                 );
+                continue;
+            };
+            let free_pos = free_cell.map(|(fx, fy)| Vec2::new(fx as f32 + 0.5, fy as f32 + 0.5));
+            match (policy, free_pos) {
+                (WallCrushPolicy::Kill, pos) => {
+                    if let Some(pos) = pos {
+                        colony.relocate_ant(ant_ref.key, pos, &mut self.map);
+                    }
+                    colony.wall_deaths += 1;
+                    colony.despawn_ant(ant_ref.key, &mut self.map);
+                }
+                (WallCrushPolicy::Relocate, Some(pos)) => {
+                    colony.relocate_ant(ant_ref.key, pos, &mut self.map);
+                }
+                // No free cell left to relocate to
+                (WallCrushPolicy::Respawn | WallCrushPolicy::Relocate, _) => {
+                    colony.respawn_ant(ant_ref.key, colony.pos, &mut self.map);
+                }
             }
         }
     }
@@ -194,6 +507,12 @@ impl Simulation {
             .and_then(|colony| colony.ants.get(ant_ref.key))
     }
 
+    pub fn get_ant_mut(&mut self, ant_ref: &AntRef) -> Option<&mut Ant> {
+        self.colonies
+            .get_mut(&ant_ref.colony_id)
+            .and_then(|colony| colony.ants.get_mut(ant_ref.key))
+    }
+
     pub fn get_ant_at_world_pos(&self, world_pos: Vec2, click_radius: f32) -> Option<AntRef> {
         let cell_x = world_pos.x.floor() as isize;
         let cell_y = world_pos.y.floor() as isize;
@@ -219,8 +538,7 @@ impl Simulation {
                     && check_x < self.map.width as isize
                     && check_y < self.map.height as isize
                 {
-                    let ants_in_cell = &self.map.ants_in_cell[check_y as usize][check_x as usize];
-                    for ant_ref in ants_in_cell {
+                    for ant_ref in self.map.ants_at(check_x as usize, check_y as usize) {
                         if let Some(ant) = self.get_ant(ant_ref) {
                             let dist_sq = ant.pos.distance_squared(world_pos);
                             if dist_sq < min_dist_sq {
@@ -244,7 +562,16 @@ impl Simulation {
     }
 
     pub fn remove_colony(&mut self, colony_id: u32) -> bool {
-        if let Some(colony) = self.colonies.remove(&colony_id) {
+        // Removing a colony mid-match counts as an elimination
+        if self.tick > 0 && self.colonies.contains_key(&colony_id) {
+            self.record_elimination(colony_id);
+        }
+        if let Some(mut colony) = self.colonies.remove(&colony_id) {
+            if self.config.ghost_pheromones && self.tick > 0 {
+                let channels = std::mem::take(&mut colony.pheromones);
+                self.ghost_pheromones
+                    .push(GhostPheromones::new(colony.color, channels));
+            }
             let x = colony.pos.x.floor() as usize;
             let y = colony.pos.y.floor() as usize;
 
@@ -271,6 +598,11 @@ impl Simulation {
     pub fn reset(&mut self) {
         self.pause();
         self.tick = 0;
+        self.order_rng = order_rng(&self.config);
+        self.restore_sudden_death_cells();
+        if let Some(log) = &mut self.population_log {
+            log.restart();
+        }
 
         // Capture current colony and nest placeholder positions with their IDs
         let mut colony_spawn_data = Vec::new();
@@ -320,7 +652,7 @@ impl Simulation {
         }
 
         // Clear colonies and placeholder of loaded map
-        self.colonies.clear();
+        self.clear_colonies();
         self.map.placeholder_colony_locations.clear();
 
         // Clear all cells
@@ -328,7 +660,7 @@ impl Simulation {
 
         // Re-spawn colonies at their original positions with their original IDs
         for (colony_id, pos, color, player_cfg) in colony_spawn_data.into_iter() {
-            println!(
+            eprintln!(
                 "Spawning colony {} at {:?} with color {:?}",
                 colony_id, pos, color
             );
@@ -348,9 +680,30 @@ impl Simulation {
 
     pub fn create_new_map(&mut self, width: u32, height: u32) {
         self.map = GameMap::new(width, height);
-        self.colonies.clear();
+        self.clear_colonies();
         self.tick = 0;
         self.pause();
+        if let Some(log) = &mut self.population_log {
+            log.restart();
+        }
+    }
+
+    /// Returns the player name of a colony, suffixed with its colony ID when another
+    /// colony runs the same player (e.g. self-play).
+    pub fn colony_display_name(&self, colony_id: u32) -> String {
+        let Some(colony) = self.colonies.get(&colony_id) else {
+            return String::new();
+        };
+        let name = &colony.player_config.name;
+        let is_duplicate = self
+            .colonies
+            .values()
+            .any(|other| other.colony_id != colony_id && other.player_config.name == *name);
+        if is_duplicate {
+            format!("{} #{}", name, colony_id)
+        } else {
+            name.clone()
+        }
     }
 
     /// Returns the total number of ants across all colonies
@@ -363,3 +716,209 @@ impl Simulation {
         self.colonies.clear();
     }
 }
+
+/// Survivors all share first place. Eliminated colonies follow, latest first,
+/// and colonies eliminated on the same tick share a place.
+fn rank_placements(survivors: &[String], elimination_order: &[Elimination]) -> Vec<Placement> {
+    let mut placements: Vec<Placement> = survivors
+        .iter()
+        .map(|name| Placement {
+            place: 1,
+            name: name.clone(),
+            eliminated_at: None,
+        })
+        .collect();
+
+    let mut place = placements.len() + 1;
+    let mut last_tick = None;
+    for elimination in elimination_order.iter().rev() {
+        if last_tick != Some(elimination.tick) {
+            place = placements.len() + 1;
+            last_tick = Some(elimination.tick);
+        }
+        placements.push(Placement {
+            place,
+            name: elimination.name.clone(),
+            eliminated_at: Some(elimination.tick),
+        });
+    }
+    placements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn elimination(colony_id: u32, tick: u32) -> Elimination {
+        Elimination {
+            colony_id,
+            name: format!("colony{}", colony_id),
+            tick,
+        }
+    }
+
+    #[test]
+    fn test_spawn_jitter_is_symmetric_and_avoids_walls() {
+        let mut map = GameMap::new(41, 41);
+        map.place_nest_placeholder_at(10, 20);
+        map.place_nest_placeholder_at(30, 20);
+        let placeholders = map.placeholder_colony_locations.clone();
+        assert_eq!(
+            jittered_spawns(&map, &placeholders, 0.0, &mut SeededRng::new(3)),
+            placeholders
+        );
+
+        let spawns = jittered_spawns(&map, &placeholders, 3.0, &mut SeededRng::new(3));
+        let offsets: Vec<Vec2> = spawns
+            .iter()
+            .zip(&placeholders)
+            .map(|(s, p)| *s - *p)
+            .collect();
+        // Mirror images through the map center
+        assert_eq!(offsets[0], -offsets[1]);
+        assert!(offsets[0].length() <= 3.0 * std::f32::consts::SQRT_2);
+
+        // Walls around the jittered cell push the spawn to the nearest empty cell
+        let (x, y) = (spawns[0].x as usize, spawns[0].y as usize);
+        map.place_wall_at(x, y);
+        let moved = jittered_spawns(&map, &placeholders, 3.0, &mut SeededRng::new(3));
+        assert_ne!(moved[0], spawns[0]);
+        assert!(moved[0].distance(spawns[0]) < 1.5);
+        assert_eq!(
+            map.get_terrain_at(moved[0].x as usize, moved[0].y as usize),
+            Some(&Terrain::Empty)
+        );
+    }
+
+    #[test]
+    fn test_simultaneous_eliminations_share_a_place() {
+        let survivors = vec!["winner".to_string()];
+        let eliminations = [elimination(3, 10), elimination(1, 42), elimination(2, 42)];
+        let placements = rank_placements(&survivors, &eliminations);
+
+        let ranks: Vec<(usize, &str)> = placements
+            .iter()
+            .map(|p| (p.place, p.name.as_str()))
+            .collect();
+        assert_eq!(
+            ranks,
+            vec![
+                (1, "winner"),
+                (2, "colony2"),
+                (2, "colony1"),
+                (4, "colony3")
+            ]
+        );
+        assert_eq!(placements[0].eliminated_at, None);
+        assert_eq!(placements[3].eliminated_at, Some(10));
+    }
+
+    /// Simulation with a two-ant colony nested at (0, 0) of a `width` x 1 map, whose first ant
+    /// carries food on the last cell, about to be walled. With a width of 2, the nest is walled
+    /// too, leaving no free cell.
+    fn ant_under_new_wall(wall_crush: WallCrushPolicy, width: u32) -> (Simulation, AntRef) {
+        let config = SimulationConfig {
+            colony_initial_population: 2,
+            wall_crush,
+            ..Default::default()
+        };
+        let mut simulation = Simulation::new(&config, Vec::new(), None);
+        simulation.map = GameMap::new(width, 1);
+        let colony = Colony::detached(0, Vec2::new(0.5, 0.5), &simulation.map, &config);
+        simulation.add_colony(colony);
+        let colony = simulation.colonies.get_mut(&0).unwrap();
+        let key = colony.ants.keys().next().unwrap();
+        let last_cell = Vec2::new(width as f32 - 0.5, 0.5);
+        colony.relocate_ant(key, last_cell, &mut simulation.map);
+        let ant = colony.ants.get_mut(key).unwrap();
+        ant.set_carrying_food(true, &mut simulation.map);
+        let ant_ref = ant.ant_ref.clone();
+        if width == 2 {
+            simulation.map.place_wall_at(0, 0);
+        }
+        (simulation, ant_ref)
+    }
+
+    #[test]
+    fn test_wall_crush_kills_count_as_deaths() {
+        let (mut simulation, ant_ref) = ant_under_new_wall(WallCrushPolicy::Kill, 20);
+
+        simulation.place_wall_at(19, 0);
+        assert!(simulation.get_ant(&ant_ref).is_none());
+        let colony = &simulation.colonies[&0];
+        assert_eq!(colony.ants.len(), 1);
+        assert_eq!((colony.deaths, colony.wall_deaths), (1, 1));
+        assert_eq!(colony.combat_deaths, 0);
+        // The food is dropped on the nearest free cell, not under the wall
+        assert_eq!(simulation.get_terrain_at(18, 0), Some(&Terrain::Food(1)));
+    }
+
+    #[test]
+    fn test_wall_crush_kills_without_a_free_cell() {
+        let (mut simulation, ant_ref) = ant_under_new_wall(WallCrushPolicy::Kill, 2);
+
+        simulation.place_wall_at(1, 0);
+        assert!(simulation.get_ant(&ant_ref).is_none());
+        let colony = &simulation.colonies[&0];
+        assert_eq!(colony.ants.len(), 1);
+        assert_eq!((colony.deaths, colony.wall_deaths), (1, 1));
+        assert_eq!(simulation.get_terrain_at(1, 0), Some(&Terrain::Wall));
+    }
+
+    #[test]
+    fn test_wall_crush_relocates_to_the_nearest_free_cell() {
+        let (mut simulation, ant_ref) = ant_under_new_wall(WallCrushPolicy::Relocate, 20);
+
+        simulation.place_wall_at(19, 0);
+        let ant = simulation.get_ant(&ant_ref).unwrap();
+        assert_eq!(ant.pos, Vec2::new(18.5, 0.5));
+        assert!(ant.is_carrying_food());
+        assert_eq!(simulation.map.ants_at(18, 0), [ant_ref]);
+        assert_eq!(simulation.colonies[&0].deaths, 0);
+    }
+
+    #[test]
+    fn test_wall_crush_relocate_respawns_without_a_free_cell() {
+        let (mut simulation, ant_ref) = ant_under_new_wall(WallCrushPolicy::Relocate, 2);
+
+        simulation.place_wall_at(1, 0);
+        assert!(simulation.get_ant(&ant_ref).is_none());
+        let colony = &simulation.colonies[&0];
+        assert_eq!(colony.ants.len(), 2);
+        assert_eq!(colony.deaths, 0);
+        assert!(simulation.map.ants_at(1, 0).is_empty());
+    }
+
+    #[test]
+    fn test_wall_crush_respawns_at_the_nest() {
+        let (mut simulation, ant_ref) = ant_under_new_wall(WallCrushPolicy::Respawn, 20);
+
+        simulation.place_wall_at(19, 0);
+        assert!(simulation.get_ant(&ant_ref).is_none());
+        let colony = &simulation.colonies[&0];
+        assert_eq!(colony.ants.len(), 2);
+        assert!(colony.ants.values().all(|ant| ant.pos == colony.pos));
+        assert_eq!(colony.deaths, 0);
+        assert!(simulation.map.ants_at(19, 0).is_empty());
+    }
+
+    #[test]
+    fn test_colony_processing_order() {
+        let ids = vec![4, 0, 2, 1, 3];
+        let fixed = processing_order(ids.clone(), ColonyOrder::Fixed, &mut SeededRng::new(1));
+        assert_eq!(fixed, [0, 1, 2, 3, 4]);
+
+        // A seeded shuffle doesn't depend on the order the ids come in
+        let mut rng = SeededRng::new(9);
+        let first = processing_order(ids.clone(), ColonyOrder::Shuffle, &mut rng);
+        let mut reversed = ids.clone();
+        reversed.reverse();
+        assert_eq!(
+            processing_order(reversed, ColonyOrder::Shuffle, &mut SeededRng::new(9)),
+            first
+        );
+        let mut sorted = first.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, fixed);
+    }
+}
@@ -0,0 +1,157 @@
+use super::{Simulation, Terrain};
+
+/// Cells of the `ring`-th outer ring of a `width` x `height` map, ring 0 being the map's edge.
+fn ring_cells(width: usize, height: usize, ring: usize) -> Vec<(usize, usize)> {
+    if 2 * ring >= width || 2 * ring >= height {
+        return Vec::new();
+    }
+    let (left, top) = (ring, ring);
+    let (right, bottom) = (width - 1 - ring, height - 1 - ring);
+    let mut cells = Vec::new();
+    for x in left..=right {
+        cells.push((x, top));
+        if bottom != top {
+            cells.push((x, bottom));
+        }
+    }
+    for y in top + 1..bottom {
+        cells.push((left, y));
+        if right != left {
+            cells.push((right, y));
+        }
+    }
+    cells
+}
+
+impl Simulation {
+    /// Sudden death endgame: from `start_tick` on, colonies stop spawning ants and every
+    /// `shrink_interval` ticks the next outer ring of the map turns to walls. Ants caught
+    /// under the new walls are handled by the `wall_crush` policy; nests are spared.
+    pub(super) fn update_sudden_death(&mut self) {
+        let config = &self.config.sudden_death;
+        if !config.enabled || self.tick < config.start_tick {
+            return;
+        }
+        for colony in self.colonies.values_mut() {
+            colony.spawns_ants = false;
+        }
+
+        let elapsed = self.tick - config.start_tick;
+        let interval = config.shrink_interval.max(1);
+        if !elapsed.is_multiple_of(interval) {
+            return;
+        }
+        let ring = (elapsed / interval) as usize;
+        let cells = ring_cells(self.map.width as usize, self.map.height as usize, ring);
+        for (x, y) in cells {
+            let terrain = match self.map.get_terrain_at(x, y) {
+                Some(Terrain::Wall | Terrain::Nest(_) | Terrain::PlaceholderColony) | None => {
+                    continue;
+                }
+                Some(terrain) => terrain.clone(),
+            };
+            self.place_wall_at(x, y);
+            self.map.sudden_death_cells.push((x, y, terrain));
+            self.walls_changed = true;
+        }
+    }
+
+    /// Gives back the cells walled by sudden death their terrain, on a map a reset doesn't
+    /// reload from its file. Cells edited since are left as they are.
+    pub(super) fn restore_sudden_death_cells(&mut self) {
+        for (x, y, terrain) in std::mem::take(&mut self.map.sudden_death_cells) {
+            if self.map.get_terrain_at(x, y) != Some(&Terrain::Wall) {
+                continue;
+            }
+            self.map.remove_terrain_at(x, y);
+            if let Terrain::Food(amount) = terrain {
+                self.map.place_food_at(x, y, amount);
+            }
+            self.walls_changed = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{SimulationConfig, SuddenDeathConfig};
+    use crate::simulation::{Colony, GameMap};
+    use macroquad::prelude::Vec2;
+
+    #[test]
+    fn test_ring_cells() {
+        let mut edge = ring_cells(4, 3, 0);
+        edge.sort_unstable();
+        assert_eq!(
+            edge,
+            [
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (1, 0),
+                (1, 2),
+                (2, 0),
+                (2, 2),
+                (3, 0),
+                (3, 1),
+                (3, 2)
+            ]
+        );
+        // The innermost ring of an odd map is a single row
+        assert_eq!(ring_cells(4, 3, 1), [(1, 1), (2, 1)]);
+        assert!(ring_cells(4, 3, 2).is_empty());
+        assert_eq!(ring_cells(5, 5, 2), [(2, 2)]);
+    }
+
+    #[test]
+    fn test_sudden_death_stops_spawns_and_shrinks_the_map_until_a_reset() {
+        let config = SimulationConfig {
+            colony_initial_population: 0,
+            sudden_death: SuddenDeathConfig {
+                enabled: true,
+                start_tick: 3,
+                shrink_interval: 2,
+            },
+            ..Default::default()
+        };
+        let mut simulation = Simulation::new(&config, Vec::new(), None);
+        simulation.map = GameMap::new(6, 6);
+        simulation.map.place_food_at(0, 0, 5);
+        simulation.map.place_wall_at(5, 5);
+        let colony = Colony::detached(0, Vec2::new(2.5, 2.5), &simulation.map, &config);
+        simulation.add_colony(colony);
+        simulation.unpause();
+
+        for _ in 0..3 {
+            simulation.update(0.1);
+        }
+        assert!(simulation.colonies[&0].spawns_ants);
+        assert_eq!(simulation.get_terrain_at(3, 0), Some(&Terrain::Empty));
+
+        // Tick 3 walls the edge, then every other tick the next ring, sparing the nest
+        simulation.update(0.1);
+        assert!(!simulation.colonies[&0].spawns_ants);
+        assert_eq!(simulation.get_terrain_at(0, 0), Some(&Terrain::Wall));
+        assert_eq!(simulation.get_terrain_at(3, 0), Some(&Terrain::Wall));
+        simulation.update(0.1);
+        assert_eq!(simulation.get_terrain_at(1, 1), Some(&Terrain::Empty));
+        simulation.update(0.1);
+        assert_eq!(simulation.get_terrain_at(1, 1), Some(&Terrain::Wall));
+        simulation.update(0.1);
+        simulation.update(0.1);
+        assert_eq!(simulation.get_terrain_at(3, 3), Some(&Terrain::Wall));
+        assert_eq!(simulation.get_terrain_at(2, 2), Some(&Terrain::Nest(0)));
+
+        // Resetting the unsaved map gives the walled cells their terrain back. Colonies are
+        // removed first, as a reset respawns them through their players.
+        simulation.colonies.clear();
+        simulation.walls_changed = false;
+        simulation.reset();
+        assert!(simulation.walls_changed);
+        assert_eq!(simulation.get_terrain_at(0, 0), Some(&Terrain::Food(5)));
+        assert_eq!(simulation.get_terrain_at(3, 0), Some(&Terrain::Empty));
+        assert_eq!(simulation.get_terrain_at(1, 1), Some(&Terrain::Empty));
+        assert_eq!(simulation.get_terrain_at(5, 5), Some(&Terrain::Wall));
+    }
+}
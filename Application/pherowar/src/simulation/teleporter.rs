@@ -0,0 +1,109 @@
+use bincode_derive::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+use super::{GameMap, Terrain};
+
+/// Pair of linked cells authored in the editor: an entrance and its exit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct TeleporterPair {
+    pub entrance: (u32, u32),
+    pub exit: (u32, u32),
+}
+
+impl TeleporterPair {
+    /// Whether either end of the pair is on the cell
+    pub fn has_end_at(&self, x: usize, y: usize) -> bool {
+        let cell = (x as u32, y as u32);
+        self.entrance == cell || self.exit == cell
+    }
+}
+
+impl GameMap {
+    /// Teleporter pairs of the map
+    pub fn teleporters(&self) -> &[TeleporterPair] {
+        &self.teleporters
+    }
+
+    /// Whether a teleporter end can go on the cell: inside the map, not a wall and not already an end.
+    pub fn can_place_teleporter_at(&self, x: usize, y: usize) -> bool {
+        matches!(self.get_terrain_at(x, y), Some(terrain) if terrain != &Terrain::Wall)
+            && self.teleporter_at(x, y).is_none()
+    }
+
+    /// Links two cells. Returns false if they are the same cell or either can't hold a teleporter end.
+    pub fn add_teleporter(&mut self, entrance: (usize, usize), exit: (usize, usize)) -> bool {
+        if entrance == exit
+            || !self.can_place_teleporter_at(entrance.0, entrance.1)
+            || !self.can_place_teleporter_at(exit.0, exit.1)
+        {
+            return false;
+        }
+        self.teleporters.push(TeleporterPair {
+            entrance: (entrance.0 as u32, entrance.1 as u32),
+            exit: (exit.0 as u32, exit.1 as u32),
+        });
+        true
+    }
+
+    /// Teleporter pair with an end on the cell, if any
+    pub fn teleporter_at(&self, x: usize, y: usize) -> Option<&TeleporterPair> {
+        self.teleporters.iter().find(|pair| pair.has_end_at(x, y))
+    }
+
+    /// Removes the pair with an end on the cell, along with its other end.
+    /// Returns true if a pair was removed.
+    pub fn remove_teleporter_at(&mut self, x: usize, y: usize) -> bool {
+        let count = self.teleporters.len();
+        self.teleporters.retain(|pair| !pair.has_end_at(x, y));
+        self.teleporters.len() != count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::map::SerializedMap;
+
+    #[test]
+    fn test_teleporter_pairs_are_removed_as_a_whole() {
+        let mut map = GameMap::new(10, 10);
+        map.place_wall_at(5, 5);
+
+        assert!(!map.add_teleporter((1, 1), (1, 1)));
+        assert!(!map.add_teleporter((1, 1), (5, 5)));
+        assert!(!map.add_teleporter((1, 1), (10, 1)));
+        assert!(map.add_teleporter((1, 1), (8, 8)));
+        // Each cell holds at most one end
+        assert!(!map.add_teleporter((8, 8), (2, 2)));
+        assert!(map.add_teleporter((2, 2), (3, 3)));
+
+        // Removing one end removes its exit too, leaving no orphan
+        assert!(map.remove_teleporter_at(8, 8));
+        assert!(map.teleporter_at(1, 1).is_none());
+        assert_eq!(map.teleporters().len(), 1);
+
+        // A wall placed over an end breaks the pair
+        map.place_wall_at(3, 3);
+        assert!(map.teleporters().is_empty());
+    }
+
+    #[test]
+    fn test_teleporters_survive_serialization() {
+        let mut map = GameMap::new(10, 10);
+        map.add_teleporter((1, 2), (7, 8));
+
+        let data =
+            bincode::encode_to_vec(SerializedMap::from(&map), bincode::config::standard()).unwrap();
+        let reloaded = GameMap::from(SerializedMap::decode(&data).unwrap());
+        assert_eq!(reloaded.teleporters(), map.teleporters());
+
+        // Legacy map files are the current format without the trailing (here empty) pair list
+        map.place_wall_at(7, 8);
+        let mut data =
+            bincode::encode_to_vec(SerializedMap::from(&map), bincode::config::standard()).unwrap();
+        data.pop();
+        let legacy = GameMap::from(SerializedMap::decode(&data).unwrap());
+        assert_eq!(legacy.get_terrain_at(7, 8), Some(&Terrain::Wall));
+        assert!(legacy.teleporters().is_empty());
+    }
+}
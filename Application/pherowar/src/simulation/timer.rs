@@ -35,4 +35,44 @@ impl Timer {
     pub fn force_ready(&mut self) {
         self.value = self.max_value;
     }
+
+    /// Fraction of the interval elapsed, from 0.0 up to 1.0 once ready
+    pub fn progress(&self) -> f32 {
+        if self.max_value <= 0.0 {
+            return 1.0;
+        }
+        (self.value / self.max_value).clamp(0.0, 1.0)
+    }
+
+    /// Number of whole intervals elapsed since the timer was last wrapped
+    pub fn elapsed_intervals(&self) -> u32 {
+        if self.max_value <= 0.0 {
+            return 0;
+        }
+        (self.value / self.max_value).max(0.0) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_and_elapsed_intervals() {
+        let mut timer = Timer::new(2.0, 0.0);
+        assert_eq!(timer.progress(), 0.0);
+        assert_eq!(timer.elapsed_intervals(), 0);
+
+        timer.update(1.5);
+        assert_eq!(timer.progress(), 0.75);
+        assert_eq!(timer.elapsed_intervals(), 0);
+
+        timer.update(3.0);
+        assert_eq!(timer.progress(), 1.0);
+        assert_eq!(timer.elapsed_intervals(), 2);
+
+        timer.wrap();
+        assert_eq!(timer.progress(), 0.25);
+        assert_eq!(timer.elapsed_intervals(), 0);
+    }
 }
@@ -0,0 +1,91 @@
+use slotmap::Key;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use super::{AntRef, Simulation};
+
+/// Trace file of a single ant, receiving one line per think tick.
+pub struct AntTrace {
+    pub ant_ref: AntRef,
+    pub file_name: String,
+    writer: BufWriter<File>,
+}
+
+impl AntTrace {
+    /// Creates the trace file of `ant_ref` in `dir`, truncating older traces.
+    fn create(dir: &Path, namespace: &str, ant_ref: &AntRef) -> io::Result<Self> {
+        let mut file_name = format!("trace_{}_{:?}.log", ant_ref.colony_id, ant_ref.key.data());
+        if !namespace.is_empty() {
+            file_name = format!("{}_{}", namespace, file_name);
+        }
+        let file_name = dir.join(file_name).display().to_string();
+        Ok(Self {
+            ant_ref: ant_ref.clone(),
+            writer: BufWriter::new(File::create(&file_name)?),
+            file_name,
+        })
+    }
+}
+
+impl Drop for AntTrace {
+    /// Flushes the last lines when the trace stops.
+    fn drop(&mut self) {
+        if let Err(e) = self.writer.flush() {
+            eprintln!("Warning: Failed to write trace {}: {}", self.file_name, e);
+        }
+    }
+}
+
+impl Simulation {
+    /// Starts tracing `ant_ref`'s think ticks to a file in the working directory, replacing
+    /// any current trace. Returns the trace file name.
+    pub fn start_trace(&mut self, ant_ref: &AntRef) -> io::Result<String> {
+        // Joined to an empty path, the file name stays relative to the working directory
+        self.start_trace_in(Path::new(""), ant_ref)
+    }
+
+    /// Starts tracing `ant_ref`'s think ticks to a file in `dir`, replacing any current trace.
+    /// Returns the trace file name.
+    pub fn start_trace_in(&mut self, dir: &Path, ant_ref: &AntRef) -> io::Result<String> {
+        self.stop_trace();
+        let trace = AntTrace::create(dir, &self.namespace, ant_ref)?;
+        let file_name = trace.file_name.clone();
+        if let Some(ant) = self.get_ant_mut(ant_ref) {
+            ant.trace = true;
+        }
+        self.trace = Some(trace);
+        Ok(file_name)
+    }
+
+    /// Stops the current trace, flushing and closing its file.
+    pub fn stop_trace(&mut self) {
+        if let Some(trace) = self.trace.take()
+            && let Some(ant) = self.get_ant_mut(&trace.ant_ref)
+        {
+            ant.trace = false;
+        }
+    }
+
+    /// Writes the traced ant's line of this tick, and stops the trace once the ant is gone.
+    /// A new ant reusing the traced ant's key isn't traced.
+    pub(super) fn collect_trace(&mut self) {
+        let Some(ant_ref) = self.trace.as_ref().map(|trace| trace.ant_ref.clone()) else {
+            return;
+        };
+        let tick = self.tick;
+        let Some(ant) = self.get_ant_mut(&ant_ref).filter(|ant| ant.trace) else {
+            self.stop_trace();
+            return;
+        };
+        let Some(line) = ant.trace_line.take() else {
+            return;
+        };
+        if let Some(trace) = &mut self.trace
+            && let Err(e) = writeln!(trace.writer, "tick={} {}", tick, line)
+        {
+            eprintln!("Warning: Failed to write trace {}: {}", trace.file_name, e);
+            self.stop_trace();
+        }
+    }
+}
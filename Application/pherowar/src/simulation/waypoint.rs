@@ -0,0 +1,106 @@
+use bincode_derive::{Decode, Encode};
+use macroquad::math::Vec2;
+use serde::{Deserialize, Serialize};
+use shared::{MAX_WAYPOINTS, MapInfo, Waypoint};
+
+use super::{GameMap, Terrain};
+
+/// Named cell authored in the editor, e.g. "chokepoint", handed to brains at connection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct MapWaypoint {
+    pub name: String,
+    pub cell: (u32, u32),
+}
+
+impl MapWaypoint {
+    pub fn center(&self) -> Vec2 {
+        Vec2::new(self.cell.0 as f32 + 0.5, self.cell.1 as f32 + 0.5)
+    }
+}
+
+impl GameMap {
+    /// Waypoints of the map
+    pub fn waypoints(&self) -> &[MapWaypoint] {
+        &self.waypoints
+    }
+
+    /// Names the cell. Returns false if the name is empty, the cell is outside the map, a wall
+    /// or already a waypoint, or the map has `MAX_WAYPOINTS` already.
+    /// Names longer than brains can receive are cut.
+    pub fn add_waypoint(&mut self, name: &str, x: usize, y: usize) -> bool {
+        let name = Waypoint::new(name.trim(), (0.0, 0.0)).name().to_string();
+        if name.is_empty()
+            || self.waypoints.len() >= MAX_WAYPOINTS
+            || !matches!(self.get_terrain_at(x, y), Some(terrain) if terrain != &Terrain::Wall)
+            || self.waypoint_at(x, y).is_some()
+        {
+            return false;
+        }
+        self.waypoints.push(MapWaypoint {
+            name,
+            cell: (x as u32, y as u32),
+        });
+        true
+    }
+
+    pub fn waypoint_at(&self, x: usize, y: usize) -> Option<&MapWaypoint> {
+        let cell = (x as u32, y as u32);
+        self.waypoints.iter().find(|waypoint| waypoint.cell == cell)
+    }
+
+    /// Removes the waypoint on the cell. Returns true if there was one.
+    pub fn remove_waypoint_at(&mut self, x: usize, y: usize) -> bool {
+        let cell = (x as u32, y as u32);
+        let count = self.waypoints.len();
+        self.waypoints.retain(|waypoint| waypoint.cell != cell);
+        self.waypoints.len() != count
+    }
+
+    /// Waypoints as sent to the brains of the colony nested at `nest`, relative to it.
+    pub fn map_info_for(&self, nest: Vec2) -> MapInfo {
+        let waypoints: Vec<Waypoint> = self
+            .waypoints
+            .iter()
+            .map(|waypoint| {
+                let offset = waypoint.center() - nest;
+                Waypoint::new(&waypoint.name, (offset.x, offset.y))
+            })
+            .collect();
+        MapInfo::new(&waypoints)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::map::SerializedMap;
+
+    #[test]
+    fn test_waypoints_are_saved_and_sent_relative_to_the_nest() {
+        let mut map = GameMap::new(20, 20);
+        map.place_wall_at(5, 5);
+        assert!(!map.add_waypoint("wall", 5, 5));
+        assert!(!map.add_waypoint(" ", 1, 1));
+        assert!(map.add_waypoint("chokepoint", 10, 4));
+        assert!(!map.add_waypoint("again", 10, 4));
+        assert!(map.add_waypoint("resource_center_north", 2, 15));
+
+        let data =
+            bincode::encode_to_vec(SerializedMap::from(&map), bincode::config::standard()).unwrap();
+        let reloaded = GameMap::from(SerializedMap::decode(&data).unwrap());
+        assert_eq!(reloaded.waypoints(), map.waypoints());
+        assert_eq!(reloaded.waypoints()[1].name, "resource_center");
+
+        let info = reloaded.map_info_for(Vec2::new(4.5, 4.5));
+        assert_eq!(info.waypoint_count, 2);
+        assert_eq!(info.waypoints[0].name(), "chokepoint");
+        assert_eq!(info.waypoints[0].offset, (6.0, 0.0));
+        assert_eq!(info.waypoints[1].offset, (-2.0, 11.0));
+
+        // A wall placed over a waypoint removes it
+        map.place_wall_at(10, 4);
+        assert!(map.waypoint_at(10, 4).is_none());
+        assert!(map.remove_waypoint_at(2, 15));
+        assert!(map.waypoints().is_empty());
+    }
+}
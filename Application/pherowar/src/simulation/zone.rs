@@ -0,0 +1,131 @@
+use bincode_derive::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+use super::{GameMap, MUD_SPEED_FACTOR, PAD_SPEED_FACTOR};
+
+/// Region painted in the editor, changing how ants behave on its tiles.
+/// Zones lie under the terrain: a food or wall tile keeps its zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, Encode, Decode)]
+pub enum Zone {
+    #[default]
+    None,
+    /// Slows ants down
+    Mud,
+    /// Speeds ants up
+    Pad,
+    /// Pheromones laid here are lost
+    Scentless,
+}
+
+impl Zone {
+    /// Zones the editor can paint
+    pub const PAINTABLE: [Zone; 3] = [Zone::Mud, Zone::Pad, Zone::Scentless];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Zone::None => "None",
+            Zone::Mud => "Mud",
+            Zone::Pad => "Speed Pad",
+            Zone::Scentless => "No Scent",
+        }
+    }
+
+    /// Factor applied to the speed of ants moving from a tile of this zone
+    pub fn speed_factor(&self) -> f32 {
+        match self {
+            Zone::Mud => MUD_SPEED_FACTOR,
+            Zone::Pad => PAD_SPEED_FACTOR,
+            Zone::None | Zone::Scentless => 1.0,
+        }
+    }
+
+    /// Whether pheromones can be laid on tiles of this zone
+    pub fn allows_pheromones(&self) -> bool {
+        *self != Zone::Scentless
+    }
+}
+
+/// Painted cell as stored in map files, which only list the cells with a zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct ZoneCell {
+    pub x: u32,
+    pub y: u32,
+    pub zone: Zone,
+}
+
+impl GameMap {
+    /// Zone of the cell, `Zone::None` outside the map
+    #[inline(always)]
+    pub fn zone_at(&self, x: usize, y: usize) -> Zone {
+        if x < self.width as usize && y < self.height as usize {
+            self.zones[y * self.width as usize + x]
+        } else {
+            Zone::None
+        }
+    }
+
+    /// Paints the cell with `zone` (`Zone::None` clears it).
+    /// Returns true if the cell changed.
+    pub fn set_zone_at(&mut self, x: usize, y: usize, zone: Zone) -> bool {
+        if x >= self.width as usize || y >= self.height as usize {
+            return false;
+        }
+        let cell = &mut self.zones[y * self.width as usize + x];
+        let changed = *cell != zone;
+        *cell = zone;
+        changed
+    }
+
+    /// Painted cells of the map, row by row
+    pub fn zone_cells(&self) -> Vec<ZoneCell> {
+        let width = self.width as usize;
+        self.zones
+            .iter()
+            .enumerate()
+            .filter(|(_, zone)| **zone != Zone::None)
+            .map(|(index, &zone)| ZoneCell {
+                x: (index % width) as u32,
+                y: (index / width) as u32,
+                zone,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::map::SerializedMap;
+
+    #[test]
+    fn test_zones_survive_serialization() {
+        let mut map = GameMap::new(10, 10);
+        assert!(map.set_zone_at(2, 3, Zone::Mud));
+        assert!(!map.set_zone_at(2, 3, Zone::Mud));
+        assert!(map.set_zone_at(9, 9, Zone::Scentless));
+        assert!(!map.set_zone_at(10, 0, Zone::Pad));
+        assert_eq!(map.zone_at(10, 0), Zone::None);
+
+        let data =
+            bincode::encode_to_vec(SerializedMap::from(&map), bincode::config::standard()).unwrap();
+        let reloaded = GameMap::from(SerializedMap::decode(&data).unwrap());
+        assert_eq!(reloaded.zone_cells(), map.zone_cells());
+        assert_eq!(reloaded.zone_at(2, 3), Zone::Mud);
+
+        // Map files from before zones end right after the teleporter pairs
+        map.add_teleporter((1, 1), (5, 5));
+        let mut data =
+            bincode::encode_to_vec(SerializedMap::from(&map), bincode::config::standard()).unwrap();
+        let config = bincode::config::standard();
+        let zone_bytes = bincode::encode_to_vec(map.zone_cells(), config)
+            .unwrap()
+            .len()
+            + bincode::encode_to_vec(map.waypoints(), config)
+                .unwrap()
+                .len();
+        data.truncate(data.len() - zone_bytes);
+        let legacy = GameMap::from(SerializedMap::decode(&data).unwrap());
+        assert_eq!(legacy.teleporters(), map.teleporters());
+        assert!(legacy.zone_cells().is_empty());
+    }
+}
@@ -26,7 +26,7 @@ impl AntStatusBar {
                     (colony.color.b * 255.0) as u8,
                     255,
                 );
-                let name = colony.player_config.name.clone();
+                let name = simulation.colony_display_name(colony.colony_id);
                 (colony.colony_id, ant_count, color, name)
             })
             .filter(|(_, ant_count, _, _)| *ant_count > 0)
@@ -3,7 +3,7 @@ use new_egui_macroquad::egui::Color32;
 
 use crate::editor::EditorManager;
 use crate::editor::ToolType;
-use crate::editor::color_palette::{ColorPalette, PREDEFINED_COLONY_COLORS};
+use crate::editor::color_palette::ColorPalette;
 use crate::simulation::Simulation;
 use crate::ui::BASE_SPACING;
 use crate::ui::events::UIEvent;
@@ -79,12 +79,12 @@ impl ColonyOptions {
         let all_colors_currently_used = ColorPalette::are_all_colors_used(simulation);
 
         // Auto-select an available color if the current palette selection is already in use by a colony.
-        if !PREDEFINED_COLONY_COLORS.is_empty() {
-            let current_selected_idx = editor_manager.color_palette.get_selected_index();
-            let current_selected_color_value = PREDEFINED_COLONY_COLORS[current_selected_idx];
+        let palette_colors: Vec<_> = editor_manager.color_palette.colors().collect();
+        if !palette_colors.is_empty() {
+            let current_selected_color_value = editor_manager.color_palette.get_selected_color();
 
             if ColorPalette::is_color_used(current_selected_color_value, simulation) {
-                if let Some(first_available_index) = PREDEFINED_COLONY_COLORS
+                if let Some(first_available_index) = palette_colors
                     .iter()
                     .position(|&color| !ColorPalette::is_color_used(color, simulation))
                 {
@@ -103,7 +103,7 @@ impl ColonyOptions {
 
         // Color Palette Display
         ui.horizontal_wrapped(|ui| {
-            for (index, &color_val_macroquad) in PREDEFINED_COLONY_COLORS.iter().enumerate() {
+            for (index, &color_val_macroquad) in palette_colors.iter().enumerate() {
                 let is_selected = editor_manager.color_palette.get_selected_index() == index;
 
                 let color_val_egui = Color32::from_rgb(
@@ -0,0 +1,74 @@
+use crate::simulation::{MAX_TIME_MULTIPLIER, MIN_TIME_MULTIPLIER, Simulation};
+use crate::ui::BASE_SPACING;
+use crate::ui::events::AppAction;
+use macroquad::prelude::*;
+use new_egui_macroquad::egui;
+
+/// Controls of the second simulation in compare mode, with its own time control
+pub struct ComparePanel {
+    pub time_multiplier: f32,
+}
+
+impl ComparePanel {
+    pub fn new() -> Self {
+        Self {
+            time_multiplier: 1.0,
+        }
+    }
+
+    /// Draw the compare panel over the right half of the screen
+    pub fn draw(&mut self, egui_ctx: &egui::Context, simulation: &Simulation) -> Option<AppAction> {
+        let mut app_action = None;
+
+        egui::Window::new("Compare")
+            .resizable(false)
+            .collapsible(true)
+            .default_pos(egui::pos2(screen_width() / 2.0 + 12.0, 56.0))
+            .show(egui_ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let label = if simulation.is_paused {
+                        "▶ Play"
+                    } else {
+                        "⏸ Pause"
+                    };
+                    if ui.button(label).clicked() {
+                        app_action = Some(AppAction::ToggleComparePause);
+                    }
+                    ui.add(
+                        egui::Slider::new(
+                            &mut self.time_multiplier,
+                            MIN_TIME_MULTIPLIER..=MAX_TIME_MULTIPLIER,
+                        )
+                        .clamp_to_range(true)
+                        .logarithmic(true)
+                        .custom_formatter(|n, _decimals| format!("{:.2}x", n)),
+                    );
+                });
+
+                let mut colony_ids: Vec<u32> = simulation.colonies.keys().copied().collect();
+                colony_ids.sort_unstable();
+                egui::Grid::new("compare_stats")
+                    .num_columns(2)
+                    .spacing([BASE_SPACING * 2.0, BASE_SPACING])
+                    .show(ui, |ui| {
+                        ui.label("Tick:");
+                        ui.label(simulation.tick.to_string());
+                        ui.end_row();
+
+                        for colony_id in colony_ids {
+                            let colony = &simulation.colonies[&colony_id];
+                            let color = egui::Color32::from_rgb(
+                                (colony.color.r * 255.0) as u8,
+                                (colony.color.g * 255.0) as u8,
+                                (colony.color.b * 255.0) as u8,
+                            );
+                            ui.colored_label(color, simulation.colony_display_name(colony_id));
+                            ui.label(format!("{} ants", colony.ants.len()));
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        app_action
+    }
+}
@@ -2,12 +2,16 @@ use crate::engine::GameCamera;
 use crate::simulation::ant::Ant;
 use crate::simulation::{Colony, MAX_TIME_MULTIPLIER, MIN_TIME_MULTIPLIER, Simulation};
 use crate::ui::events::AppAction;
+use crate::ui::preferences::UIPreferences;
 use crate::ui::{BASE_PADDING, BASE_SPACING};
 use egui::RichText;
 use macroquad::prelude::*;
 use new_egui_macroquad::egui;
 use new_egui_macroquad::egui::Color32;
-use shared::MEMORY_SIZE;
+use shared::{MEMORY_SIZE, PHEROMONE_CHANNEL_COUNT, ROLE_SCOUT, ROLE_SOLDIER};
+
+/// How often the pheromone totals are recomputed (in seconds)
+const PHEROMONE_TOTALS_REFRESH_INTERVAL: f32 = 0.5;
 
 /// Debug panel component that displays debug information
 pub struct DebugPanel {
@@ -16,6 +20,9 @@ pub struct DebugPanel {
     show_debug: bool,
     pub time_multiplier: Option<f32>, // None = 1.0x, Some(x) = custom
     pub unlimited: bool,
+    pheromone_colony_id: Option<u32>,
+    pheromone_totals: [f32; PHEROMONE_CHANNEL_COUNT],
+    pheromone_totals_timer: f32,
 }
 
 impl DebugPanel {
@@ -26,16 +33,38 @@ impl DebugPanel {
             show_debug: false,
             time_multiplier: Some(1.0),
             unlimited: false,
+            pheromone_colony_id: None,
+            pheromone_totals: [0.0; PHEROMONE_CHANNEL_COUNT],
+            pheromone_totals_timer: PHEROMONE_TOTALS_REFRESH_INTERVAL,
         }
     }
 
-    /// Update the FPS counter
+    /// Update the FPS counter and the pheromone totals refresh timer
     pub fn update(&mut self) {
-        self.fps_timer += get_frame_time();
+        let frame_time = get_frame_time();
+        self.fps_timer += frame_time;
         if self.fps_timer >= 0.5 {
             self.displayed_fps = get_fps();
             self.fps_timer = 0.0;
         }
+        self.pheromone_totals_timer += frame_time;
+    }
+
+    /// Recompute the cached pheromone totals of the selected colony if the refresh interval elapsed
+    fn refresh_pheromone_totals(&mut self, simulation: &Simulation) {
+        if self.pheromone_totals_timer < PHEROMONE_TOTALS_REFRESH_INTERVAL {
+            return;
+        }
+        self.pheromone_totals_timer = 0.0;
+        self.pheromone_totals = [0.0; PHEROMONE_CHANNEL_COUNT];
+        if let Some(colony) = self
+            .pheromone_colony_id
+            .and_then(|id| simulation.colonies.get(&id))
+        {
+            for (channel, total) in self.pheromone_totals.iter_mut().enumerate() {
+                *total = colony.total_pheromone(channel);
+            }
+        }
     }
 
     /// Check if debug panel is enabled
@@ -43,6 +72,22 @@ impl DebugPanel {
         return self.show_debug;
     }
 
+    /// Restores the saved panel visibility and simulation speed
+    pub fn apply_preferences(&mut self, preferences: &UIPreferences) {
+        self.show_debug = preferences.debug_panel_visible;
+        self.time_multiplier = preferences
+            .time_multiplier
+            .map(|m| m.clamp(MIN_TIME_MULTIPLIER, MAX_TIME_MULTIPLIER));
+        self.unlimited = self.time_multiplier.is_none();
+    }
+
+    /// Sets the simulation speed: a time multiplier, or None for unlimited speed
+    pub fn set_speed(&mut self, multiplier: Option<f32>) {
+        self.unlimited = multiplier.is_none();
+        self.time_multiplier =
+            multiplier.map(|m| m.clamp(MIN_TIME_MULTIPLIER, MAX_TIME_MULTIPLIER));
+    }
+
     /// Toggle debug panel visibility
     pub fn toggle(&mut self) -> bool {
         self.show_debug = !self.show_debug;
@@ -64,6 +109,16 @@ impl DebugPanel {
 
         let mut app_action = None;
 
+        // Keep the pheromone totals pointed at an existing colony
+        if self
+            .pheromone_colony_id
+            .is_none_or(|id| !simulation.colonies.contains_key(&id))
+        {
+            self.pheromone_colony_id = simulation.colonies.keys().min().copied();
+            self.pheromone_totals_timer = PHEROMONE_TOTALS_REFRESH_INTERVAL;
+        }
+        self.refresh_pheromone_totals(simulation);
+
         egui::Window::new("Debug Info")
             .resizable(true)
             .collapsible(true)
@@ -155,7 +210,7 @@ impl DebugPanel {
                                 ui.end_row();
 
                                 // Collect colonies and sort by ID for consistent display order
-                                let mut colony_list: Vec<(&u32, &Colony)> = 
+                                let mut colony_list: Vec<(&u32, &Colony)> =
                                     simulation.colonies.iter().collect();
                                 colony_list.sort_by_key(|(id, _)| *id);
 
@@ -176,16 +231,100 @@ impl DebugPanel {
                                     ui.end_row();
 
                                     ui.label(format!(
-                                        "  Ants: {}, Food: {}",
+                                        "  Ants: {}, Food: {}, Thinks: {}",
                                         colony.ants.len(),
-                                        colony.food_collected
+                                        colony.food_collected,
+                                        colony.think_count
                                     ));
                                     ui.end_row();
+
+                                    ui.label("  Think time:");
+                                    let limit = match colony.max_processing_time {
+                                        Some(nanos) => {
+                                            format!("Calls over {} µs kill the ant.", nanos / 1000)
+                                        }
+                                        None => "Timeout disabled.".to_string(),
+                                    };
+                                    ui.label(colony.think_latency.to_string()).on_hover_text(
+                                        format!("Brain round-trip times. {}", limit),
+                                    );
+                                    ui.end_row();
+
+                                    ui.label("  Foraging:");
+                                    ui.label(colony.foraging.to_string()).on_hover_text(
+                                        "Food brought back per minute of ant life, and average time from leaving the nest to bringing food back.",
+                                    );
+                                    ui.end_row();
+
+                                    ui.label("  Next spawn:");
+                                    ui.add(
+                                        egui::ProgressBar::new(colony.spawn_progress())
+                                            .desired_width(120.0),
+                                    );
+                                    ui.end_row();
                                 }
                             }
                         });
                 });
 
+                if let Some(selected_colony_id) = self.pheromone_colony_id {
+                    ui.add_space(BASE_PADDING);
+                    ui.heading("Pheromones");
+                    ui.group(|ui| {
+                        let mut colony_ids: Vec<u32> =
+                            simulation.colonies.keys().copied().collect();
+                        colony_ids.sort();
+
+                        let colony_label = |id: u32| {
+                            simulation.colonies.get(&id).map_or_else(
+                                || format!("Colony {}", id),
+                                |c| format!("Colony {} ({})", id, c.player_config.name),
+                            )
+                        };
+
+                        egui::ComboBox::from_id_source("pheromone_totals_colony")
+                            .selected_text(colony_label(selected_colony_id))
+                            .show_ui(ui, |ui| {
+                                for id in colony_ids {
+                                    if ui
+                                        .selectable_label(
+                                            id == selected_colony_id,
+                                            colony_label(id),
+                                        )
+                                        .clicked()
+                                    {
+                                        self.pheromone_colony_id = Some(id);
+                                        self.pheromone_totals_timer =
+                                            PHEROMONE_TOTALS_REFRESH_INTERVAL;
+                                    }
+                                }
+                            });
+
+                        egui::Grid::new("pheromone_totals_grid")
+                            .num_columns(2)
+                            .spacing([BASE_SPACING * 2.0, BASE_SPACING])
+                            .show(ui, |ui| {
+                                for (channel, total) in self.pheromone_totals.iter().enumerate() {
+                                    let tint = crate::engine::CHANNEL_COLORS[channel];
+                                    ui.colored_label(
+                                        Color32::from_rgb(
+                                            (tint.r * 255.0) as u8,
+                                            (tint.g * 255.0) as u8,
+                                            (tint.b * 255.0) as u8,
+                                        ),
+                                        format!("Channel {}:", channel + 1),
+                                    );
+                                    ui.label(format!("{:.1}", total));
+                                    ui.end_row();
+                                }
+                            });
+                        if ui.button("Clear All Pheromones").clicked() {
+                            app_action = Some(AppAction::ClearPheromones);
+                            self.pheromone_totals_timer = PHEROMONE_TOTALS_REFRESH_INTERVAL;
+                        }
+                    });
+                }
+
                 if let Some(ant) = selected_ant {
                     ui.add_space(BASE_PADDING);
                     ui.heading("Selected Ant:");
@@ -229,7 +368,7 @@ impl DebugPanel {
                                 ui.end_row();
 
                                 ui.label("is_carrying_food:");
-                                ui.label(ant.carrying_food.to_string());
+                                ui.label(ant.is_carrying_food().to_string());
                                 ui.end_row();
 
                                 ui.label("is_on_food:");
@@ -240,10 +379,25 @@ impl DebugPanel {
                                 ui.label(ant.is_on_colony.to_string());
                                 ui.end_row();
 
+                                ui.label("role:");
+                                ui.label(match ant.role {
+                                    ROLE_SCOUT => "scout",
+                                    ROLE_SOLDIER => "soldier",
+                                    _ => "worker",
+                                });
+                                ui.end_row();
+
                                 ui.label("longevity:");
                                 ui.label(ant.longevity.to_string());
                                 ui.end_row();
 
+                                ui.label("Next think:");
+                                ui.add(
+                                    egui::ProgressBar::new(ant.think_timer.progress())
+                                        .desired_width(120.0),
+                                );
+                                ui.end_row();
+
                                 ui.label("Fighting:");
                                 if !ant.fight_opponents.is_empty() {
                                     ui.label(format!(
@@ -310,6 +464,14 @@ impl DebugPanel {
                         if ui.button(button_text).clicked() {
                             app_action = Some(AppAction::ToggleCameraLockOnSelectedAnt);
                         }
+                        let trace_text = if ant.trace {
+                            "Stop Tracing Ant"
+                        } else {
+                            "Trace Ant to File"
+                        };
+                        if ui.button(trace_text).clicked() {
+                            app_action = Some(AppAction::ToggleTraceOnSelectedAnt);
+                        }
                     });
                 }
             });
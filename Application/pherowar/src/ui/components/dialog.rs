@@ -1,5 +1,42 @@
+use image::RgbaImage;
 use new_egui_macroquad::egui;
 
+use crate::editor::symmetry_mode::SymmetryMode;
+use crate::simulation::FoodScatter;
+
+/// Minimum number of players required to start a quick match
+const QUICK_MATCH_MIN_PLAYERS: usize = 2;
+/// Allowed width and height of a new map
+const MAP_SIZE_RANGE: std::ops::RangeInclusive<u32> = 16..=4096;
+/// Default procedural food settings of the new map dialog
+const DEFAULT_FOOD_DENSITY: f32 = 0.02;
+const DEFAULT_FOOD_CLUSTERING: f32 = 0.8;
+
+/// Preview image of a choice option, uploaded as a texture when first shown
+#[derive(Clone)]
+pub struct ChoicePreview {
+    image: egui::ColorImage,
+    texture: Option<egui::TextureHandle>,
+}
+
+impl ChoicePreview {
+    pub fn from_rgba(image: &RgbaImage) -> Self {
+        let size = [image.width() as usize, image.height() as usize];
+        Self {
+            image: egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw()),
+            texture: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for ChoicePreview {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChoicePreview")
+            .field("size", &self.image.size)
+            .finish()
+    }
+}
+
 /// Dialog purpose - what action should be taken when confirmed
 #[derive(Debug, Clone)]
 pub enum DialogPurpose {
@@ -8,6 +45,9 @@ pub enum DialogPurpose {
     NewMap,
     LoadMap,
     SaveMap,
+    QuickMatch,
+    EnforceSymmetry(SymmetryMode),
+    RecoverAutosave,
 }
 
 /// Dialog content types
@@ -18,20 +58,29 @@ pub enum DialogContent {
         label: String,
         value: String,
     },
-    TwoNumbers {
-        label1: String,
-        label2: String,
-        value1: f64,
-        value2: f64,
-        min1: Option<f64>,
-        max1: Option<f64>,
-        min2: Option<f64>,
-        max2: Option<f64>,
+    NewMap {
+        width: u32,
+        height: u32,
+        /// Surround the new map with walls
+        bordered: bool,
+        /// Scatter procedural food on the new map
+        scatter_food: bool,
+        food: FoodScatter,
     },
     Choice {
         label: String,
         options: Vec<String>,
         selected: usize,
+        /// Optional preview per option, shown under the picker
+        previews: Vec<Option<ChoicePreview>>,
+    },
+    QuickMatch {
+        players: Vec<String>,
+        selected_players: Vec<bool>,
+        maps: Vec<String>,
+        selected_map: usize,
+        /// Maximum number of selected players (`max_colonies`)
+        max_players: usize,
     },
 }
 
@@ -41,8 +90,17 @@ pub enum DialogResult {
     Confirmed,
     Cancelled,
     InputConfirmed,
-    TwoNumberConfirmed(f64, f64),
+    NewMapConfirmed {
+        width: u32,
+        height: u32,
+        bordered: bool,
+        food: Option<FoodScatter>,
+    },
     ChoiceConfirmed(String),
+    QuickMatchConfirmed {
+        map_name: String,
+        players: Vec<String>,
+    },
 }
 
 /// Main dialog popup struct
@@ -90,8 +148,13 @@ impl DialogPopup {
         }
     }
 
-    pub fn new_map_picker(options: Vec<String>) -> Self {
+    /// Create a map picker, with an optional thumbnail per map
+    pub fn new_map_picker(options: Vec<String>, thumbnails: Vec<Option<RgbaImage>>) -> Self {
         let selected = 0;
+        let previews = thumbnails
+            .iter()
+            .map(|thumbnail| thumbnail.as_ref().map(ChoicePreview::from_rgba))
+            .collect();
         Self {
             open: true,
             title: Some("Load Map".to_string()),
@@ -100,6 +163,7 @@ impl DialogPopup {
                 label: "Select map to load:".to_string(),
                 options,
                 selected,
+                previews,
             },
             result: None,
         }
@@ -121,15 +185,66 @@ impl DialogPopup {
             open: true,
             title: Some("Create New Map".to_string()),
             purpose: DialogPurpose::NewMap,
-            content: DialogContent::TwoNumbers {
-                label1: "Width:".to_string(),
-                label2: "Height:".to_string(),
-                value1: default_width as f64,
-                value2: default_height as f64,
-                min1: Some(16.0),
-                max1: Some(4096.0),
-                min2: Some(16.0),
-                max2: Some(4096.0),
+            content: DialogContent::NewMap {
+                width: default_width,
+                height: default_height,
+                bordered: false,
+                scatter_food: false,
+                food: FoodScatter {
+                    density: DEFAULT_FOOD_DENSITY,
+                    clustering: DEFAULT_FOOD_CLUSTERING,
+                    seed: macroquad::rand::rand() as u64,
+                },
+            },
+            result: None,
+        }
+    }
+
+    /// Confirmation dialog for mirroring the map under the given symmetry mode
+    pub fn new_enforce_symmetry(mode: SymmetryMode, score: f32) -> Self {
+        Self {
+            open: true,
+            title: Some("Map Symmetry".to_string()),
+            purpose: DialogPurpose::EnforceSymmetry(mode),
+            content: DialogContent::Message(format!(
+                "The map is {:.1}% symmetric (\"{}\").\n\
+                 Mirror the top/left part onto the rest of the map?\n\
+                 Colonies will be removed and food reset.",
+                score * 100.0,
+                mode.label()
+            )),
+            result: None,
+        }
+    }
+
+    /// Offers to recover the map autosaved by a previous session
+    pub fn new_recover_autosave() -> Self {
+        Self {
+            open: true,
+            title: Some("Recover Map".to_string()),
+            purpose: DialogPurpose::RecoverAutosave,
+            content: DialogContent::Message(
+                "A map autosaved by a previous editing session was found.\n\
+                 Confirm to load it, Cancel to delete it."
+                    .to_string(),
+            ),
+            result: None,
+        }
+    }
+
+    /// Quick match setup dialog constructor
+    pub fn new_quick_match(players: Vec<String>, maps: Vec<String>, max_players: usize) -> Self {
+        let selected_players = vec![false; players.len()];
+        Self {
+            open: true,
+            title: Some("Quick Match".to_string()),
+            purpose: DialogPurpose::QuickMatch,
+            content: DialogContent::QuickMatch {
+                players,
+                selected_players,
+                maps,
+                selected_map: 0,
+                max_players,
             },
             result: None,
         }
@@ -166,7 +281,12 @@ impl DialogPopup {
                         ui.add_space(8.0);
 
                         // Handle keyboard input for confirmation dialogs
-                        if matches!(self.purpose, DialogPurpose::Confirmation) {
+                        if matches!(
+                            self.purpose,
+                            DialogPurpose::Confirmation
+                                | DialogPurpose::EnforceSymmetry(_)
+                                | DialogPurpose::RecoverAutosave
+                        ) {
                             if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
                                 self.result = Some(DialogResult::Confirmed);
                                 self.open = false;
@@ -179,7 +299,9 @@ impl DialogPopup {
                         }
 
                         ui.horizontal(|ui| match self.purpose {
-                            DialogPurpose::Confirmation => {
+                            DialogPurpose::Confirmation
+                            | DialogPurpose::EnforceSymmetry(_)
+                            | DialogPurpose::RecoverAutosave => {
                                 if ui.button("Confirm").clicked() {
                                     self.result = Some(DialogResult::Confirmed);
                                     self.open = false;
@@ -233,43 +355,50 @@ impl DialogPopup {
                             }
                         });
                     }
-                    DialogContent::TwoNumbers {
-                        label1,
-                        label2,
-                        value1,
-                        value2,
-                        min1,
-                        max1,
-                        min2,
-                        max2,
+                    DialogContent::NewMap {
+                        width,
+                        height,
+                        bordered,
+                        scatter_food,
+                        food,
                     } => {
                         ui.horizontal(|ui| {
-                            ui.label(label1.as_str());
-                            let mut v1 = *value1 as i32;
-                            let range1 =
-                                min1.unwrap_or(0.0) as i32..=max1.unwrap_or(10000.0) as i32;
-                            if ui
-                                .add(egui::DragValue::new(&mut v1).range(range1))
-                                .changed()
-                            {
-                                *value1 = v1.max(min1.unwrap_or(0.0) as i32) as f64;
-                            }
+                            ui.label("Width:");
+                            ui.add(egui::DragValue::new(width).range(MAP_SIZE_RANGE));
+                            ui.label("Height:");
+                            ui.add(egui::DragValue::new(height).range(MAP_SIZE_RANGE));
+                        });
 
-                            ui.label(label2.as_str());
-                            let mut v2 = *value2 as i32;
-                            let range2 =
-                                min2.unwrap_or(0.0) as i32..=max2.unwrap_or(10000.0) as i32;
-                            if ui
-                                .add(egui::DragValue::new(&mut v2).range(range2))
-                                .changed()
-                            {
-                                *value2 = v2.max(min2.unwrap_or(0.0) as i32) as f64;
-                            }
+                        ui.checkbox(bordered, "Bordered");
+                        ui.checkbox(scatter_food, "Scatter food");
+                        ui.add_enabled_ui(*scatter_food, |ui| {
+                            ui.add(
+                                egui::Slider::new(&mut food.density, 0.0..=0.2)
+                                    .text("Density")
+                                    .custom_formatter(|v, _| format!("{:.1}%", v * 100.0)),
+                            );
+                            ui.add(
+                                egui::Slider::new(&mut food.clustering, 0.0..=1.0)
+                                    .text("Clustering"),
+                            );
+                            ui.horizontal(|ui| {
+                                ui.label("Seed:");
+                                ui.add(egui::DragValue::new(&mut food.seed));
+                                if ui.button("Randomize").clicked() {
+                                    food.seed = macroquad::rand::rand() as u64;
+                                }
+                            });
                         });
+                        let confirmed = DialogResult::NewMapConfirmed {
+                            width: *width,
+                            height: *height,
+                            bordered: *bordered,
+                            food: scatter_food.then_some(*food),
+                        };
 
                         // Handle keyboard input
                         if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                            self.result = Some(DialogResult::TwoNumberConfirmed(*value1, *value2));
+                            self.result = Some(confirmed.clone());
                             self.open = false;
                             still_open = false;
                         } else if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
@@ -280,8 +409,7 @@ impl DialogPopup {
 
                         ui.horizontal(|ui| {
                             if ui.button("Create").clicked() {
-                                self.result =
-                                    Some(DialogResult::TwoNumberConfirmed(*value1, *value2));
+                                self.result = Some(confirmed);
                                 self.open = false;
                                 still_open = false;
                             }
@@ -296,6 +424,7 @@ impl DialogPopup {
                         label,
                         options,
                         selected,
+                        previews,
                     } => {
                         ui.label(label.as_str());
                         let current_option = options.get(*selected).cloned().unwrap_or_default();
@@ -308,6 +437,16 @@ impl DialogPopup {
                                     }
                                 }
                             });
+                        if let Some(Some(preview)) = previews.get_mut(*selected) {
+                            let texture = preview.texture.get_or_insert_with(|| {
+                                ui.ctx().load_texture(
+                                    format!("dialog_choice_preview_{}", *selected),
+                                    preview.image.clone(),
+                                    egui::TextureOptions::NEAREST,
+                                )
+                            });
+                            ui.image((texture.id(), texture.size_vec2()));
+                        }
 
                         // Handle keyboard input
                         if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
@@ -337,6 +476,85 @@ impl DialogPopup {
                             }
                         });
                     }
+                    DialogContent::QuickMatch {
+                        players,
+                        selected_players,
+                        maps,
+                        selected_map,
+                        max_players,
+                    } => {
+                        ui.label("Map:");
+                        let current_map = maps.get(*selected_map).cloned().unwrap_or_default();
+                        egui::ComboBox::from_id_source("quick_match_map_combo")
+                            .selected_text(current_map.clone())
+                            .show_ui(ui, |ui| {
+                                for (i, map) in maps.iter().enumerate() {
+                                    if ui.selectable_label(*selected_map == i, map).clicked() {
+                                        *selected_map = i;
+                                    }
+                                }
+                            });
+                        ui.add_space(8.0);
+
+                        let selected_count = selected_players.iter().filter(|&&s| s).count();
+                        ui.label(format!(
+                            "Players ({}-{}):",
+                            QUICK_MATCH_MIN_PLAYERS, max_players
+                        ));
+                        egui::ScrollArea::vertical()
+                            .max_height(200.0)
+                            .show(ui, |ui| {
+                                for (name, selected) in
+                                    players.iter().zip(selected_players.iter_mut())
+                                {
+                                    // Prevent selecting more players than colonies allowed
+                                    let enabled = *selected || selected_count < *max_players;
+                                    ui.add_enabled(enabled, egui::Checkbox::new(selected, name));
+                                }
+                            });
+                        ui.add_space(8.0);
+
+                        let can_start = !current_map.is_empty()
+                            && (QUICK_MATCH_MIN_PLAYERS..=*max_players).contains(&selected_count);
+                        let confirmed = |players: &[String], selected_players: &[bool]| {
+                            DialogResult::QuickMatchConfirmed {
+                                map_name: current_map.clone(),
+                                players: players
+                                    .iter()
+                                    .zip(selected_players)
+                                    .filter(|(_, selected)| **selected)
+                                    .map(|(name, _)| name.clone())
+                                    .collect(),
+                            }
+                        };
+
+                        // Handle keyboard input
+                        if can_start && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                            self.result = Some(confirmed(players, selected_players));
+                            self.open = false;
+                            still_open = false;
+                        } else if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                            self.result = Some(DialogResult::Cancelled);
+                            self.open = false;
+                            still_open = false;
+                        }
+
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(can_start, egui::Button::new("Start"))
+                                .clicked()
+                            {
+                                self.result = Some(confirmed(players, selected_players));
+                                self.open = false;
+                                still_open = false;
+                            }
+                            if ui.button("Cancel").clicked() {
+                                self.result = Some(DialogResult::Cancelled);
+                                self.open = false;
+                                still_open = false;
+                            }
+                        });
+                    }
                 }
             });
         still_open
@@ -0,0 +1,231 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use macroquad::prelude::*;
+use new_egui_macroquad::egui;
+
+use crate::player::log_file_name;
+use crate::simulation::Simulation;
+
+const LOG_REFRESH_INTERVAL: f32 = 0.25;
+const MAX_LOG_LINES: usize = 500;
+/// Bytes read from the end of a log opened for the first time; older output is skipped
+const MAX_INITIAL_LOG_BYTES: u64 = 64 * 1024;
+
+/// Incremental reader of a growing log file, keeping its last lines
+struct LogTail {
+    path: PathBuf,
+    /// Bytes of the file already read
+    offset: u64,
+    /// Bytes read after the last complete line
+    partial: Vec<u8>,
+    lines: VecDeque<String>,
+    /// Whether the file existed at the last poll
+    found: bool,
+}
+
+impl LogTail {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            offset: 0,
+            partial: Vec::new(),
+            lines: VecDeque::new(),
+            found: false,
+        }
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the output appended since the last poll. A missing file is not an error:
+    /// the container may not have started yet.
+    fn poll(&mut self) {
+        let Ok(mut file) = File::open(&self.path) else {
+            self.found = false;
+            return;
+        };
+        self.found = true;
+        let len = file.metadata().map_or(0, |metadata| metadata.len());
+        if len < self.offset {
+            // Truncated by a new container of the same colony
+            self.offset = 0;
+            self.partial.clear();
+            self.lines.clear();
+        }
+        if self.offset == 0 && len > MAX_INITIAL_LOG_BYTES {
+            self.offset = len - MAX_INITIAL_LOG_BYTES;
+        }
+        if len == self.offset || file.seek(SeekFrom::Start(self.offset)).is_err() {
+            return;
+        }
+
+        let mut bytes = Vec::new();
+        let Ok(read) = file.take(len - self.offset).read_to_end(&mut bytes) else {
+            return;
+        };
+        self.offset += read as u64;
+        self.partial.extend_from_slice(&bytes);
+        while let Some(end) = self.partial.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.partial.drain(..=end).collect();
+            self.lines
+                .push_back(String::from_utf8_lossy(&line).trim_end().to_string());
+            if self.lines.len() > MAX_LOG_LINES {
+                self.lines.pop_front();
+            }
+        }
+    }
+}
+
+/// Window tailing the log file of a colony's brain container
+pub struct LogPanel {
+    show_logs: bool,
+    colony_id: Option<u32>,
+    tail: Option<LogTail>,
+    refresh_timer: f32,
+}
+
+impl LogPanel {
+    pub fn new() -> Self {
+        Self {
+            show_logs: false,
+            colony_id: None,
+            tail: None,
+            refresh_timer: LOG_REFRESH_INTERVAL,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.show_logs
+    }
+
+    /// Toggle log panel visibility
+    pub fn toggle(&mut self) -> bool {
+        self.show_logs = !self.show_logs;
+        self.show_logs
+    }
+
+    /// Draw the log panel, reading new log output every `LOG_REFRESH_INTERVAL`
+    pub fn draw(&mut self, egui_ctx: &egui::Context, simulation: &Simulation) {
+        if !self.show_logs {
+            return;
+        }
+
+        // Keep the panel pointed at an existing colony
+        if self
+            .colony_id
+            .is_none_or(|id| !simulation.colonies.contains_key(&id))
+        {
+            self.colony_id = simulation.colonies.keys().min().copied();
+        }
+        let path = self.colony_id.and_then(|id| {
+            let colony = simulation.colonies.get(&id)?;
+            Some(PathBuf::from(log_file_name(
+                &simulation.namespace,
+                &colony.player_config.name,
+                id,
+            )))
+        });
+        match path {
+            Some(path) if self.tail.as_ref().is_none_or(|tail| tail.path() != path) => {
+                self.tail = Some(LogTail::new(path));
+                self.refresh_timer = LOG_REFRESH_INTERVAL;
+            }
+            Some(_) => {}
+            None => self.tail = None,
+        }
+
+        self.refresh_timer += get_frame_time();
+        if self.refresh_timer >= LOG_REFRESH_INTERVAL {
+            self.refresh_timer = 0.0;
+            if let Some(tail) = &mut self.tail {
+                tail.poll();
+            }
+        }
+
+        egui::Window::new("Brain Log")
+            .resizable(true)
+            .collapsible(true)
+            .default_pos(egui::pos2(12.0, screen_height() * 0.55))
+            .default_size(egui::vec2(480.0, screen_height() * 0.35))
+            .show(egui_ctx, |ui| {
+                let Some(selected_id) = self.colony_id else {
+                    ui.label("No colony");
+                    return;
+                };
+
+                let mut colony_ids: Vec<u32> = simulation.colonies.keys().copied().collect();
+                colony_ids.sort_unstable();
+                egui::ComboBox::from_id_source("log_panel_colony")
+                    .selected_text(simulation.colony_display_name(selected_id))
+                    .show_ui(ui, |ui| {
+                        for id in colony_ids {
+                            if ui
+                                .selectable_label(
+                                    id == selected_id,
+                                    simulation.colony_display_name(id),
+                                )
+                                .clicked()
+                            {
+                                self.colony_id = Some(id);
+                            }
+                        }
+                    });
+
+                let Some(tail) = &self.tail else {
+                    return;
+                };
+                if !tail.found {
+                    ui.label(format!("No log yet ({})", tail.path().display()));
+                    return;
+                }
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for line in &tail.lines {
+                            ui.monospace(line);
+                        }
+                    });
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_log_tail_reads_appended_lines_only() {
+        let path =
+            std::env::temp_dir().join(format!("pherowar_log_tail_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut tail = LogTail::new(path.clone());
+        tail.poll();
+        assert!(!tail.found);
+
+        let mut file = File::create(&path).unwrap();
+        write!(file, "first\nsec").unwrap();
+        tail.poll();
+        assert!(tail.found);
+        assert_eq!(tail.lines, ["first"]);
+
+        // The unfinished line completes on a later poll
+        write!(file, "ond\nthird\n").unwrap();
+        tail.poll();
+        assert_eq!(tail.lines, ["first", "second", "third"]);
+
+        // A restarted colony truncates its log
+        drop(file);
+        std::fs::write(&path, "restart\n").unwrap();
+        tail.poll();
+        assert_eq!(tail.lines, ["restart"]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,8 +1,10 @@
 // Components for the UI system
 mod ant_status_bar;
 mod colony_options;
+mod compare_panel;
 mod debug_panel;
 mod dialog;
+mod log_panel;
 mod tool_size_slider;
 mod top_panel;
 mod visual_options;
@@ -10,8 +12,10 @@ mod visual_options;
 // Export components
 pub use ant_status_bar::AntStatusBar;
 pub use colony_options::ColonyOptions;
+pub use compare_panel::ComparePanel;
 pub use debug_panel::DebugPanel;
 pub use dialog::{DialogContent, DialogPopup, DialogPurpose, DialogResult};
+pub use log_panel::LogPanel;
 pub use tool_size_slider::ToolSizeSlider;
 pub use top_panel::TopPanel;
-pub use visual_options::{PheromoneDisplayMode, VisualOptionsPanel};
+pub use visual_options::{PheromoneDisplayMode, ViewMode, VisualOptionsPanel};
@@ -5,13 +5,21 @@ use new_egui_macroquad::egui::{self, epaint};
 
 use crate::editor::symmetry_mode::SymmetryMode;
 use crate::editor::{EditorManager, ToolType};
-use crate::simulation::Simulation;
+use crate::simulation::{MAX_WAYPOINT_NAME_LEN, Simulation, Zone};
 use crate::ui::components::{ColonyOptions, ToolSizeSlider};
 use crate::ui::events::{AppAction, UIEvent};
 use crate::ui::{
     BASE_BUTTON_HEIGHT, BASE_BUTTON_WIDTH, BASE_ICON_SIZE, BASE_PADDING, BASE_SPACING,
 };
 
+/// Speed preset buttons of the top panel: label and time multiplier (None = unlimited)
+const SPEED_PRESETS: [(&str, Option<f32>); 4] = [
+    ("0.5x", Some(0.5)),
+    ("1x", Some(1.0)),
+    ("2x", Some(2.0)),
+    ("Max", None),
+];
+
 /// Component for the main tool panel at the top of the screen
 pub struct TopPanel {
     tool_size_slider: ToolSizeSlider,
@@ -51,6 +59,16 @@ impl TopPanel {
         ui.add_sized([BASE_ICON_SIZE, BASE_ICON_SIZE], button)
     }
 
+    /// Text button as tall as the icon buttons, widening for longer labels
+    fn speed_button(&self, ui: &mut egui::Ui, label: &str, active: bool) -> egui::Response {
+        let mut button = egui::Button::new(egui::RichText::new(label).size(14.0))
+            .min_size(egui::vec2(BASE_ICON_SIZE, BASE_ICON_SIZE));
+        if active {
+            button = button.fill(catppuccin_egui::MOCHA.overlay0);
+        }
+        ui.add(button)
+    }
+
     fn draw_help_tooltip(&self, egui_ctx: &egui::Context) {
         if let Some(mouse_pos) = egui_ctx.input(|i| i.pointer.hover_pos()) {
             egui::Window::new("")
@@ -95,14 +113,20 @@ impl TopPanel {
             ("1", "Select Food tool"),
             ("2", "Select Wall tool"),
             ("3", "Select Colony tool"),
+            ("4", "Select Teleporter tool"),
+            ("5", "Select Zone tool"),
+            ("6", "Select Waypoint tool"),
+            ("[ / ]", "Decrease/increase tool size"),
             ("Esc", "Deselect tool / Close dialog"),
             ("P or Space", "Pause/resume simulation"),
             ("R", "Reset simulation"),
+            ("C", "Reload config settings"),
             ("S", "Save map"),
             ("L", "Load map"),
             ("F", "Toggle tool panel"),
             ("D", "Toggle debug panel"),
             ("V", "Toggle visual options panel"),
+            ("B", "Toggle brain log panel"),
         ]
     }
 
@@ -112,6 +136,7 @@ impl TopPanel {
             ("Left Click", "Drag view / Use tool"),
             ("Ctrl+Left Click", "Drag view (alternative)"),
             ("Right Click", "Remove with tool"),
+            ("Ctrl+Scroll", "Resize tool"),
             ("Alt+Left Click or Double Click", "Select/deselect ant"),
         ]
     }
@@ -192,6 +217,7 @@ impl TopPanel {
         simulation: &Simulation,
         debug_panel: &crate::ui::components::DebugPanel,
         visual_options_panel: &crate::ui::components::VisualOptionsPanel,
+        log_panel: &crate::ui::components::LogPanel,
     ) -> (Option<UIEvent>, Option<AppAction>, bool, f32) {
         let mut ui_event = None;
         let mut app_action = None;
@@ -224,6 +250,8 @@ impl TopPanel {
                                 let show_size = current_tool.map_or(false, |t| t.is_sizeable());
                                 let show_colony =
                                     current_tool.map_or(false, |t| t == ToolType::Colony);
+                                let show_zone = current_tool == Some(ToolType::Zone);
+                                let show_waypoint = current_tool == Some(ToolType::Waypoint);
                                 ui.horizontal(|ui| {
                                     ui.spacing_mut().item_spacing.x = BASE_SPACING;
                                     for &tool in ToolType::all() {
@@ -269,6 +297,13 @@ impl TopPanel {
                                                 ui_event = Some(UIEvent::ToggleVisualOptionsPanel);
                                                 input_consumed = true;
                                             }
+                                            let log_btn = self
+                                                .icon_button(ui, "📜", log_panel.is_enabled())
+                                                .on_hover_text("Show/hide brain logs");
+                                            if log_btn.clicked() {
+                                                ui_event = Some(UIEvent::ToggleLogPanel);
+                                                input_consumed = true;
+                                            }
                                             let new_map_btn = self
                                                 .icon_button(ui, "⛶", false)
                                                 .on_hover_text("Create new map");
@@ -276,6 +311,13 @@ impl TopPanel {
                                                 ui_event = Some(UIEvent::ShowNewMapDialog);
                                                 input_consumed = true;
                                             }
+                                            let quick_match_btn = self
+                                                .icon_button(ui, "⚔", false)
+                                                .on_hover_text("Quick match");
+                                            if quick_match_btn.clicked() {
+                                                ui_event = Some(UIEvent::ShowQuickMatchDialog);
+                                                input_consumed = true;
+                                            }
                                             let load_btn = self
                                                 .icon_button(ui, "🗁", false)
                                                 .on_hover_text("Load map");
@@ -292,7 +334,21 @@ impl TopPanel {
                                                     Some(AppAction::RequestSaveMap("".to_string()));
                                                 input_consumed = true;
                                             }
+                                            let symmetry_btn = self
+                                                .icon_button(ui, "⚖", false)
+                                                .on_hover_text("Check/fix map symmetry");
+                                            if symmetry_btn.clicked() {
+                                                app_action = Some(AppAction::RequestSymmetryCheck);
+                                                input_consumed = true;
+                                            }
                                             ui.add_space(2.0 * BASE_SPACING);
+                                            let reload_btn = self
+                                                .icon_button(ui, "⚙", false)
+                                                .on_hover_text("Reload config settings");
+                                            if reload_btn.clicked() {
+                                                app_action = Some(AppAction::ReloadConfig);
+                                                input_consumed = true;
+                                            }
                                             let reset_btn = self
                                                 .icon_button(ui, "🔄", false)
                                                 .on_hover_text("Reset simulation");
@@ -315,6 +371,27 @@ impl TopPanel {
                                                 app_action = Some(AppAction::TogglePause);
                                                 input_consumed = true;
                                             }
+                                            ui.add_space(2.0 * BASE_SPACING);
+                                            // Right to left: add the presets from the fastest
+                                            for &(label, multiplier) in SPEED_PRESETS.iter().rev() {
+                                                let active = match multiplier {
+                                                    Some(m) => {
+                                                        !debug_panel.unlimited
+                                                            && debug_panel.time_multiplier
+                                                                == Some(m)
+                                                    }
+                                                    None => debug_panel.unlimited,
+                                                };
+                                                let speed_btn = self
+                                                    .speed_button(ui, label, active)
+                                                    .on_hover_text("Simulation speed");
+                                                if speed_btn.clicked() {
+                                                    ui_event = Some(UIEvent::SpeedPresetSelected(
+                                                        multiplier,
+                                                    ));
+                                                    input_consumed = true;
+                                                }
+                                            }
                                         },
                                     );
                                 });
@@ -345,6 +422,40 @@ impl TopPanel {
                                                 input_consumed = true; // Assume input is consumed if there's a colony event
                                             }
                                         }
+                                        if show_zone {
+                                            ui.add_space(BASE_SPACING);
+                                            ui.label(egui::RichText::new("Zone").strong());
+                                            egui::ComboBox::from_id_source("zone_type_selector")
+                                                .width(90.0)
+                                                .selected_text(editor.zone_type.label())
+                                                .show_ui(ui, |ui| {
+                                                    for zone in Zone::PAINTABLE {
+                                                        if ui
+                                                            .selectable_label(
+                                                                editor.zone_type == zone,
+                                                                zone.label(),
+                                                            )
+                                                            .clicked()
+                                                        {
+                                                            editor.zone_type = zone;
+                                                        }
+                                                    }
+                                                });
+                                        }
+                                        if show_waypoint {
+                                            ui.add_space(BASE_SPACING);
+                                            ui.label(egui::RichText::new("Name").strong());
+                                            ui.add(
+                                                egui::TextEdit::singleline(
+                                                    &mut editor.waypoint_name,
+                                                )
+                                                .char_limit(MAX_WAYPOINT_NAME_LEN)
+                                                .desired_width(110.0),
+                                            )
+                                            .on_hover_text(
+                                                "Name of the waypoints placed, sent to brains",
+                                            );
+                                        }
                                         // Symmetry selector: compact, next to tool size/colony color
                                         ui.add_space(BASE_SPACING);
                                         ui.label(egui::RichText::new("Symmetry").strong());
@@ -1,12 +1,36 @@
 use crate::ui::BASE_PADDING;
+use crate::ui::preferences::UIPreferences;
 use new_egui_macroquad::egui;
+use serde::{Deserialize, Serialize};
 
 /// Visual options for pheromone display
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PheromoneDisplayMode {
     None,
     Colony { colony_id: u32 },
     Channel { colony_id: u32, channel: u8 },
+    AllChannels { colony_id: u32 },
+}
+
+/// What the world view reveals. Only the renderer uses it; brains keep their own perception.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewMode {
+    /// Everything is visible
+    Spectator,
+    /// Only what a colony's nest and ants can see
+    Colony { colony_id: u32 },
+}
+
+impl PheromoneDisplayMode {
+    /// Colony whose pheromones are displayed, if any
+    pub fn colony_id(&self) -> Option<u32> {
+        match *self {
+            PheromoneDisplayMode::None => None,
+            PheromoneDisplayMode::Colony { colony_id }
+            | PheromoneDisplayMode::Channel { colony_id, .. }
+            | PheromoneDisplayMode::AllChannels { colony_id } => Some(colony_id),
+        }
+    }
 }
 
 /// Visual options panel component
@@ -16,6 +40,14 @@ pub struct VisualOptionsPanel {
     pub selected_colony_id: Option<u32>, // For both modes
     pub selected_channel: u8,            // For Channel mode
     pub show_ants: bool,
+    pub show_sense_cone: bool,
+    /// Pulse on the cells where ants lay pheromones
+    pub show_lay_pulses: bool,
+    /// Sparks and damage numbers on hits
+    pub show_combat_effects: bool,
+    pub view_mode: ViewMode,
+    /// Keeps the view on spectator mode, e.g. during a competitive match
+    spectator_locked: bool,
 }
 
 impl VisualOptionsPanel {
@@ -26,6 +58,11 @@ impl VisualOptionsPanel {
             selected_colony_id: None,
             selected_channel: 1,
             show_ants: true,
+            show_sense_cone: false,
+            show_lay_pulses: false,
+            show_combat_effects: false,
+            view_mode: ViewMode::Spectator,
+            spectator_locked: false,
         }
     }
 
@@ -34,14 +71,43 @@ impl VisualOptionsPanel {
         self.show_visual_options
     }
 
+    /// Restores the saved pheromone display and ant visibility
+    pub fn apply_preferences(&mut self, preferences: &UIPreferences) {
+        self.show_visual_options = preferences.visual_options_visible;
+        self.pheromone_mode = preferences.pheromone_mode;
+        self.selected_colony_id = preferences.pheromone_mode.colony_id();
+        if let PheromoneDisplayMode::Channel { channel, .. } = preferences.pheromone_mode {
+            self.selected_channel = channel;
+        }
+        self.show_ants = preferences.show_ants;
+    }
+
     /// Toggle visual options panel visibility
     pub fn toggle(&mut self) -> bool {
         self.show_visual_options = !self.show_visual_options;
         self.show_visual_options
     }
 
+    /// Forces the spectator view and prevents switching to a colony view
+    pub fn lock_spectator_view(&mut self) {
+        self.view_mode = ViewMode::Spectator;
+        self.spectator_locked = true;
+    }
+
     /// Draw the visual options panel
-    pub fn draw(&mut self, egui_ctx: &egui::Context, colonies: &[(u32, egui::Color32)]) {
+    /// `channel_labels` are the channel names of the selected colony.
+    pub fn draw(
+        &mut self,
+        egui_ctx: &egui::Context,
+        colonies: &[(u32, egui::Color32)],
+        channel_labels: &[String],
+    ) {
+        // Fall back to the spectator view when the viewed colony is removed
+        if let ViewMode::Colony { colony_id } = self.view_mode
+            && !colonies.iter().any(|(id, _)| *id == colony_id)
+        {
+            self.view_mode = ViewMode::Spectator;
+        }
         if !self.show_visual_options {
             return;
         }
@@ -53,15 +119,60 @@ impl VisualOptionsPanel {
             .show(egui_ctx, |ui| {
                 ui.heading("Ants");
                 ui.checkbox(&mut self.show_ants, "Draw Ants");
+                ui.checkbox(&mut self.show_sense_cone, "Draw Selected Ant Senses");
+                ui.checkbox(&mut self.show_combat_effects, "Combat Effects");
+                ui.add_space(BASE_PADDING);
+
+                ui.heading("View");
+                ui.add_enabled_ui(!self.spectator_locked, |ui| {
+                    ui.horizontal(|ui| {
+                        let spectator_selected = self.view_mode == ViewMode::Spectator;
+                        if ui
+                            .selectable_label(spectator_selected, "Spectator")
+                            .clicked()
+                        {
+                            self.view_mode = ViewMode::Spectator;
+                        }
+                        for (colony_id, color32) in colonies.iter() {
+                            let is_selected = self.view_mode
+                                == ViewMode::Colony {
+                                    colony_id: *colony_id,
+                                };
+                            let button = ui
+                                .add_sized(
+                                    egui::vec2(24.0, 24.0),
+                                    egui::Button::new("").fill(*color32).stroke(if is_selected {
+                                        egui::Stroke::new(2.0, egui::Color32::WHITE)
+                                    } else {
+                                        egui::Stroke::NONE
+                                    }),
+                                )
+                                .on_hover_text("Colony view");
+                            if button.clicked() {
+                                self.view_mode = ViewMode::Colony {
+                                    colony_id: *colony_id,
+                                };
+                            }
+                        }
+                    });
+                });
+                if self.spectator_locked {
+                    ui.label("Locked to spectator view.");
+                }
                 ui.add_space(BASE_PADDING);
 
                 ui.heading("Pheromones");
+                ui.checkbox(&mut self.show_lay_pulses, "Pulse on Deposits");
                 ui.horizontal(|ui| {
                     let hide_selected = matches!(self.pheromone_mode, PheromoneDisplayMode::None);
                     let colony_selected =
                         matches!(self.pheromone_mode, PheromoneDisplayMode::Colony { .. });
                     let channel_selected =
                         matches!(self.pheromone_mode, PheromoneDisplayMode::Channel { .. });
+                    let all_channels_selected = matches!(
+                        self.pheromone_mode,
+                        PheromoneDisplayMode::AllChannels { .. }
+                    );
 
                     if ui.selectable_label(hide_selected, "Hide").clicked() {
                         self.pheromone_mode = PheromoneDisplayMode::None;
@@ -87,6 +198,15 @@ impl VisualOptionsPanel {
                             }
                         }
                     }
+                    if ui.selectable_label(all_channels_selected, "All").clicked()
+                        && !all_channels_selected
+                        && let Some((colony_id, _)) = colonies.first()
+                    {
+                        self.selected_colony_id = Some(*colony_id);
+                        self.pheromone_mode = PheromoneDisplayMode::AllChannels {
+                            colony_id: *colony_id,
+                        };
+                    }
                 });
                 // Always keep one selected
                 if !matches!(
@@ -94,11 +214,14 @@ impl VisualOptionsPanel {
                     PheromoneDisplayMode::None
                         | PheromoneDisplayMode::Colony { .. }
                         | PheromoneDisplayMode::Channel { .. }
+                        | PheromoneDisplayMode::AllChannels { .. }
                 ) {
                     self.pheromone_mode = PheromoneDisplayMode::None;
                 }
                 match self.pheromone_mode {
-                    PheromoneDisplayMode::Colony { .. } | PheromoneDisplayMode::Channel { .. } => {
+                    PheromoneDisplayMode::Colony { .. }
+                    | PheromoneDisplayMode::Channel { .. }
+                    | PheromoneDisplayMode::AllChannels { .. } => {
                         ui.label("Select Colony:");
                         egui::Grid::new("colony_color_grid_visual_opts")
                             .spacing([8.0, 8.0])
@@ -134,6 +257,12 @@ impl VisualOptionsPanel {
                                                         channel,
                                                     };
                                             }
+                                            PheromoneDisplayMode::AllChannels { .. } => {
+                                                self.pheromone_mode =
+                                                    PheromoneDisplayMode::AllChannels {
+                                                        colony_id: *colony_id,
+                                                    };
+                                            }
                                             _ => {}
                                         }
                                     }
@@ -152,14 +281,10 @@ impl VisualOptionsPanel {
                 } = &mut self.pheromone_mode
                 {
                     ui.label("Select Channel:");
-                    for ch_val in 1..=8 {
-                        let channel_u8 = ch_val as u8;
+                    for (ch_idx, label) in channel_labels.iter().enumerate() {
+                        let channel_u8 = ch_idx as u8 + 1;
                         if ui
-                            .radio_value(
-                                &mut self.selected_channel,
-                                channel_u8,
-                                format!("Channel {}", ch_val),
-                            )
+                            .radio_value(&mut self.selected_channel, channel_u8, label)
                             .clicked()
                         {
                             // Update pheromone_mode when a radio button is clicked
@@ -1,4 +1,6 @@
 use crate::editor::ToolType;
+use crate::editor::symmetry_mode::SymmetryMode;
+use crate::simulation::FoodScatter;
 
 /// Events generated by UI components, primarily for internal UI state changes or simple editor updates.
 #[derive(Debug, Clone)]
@@ -13,20 +15,67 @@ pub enum UIEvent {
     ToggleDebugPanel,
     /// Toggle visual options panel
     ToggleVisualOptionsPanel,
+    /// Toggle the brain log panel
+    ToggleLogPanel,
     /// Show the new map dialog
     ShowNewMapDialog,
     /// Show the reset confirmation dialog
     ShowResetConfirmDialog,
+    /// Show the quick match setup dialog
+    ShowQuickMatchDialog,
     /// Toggle the top panel (retract/expand)
     ToggleTopPanel,
+    /// A speed preset was clicked: time multiplier, or None for unlimited speed
+    SpeedPresetSelected(Option<f32>),
 }
 
 /// Events generated by the UI that require immediate action from the application core.
 pub enum AppAction {
     TogglePause,
+    /// Pause or resume the second simulation in compare mode
+    ToggleComparePause,
     RequestReset,
+    /// Re-read the config file and apply its settings to the running match
+    ReloadConfig,
     RequestSaveMap(String),
     RequestLoadMap(String),
-    RequestNewMap { width: u32, height: u32 },
+    RequestNewMap {
+        width: u32,
+        height: u32,
+        /// Surround the map with walls
+        bordered: bool,
+        food: Option<FoodScatter>,
+    },
+    RequestQuickMatch {
+        map_name: String,
+        players: Vec<String>,
+    },
     ToggleCameraLockOnSelectedAnt,
+    /// Clear every colony's pheromones, keeping ants, food and nests
+    ClearPheromones,
+    /// Start or stop logging the selected ant's think ticks to a trace file
+    ToggleTraceOnSelectedAnt,
+    RequestSymmetryCheck,
+    RequestEnforceSymmetry(SymmetryMode),
+    /// Load the editor's autosave found at startup
+    RecoverAutosave,
+    /// Delete the editor's autosave found at startup
+    DiscardAutosave,
+}
+
+impl AppAction {
+    /// Whether the action replaces or edits the map, the colonies or the settings, which a
+    /// replay takes from its recording.
+    pub fn changes_match(&self) -> bool {
+        matches!(
+            self,
+            AppAction::RequestReset
+                | AppAction::ReloadConfig
+                | AppAction::RequestLoadMap(_)
+                | AppAction::RequestNewMap { .. }
+                | AppAction::RequestQuickMatch { .. }
+                | AppAction::RequestEnforceSymmetry(_)
+                | AppAction::RecoverAutosave
+        )
+    }
 }
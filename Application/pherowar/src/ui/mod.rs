@@ -1,5 +1,6 @@
 pub mod components;
 pub mod events;
+pub mod preferences;
 
 pub use ui_manager::UIManager;
 
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::UI_PREFERENCES_PATH;
+use crate::ui::components::PheromoneDisplayMode;
+
+/// UI state kept across launches. Missing fields fall back to their defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UIPreferences {
+    pub top_panel_visible: bool,
+    pub debug_panel_visible: bool,
+    pub visual_options_visible: bool,
+    /// None = unlimited simulation speed
+    pub time_multiplier: Option<f32>,
+    pub pheromone_mode: PheromoneDisplayMode,
+    pub show_ants: bool,
+}
+
+impl Default for UIPreferences {
+    fn default() -> Self {
+        Self {
+            top_panel_visible: true,
+            debug_panel_visible: false,
+            visual_options_visible: false,
+            time_multiplier: Some(1.0),
+            pheromone_mode: PheromoneDisplayMode::None,
+            show_ants: true,
+        }
+    }
+}
+
+impl UIPreferences {
+    /// Loads the saved preferences, or the defaults if there are none or they can't be read.
+    pub fn load() -> Self {
+        let content = match std::fs::read_to_string(UI_PREFERENCES_PATH) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                eprintln!(
+                    "Warning: Failed to read UI preferences '{}': {}",
+                    UI_PREFERENCES_PATH, e
+                );
+                return Self::default();
+            }
+        };
+        toml::from_str(&content).unwrap_or_else(|e| {
+            eprintln!(
+                "Warning: Ignoring invalid UI preferences '{}': {}",
+                UI_PREFERENCES_PATH, e
+            );
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) {
+        let result = toml::to_string(self)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                std::fs::write(UI_PREFERENCES_PATH, content).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            eprintln!(
+                "Warning: Failed to save UI preferences '{}': {}",
+                UI_PREFERENCES_PATH, e
+            );
+        }
+    }
+}
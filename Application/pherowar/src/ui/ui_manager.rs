@@ -1,16 +1,19 @@
 use catppuccin_egui::set_theme;
 use egui::{self};
 use macroquad::prelude::*;
+use shared::PHEROMONE_CHANNEL_COUNT;
 
+use crate::config::list_players;
 use crate::editor::EditorManager;
 use crate::engine::GameCamera;
 use crate::simulation::ant::{Ant, AntRef};
-use crate::simulation::{DEFAULT_MAP_HEIGHT, DEFAULT_MAP_WIDTH, Simulation};
+use crate::simulation::{DEFAULT_MAP_HEIGHT, DEFAULT_MAP_WIDTH, GameMap, Simulation};
 use crate::ui::components::{
-    AntStatusBar, DebugPanel, DialogContent, DialogPopup, DialogPurpose, DialogResult,
-    PheromoneDisplayMode, TopPanel, VisualOptionsPanel,
+    AntStatusBar, ComparePanel, DebugPanel, DialogContent, DialogPopup, DialogPurpose,
+    DialogResult, LogPanel, PheromoneDisplayMode, TopPanel, ViewMode, VisualOptionsPanel,
 };
 use crate::ui::events::{AppAction, UIEvent};
+use crate::ui::preferences::UIPreferences;
 
 fn auto_zoom(ctx: &egui::Context, base_px: egui::Vec2) -> f32 {
     let logical = ctx.screen_rect().size();
@@ -25,6 +28,8 @@ pub struct UIManager {
     pub top_panel: TopPanel,
     pub visual_options_panel: VisualOptionsPanel,
     pub ant_status_bar: AntStatusBar,
+    pub compare_panel: ComparePanel,
+    pub log_panel: LogPanel,
     pub dialog_popup: Option<DialogPopup>,
     selected_ant: Option<AntRef>,
     camera_locked_on_ant: Option<AntRef>,
@@ -37,18 +42,41 @@ impl UIManager {
     pub fn new() -> Self {
         let window_w = screen_width();
         let window_h = screen_height();
+        let preferences = UIPreferences::load();
+        let mut debug_panel = DebugPanel::new();
+        debug_panel.apply_preferences(&preferences);
+        let mut visual_options_panel = VisualOptionsPanel::new();
+        visual_options_panel.apply_preferences(&preferences);
         Self {
             drag_started_on_ui: false,
-            debug_panel: DebugPanel::new(),
+            debug_panel,
             top_panel: TopPanel::new(),
             last_screen_size: (window_w, window_h),
             last_win_px: egui::vec2(0.0, 0.0),
-            visual_options_panel: VisualOptionsPanel::new(),
+            visual_options_panel,
             ant_status_bar: AntStatusBar::new(),
+            compare_panel: ComparePanel::new(),
+            log_panel: LogPanel::new(),
             dialog_popup: None,
             selected_ant: None,
             camera_locked_on_ant: None,
-            top_panel_visible: true,
+            top_panel_visible: preferences.top_panel_visible,
+        }
+    }
+
+    /// Current UI preferences, to be saved for the next launch
+    pub fn preferences(&self) -> UIPreferences {
+        UIPreferences {
+            top_panel_visible: self.top_panel_visible,
+            debug_panel_visible: self.debug_panel.is_enabled(),
+            visual_options_visible: self.visual_options_panel.is_enabled(),
+            time_multiplier: if self.debug_panel.unlimited {
+                None
+            } else {
+                self.debug_panel.time_multiplier
+            },
+            pheromone_mode: self.visual_options_panel.pheromone_mode,
+            show_ants: self.visual_options_panel.show_ants,
         }
     }
 
@@ -116,6 +144,7 @@ impl UIManager {
         &mut self,
         editor: &mut EditorManager,
         simulation: &Simulation,
+        compare_simulation: Option<&Simulation>,
         camera: &mut GameCamera,
     ) -> (Option<AppAction>, bool) {
         let window_w = screen_width();
@@ -165,18 +194,41 @@ impl UIManager {
                             }
                             (
                                 DialogPurpose::NewMap,
-                                DialogResult::TwoNumberConfirmed(width, height),
+                                DialogResult::NewMapConfirmed {
+                                    width,
+                                    height,
+                                    bordered,
+                                    food,
+                                },
                             ) => {
                                 app_action = Some(AppAction::RequestNewMap {
-                                    width: width as u32,
-                                    height: height as u32,
+                                    width,
+                                    height,
+                                    bordered,
+                                    food,
                                 });
                             }
+                            (
+                                DialogPurpose::QuickMatch,
+                                DialogResult::QuickMatchConfirmed { map_name, players },
+                            ) => {
+                                app_action =
+                                    Some(AppAction::RequestQuickMatch { map_name, players });
+                            }
                             (DialogPurpose::SaveMap, DialogResult::InputConfirmed) => {
                                 if let DialogContent::Input { value, .. } = &dialog.content {
                                     app_action = Some(AppAction::RequestSaveMap(value.clone()));
                                 }
                             }
+                            (DialogPurpose::EnforceSymmetry(mode), DialogResult::Confirmed) => {
+                                app_action = Some(AppAction::RequestEnforceSymmetry(*mode));
+                            }
+                            (DialogPurpose::RecoverAutosave, DialogResult::Confirmed) => {
+                                app_action = Some(AppAction::RecoverAutosave);
+                            }
+                            (DialogPurpose::RecoverAutosave, DialogResult::Cancelled) => {
+                                app_action = Some(AppAction::DiscardAutosave);
+                            }
                             (DialogPurpose::Confirmation, DialogResult::Confirmed) => {
                                 if let DialogContent::Message(message) = &dialog.content {
                                     if message.contains("reset") {
@@ -196,6 +248,7 @@ impl UIManager {
                         egui_ctx,
                         editor,
                         simulation,
+                        compare_simulation,
                         camera,
                         selected_ant_data_for_debug_panel,
                         is_camera_locked_for_debug_panel,
@@ -209,7 +262,10 @@ impl UIManager {
                 input_consumed = consumed_by_components || egui_ctx.is_pointer_over_area();
                 self.update_drag_state(egui_ctx);
 
-                if !self.drag_started_on_ui && !egui_ctx.is_pointer_over_area() {
+                if !self.drag_started_on_ui
+                    && !egui_ctx.is_pointer_over_area()
+                    && camera.contains_mouse()
+                {
                     self.draw_pheromone_level_tooltip(egui_ctx, simulation, world_pos);
                     self.draw_colony_nest_hover_overlay(egui_ctx, simulation, camera);
                 }
@@ -223,6 +279,7 @@ impl UIManager {
                 UIEvent::ColorSelected(index) => editor.color_palette.set_selected_index(index),
                 UIEvent::ToggleDebugPanel => self.toggle_debug_panel(),
                 UIEvent::ToggleVisualOptionsPanel => self.toggle_visual_options_panel(),
+                UIEvent::ToggleLogPanel => self.toggle_log_panel(),
                 UIEvent::ShowNewMapDialog => self.show_dialog(DialogPopup::new_new_map(
                     DEFAULT_MAP_WIDTH,
                     DEFAULT_MAP_HEIGHT,
@@ -230,6 +287,10 @@ impl UIManager {
                 UIEvent::ShowResetConfirmDialog => self.show_dialog(DialogPopup::new_confirm(
                     "Are you sure you want to reset the simulation?",
                 )),
+                UIEvent::ShowQuickMatchDialog => {
+                    self.show_quick_match_dialog(simulation.config.max_colonies)
+                }
+                UIEvent::SpeedPresetSelected(multiplier) => self.debug_panel.set_speed(multiplier),
                 UIEvent::ToggleTopPanel => {
                     self.top_panel_visible = !self.top_panel_visible;
                 }
@@ -243,6 +304,45 @@ impl UIManager {
         self.dialog_popup = Some(dialog);
     }
 
+    /// Shows the quick match dialog, listing the available players and maps
+    fn show_quick_match_dialog(&mut self, max_players: usize) {
+        let players = match list_players() {
+            Ok(players) => players,
+            Err(e) => {
+                self.show_dialog(DialogPopup::new_info(&format!(
+                    "Failed to list players: {}",
+                    e
+                )));
+                return;
+            }
+        };
+        let maps = match GameMap::list_maps() {
+            Ok(maps) => maps,
+            Err(e) => {
+                self.show_dialog(DialogPopup::new_info(&format!(
+                    "Failed to list maps: {}",
+                    e
+                )));
+                return;
+            }
+        };
+
+        if players.len() < 2 {
+            self.show_dialog(DialogPopup::new_info(
+                "At least 2 players are needed in the players/ directory.",
+            ));
+        } else if maps.is_empty() {
+            self.show_dialog(DialogPopup::new_info("No maps found in maps/ directory."));
+        } else {
+            let player_names = players.into_iter().map(|p| p.name).collect();
+            self.show_dialog(DialogPopup::new_quick_match(
+                player_names,
+                maps,
+                max_players,
+            ));
+        }
+    }
+
     fn update_drag_state(&mut self, egui_ctx: &egui::Context) {
         if is_mouse_button_down(MouseButton::Left) && egui_ctx.is_pointer_over_area() {
             self.drag_started_on_ui = true;
@@ -251,11 +351,13 @@ impl UIManager {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn draw_ui_components(
         &mut self,
         egui_ctx: &egui::Context,
         editor: &mut EditorManager,
         simulation: &Simulation,
+        compare_simulation: Option<&Simulation>,
         camera: &GameCamera,
         selected_ant_data: Option<&Ant>,
         is_camera_locked: bool,
@@ -273,6 +375,7 @@ impl UIManager {
                     simulation,
                     &self.debug_panel,
                     &self.visual_options_panel,
+                    &self.log_panel,
                 );
 
             if panel_ui_event.is_some() {
@@ -312,6 +415,15 @@ impl UIManager {
             app_action = debug_panel_action;
         }
 
+        self.log_panel.draw(egui_ctx, simulation);
+
+        if let Some(compare_simulation) = compare_simulation {
+            let compare_action = self.compare_panel.draw(egui_ctx, compare_simulation);
+            if compare_action.is_some() {
+                app_action = compare_action;
+            }
+        }
+
         let colonies: Vec<(u32, egui::Color32)> = simulation
             .colonies
             .values()
@@ -327,7 +439,19 @@ impl UIManager {
                 )
             })
             .collect();
-        self.visual_options_panel.draw(egui_ctx, &colonies);
+        let channel_labels: Vec<String> = (0..PHEROMONE_CHANNEL_COUNT)
+            .map(|channel| {
+                self.visual_options_panel
+                    .selected_colony_id
+                    .and_then(|colony_id| simulation.colonies.get(&colony_id))
+                    .map_or_else(
+                        || format!("Channel {}", channel + 1),
+                        |colony| colony.channel_label(channel),
+                    )
+            })
+            .collect();
+        self.visual_options_panel
+            .draw(egui_ctx, &colonies, &channel_labels);
 
         // Draw the ant status bar at the bottom
         self.ant_status_bar.draw(egui_ctx, simulation);
@@ -351,18 +475,42 @@ impl UIManager {
         self.visual_options_panel.toggle();
     }
 
+    pub fn toggle_log_panel(&mut self) {
+        self.log_panel.toggle();
+    }
+
     pub fn pheromone_display_mode(&self) -> PheromoneDisplayMode {
         self.visual_options_panel.pheromone_mode
     }
 
+    pub fn view_mode(&self) -> ViewMode {
+        self.visual_options_panel.view_mode
+    }
+
     pub fn show_ants(&self) -> bool {
         self.visual_options_panel.show_ants
     }
 
+    pub fn show_sense_cone(&self) -> bool {
+        self.visual_options_panel.show_sense_cone
+    }
+
+    pub fn show_lay_pulses(&self) -> bool {
+        self.visual_options_panel.show_lay_pulses
+    }
+
+    pub fn show_combat_effects(&self) -> bool {
+        self.visual_options_panel.show_combat_effects
+    }
+
     pub fn time_multiplier(&self) -> Option<f32> {
         self.debug_panel.time_multiplier.or(Some(1.0))
     }
 
+    pub fn compare_time_multiplier(&self) -> f32 {
+        self.compare_panel.time_multiplier
+    }
+
     pub fn unlimited(&self) -> bool {
         self.debug_panel.unlimited
     }
@@ -381,20 +529,29 @@ impl UIManager {
         let level_to_display = match pheromone_mode {
             PheromoneDisplayMode::Channel { colony_id, channel } => {
                 if let Some(colony) = simulation.colonies.get(&colony_id) {
-                    let level = colony.get_pheromone_channel_at(
-                        tile_x,
-                        tile_y,
-                        channel.saturating_sub(1) as usize,
-                    );
-                    if level > 0.0 { Some(level) } else { None }
+                    let channel_index = channel.saturating_sub(1) as usize;
+                    let level = colony.get_pheromone_channel_at(tile_x, tile_y, channel_index);
+                    let name = colony
+                        .player_connection
+                        .setup
+                        .channel_name(channel_index)
+                        .map(str::to_string);
+                    if level > 0.0 {
+                        Some((level, name))
+                    } else {
+                        None
+                    }
                 } else {
                     None
                 }
             }
             _ => None,
         };
-        if let Some(level) = level_to_display {
-            let tooltip_text = format!("{:.2}", level);
+        if let Some((level, name)) = level_to_display {
+            let tooltip_text = match name {
+                Some(name) => format!("{}: {:.2}", name, level),
+                None => format!("{:.2}", level),
+            };
             let screen_pos = egui_ctx
                 .input(|i| i.pointer.hover_pos())
                 .unwrap_or_default();
@@ -421,11 +578,11 @@ impl UIManager {
         camera: &GameCamera,
     ) {
         let mouse_world = camera.get_mouse_world_pos();
-        let mut hovered_colony: Option<&str> = None;
+        let mut hovered_colony: Option<String> = None;
         for colony in simulation.colonies.values() {
             let dist = (colony.pos - mouse_world).length();
-            if dist <= crate::simulation::COLONY_NEST_SIZE / 2.0 {
-                hovered_colony = Some(&colony.player_config.name);
+            if dist <= colony.nest_radius {
+                hovered_colony = Some(simulation.colony_display_name(colony.colony_id));
                 break;
             }
         }
@@ -439,8 +596,7 @@ impl UIManager {
             let painter = egui_ctx.layer_painter(layer_id);
             let text_color = egui_ctx.style().visuals.text_color();
             let font_id = egui::FontId::proportional(24.0);
-            let text_galley =
-                egui_ctx.fonts(|f| f.layout_no_wrap(name.to_string(), font_id, text_color));
+            let text_galley = egui_ctx.fonts(|f| f.layout_no_wrap(name, font_id, text_color));
             let text_pos = egui::pos2(
                 target_pos.x - text_galley.size().x / 2.0,
                 target_pos.y - text_galley.size().y,
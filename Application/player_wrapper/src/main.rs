@@ -1,6 +1,10 @@
 use libloading::{Library, Symbol};
 use rkyv::{from_bytes, rancor::Error, to_bytes};
-use shared::{AntInput, AntOutput, AntRequest, AntResponse, PlayerSetup};
+use shared::{
+    AntInput, AntOutput, AntRequest, AntResponse, ColonyState, MapInfo, PlayerSetup, BEACON_NONE,
+    CHANNEL_NAME_SIZE, MAX_MESSAGE_SIZE, MEMORY_SIZE, PHEROMONE_CHANNEL_COUNT, PROTOCOL_VERSION,
+    ROLE_COUNT,
+};
 use std::io::{Read, Write};
 use std::os::unix::net::UnixListener;
 
@@ -15,17 +19,25 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
     println!("[player] brain.so loaded successfully.");
 
-    let update_func: Symbol<unsafe extern "C" fn(*const AntInput, *mut u8, *mut AntOutput)> =
-        match unsafe { lib.get(b"update") } {
-            Ok(sym) => {
-                println!("[player] 'update' symbol loaded.");
-                sym
-            }
-            Err(e) => {
-                eprintln!("[player][error] Failed to load 'update' symbol: {}", e);
-                return Err(Box::new(e));
-            }
-        };
+    // Brains may export `update_with_colony` to also receive the colony-wide stats
+    let colony_update_func: Option<
+        Symbol<unsafe extern "C" fn(*const AntInput, *const ColonyState, *mut u8, *mut AntOutput)>,
+    > = unsafe { lib.get(b"update_with_colony") }.ok();
+    let update_func: Option<
+        Symbol<unsafe extern "C" fn(*const AntInput, *mut u8, *mut AntOutput)>,
+    > = match unsafe { lib.get(b"update") } {
+        Ok(sym) => Some(sym),
+        Err(e) if colony_update_func.is_none() => {
+            eprintln!("[player][error] Failed to load 'update' symbol: {}", e);
+            return Err(Box::new(e));
+        }
+        Err(_) => None,
+    };
+    if colony_update_func.is_some() {
+        println!("[player] 'update_with_colony' symbol loaded.");
+    } else {
+        println!("[player] 'update' symbol loaded.");
+    }
     let setup_func: Symbol<unsafe extern "C" fn(*mut PlayerSetup)> =
         match unsafe { lib.get(b"setup") } {
             Ok(sym) => {
@@ -38,6 +50,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         };
 
+    // Brains may export `map_info` to receive the map's waypoints once, after `setup`
+    let map_info_func: Option<Symbol<unsafe extern "C" fn(*const MapInfo)>> =
+        unsafe { lib.get(b"map_info") }.ok();
+    if map_info_func.is_some() {
+        println!("[player] 'map_info' symbol loaded.");
+    }
+
     let listener = match UnixListener::bind("/tmp/pherowar/pherowar.sock") {
         Ok(l) => l,
         Err(e) => {
@@ -60,6 +79,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
      * -------------------------------------------------- */
     let mut setup = PlayerSetup {
         decay_rates: [0.9; 8],
+        initial_memory: [0; MEMORY_SIZE],
+        role_ratios: [0.0; ROLE_COUNT],
+        channel_names: [[0; CHANNEL_NAME_SIZE]; PHEROMONE_CHANNEL_COUNT],
+        diffusion_rates: [0.0; PHEROMONE_CHANNEL_COUNT],
     };
     unsafe { setup_func(&mut setup) };
 
@@ -68,14 +91,29 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     stream.write_all(&bytes)?;
     println!("[player] Setup sent to host.");
 
-    /* wait for “hello player” from the host (unchanged) */
-    let mut buf = [0u8; 64];
-    let n = stream.read(&mut buf)?;
+    /* wait for “hello player” from the host, exactly, as the map info follows it */
+    let mut buf = [0u8; b"hello player".len()];
+    stream.read_exact(&mut buf)?;
     println!(
         "[player] Received from host: {}",
-        String::from_utf8_lossy(&buf[..n])
+        String::from_utf8_lossy(&buf)
     );
 
+    /* receive the map info, sent once the host has the setup */
+    let mut len_buf = [0u8; 4];
+    stream.read_exact(&mut len_buf)?;
+    let len = u32::from_le_bytes(len_buf) as usize;
+    if len > MAX_MESSAGE_SIZE {
+        eprintln!("[player][error] oversized MapInfo");
+        return Ok(());
+    }
+    let mut info_buf = vec![0u8; len];
+    stream.read_exact(&mut info_buf)?;
+    let map_info = from_bytes::<MapInfo, Error>(&info_buf)?;
+    if let Some(func) = &map_info_func {
+        unsafe { func(&map_info) };
+    }
+
     /* --------------------------------------------------
      *  Main request/response loop (rkyv ⇄ rkyv)
      * -------------------------------------------------- */
@@ -86,7 +124,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             break;
         }
         let len = u32::from_le_bytes(len_buf) as usize;
-        if len > 256 {
+        if len > MAX_MESSAGE_SIZE {
             eprintln!("[player] oversized AntRequest");
             break;
         }
@@ -100,6 +138,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 break;
             }
         };
+        if ant_req.version != PROTOCOL_VERSION {
+            eprintln!(
+                "[player] protocol version mismatch: host {} != player {}",
+                ant_req.version, PROTOCOL_VERSION
+            );
+            break;
+        }
 
         /* ---- run user brain ---- */
         let mut memory = ant_req.memory;
@@ -107,8 +152,22 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             turn_angle: 0.0,
             pheromone_amounts: [0.0; 8],
             try_attack: false,
+            move_offset: 0.0,
+            beacon: BEACON_NONE,
+            lay_ahead: 0.0,
         };
-        unsafe { update_func(&ant_req.input, memory.as_mut_ptr(), &mut output) };
+        match (&colony_update_func, &update_func) {
+            (Some(func), _) => unsafe {
+                func(
+                    &ant_req.input,
+                    &ant_req.colony,
+                    memory.as_mut_ptr(),
+                    &mut output,
+                )
+            },
+            (None, Some(func)) => unsafe { func(&ant_req.input, memory.as_mut_ptr(), &mut output) },
+            (None, None) => unreachable!("checked when loading the brain"),
+        }
         let ant_resp = AntResponse { output, memory };
 
         /* ---- encode & send response ---- */
@@ -2,11 +2,29 @@ use rkyv::{Archive, Deserialize, Serialize};
 
 pub const MEMORY_SIZE: usize = 32;
 pub const PHEROMONE_CHANNEL_COUNT: usize = 8;
+pub const CHANNEL_NAME_SIZE: usize = 16; // bytes, including the NUL terminator
+pub const MAX_WAYPOINTS: usize = 8;
+pub const WAYPOINT_NAME_SIZE: usize = 16; // bytes, including the NUL terminator
+
+/// Version of the host/player message schema, bumped on every layout change
+pub const PROTOCOL_VERSION: u32 = 12;
+/// Maximum serialized size of a message between host and player, in bytes
+pub const MAX_MESSAGE_SIZE: usize = 512;
+
+// Ant roles assigned by the host at spawn
+pub const ROLE_COUNT: usize = 3;
+pub const ROLE_WORKER: u8 = 0;
+pub const ROLE_SCOUT: u8 = 1;
+pub const ROLE_SOLDIER: u8 = 2;
+
+// `AntOutput::beacon` orders; 1 to PHEROMONE_CHANNEL_COUNT plant a beacon of channel `beacon - 1`
+pub const BEACON_NONE: u8 = 0;
+pub const BEACON_REMOVE: u8 = 255;
 
 #[derive(Archive, Serialize, Deserialize, Debug, Clone, Copy)]
 #[repr(C)]
 pub struct AntInput {
-    pub is_carrying_food: bool,
+    pub is_carrying_food: bool, // Ants carry one unit at most: always full when carrying
     pub is_on_colony: bool,
     pub is_on_food: bool,
     pub pheromone_senses: [(f32, f32); PHEROMONE_CHANNEL_COUNT], // angle, intensity
@@ -17,6 +35,16 @@ pub struct AntInput {
     pub enemy_sense: (f32, f32),                                 // angle, distance
     pub longevity: f32,
     pub is_fighting: bool,
+    pub role: u8,                 // ROLE_WORKER, ROLE_SCOUT or ROLE_SOLDIER
+    pub friend_sense: (f32, f32), // angle, distance
+    pub enemy_carrying_food: bool,
+    pub friend_carrying_food: bool,
+    pub colony_food_level: f32, // colony food stock over the server's reference, 0.0 to 1.0
+    pub attacker_sense: (f32, f32), // angle, distance of the last ant that hit this one since its previous think
+    pub time_since_discovery: f32,  // seconds since the ant last found food or met an enemy
+    pub enemy_pheromone_senses: [(f32, f32); PHEROMONE_CHANNEL_COUNT], // angle, strongest enemy intensity
+    pub enemy_cell_sense: [f32; PHEROMONE_CHANNEL_COUNT],              // strongest enemy intensity
+    pub suggested_heading: f32, // clearest direction within the sense cone, relative to the facing
 }
 
 #[derive(Archive, Serialize, Deserialize, Debug, Clone, Copy)]
@@ -25,13 +53,27 @@ pub struct AntOutput {
     pub turn_angle: f32,
     pub pheromone_amounts: [f32; PHEROMONE_CHANNEL_COUNT],
     pub try_attack: bool,
+    pub move_offset: f32, // movement direction relative to the facing (0.0 = forward, PI = backward)
+    pub beacon: u8,       // BEACON_NONE, BEACON_REMOVE or channel + 1 to plant a beacon here
+    pub lay_ahead: f32,   // cells ahead along the facing to lay pheromone at (0.0 = own cell)
+}
+
+/// Colony-wide stats, snapshotted at the start of the colony's update
+#[derive(Archive, Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[repr(C)]
+pub struct ColonyState {
+    pub food_collected: u32, // food in stock, not yet spent on spawns
+    pub population: u32,     // living ants
+    pub kills: u32,          // enemy ants killed since the colony was placed
 }
 
 #[derive(Archive, Serialize, Deserialize, Debug, Clone, Copy)]
 #[repr(C)]
 pub struct AntRequest {
+    pub version: u32, // PROTOCOL_VERSION of the host
     pub input: AntInput,
     pub memory: [u8; MEMORY_SIZE],
+    pub colony: ColonyState,
 }
 
 #[derive(Archive, Serialize, Deserialize, Debug, Clone, Copy)]
@@ -45,4 +87,175 @@ pub struct AntResponse {
 #[repr(C)]
 pub struct PlayerSetup {
     pub decay_rates: [f32; PHEROMONE_CHANNEL_COUNT],
+    pub initial_memory: [u8; MEMORY_SIZE], // copied into the memory of every newly spawned ant
+    pub role_ratios: [f32; ROLE_COUNT],    // all zero = every ant is a worker
+    pub channel_names: [[u8; CHANNEL_NAME_SIZE]; PHEROMONE_CHANNEL_COUNT], // NUL-terminated UTF-8, empty = unnamed
+    pub diffusion_rates: [f32; PHEROMONE_CHANNEL_COUNT], // share spread to neighbor cells per decay tick, 0 = none
+}
+
+impl PlayerSetup {
+    /// Name given by the player to a pheromone channel, if any.
+    pub fn channel_name(&self, channel: usize) -> Option<&str> {
+        let bytes = self.channel_names.get(channel)?;
+        let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        std::str::from_utf8(&bytes[..len])
+            .ok()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+    }
+}
+
+/// Named position of the map, authored in the editor
+#[derive(Archive, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct Waypoint {
+    pub name: [u8; WAYPOINT_NAME_SIZE], // NUL-terminated UTF-8
+    pub offset: (f32, f32),             // x, y in tiles from the colony's nest center (y down)
+}
+
+impl Waypoint {
+    /// Waypoint named `name`, cut to fit the name buffer on a character boundary.
+    pub fn new(name: &str, offset: (f32, f32)) -> Self {
+        let mut len = name.len().min(WAYPOINT_NAME_SIZE - 1);
+        while !name.is_char_boundary(len) {
+            len -= 1;
+        }
+        let mut bytes = [0; WAYPOINT_NAME_SIZE];
+        bytes[..len].copy_from_slice(&name.as_bytes()[..len]);
+        Self {
+            name: bytes,
+            offset,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        let len = self
+            .name
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(self.name.len());
+        std::str::from_utf8(&self.name[..len]).unwrap_or("")
+    }
+}
+
+/// Map details sent once to the player after its setup
+#[derive(Archive, Serialize, Deserialize, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct MapInfo {
+    pub waypoints: [Waypoint; MAX_WAYPOINTS], // the first `waypoint_count` are set
+    pub waypoint_count: u32,
+}
+
+impl MapInfo {
+    /// Map info holding the first `MAX_WAYPOINTS` of `waypoints`.
+    pub fn new(waypoints: &[Waypoint]) -> Self {
+        let count = waypoints.len().min(MAX_WAYPOINTS);
+        let mut info = Self {
+            waypoints: [Waypoint::new("", (0.0, 0.0)); MAX_WAYPOINTS],
+            waypoint_count: count as u32,
+        };
+        info.waypoints[..count].copy_from_slice(&waypoints[..count]);
+        info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_with_names(names: &[&[u8]]) -> PlayerSetup {
+        let mut setup = PlayerSetup {
+            decay_rates: [0.0; PHEROMONE_CHANNEL_COUNT],
+            initial_memory: [0; MEMORY_SIZE],
+            role_ratios: [0.0; ROLE_COUNT],
+            channel_names: [[0; CHANNEL_NAME_SIZE]; PHEROMONE_CHANNEL_COUNT],
+            diffusion_rates: [0.0; PHEROMONE_CHANNEL_COUNT],
+        };
+        for (channel, name) in names.iter().enumerate() {
+            setup.channel_names[channel][..name.len()].copy_from_slice(name);
+        }
+        setup
+    }
+
+    #[test]
+    fn test_messages_fit_message_size() {
+        let input = AntInput {
+            is_carrying_food: false,
+            is_on_colony: false,
+            is_on_food: false,
+            pheromone_senses: [(0.0, 0.0); PHEROMONE_CHANNEL_COUNT],
+            cell_sense: [0.0; PHEROMONE_CHANNEL_COUNT],
+            wall_sense: (0.0, -1.0),
+            food_sense: (0.0, -1.0),
+            colony_sense: (0.0, -1.0),
+            enemy_sense: (0.0, -1.0),
+            longevity: 0.0,
+            is_fighting: false,
+            role: ROLE_WORKER,
+            friend_sense: (0.0, -1.0),
+            enemy_carrying_food: false,
+            friend_carrying_food: false,
+            colony_food_level: 0.0,
+            attacker_sense: (0.0, -1.0),
+            time_since_discovery: 0.0,
+            enemy_pheromone_senses: [(0.0, 0.0); PHEROMONE_CHANNEL_COUNT],
+            enemy_cell_sense: [0.0; PHEROMONE_CHANNEL_COUNT],
+            suggested_heading: 0.0,
+        };
+        let request = AntRequest {
+            version: PROTOCOL_VERSION,
+            input,
+            memory: [0; MEMORY_SIZE],
+            colony: ColonyState::default(),
+        };
+        let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&request).unwrap();
+        assert!(bytes.len() <= MAX_MESSAGE_SIZE, "{} bytes", bytes.len());
+
+        let response = AntResponse {
+            output: AntOutput {
+                turn_angle: 0.0,
+                pheromone_amounts: [0.0; PHEROMONE_CHANNEL_COUNT],
+                try_attack: false,
+                move_offset: 0.0,
+                beacon: BEACON_NONE,
+                lay_ahead: 0.0,
+            },
+            memory: [0; MEMORY_SIZE],
+        };
+        let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&response).unwrap();
+        assert!(bytes.len() <= MAX_MESSAGE_SIZE, "{} bytes", bytes.len());
+
+        let map_info = MapInfo::new(&[Waypoint::new("chokepoint", (0.0, 0.0)); MAX_WAYPOINTS]);
+        let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&map_info).unwrap();
+        assert!(bytes.len() <= MAX_MESSAGE_SIZE, "{} bytes", bytes.len());
+    }
+
+    #[test]
+    fn test_waypoint_names_fit_their_buffer() {
+        assert_eq!(
+            Waypoint::new("chokepoint", (1.0, -2.0)).name(),
+            "chokepoint"
+        );
+        assert_eq!(
+            Waypoint::new("resource_center_north", (0.0, 0.0)).name(),
+            "resource_center"
+        );
+        // Cut before a character that doesn't fit whole
+        assert_eq!(Waypoint::new("ééééééééé", (0.0, 0.0)).name(), "ééééééé");
+
+        let info = MapInfo::new(&[Waypoint::new("a", (0.0, 0.0)); MAX_WAYPOINTS + 2]);
+        assert_eq!(info.waypoint_count, MAX_WAYPOINTS as u32);
+    }
+
+    #[test]
+    fn test_channel_name() {
+        let setup = setup_with_names(&[b"to_food", b"", b"0123456789abcdef", &[0xff, 0xfe]]);
+        assert_eq!(setup.channel_name(0), Some("to_food"));
+        assert_eq!(setup.channel_name(1), None);
+        // A name filling the whole buffer has no terminator but is still read
+        assert_eq!(setup.channel_name(2), Some("0123456789abcdef"));
+        // Invalid UTF-8 is ignored
+        assert_eq!(setup.channel_name(3), None);
+        assert_eq!(setup.channel_name(PHEROMONE_CHANNEL_COUNT), None);
+    }
 }
@@ -19,6 +19,8 @@ void setup(PlayerSetup* setup) {
     srand((unsigned)time(NULL));
     setup->decay_rates[TO_COLONY] = 0.99f;
     setup->decay_rates[TO_FOOD] = 0.9f;
+    strncpy(setup->channel_names[TO_COLONY], "to_colony", CHANNEL_NAME_SIZE - 1);
+    strncpy(setup->channel_names[TO_FOOD], "to_food", CHANNEL_NAME_SIZE - 1);
     printf("Hello from dummy brain `setup` func\n");
 }
 
@@ -13,6 +13,27 @@
 // The memory is initialized to zeros when an ant is spawned.
 #define MEMORY_SIZE 32
 
+// Roles the game engine can assign to ants at spawn (see PlayerSetup.role_ratios).
+#define ROLE_COUNT 3
+#define ROLE_WORKER 0
+#define ROLE_SCOUT 1
+#define ROLE_SOLDIER 2
+
+// AntOutput.beacon orders (see the server's [beacons] setting). Values 1 to PHEROMONE_CHANNEL_COUNT
+// plant a beacon of channel beacon - 1 on the ant's cell.
+#define BEACON_NONE 0
+#define BEACON_REMOVE 255
+
+// Largest AntOutput.lay_ahead, in cells.
+#define MAX_LAY_AHEAD 5.0f
+
+// Size in bytes of a pheromone channel name in PlayerSetup, including the NUL terminator.
+#define CHANNEL_NAME_SIZE 16
+
+// Maximum number of waypoints in MapInfo, and size in bytes of a waypoint name including the NUL terminator.
+#define MAX_WAYPOINTS 8
+#define WAYPOINT_NAME_SIZE 16
+
 // Provides all sensory information and state data for an ant from the game simulation.
 // This structure is passed as read-only input to the update function for each ant.
 // It contains information about the ant's current status (e.g., carrying food, on colony),
@@ -78,6 +99,49 @@ typedef struct {
 
     // is_fighting: True if the ant is currently engaged in combat (e.g., has an active opponent list in the simulation), false otherwise.
     bool is_fighting;
+
+    // role: Role assigned to the ant at spawn (ROLE_WORKER, ROLE_SCOUT or ROLE_SOLDIER).
+    // Roles are distributed following PlayerSetup.role_ratios. Always ROLE_WORKER if no ratio is set.
+    uint8_t role;
+
+    // friend_sense[2]:
+    // Sensory data for the nearest ant of the same colony detected in the current cell or forward-facing arc.
+    // - friend_sense[0]: Relative angle (in radians) from the ant's current orientation to the friend.
+    //   This will be 0.0 if the friend is in the same cell.
+    // - friend_sense[1]: Distance in tiles to the friend. Value is -1.0 if no friend is detected.
+    float friend_sense[2];
+
+    // enemy_carrying_food: True if the enemy reported by enemy_sense is carrying food.
+    // friend_carrying_food: True if the friend reported by friend_sense is carrying food.
+    // Always false when the server disables `sense_carried_food`.
+    bool enemy_carrying_food;
+    bool friend_carrying_food;
+
+    // colony_food_level: Food stock of the ant's colony, normalized by the server's `colony_food_reference`.
+    // 0.0 means an empty stock, 1.0 a stock at or above the reference. Same source as ColonyState.food_collected.
+    float colony_food_level;
+
+    // attacker_sense[2]:
+    // The last ant that damaged this one since its previous think, enemy or friend, even if this ant didn't start the fight.
+    // - attacker_sense[0]: Relative angle (in radians) from the ant's current orientation to where the attacker stood when hitting.
+    // - attacker_sense[1]: Distance in tiles to that position. Value is -1.0 if the ant wasn't hit.
+    float attacker_sense[2];
+
+    // time_since_discovery: Seconds since the ant last found food (standing on or picking it up)
+    // or met an enemy (sensing one, or being hit). Grows while foraging stagnates, e.g. to switch to exploring.
+    float time_since_discovery;
+
+    // enemy_pheromone_senses[8][2], enemy_cell_sense[8]:
+    // Same as pheromone_senses and cell_sense, for the pheromones laid by the other colonies
+    // (strongest among them per channel). Read-only: ants only ever lay their own colony's pheromones.
+    // All zeros unless the server enables sense_enemy_pheromones.
+    float enemy_pheromone_senses[8][2];
+    float enemy_cell_sense[8];
+
+    // suggested_heading: Relative angle (in radians, within the sense cone) with the most open space
+    // ahead, computed by the server from fixed wall rays. Follow it to steer around walls, or ignore it.
+    // Always 0.0 (straight ahead) unless the server enables suggest_heading.
+    float suggested_heading;
 } AntInput;
 
 // AntOutput:
@@ -100,8 +164,40 @@ typedef struct {
     // If true, the simulation will attempt to initiate or continue combat with an enemy ant
     // if one is present in the same cell or a suitable target is otherwise determined by the simulation rules.
     bool try_attack;
+
+    // move_offset: Direction (in radians) the ant moves in, relative to the direction it faces.
+    // 0.0 (the default) moves forward, PI moves backward, +/-PI/2 strafes left/right.
+    // Facing, senses and combat still follow the ant's orientation, e.g. to back away from an enemy.
+    // Wrapped to [-PI, PI] and clamped to the server's `max_move_offset`. Non-forward movement is
+    // slower, down to `backward_speed` times the normal speed when moving straight backward.
+    float move_offset;
+
+    // beacon: BEACON_NONE (the default) does nothing. 1 to PHEROMONE_CHANNEL_COUNT plant a beacon on the
+    // ant's cell, laying channel beacon - 1 every tick until removed, and BEACON_REMOVE removes the colony's
+    // beacon on the cell. Ignored unless the server enables beacons.
+    uint8_t beacon;
+
+    // lay_ahead: Distance in cells ahead of the ant at which pheromone_amounts are laid, along the
+    // direction it faces after turn_angle, e.g. to point a trail before walking it. 0.0 (the default)
+    // lays on the ant's own cell. Clamped to [0, MAX_LAY_AHEAD]; a wall or the map's edge on the way
+    // moves the deposit back to the last cell before it. Overrides the server's pheromone_trail.
+    float lay_ahead;
 } AntOutput;
 
+// ColonyState:
+// Colony-wide stats passed to update_with_colony, snapshotted once per simulation tick
+// before the colony's ants think. Identical for every ant of the colony during that tick.
+typedef struct {
+    // food_collected: Food in the colony's stock, not yet spent on spawning ants.
+    uint32_t food_collected;
+
+    // population: Number of living ants in the colony.
+    uint32_t population;
+
+    // kills: Number of enemy ants killed by the colony's ants since it was placed.
+    uint32_t kills;
+} ColonyState;
+
 // PlayerSetup:
 // Used by the player AI to configure colony-specific parameters at the start of the game.
 // This structure is passed to the setup function, allowing the AI to customize aspects
@@ -116,8 +212,50 @@ typedef struct {
     //  - 0.0 means the pheromone decays completely within 1 second (or the decay interval).
     // These rates are applied by the simulation at regular intervals (e.g., PHEROMONE_DECAY_INTERVAL).
     float decay_rates[8];
+
+    // initial_memory[MEMORY_SIZE]: Template copied into the memory of every newly spawned ant.
+    // Use it to seed state (e.g. a role flag) without spending a think tick.
+    // Zero-initialized by default, which matches the previous behavior of starting from scratch.
+    uint8_t initial_memory[MEMORY_SIZE];
+
+    // role_ratios[ROLE_COUNT]: Relative share of each role among spawned ants, indexed by ROLE_* constant.
+    // For example {0.7, 0.2, 0.1} spawns 70% workers, 20% scouts and 10% soldiers.
+    // The engine keeps the living population as close as possible to these ratios.
+    // All zeros (the default) disables role assignment: every ant is a worker.
+    float role_ratios[ROLE_COUNT];
+
+    // channel_names[8][CHANNEL_NAME_SIZE]: Optional NUL-terminated UTF-8 name of each pheromone channel
+    // (e.g. "to_food"), shown in the game's visual options and pheromone tooltip. Debug only.
+    // Empty (the default) keeps the plain "Channel N" label. Longer names are cut at CHANNEL_NAME_SIZE bytes.
+    char channel_names[8][CHANNEL_NAME_SIZE];
+
+    // diffusion_rates[8]: Share of each cell's pheromone that spreads to its neighbor cells on every decay tick
+    // (every PHEROMONE_DECAY_INTERVAL), per channel, just before the decay. 0.3 moves 30% of a cell's amount evenly to its 4 (or 8, depending on the
+    // server's diffusion_neighborhood) neighbors, widening trails while keeping their total.
+    // 0.0 (the default) keeps pheromones on the cell they were laid on. Values are clamped to 0.0..1.0.
+    float diffusion_rates[8];
 } PlayerSetup;
 
+// Waypoint:
+// Named cell placed on the map in the editor (e.g. "chokepoint").
+typedef struct {
+    // name[WAYPOINT_NAME_SIZE]: NUL-terminated UTF-8 name of the waypoint.
+    char name[WAYPOINT_NAME_SIZE];
+
+    // offset[2]: Position of the waypoint's cell center relative to the colony's nest center,
+    // in cells ([0]: x, [1]: y, y pointing down). Ants only sense angles relative to their own
+    // heading, so a brain using waypoints has to track the ant's heading and position itself.
+    float offset[2];
+} Waypoint;
+
+// MapInfo:
+// Static information about the map, passed once to map_info after setup.
+typedef struct {
+    // waypoints[MAX_WAYPOINTS]: The map's waypoints. Only the first waypoint_count entries are valid.
+    Waypoint waypoints[MAX_WAYPOINTS];
+    uint32_t waypoint_count;
+} MapInfo;
+
 // setup:
 // Initializes the player's ant colony AI.
 // This function is called once by the game engine when the player's AI is first loaded,
@@ -144,4 +282,17 @@ void setup(PlayerSetup* setup_info);
 //           to store state or other information.
 //   output: A pointer to an AntOutput struct where the AI must write the ant's desired actions
 //           for the current simulation tick (e.g., turning angle, pheromones to lay, attack intent).
-void update(const AntInput* input, uint8_t memory[MEMORY_SIZE], AntOutput* output);
\ No newline at end of file
+void update(const AntInput* input, uint8_t memory[MEMORY_SIZE], AntOutput* output);
+
+// update_with_colony (optional):
+// Same as update, with the colony-wide stats as an extra read-only parameter, e.g. to switch
+// to defense when outnumbered. When exported, it is called instead of update, which may then be omitted.
+// Parameters:
+//   colony: A pointer to a ColonyState struct with the stats of the ant's colony.
+void update_with_colony(const AntInput* input, const ColonyState* colony, uint8_t memory[MEMORY_SIZE], AntOutput* output);
+
+// map_info (optional):
+// Called once after setup, before any ant thinks, with the waypoints of the map.
+// Parameters:
+//   info: A pointer to a MapInfo struct, only valid during the call. Copy what you need.
+void map_info(const MapInfo* info);